@@ -0,0 +1,100 @@
+// Acknowledgments of known findings, read from a TOML file like
+//
+//   [[ack]]
+//   id = "duplex.mismatch"
+//   interface = "eth3"
+//   until = "2025-07-01"
+//   reason = "ticket NET-1234"
+//
+// interface, until and reason are optional.
+use std::fs;
+
+#[derive(Deserialize)]
+struct AckFile {
+    #[serde(default)]
+    ack: Vec<Ack>,
+}
+
+#[derive(Deserialize)]
+pub struct Ack {
+    pub id: String,
+    pub interface: Option<String>,
+    // last day (inclusive) the acknowledgment is valid, YYYY-MM-DD
+    pub until: Option<String>,
+    pub reason: Option<String>,
+}
+
+impl Ack {
+    pub fn matches(&self, id: &str, interface: &str) -> bool {
+        if self.id != id {
+            return false;
+        }
+        match self.interface {
+            Some(ref i) => { i == interface },
+            None => { true },
+        }
+    }
+
+    // today as days since 1970-01-01
+    pub fn is_expired(&self, today: i64) -> bool {
+        match self.until {
+            Some(ref u) => { parse_date(u).map(|d| today > d).unwrap_or(true) },
+            None => { false },
+        }
+    }
+
+    pub fn annotation(&self) -> String {
+        match (&self.until, &self.reason) {
+            (Some(u), Some(r)) => { format!("(acknowledged until {}: {})", u, r) },
+            (Some(u), None) => { format!("(acknowledged until {})", u) },
+            (None, Some(r)) => { format!("(acknowledged: {})", r) },
+            (None, None) => { "(acknowledged)".to_string() },
+        }
+    }
+}
+
+// days since 1970-01-01 of a YYYY-MM-DD date
+fn parse_date(raw: &str) -> Option<i64> {
+    let parts: Vec<&str> = raw.split('-').collect();
+    if parts.len() != 3 {
+        return None;
+    }
+    let y: i64 = parts[0].parse().ok()?;
+    let m: i64 = parts[1].parse().ok()?;
+    let d: i64 = parts[2].parse().ok()?;
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+        return None;
+    }
+
+    // days from civil, see http://howardhinnant.github.io/date_algorithms.html
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = (if y >= 0 { y } else { y - 399 }) / 400;
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some(era * 146097 + doe - 719468)
+}
+
+pub fn load(path: &str) -> Result<Vec<Ack>, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(s) => { s },
+        Err(e) => { return Err(format!("Can't read acknowledgment file {}: {}", path, e)); },
+    };
+
+    let parsed: AckFile = match toml::from_str(&content) {
+        Ok(v) => { v },
+        Err(e) => { return Err(format!("Can't parse acknowledgment file {}: {}", path, e)); },
+    };
+
+    // a typo in a date must not silently acknowledge forever or never
+    for a in &parsed.ack {
+        if let Some(ref u) = a.until {
+            if parse_date(u).is_none() {
+                return Err(format!("Invalid date {} for {} in acknowledgment file {}", u, a.id, path));
+            }
+        }
+    }
+
+    Ok(parsed.ack)
+}
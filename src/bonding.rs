@@ -0,0 +1,260 @@
+// State of bond interfaces, read from sysfs or /proc/net/bonding on older kernels.
+// The MII status and link failures of the slaves are only in /proc/net/bonding.
+use super::error::CheckError;
+use super::interface::SysfsReader;
+use super::state::State;
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+// name, number and description as shown by /proc/net/bonding/<if>
+const MODES: &[(&str, u32, &str)] = &[
+    ("balance-rr", 0, "load balancing (round-robin)"),
+    ("active-backup", 1, "fault-tolerance (active-backup)"),
+    ("balance-xor", 2, "load balancing (xor)"),
+    ("broadcast", 3, "fault-tolerance (broadcast)"),
+    ("802.3ad", 4, "IEEE 802.3ad Dynamic link aggregation"),
+    ("balance-tlb", 5, "transmit load balancing"),
+    ("balance-alb", 6, "adaptive load balancing"),
+];
+
+#[derive(Serialize)]
+pub struct Slave {
+    pub name: String,
+    // operstate is up and a carrier is present
+    pub up: bool,
+}
+
+// a "Slave Interface" section of /proc/net/bonding/<if>
+#[derive(Serialize)]
+pub struct SlaveStatus {
+    pub name: String,
+    pub mii_status: String,
+    pub link_failures: u64,
+    // link failures since the previous run, None on the first run
+    pub new_link_failures: Option<u64>,
+    // None unless the bond is in 802.3ad mode
+    pub aggregator_id: Option<u32>,
+    pub active_aggregator: Option<bool>,
+    pub actor_key: Option<u32>,
+    pub actor_port_state: Option<u8>,
+    pub partner_key: Option<u32>,
+    pub partner_port_state: Option<u8>,
+    // system MAC address of the switch, all zero if no LACPDU was received
+    pub partner_system: Option<String>,
+}
+
+impl SlaveStatus {
+    // the partner agreed on the aggregation and forwards frames on the link
+    pub fn partner_in_sync(&self) -> Option<bool> {
+        self.partner_port_state.map(|s| s & LACP_IN_SYNC == LACP_IN_SYNC)
+    }
+}
+
+// port state bits of a LACPDU (IEEE 802.1AX)
+const LACP_STATE_NAMES: &[(u8, &str)] = &[
+    (0x01, "activity"),
+    (0x02, "timeout"),
+    (0x04, "aggregation"),
+    (0x08, "synchronization"),
+    (0x10, "collecting"),
+    (0x20, "distributing"),
+    (0x40, "defaulted"),
+    (0x80, "expired"),
+];
+const LACP_SYNCHRONIZATION: u8 = 0x08;
+const LACP_COLLECTING: u8 = 0x10;
+const LACP_DISTRIBUTING: u8 = 0x20;
+const LACP_IN_SYNC: u8 = LACP_SYNCHRONIZATION | LACP_COLLECTING | LACP_DISTRIBUTING;
+
+pub fn lacp_state_names(state: u8) -> Vec<&'static str> {
+    LACP_STATE_NAMES.iter().filter(|(bit, _)| state & bit != 0).map(|(_, name)| *name).collect()
+}
+
+#[derive(Serialize)]
+pub struct Mode {
+    pub name: String,
+    pub number: u32,
+}
+
+impl Mode {
+    // accepts the name or the number of the mode
+    pub fn parse(raw: &str) -> Option<Mode> {
+        let raw = raw.trim();
+        for (name, number, _) in MODES {
+            if raw == *name || raw == number.to_string() {
+                return Some(Mode{ name: name.to_string(), number: *number });
+            }
+        }
+        None
+    }
+
+    fn from_description(description: &str) -> Option<Mode> {
+        for (name, number, d) in MODES {
+            if description.trim() == *d {
+                return Some(Mode{ name: name.to_string(), number: *number });
+            }
+        }
+        None
+    }
+}
+
+impl PartialEq for Mode {
+    fn eq(&self, other: &Mode) -> bool {
+        self.number == other.number
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.number)
+    }
+}
+
+fn is_bond(sysfs: &dyn SysfsReader, interface: &str, sysfs_path: &str) -> bool {
+    sysfs.read_dir(&format!("{}/bonding", sysfs_path)).is_ok() || Path::new(&format!("/proc/net/bonding/{}", interface)).is_file()
+}
+
+// None if the interface is not a bond
+pub fn read_slaves(sysfs: &dyn SysfsReader, sysfs_root: &str, sysfs_path: &str) -> Result<Option<Vec<Slave>>, CheckError> {
+    if sysfs.read_dir(&format!("{}/bonding", sysfs_path)).is_err() {
+        return Ok(None);
+    }
+
+    let slaves_file = format!("{}/bonding/slaves", sysfs_path);
+    let raw = match sysfs.read_to_string(&slaves_file) {
+        Ok(s) => { s },
+        Err(e) => { return Err(CheckError::io(&slaves_file, e)); },
+    };
+
+    let mut slaves = Vec::new();
+    for name in raw.split_whitespace() {
+        // a slave without carrier or with unreadable state counts as down
+        let operstate = sysfs.read_to_string(&format!("{}/{}/operstate", sysfs_root, name)).unwrap_or_default();
+        let carrier = sysfs.read_to_string(&format!("{}/{}/carrier", sysfs_root, name)).unwrap_or_default();
+        slaves.push(Slave{ name: name.to_string(), up: operstate.trim() == "up" && carrier.trim() == "1" });
+    }
+
+    Ok(Some(slaves))
+}
+
+// None if the interface is not a bond
+pub fn read_mode(sysfs: &dyn SysfsReader, interface: &str, sysfs_path: &str) -> Result<Option<Mode>, CheckError> {
+    if !is_bond(sysfs, interface, sysfs_path) {
+        return Ok(None);
+    }
+
+    // "802.3ad 4"
+    let mode_file = format!("{}/bonding/mode", sysfs_path);
+    if let Ok(s) = sysfs.read_to_string(&mode_file) {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let mode = match parts.len() {
+            2 => { Mode::parse(parts[1]) },
+            1 => { Mode::parse(parts[0]) },
+            _ => { None },
+        };
+        return match mode {
+            Some(m) => { Ok(Some(m)) },
+            None => { Err(CheckError::parse(&format!("bonding mode in {}", mode_file), &s)) },
+        };
+    }
+
+    // "Bonding Mode: IEEE 802.3ad Dynamic link aggregation"
+    let proc_file = format!("/proc/net/bonding/{}", interface);
+    let proc_status = match fs::read_to_string(&proc_file) {
+        Ok(s) => { s },
+        Err(e) => { return Err(CheckError::io(&proc_file, e)); },
+    };
+    for line in proc_status.lines() {
+        if let Some(description) = line.strip_prefix("Bonding Mode:") {
+            return match Mode::from_description(description) {
+                Some(m) => { Ok(Some(m)) },
+                None => { Err(CheckError::parse(&format!("bonding mode in {}", proc_file), description)) },
+            };
+        }
+    }
+
+    Err(CheckError::Missing(format!("Can't find bonding mode in {}", proc_file)))
+}
+
+// None if the interface is not a bond
+pub fn read_slave_status(sysfs: &dyn SysfsReader, interface: &str, sysfs_path: &str) -> Result<Option<Vec<SlaveStatus>>, CheckError> {
+    if !is_bond(sysfs, interface, sysfs_path) {
+        return Ok(None);
+    }
+
+    let proc_file = format!("/proc/net/bonding/{}", interface);
+    match fs::read_to_string(&proc_file) {
+        Ok(s) => { Ok(Some(parse_slave_status(&s))) },
+        Err(e) => { Err(CheckError::io(&proc_file, e)) },
+    }
+}
+
+// the bond's own MII status and the active aggregator come before the first
+// "Slave Interface:" line, every slave section starts with one. In 802.3ad mode the
+// LACPDU fields of actor and partner follow "details actor lacp pdu:" and "details
+// partner lacp pdu:"
+fn parse_slave_status(content: &str) -> Vec<SlaveStatus> {
+    let mut slaves: Vec<SlaveStatus> = Vec::new();
+    let mut active_aggregator: Option<u32> = None;
+    let mut partner = false;
+
+    for line in content.lines() {
+        let (key, value) = match line.find(':') {
+            Some(pos) => { (line[..pos].trim(), line[pos + 1..].trim()) },
+            None => { continue; },
+        };
+
+        match key {
+            "Slave Interface" => {
+                slaves.push(SlaveStatus{ name: value.to_string(), mii_status: "unknown".to_string(), link_failures: 0, new_link_failures: None, aggregator_id: None, active_aggregator: None, actor_key: None, actor_port_state: None, partner_key: None, partner_port_state: None, partner_system: None });
+                partner = false;
+                continue;
+            },
+            "details actor lacp pdu" => { partner = false; continue; },
+            "details partner lacp pdu" => { partner = true; continue; },
+            _ => {},
+        };
+        let slave = match slaves.last_mut() {
+            Some(s) => { s },
+            None => {
+                if key == "Aggregator ID" && active_aggregator.is_none() {
+                    active_aggregator = value.parse().ok();
+                }
+                continue;
+            },
+        };
+        match key {
+            "MII Status" => { slave.mii_status = value.to_string(); },
+            "Link Failure Count" => { slave.link_failures = value.parse().unwrap_or_default(); },
+            "Aggregator ID" => { slave.aggregator_id = value.parse().ok(); },
+            // the actor's key is "port key", the partner's "oper key"
+            "port key" | "oper key" if partner => { slave.partner_key = value.parse().ok(); },
+            "port key" | "oper key" => { slave.actor_key = value.parse().ok(); },
+            "port state" if partner => { slave.partner_port_state = value.parse().ok(); },
+            "port state" => { slave.actor_port_state = value.parse().ok(); },
+            "system mac address" if partner => { slave.partner_system = Some(value.to_string()); },
+            _ => {},
+        };
+    }
+
+    if let Some(active) = active_aggregator {
+        for slave in slaves.iter_mut() {
+            slave.active_aggregator = slave.aggregator_id.map(|id| id == active);
+        }
+    }
+    slaves
+}
+
+// store the link failure counts and set the number of failures since the previous
+// run, a lower count means the bonding module was reloaded
+pub fn update_link_failures(state: &mut State, slaves: &mut [SlaveStatus]) {
+    for slave in slaves.iter_mut() {
+        let key = format!("bond.{}.link_failures", slave.name);
+        if let Some(previous) = state.get_u64(&key) {
+            slave.new_link_failures = Some(slave.link_failures.saturating_sub(previous));
+        }
+        state.set(&key, slave.link_failures);
+    }
+}
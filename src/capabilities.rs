@@ -0,0 +1,63 @@
+// Self description of this build for generators of monitoring configurations: what
+// is compiled in, which backends and privileges are usable on this host and which
+// finding identifiers can be reported.
+use super::netlink;
+use super::output;
+
+use std::fs;
+
+// cargo features compiled into this build
+const FEATURES: &[&str] = &[];
+
+const OUTPUT_FORMATS: &[&str] = &["plain", "json", "checkmk", "prometheus-textfile"];
+
+#[derive(Serialize)]
+pub struct Backend {
+    pub name: &'static str,
+    pub available: bool,
+}
+
+#[derive(Serialize)]
+pub struct Privileges {
+    // required for LLDP, frame size sampling and ARP/NDP probes
+    pub raw_sockets: bool,
+}
+
+#[derive(Serialize)]
+pub struct Capabilities {
+    pub version: &'static str,
+    pub features: &'static [&'static str],
+    pub backends: Vec<Backend>,
+    pub output_formats: &'static [&'static str],
+    pub output_versions: Vec<u32>,
+    pub privileges: Privileges,
+    pub finding_ids: Vec<&'static str>,
+}
+
+// opening a packet socket is enough to know if CAP_NET_RAW is available
+fn raw_sockets_usable() -> bool {
+    let fd = unsafe { libc::socket(libc::AF_PACKET, libc::SOCK_RAW, 0) };
+    if fd < 0 {
+        return false;
+    }
+    unsafe { libc::close(fd) };
+    true
+}
+
+pub fn probe(finding_ids: Vec<&'static str>) -> Capabilities {
+    let backends = vec![
+        Backend{ name: "sysfs", available: fs::read_dir("/sys/class/net").is_ok() },
+        Backend{ name: "netlink", available: netlink::all_addresses().is_ok() },
+        Backend{ name: "proc-vlan", available: fs::metadata("/proc/net/vlan/config").is_ok() },
+    ];
+
+    Capabilities{
+        version: env!("CARGO_PKG_VERSION"),
+        features: FEATURES,
+        backends,
+        output_formats: OUTPUT_FORMATS,
+        output_versions: (output::MIN_SCHEMA_VERSION..=output::SCHEMA_VERSION).collect(),
+        privileges: Privileges{ raw_sockets: raw_sockets_usable() },
+        finding_ids,
+    }
+}
@@ -0,0 +1,53 @@
+// Detection of metrics changing between two runs, the last value of every selected
+// metric is kept in the state file.
+use super::state::State;
+
+pub const METRICS: &[&str] = &["speed", "duplex", "mtu", "ipv4_count", "ipv6_count", "master", "driver"];
+
+#[derive(Serialize)]
+pub struct Change {
+    pub metric: String,
+    pub previous: String,
+    pub current: String,
+    // unix time of the run which observed the previous value
+    pub last_seen: u64,
+}
+
+// store the current value and return the change since the previous run
+pub fn update(state: &mut State, metric: &str, value: &str, now: u64) -> Option<Change> {
+    let value_key = format!("change.{}.value", metric);
+    let time_key = format!("change.{}.time", metric);
+
+    let previous = match (state.get(&value_key), state.get_u64(&time_key)) {
+        (Some(v), Some(t)) => { Some((v.to_string(), t)) },
+        _ => { None },
+    };
+
+    state.set(&value_key, value);
+    state.set(&time_key, now);
+
+    // nothing to compare against on the first run
+    let (last_value, last_seen) = previous?;
+    if last_value == value {
+        return None;
+    }
+
+    Some(Change{ metric: metric.to_string(), previous: last_value, current: value.to_string(), last_seen })
+}
+
+// short human readable age like "2d3h" or "5m"
+pub fn format_age(seconds: u64) -> String {
+    let days = seconds / 86400;
+    let hours = (seconds % 86400) / 3600;
+    let minutes = (seconds % 3600) / 60;
+
+    if days > 0 {
+        format!("{}d{}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h{}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", seconds)
+    }
+}
@@ -0,0 +1,2402 @@
+// Command line parsing into a Configuration. Options of a configuration file are
+// merged into the command line before it is parsed.
+use super::{bonding, capabilities, changes, ethtool, firsthop, interface, output, team, threshold};
+use super::{STATE_CRITICAL, STATE_OK, STATE_UNKNOWN, STATE_WARNING};
+use super::error::CheckError;
+use super::status::FindingId;
+
+use getopts::Options;
+
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+use std::process;
+use std::time::Duration;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AddressFamily {
+    // IPv4 and IPv6 together
+    Any,
+    Ipv4,
+    Ipv6,
+}
+
+impl AddressFamily {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AddressFamily::Any => "ip",
+            AddressFamily::Ipv4 => "ipv4",
+            AddressFamily::Ipv6 => "ipv6",
+        }
+    }
+}
+
+// exact number of non link local addresses of a family, from --addr-count ipv4=3
+pub struct AddressCount {
+    pub family: AddressFamily,
+    pub count: usize,
+}
+
+// minimal number of non link local addresses of a family, from -a ipv4:1,ipv6:2
+pub struct AddressMinimum {
+    pub family: AddressFamily,
+    pub count: usize,
+    // a plain -a ipv4 always reports CRITICAL, explicit counts honor -C/--critical
+    pub explicit: bool,
+}
+
+// where operational state, MTU and addresses are read from, speed and duplex are
+// always read from sysfs
+#[derive(Clone, Copy, PartialEq)]
+pub enum Backend {
+    Sysfs,
+    Netlink,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Plain,
+    Json,
+    Checkmk,
+}
+
+pub const DEFAULT_STATE_FILE: &str = "/var/tmp/check_ethernet_%i.state";
+pub const DEFAULT_SYSFS_ROOT: &str = "/sys/class/net";
+
+// global time budget of the check, active probes get a share of what is left
+pub const DEFAULT_TIMEOUT: u64 = 10;
+
+// how long to wait for another instance checking the same interface
+pub const DEFAULT_LOCK_WAIT: u64 = 10;
+
+// duplicate address detection usually completes within a second or two
+pub const DEFAULT_TENTATIVE_GRACE: u64 = 10;
+
+// the first line NRPE passes back, longer lines are cut by some of its versions
+pub const DEFAULT_MAX_OUTPUT_LENGTH: usize = 4096;
+// room for the longest state prefix and the mark of a cut line, "CRITICAL - ..."
+pub const MIN_OUTPUT_LENGTH: usize = 14;
+
+// --queues-combined auto expects one queue per online CPU, limited by the NIC
+#[derive(Clone, Copy, PartialEq)]
+pub enum QueueCount {
+    Exact(u32),
+    OnlineCpus,
+}
+
+// --stat <name>=<warn>,<crit>, rate thresholds of a counter in statistics/
+#[derive(Clone, PartialEq)]
+pub struct StatThreshold {
+    pub name: String,
+    pub warn: Option<f64>,
+    pub crit: Option<f64>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LockPolicy {
+    Wait,
+    Fail,
+}
+
+// upper bound of addresses probed per subnet by --peer-probe-limit
+pub const MAX_PEER_PROBE_LIMIT: usize = 64;
+
+// time for the whole first hop check, shared by all probes
+pub const DEFAULT_FIRST_HOP_TIMEOUT: f64 = 3.0;
+
+// time to wait for the answer of the gateway to --probe-gateway
+pub const DEFAULT_PROBE_TIMEOUT: f64 = 2.0;
+
+pub struct Configuration {
+    pub interface: Vec<String>,
+    // None if the check is disabled
+    pub mtu: Option<threshold::Range>,
+    // compare the MTU with lower devices and the master, on by default with -m
+    pub mtu_consistency: bool,
+    pub speed: Option<threshold::Range>,
+    // speeds listed with -s, empty unless more than one speed is given
+    pub allowed_speeds: Vec<i32>,
+    // replace the speed of -s and -C if one of them is set
+    pub speed_warn: Option<u32>,
+    pub speed_crit: Option<u32>,
+    // None if the duplex mode isn't checked
+    pub duplex: Option<String>,
+    // Some(true) never checks speed and duplex, None skips them for devices without a
+    // device directory, Some(false) if a speed was requested explicitly
+    pub virtual_interface: Option<bool>,
+    // true if auto-negotiation is required to be on
+    pub autoneg: Option<bool>,
+    // expected active forward error correction, "rs", "baser", "off" or "auto"
+    pub fec: Option<String>,
+    // true if promiscuous mode is required to be on, e.g. on sensors
+    pub promisc: Option<bool>,
+    // IFF_* flags which must be set or cleared, 0 if not checked
+    pub flags_set: u32,
+    pub flags_clear: u32,
+    pub report_critical: bool,
+    // state reported for the operational state "unknown"
+    pub operstate_unknown: i32,
+    // state reported for a missing interface
+    pub missing_state: i32,
+    pub address_minimums: Vec<AddressMinimum>,
+    pub address_counts: Vec<AddressCount>,
+    pub check_napi_balance: bool,
+    pub check_wq_balance: bool,
+    pub effective_mtu_to: Option<IpAddr>,
+    pub interface_regex: Option<regex::Regex>,
+    // every interface except loopback, with all_physical_only only those backed by a device
+    pub all: bool,
+    pub all_physical_only: bool,
+    // shell style patterns, applied to interfaces selected by pattern or --all
+    pub exclude: Vec<glob::Pattern>,
+    pub min_matches: usize,
+    pub state_file: String,
+    pub sysfs_root: String,
+    pub enrich_from_lldp: bool,
+    pub output: OutputFormat,
+    pub output_version: u32,
+    // check_mk local check service, None for ethernet_<interface>
+    pub service_name: Option<String>,
+    // "CRITICAL - " in front of the first line of the plain output
+    pub status_prefix: bool,
+    // bytes of the first line of the plain output
+    pub max_output_length: usize,
+    pub expect_no_address: bool,
+    // unique local IPv6 addresses don't count as assigned addresses
+    pub ipv6_require_global: bool,
+    // at least one fe80::/10 address must be assigned
+    pub require_ipv6_link_local: bool,
+    // at least one non-temporary global IPv6 address must be assigned
+    pub ipv6_require_stable: bool,
+    // tentative addresses older than this are reported
+    pub tentative_grace: Duration,
+    pub strict: bool,
+    // prefix length only if given by the user
+    pub expect_address: Vec<(IpAddr, Option<u8>)>,
+    // neighbor table entries which must be resolved, with the MAC address if given
+    pub expect_neighbors: Vec<(IpAddr, Option<String>)>,
+    // addresses inside these prefixes don't count for -a and --addr-count
+    pub ignore_prefixes: Vec<ipnetwork::IpNetwork>,
+    // a default route of every family must leave through the interface, via the
+    // gateway if one was given
+    pub expect_default_route: bool,
+    pub default_route_families: Vec<AddressFamily>,
+    pub default_gateway: Option<IpAddr>,
+    pub prometheus_textfile: Option<String>,
+    pub errors_warn: Option<f64>,
+    pub errors_crit: Option<f64>,
+    pub drops_warn: Option<f64>,
+    pub drops_crit: Option<f64>,
+    // carrier changes per hour
+    pub flaps_warn: Option<f64>,
+    pub flaps_crit: Option<f64>,
+    // collisions per second
+    pub collisions_warn: Option<f64>,
+    pub collisions_crit: Option<f64>,
+    pub stats: Vec<StatThreshold>,
+    pub bandwidth_warn: Option<f64>,
+    pub bandwidth_crit: Option<f64>,
+    pub bandwidth_max: Option<f64>,
+    // dBm, lower is worse
+    pub signal_warn: Option<f64>,
+    pub signal_crit: Option<f64>,
+    // percent of a threshold a value has to clear it by, None if disabled
+    pub hysteresis: Option<f64>,
+    pub check_first_hop: bool,
+    // None if frame sizes are not sampled
+    pub frame_size_window: Option<Duration>,
+    pub first_hop_methods: Vec<firsthop::Method>,
+    pub first_hop_timeout: Duration,
+    // --probe-gateway, the gateway is taken from the routing table if no address is given
+    pub probe_gateway: bool,
+    pub probe_gateway_address: Option<IpAddr>,
+    pub probe_timeout: Duration,
+    pub probe_warn_ms: Option<f64>,
+    // provider prefix of a point-to-point uplink, None if not checked
+    pub uplink_check: Option<ipnetwork::Ipv4Network>,
+    pub cross_check_backends: bool,
+    pub backend: Backend,
+    // remote device queried with SNMPv2c instead of the local interfaces
+    pub snmp_host: Option<String>,
+    pub snmp_community: String,
+    pub lock_wait: Duration,
+    pub lock_policy: LockPolicy,
+    pub alert_on_change: Vec<String>,
+    pub bond_min_slaves: Option<usize>,
+    pub bond_mode: Option<bonding::Mode>,
+    // MII status, link failures and aggregator of every slave
+    pub bond_slave_status: bool,
+    // partner state and aggregator of the slaves of a 802.3ad bond
+    pub check_lacp: bool,
+    pub team_min_ports: Option<usize>,
+    // runner of a team interface as named by teamd, e.g. lacp
+    pub team_runner: Option<String>,
+    pub ack_file: Option<String>,
+    pub probe_subnet_peers: bool,
+    pub peer_probe_limit: usize,
+    pub bridge_ports: Vec<String>,
+    pub bridge_ports_min_match: bool,
+    pub vlan_id: Option<u16>,
+    pub vlan_parent: Option<String>,
+    pub check_vlans_of: Option<String>,
+    pub by_address: Option<IpAddr>,
+    pub expect_qdisc: Option<String>,
+    // true if runtime power management is expected to be enabled
+    pub expect_runtime_pm: Option<bool>,
+    pub driver: Option<String>,
+    pub txqueuelen: Option<u64>,
+    // expected RX and TX ring buffer sizes
+    pub ring_rx: Option<u32>,
+    pub ring_tx: Option<u32>,
+    // offload features and their expected state, names as given by --feature
+    pub features: Vec<(String, bool)>,
+    // expected pause frame settings, "rx", "tx" or "autoneg" and their state
+    pub pause: Vec<(String, bool)>,
+    // expected number of RX and TX queues in sysfs and of combined channels
+    pub queues_rx: Option<u32>,
+    pub queues_tx: Option<u32>,
+    pub queues_combined: Option<QueueCount>,
+    // NUMA node the device is expected to be attached to
+    pub numa_node: Option<u32>,
+    // number of SR-IOV virtual functions which must be enabled
+    pub sriov_vfs: Option<u32>,
+    // minimal PCIe link speed in GT/s and width in lanes
+    pub pcie_speed: Option<f64>,
+    pub pcie_width: Option<u32>,
+    // true if a PCIe link below the maximum of device and slot is reported
+    pub pcie_check_max: bool,
+    // firmware version as reported by the driver, exact or the minimum
+    pub firmware: Option<String>,
+    pub firmware_min: Option<String>,
+    // bond, bridge or VRF the interface must be enslaved to
+    pub master: Option<String>,
+    // true if the interface must not be enslaved at all
+    pub no_master: bool,
+    // alternative name which must be configured on the interface
+    pub expect_altname: Option<String>,
+    // VLAN ids, sorted
+    pub expect_vlan_set: Option<Vec<u16>>,
+    pub timeout: Duration,
+    pub verbose: bool,
+    pub dry_run: bool,
+}
+
+// exact values of 0 or below disable the check, e.g. -s 0
+fn enabled_range(range: threshold::Range) -> Option<threshold::Range> {
+    if range.is_exact() && range.start.unwrap_or(0.0) <= 0.0 {
+        return None;
+    }
+    Some(range)
+}
+
+// link speed in MBit/s with an optional, case insensitive m or g suffix, e.g. 2.5g for
+// 2500 MBit/s. Speeds are whole MBit/s, negative values or garbage like 10gg are None
+fn parse_speed(raw: &str) -> Option<f64> {
+    let raw = raw.to_lowercase();
+    let (value, factor) = if let Some(v) = raw.strip_suffix('g') {
+        (v, 1000.0)
+    } else if let Some(v) = raw.strip_suffix('m') {
+        (v, 1.0)
+    } else {
+        (raw.as_str(), 1.0)
+    };
+    if value.is_empty() || !value.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    let speed = value.parse::<f64>().ok()? * factor;
+    if !speed.is_finite() || speed.fract() != 0.0 || speed > u32::MAX as f64 {
+        return None;
+    }
+    Some(speed)
+}
+
+// a range of speeds like @1g:10g with the bounds converted to MBit/s for threshold::Range
+fn speed_range(raw: &str) -> Option<String> {
+    let (prefix, bounds) = match raw.strip_prefix('@') {
+        Some(b) => { ("@", b) },
+        None => { ("", raw) },
+    };
+    let mut converted = Vec::new();
+    for bound in bounds.split(':') {
+        if bound.is_empty() || bound == "~" {
+            converted.push(bound.to_string());
+        } else {
+            converted.push(parse_speed(bound)?.to_string());
+        }
+    }
+    if converted.len() > 2 {
+        return None;
+    }
+    Some(format!("{}{}", prefix, converted.join(":")))
+}
+
+// 9000-9216 for NICs configured with different jumbo frame sizes, None if it's not
+// of this form
+fn mtu_range(raw: &str) -> Option<Result<threshold::Range, CheckError>> {
+    let (min, max) = raw.split_once('-')?;
+    let (min, max) = match (min.parse::<u32>(), max.parse::<u32>()) {
+        (Ok(min), Ok(max)) => { (min, max) },
+        _ => { return None; },
+    };
+    if min > max {
+        return Some(Err(CheckError::invalid("Invalid MTU range, minimum is greater than maximum")));
+    }
+    Some(Ok(threshold::Range{ start: Some(min as f64), end: Some(max as f64), inside: false }))
+}
+
+// options reading data SNMP doesn't provide or probing from the local host
+const SNMP_UNSUPPORTED: &[&str] = &["all", "all-physical-only", "interface-regex", "by-address", "check-vlans-of", "exclude", "min-matches",
+    "effective-mtu-to", "duplex", "virtual", "require-autoneg", "require-no-autoneg", "fec", "no-promisc", "require-promisc", "flags-set", "flags-clear",
+    "check-napi-balance", "check-wq-balance", "enrich-from-lldp", "sysfs-root", "errors-warn", "errors-crit", "drops-warn", "drops-crit", "flaps-warn", "flaps-crit", "collisions-warn", "collisions-crit", "stat", "bandwidth-warn",
+    "bandwidth-crit", "signal-warn", "signal-crit", "hysteresis", "bond-min-slaves", "bond-mode", "bond-slave-status", "check-lacp", "team-min-ports", "team-runner", "bridge-ports", "vlan", "vlan-parent",
+    "expect-qdisc", "expect-runtime-pm", "driver", "txqueuelen", "ring-rx", "ring-tx", "feature", "pause", "queues-rx", "queues-tx", "queues-combined", "numa-node", "sriov-vfs", "pcie-speed", "pcie-width", "pcie-check-max", "firmware", "firmware-min", "master", "no-master", "expect-altname", "probe-subnet-peers", "check-first-hop", "probe-gateway", "uplink-check",
+    "frame-size-histogram", "cross-check-backends", "backend", "alert-on-change", "ipv6-require-stable", "tentative-grace",
+    "expect-neighbor", "expect-default-route"];
+
+pub fn state_file_path(cfg: &Configuration, interface: &str) -> String {
+    cfg.state_file.replace("%i", interface)
+}
+
+fn usage() {
+    println!("check_ethernet version 0.2.1\n\
+Copyright (C) by Andreas Maus <maus@ypbind.de>\n\
+This program comes with ABSOLUTELY NO WARRANTY.\n\
+\n\
+check_ethernet is distributed under the Terms of the GNU General\n\
+Public License Version 3. (http://www.gnu.org/copyleft/gpl.html)\n\
+\n\
+Usage: check_ethernet -i <if>[,<if>,...]|--interface=<if>[,<if>,...]|--interface-regex=<re>|--all [--all-physical-only]|--by-address=<ip>|--check-vlans-of=<if> [--expect-vlan-set=<id>[,<id>,...]] [--exclude=<if>[,<if>,...]] [--min-matches=<n>] [-m <mtu>[-<mtu>]|--mtu=<mtu>[-<mtu>]] [--no-mtu-consistency] [--effective-mtu-to=<ip>] [-s <state>|--state=<state>] [--duplex=half|full|any] [--min-speed=<mbit>] [--speed-warn=<mbit>] [--speed-crit=<mbit>] [--virtual] [--require-autoneg|--require-no-autoneg] [--fec=rs|baser|off|auto] [--no-promisc|--require-promisc] [--flags-set=<flag>[,<flag>,...]] [--flags-clear=<flag>[,<flag>,...]] [-C|--critical] [--operstate-unknown-is=ok|warning|critical] [--missing-state=ok|warning|critical|unknown] [--check-napi-balance] [--check-wq-balance] [--enrich-from-lldp] [--state-file=<file>] [--sysfs-root=<dir>] [--errors-warn=<rate>] [--errors-crit=<rate>] [--drops-warn=<rate>] [--drops-crit=<rate>] [--flaps-warn=<n>] [--flaps-crit=<n>] [--collisions-warn=<rate>] [--collisions-crit=<rate>] [--stat=<name>=<warn>,<crit> ...] [--bandwidth-warn=<pct>] [--bandwidth-crit=<pct>] [--bandwidth-max=<mbit>] [--signal-warn=<dbm>] [--signal-crit=<dbm>] [--hysteresis=<pct>] [--bond-min-slaves=<n>] [--bond-mode=<mode>] [--bond-slave-status] [--check-lacp] [--team-min-ports=<n>] [--team-runner=<runner>] [--bridge-ports=<if>[,<if>,...] [--bridge-ports-min-match]] [--vlan=<id>] [--vlan-parent=<if>] [--expect-qdisc=<kind>] [--expect-runtime-pm=on|off] [--driver=<name>] [--txqueuelen=<n>] [--ring-rx=<n>] [--ring-tx=<n>] [--feature=<name>=on|off ...] [--pause=<setting>=on|off[,...]] [--queues-rx=<n>] [--queues-tx=<n>] [--queues-combined=<n>|auto] [--numa-node=<n>] [--sriov-vfs=<n>] [--pcie-speed=<gt/s>] [--pcie-width=<lanes>] [--pcie-check-max] [--firmware=<version>|--firmware-min=<version>] [--master=<if>|--no-master] [--expect-altname=<name>] [--probe-subnet-peers [--peer-probe-limit=<n>]] [--check-first-hop [--first-hop-methods=<list>] [--first-hop-timeout=<sec>]] [--probe-gateway[=<ip>] [--probe-timeout=<sec>] [--probe-warn-ms=<ms>]] [--uplink-check=<prefix>] [--frame-size-histogram=<sec>] [--cross-check-backends] [--backend=sysfs|netlink] [--snmp-host=<host>[:<port>] [--snmp-community=<community>]] [--lock-wait=<sec>] [--lock-policy=wait|fail] [--output=plain|json|checkmk [--output-version=<n>] [--service-name=<name>]] [--no-status-prefix] [--max-output-length=<bytes>] [--prometheus-textfile=<file>] [--alert-on-change=<metric>[,<metric>,...]] [--ack-file=<file>] [-t <sec>|--timeout=<sec>] [-v|--verbose] [--config=<file>] [--dry-run] [--capabilities] [-h|--help] [-a=<family>[:<n>][,...]|--address-assigned=<family>[:<n>][,...]|-a none [--strict-none]] [--addr-count=<family>=<n> ...] [--ignore-prefix=<cidr> ...] [--ipv6-require-global|--ipv6-accept-ula] [--ipv6-require-stable] [--require-ipv6-link-local] [--tentative-grace=<sec>] [--expect-address=<ip>[/<prefix>] ...] [--expect-no-address [--strict]] [--expect-neighbor=<ip>[=<mac>] ...] [--expect-default-route[=<gateway>] [-4] [-6]]\n\
+\n\
+    -a =[ip|ipv4|ipv6|none]             Check if non-link local address has been assigned to the interface\n\
+    --address-assigned=[ip|ipv4|ipv6]   ip   - IPv4 (169.254.0.0/16) and IPv6 (fe80::/10)
+                                        ipv4 - IPv4 (169.254.0.0/16) only
+                                        ipv6 - IPv6 (fe80::/10) only
+                                        A minimal number of non-link local addresses can be given per family, e.g.
+                                        ipv4:1,ipv6:2. Falling below it is reported as WARNING (CRITICAL if -C/--critical
+                                        is used), without a number CRITICAL is reported if no address is assigned.
+                                        On the loopback interface CRITICAL is reported if 127.0.0.1/8 (ip, ipv4) or
+                                        ::1/128 (ip, ipv6) is missing instead.
+                                        none - no address must be assigned, like --expect-no-address. IPv6 link
+                                        local addresses are ignored unless --strict-none (or --strict) is used.
+
+    --addr-count=<family>=<n>           Exact number of non link local addresses of the family ip, ipv4 or ipv6, e.g.
+                                        ipv4=3 for an interface carrying virtual IPs. Fewer addresses are reported as
+                                        CRITICAL, more as WARNING (CRITICAL if -C/--critical is used). Can be repeated
+                                        for each family and combined with -a/--address-assigned.
+
+    --ignore-prefix=<cidr>              Addresses inside <cidr>, e.g. 10.88.0.0/16 managed by CNI, don't count for
+                                        -a/--address-assigned and --addr-count. Can be repeated, IPv4 and IPv6. The
+                                        ignored addresses are listed with -v/--verbose.
+
+    --ipv6-require-global               Don't count IPv6 unique local addresses (fc00::/7) for -a/--address-assigned,
+    --ipv6-accept-ula                   at least one global IPv6 address must be assigned. --ipv6-accept-ula counts
+                                        them like global addresses. Default: --ipv6-accept-ula
+
+    --ipv6-require-stable               Report CRITICAL if no stable (EUI-64 or stable privacy) non link local IPv6
+                                        address is assigned, e.g. because only temporary addresses of the privacy
+                                        extensions are left. Requires the address flags from netlink, UNKNOWN is
+                                        reported if they are not available.
+
+    --require-ipv6-link-local           Report CRITICAL if no IPv6 link local address (fe80::/10) is assigned, e.g.
+                                        for OSPFv3 or BGP unnumbered. Can be combined with -a/--address-assigned.
+
+    --tentative-grace=<sec>             IPv6 addresses which failed duplicate address detection are reported as
+                                        CRITICAL, addresses still tentative after <sec> seconds as WARNING. Neither
+                                        counts for -a/--address-assigned. Default: 10
+
+    --cross-check-backends              Read operational state, MTU and addresses from sysfs and from netlink and\n\
+                                        report WARNING if they disagree, e.g. because of stale sysfs data.\n\
+\n\
+    --backend=sysfs|netlink             Read operational state, MTU, flags and addresses from sysfs or from a single\n\
+                                        rtnetlink query. Speed and duplex are always read from sysfs.\n\
+                                        Default: sysfs\n\
+\n\
+    --snmp-host=<host>[:<port>]         Read operational state, speed, MTU, hardware address and addresses of the\n\
+                                        interface from IF-MIB and IP-MIB of a remote device with SNMPv2c. -i is\n\
+                                        matched against ifName and ifDescr. IF-MIB has no duplex mode, duplex is\n\
+                                        not checked. Options which need local access to the interface can't be used.\n\
+\n\
+    --snmp-community=<community>        SNMP community of --snmp-host. Default: public\n\
+\n\
+    --drops-warn=<rate>                 Report WARNING or CRITICAL if the rate of rx_dropped or tx_dropped since the last\n\
+    --drops-crit=<rate>                 run exceeds <rate> packets per second. A counter going backwards (e.g. after a\n\
+                                        driver reload) starts a new baseline instead of reporting a rate.\n\
+\n\
+    --flaps-warn=<n>                    Report WARNING or CRITICAL if the link went up or down more than <n> times per\n\
+    --flaps-crit=<n>                    hour since the last run, from carrier_changes in sysfs. The counter is kept in\n\
+                                        the state file, a counter going backwards (e.g. after a reboot) starts a new\n\
+                                        baseline.\n\
+\n\
+    --collisions-warn=<rate>            Report WARNING or CRITICAL if the rate of collisions since the last run exceeds\n\
+    --collisions-crit=<rate>            <rate> collisions per second. The counter is kept in the state file, the first\n\
+                                        run only collects the baseline. Without these options collisions counted on a\n\
+                                        full duplex link, usually a duplex mismatch, are mentioned in the output.\n\
+\n\
+    --stat=<name>=<warn>,<crit>         Report WARNING or CRITICAL if the rate of the counter <name> from\n\
+                                        /sys/class/net/<if>/statistics since the last run exceeds <warn> or <crit> per\n\
+                                        second, e.g. --stat=rx_fifo_errors=1,10. One of the thresholds may be empty.\n\
+                                        Can be repeated. The counters are kept in the state file, the first run only\n\
+                                        collects the baseline. Report UNKNOWN if the interface has no such counter.\n\
+\n\
+    --errors-warn=<rate>                Report WARNING or CRITICAL if the rate of rx_errors or tx_errors since the last\n\
+    --errors-crit=<rate>                run exceeds <rate> errors per second. The counters are kept in the state file,\n\
+                                        the first run only collects the baseline.\n\
+\n\
+    --ack-file=<file>                   TOML file with acknowledged findings. Acknowledged findings are reported as OK.\n\
+                                        Every [[ack]] entry has the finding identifier as \"id\" and the optional\n\
+                                        \"interface\", \"until\" (YYYY-MM-DD) and \"reason\" keys. Expired acknowledgments are\n\
+                                        ignored and reported as WARNING, an invalid file as UNKNOWN.\n\
+\n\
+    --alert-on-change=<metric>,...      Report WARNING if one of the metrics changed since the last run. Metrics are\n\
+                                        speed, duplex, mtu, ipv4_count, ipv6_count, master and driver. The last values\n\
+                                        of the selected metrics are kept in the state file.\n\
+\n\
+    --bandwidth-warn=<pct>              Report WARNING or CRITICAL if the receive or transmit throughput since the last\n\
+    --bandwidth-crit=<pct>              run exceeds <pct> percent of the negotiated link speed. The byte counters are\n\
+                                        kept in the state file, the first run only collects the baseline.\n\
+\n\
+    --bandwidth-max=<mbit>              Link speed in MBit/s used by --bandwidth-warn/--bandwidth-crit for interfaces\n\
+                                        not reporting a speed, e.g. virtual interfaces.\n\
+\n\
+    --signal-warn=<dbm>                 Report WARNING (CRITICAL) if the signal level of a wireless interface is below\n\
+    --signal-crit=<dbm>                 <dbm>, e.g. --signal-warn=-70. Speed and duplex of wireless interfaces are\n\
+                                        never checked, the SSID, link quality and bitrate are shown instead.\n\
+\n\
+    --hysteresis=<pct>                  Keep a WARNING or CRITICAL state of --speed-warn/--speed-crit, --errors-*,\n\
+                                        --drops-* and --bandwidth-* until the value clears the threshold by <pct>\n\
+                                        percent of the threshold. The reported states are kept in the state file.\n\
+                                        Default: disabled\n\
+\n\
+    --bond-min-slaves=<n>               Report WARNING if less than <n> slaves of a bond interface are up, CRITICAL if\n\
+                                        no slave is up. Report UNKNOWN if the interface is not a bond.\n\
+\n\
+    --bond-mode=<mode>                  Expected bonding mode of a bond interface, either the name (e.g. 802.3ad) or\n\
+                                        the number (e.g. 4). Report WARNING (or CRITICAL if -C/--critical is used)\n\
+                                        if the mode differs. Report UNKNOWN if the interface is not a bond.\n\
+\n\
+    --bond-slave-status                 Report MII status and link failure count of every slave of a bond interface\n\
+                                        from /proc/net/bonding/<if>, one line per slave. Report WARNING for every slave\n\
+                                        which is not up and, in 802.3ad mode, for every slave not attached to the\n\
+                                        active aggregator. Link failures since the last run are taken from the state\n\
+                                        file. Report UNKNOWN if the interface is not a bond.\n\
+\n\
+    --check-lacp                        Report CRITICAL for every slave of a 802.3ad bond whose LACP partner hasn't set\n\
+                                        the synchronization, collecting and distributing bits of its port state, e.g.\n\
+                                        if the switch ports are not configured for LACP. Report WARNING if the slaves\n\
+                                        are in different aggregators. Report UNKNOWN if the interface is not a bond in\n\
+                                        802.3ad mode.\n\
+\n\
+    --team-min-ports=<n>                Report WARNING if less than <n> ports of a team interface are up, CRITICAL if\n\
+                                        no port is up. Report UNKNOWN if the interface is not a team.\n\
+\n\
+    --team-runner=<runner>              Expected runner of a team interface, one of activebackup, broadcast, lacp,\n\
+                                        loadbalance, random or roundrobin. The runner is read from the control socket\n\
+                                        of teamd. Report WARNING (or CRITICAL if -C/--critical is used) if the runner\n\
+                                        differs. Report UNKNOWN if the interface is not a team.\n\
+\n\
+    --bridge-ports=<if>,...             Ports expected to be enslaved to the bridge. Report CRITICAL for missing ports\n\
+                                        and WARNING for additional ports. Report UNKNOWN if the interface is not a bridge.\n\
+\n\
+    --bridge-ports-min-match            Don't report additional ports not listed by --bridge-ports.\n\
+\n\
+    --vlan=<id>                         Report CRITICAL if the interface is not a VLAN interface with the VLAN id <id>.\n\
+\n\
+    --vlan-parent=<if>                  Report CRITICAL if the interface is not a VLAN interface on the lower device <if>.\n\
+                                        --vlan and --vlan-parent report CRITICAL if the lower device is not up.\n\
+\n\
+    --by-address=<ip>                   Check the interface the address <ip> is currently assigned to. Report CRITICAL\n\
+                                        if no interface holds the address. If several interfaces hold the address, all\n\
+                                        of them are checked and WARNING is reported.\n\
+\n\
+    --expect-qdisc=<kind>               Report WARNING if the root qdisc of the interface is not <kind>, e.g. fq or\n\
+                                        fq_codel. If the root qdisc is mq, the qdiscs of all transmit queues are\n\
+                                        compared instead.\n\
+\n\
+    --expect-runtime-pm=on|off          Report WARNING if runtime power management of the device is not enabled (on,\n\
+                                        power/control is auto) or disabled (off, power/control is on). Report\n\
+                                        UNKNOWN if the device has no power management, e.g. virtual devices.\n\
+\n\
+    --driver=<name>                     Report WARNING if the kernel driver bound to the device is not <name>, e.g.\n\
+                                        ice. Virtual interfaces have no driver and are always reported.\n\
+\n\
+    --txqueuelen=<n>                    Report WARNING (CRITICAL if -C/--critical is used) if the transmit queue length\n\
+                                        of the interface is not <n>. The measured value is reported as performance data.\n\
+\n\
+    --ring-rx=<n>                       Report WARNING (CRITICAL if -C/--critical is used) if the size of the RX (TX)\n\
+    --ring-tx=<n>                       ring buffer isn't <n>, e.g. after a driver update reset it to its default.\n\
+                                        Report UNKNOWN if the NIC doesn't support the ethtool ring parameters. The\n\
+                                        sizes are reported as performance data, -v/--verbose shows the hardware maximums.\n\
+\n\
+    --feature=<name>=on|off             Report WARNING (CRITICAL if -C/--critical is used) if the offload feature <name>\n\
+                                        isn't on (off). <name> is a feature of ethtool -k like rx-gro or one of the short\n\
+                                        names gro, lro, tso, gso, sg, rx, rxvlan, txvlan, ntuple or rxhash. Features the\n\
+                                        driver doesn't allow to change are checked too. Report UNKNOWN for features the\n\
+                                        NIC doesn't know, -v/--verbose lists the available ones. Can be repeated.\n\
+\n\
+    --pause=<setting>=on|off[,...]      Expected flow control settings, e.g. rx=on,tx=off to match the switch port.\n\
+                                        <setting> is one of rx, tx and autoneg. Report WARNING (CRITICAL if -C/--critical\n\
+                                        is used) if a setting differs, UNKNOWN if the NIC doesn't support pause frames.\n\
+                                        The current settings are shown with -v/--verbose even without this option.\n\
+\n\
+    --queues-rx=<n>                     Report WARNING if the number of RX (TX) queues in /sys/class/net/<if>/queues\n\
+    --queues-tx=<n>                     isn't <n>. The counts are reported as performance data.\n\
+\n\
+    --queues-combined=<n>|auto          Report WARNING if the number of combined channels isn't <n>, or with auto the\n\
+                                        number of online CPUs (at most the maximum of the NIC). Report UNKNOWN if the\n\
+                                        NIC doesn't support the ethtool channel parameters.\n\
+\n\
+    --numa-node=<n>                     Report WARNING (CRITICAL if -C/--critical is used) if the device isn't attached\n\
+                                        to NUMA node <n>, e.g. after the NIC was moved to another slot, or has no NUMA\n\
+                                        affinity at all. Report UNKNOWN for interfaces without a device directory.\n\
+                                        The NUMA node is shown with -v/--verbose even without this option.\n\
+\n\
+    --sriov-vfs=<n>                     Report CRITICAL if the number of enabled SR-IOV virtual functions isn't <n>,\n\
+                                        e.g. after a firmware update reset it to 0. Report UNKNOWN if the NIC doesn't\n\
+                                        support SR-IOV. The enabled and supported VFs are reported as performance data.\n\
+\n\
+    --pcie-speed=<gt/s>                 Report WARNING (CRITICAL if -C/--critical is used) if the PCIe link of the\n\
+    --pcie-width=<lanes>                device runs below <gt/s> GT/s, e.g. 8 for Gen3 (or with less than <lanes>\n\
+                                        lanes). Virtual functions are checked against the link of their physical\n\
+                                        function, interfaces without a device directory are not checked.\n\
+\n\
+    --pcie-check-max                    Report WARNING (CRITICAL if -C/--critical is used) if the PCIe link runs below\n\
+                                        the maximum speed or width of device and slot.\n\
+\n\
+    --firmware=<version>                Report WARNING if the firmware version reported by the driver isn't <version>\n\
+    --firmware-min=<version>            (is older than <version>). Numbers in the version are compared as numbers, e.g.\n\
+                                        14.32.1010 is newer than 14.9.2000. Report UNKNOWN if the driver doesn't report\n\
+                                        a firmware version. Firmware and driver version are shown with -v/--verbose.\n\
+\n\
+    --master=<if>                       Report CRITICAL if the interface isn't enslaved to the bond, bridge or VRF <if>,\n\
+                                        e.g. a bond slave or bridge port which fell out of its master.\n\
+\n\
+    --no-master                         Report CRITICAL if the interface is enslaved to a bond, bridge or VRF, e.g. an\n\
+                                        uplink added to a bridge by accident.\n\
+\n\
+    --expect-altname=<name>             Report WARNING if <name> isn't an alternative name (ip link property) of the\n\
+                                        interface. -i/--interface accepts alternative names too, the interface is\n\
+                                        checked and reported by its primary name then.\n\
+\n\
+    --check-vlans-of=<if>               Check all VLAN interfaces of the parent device <if>, as found in\n\
+                                        /proc/net/vlan/config or by netlink, with the same options as all other\n\
+                                        interfaces. Report WARNING (CRITICAL if -C/--critical is used) if the state\n\
+                                        of a VLAN interface differs from the state of <if>. Expectations for every VLAN\n\
+                                        are given as usual, e.g. --addr-count=ipv4=1 for exactly one IPv4 address.\n\
+\n\
+    --expect-vlan-set=<id>,...          VLAN ids which must exist on the parent of --check-vlans-of. Report CRITICAL\n\
+                                        for missing VLANs and WARNING for additional VLANs.\n\
+\n\
+    --check-first-hop                   Check if the default gateway(s) of the interface are alive. The neighbor cache\n\
+                                        is consulted first, if the entry is stale or missing an ARP (IPv4) or NDP (IPv6)\n\
+                                        probe is sent. Report CRITICAL if no method finds the gateway alive.\n\
+\n\
+    --first-hop-methods=<list>          Comma separated list of methods to use for --check-first-hop, in this order.\n\
+                                        Methods are neighbor, arp and ndp. The probes require raw sockets (CAP_NET_RAW).\n\
+                                        Default: neighbor,arp,ndp\n\
+\n\
+    --first-hop-timeout=<sec>           Time for all steps of --check-first-hop, shared by the probes. Default: 3\n\
+\n\
+    --probe-gateway[=<ip>]              Send an ARP request (IPv4) or neighbor solicitation (IPv6) to <ip> or, without\n\
+                                        <ip>, to the default gateway(s) of the interface and report CRITICAL if no reply\n\
+                                        is received. The neighbor cache is not used. The round trip time is reported as\n\
+                                        performance data. Requires raw sockets (CAP_NET_RAW), UNKNOWN is reported if\n\
+                                        the socket can't be opened.\n\
+\n\
+    --probe-timeout=<sec>               Time to wait for the reply to --probe-gateway, shared if several gateways are\n\
+                                        probed. Default: 2\n\
+\n\
+    --probe-warn-ms=<ms>                Report WARNING if the gateway replies slower than <ms> milliseconds.\n\
+\n\
+    --uplink-check=<prefix>             Check a point-to-point uplink from the IPv4 provider block <prefix>: exactly\n\
+                                        one address from <prefix> with a /31 or /30 prefix must be assigned, the peer\n\
+                                        address of that subnet must be alive (using --first-hop-methods and\n\
+                                        --first-hop-timeout) and a default route via the peer must exist. Every failed\n\
+                                        part is reported as CRITICAL.\n\
+\n\
+    --frame-size-histogram=<sec>        Capture received frames for <sec> seconds and report the number of frames of\n\
+                                        64, 65-511, 512-1023, 1024-1518 and 1519-9216 bytes as performance data. If the\n\
+                                        MTU is above 1500, report WARNING if frames were received but none of them was\n\
+                                        a jumbo frame. Requires raw sockets (CAP_NET_RAW). Frames merged by receive\n\
+                                        offloading (GRO/LRO) are counted as jumbo frames. Limited by -t/--timeout.\n\
+\n\
+    --expect-address=<ip>[/<prefix>]    Report CRITICAL if the address <ip> is not assigned to the interface. If a\n\
+                                        prefix length is given, report WARNING if the address is assigned with a\n\
+                                        different prefix length. Can be used several times.\n\
+\n\
+    --expect-default-route[=<gateway>]  Report CRITICAL if no default route leaves through the interface, e.g. because\n\
+                                        a DHCP client moved it to another interface. If <gateway> is given, report\n\
+                                        WARNING if the default route uses a different gateway. The routing table is\n\
+                                        read from netlink or from /proc/net/route and /proc/net/ipv6_route.\n\
+    -4                                  Check the IPv4 default route. This is the default unless <gateway> is an IPv6\n\
+    -6                                  address. Check the IPv6 default route with -6, both with -4 -6.\n\
+\n\
+    --expect-neighbor=<ip>[=<mac>]      Report CRITICAL if <ip>, e.g. the VRRP partner, is missing from the neighbor\n\
+                                        table of the interface or its entry is FAILED or INCOMPLETE. If <mac> is given,\n\
+                                        report CRITICAL if the entry has another MAC address (replaced hardware or ARP\n\
+                                        spoofing). Can be used several times.\n\
+\n\
+    --expect-no-address                 Report CRITICAL if any address is assigned to the interface, e.g. for bond\n\
+                                        slaves or bridge ports. IPv6 link local addresses are ignored unless\n\
+    --strict                            --strict is used. Can't be combined with -a/--address-assigned or other\n\
+                                        address requirements like --expect-address or --addr-count.\n\
+\n\
+    -i <if>                             Ethernet interface to check. Several interfaces can be checked at once by\n\
+    --interface=<if>                    using a comma separated list, e.g. eth0,eth1. The worst state of all\n\
+                                        interfaces will be reported.\n\
+\n\
+    --interface-regex=<re>              Check all interfaces with a name matching the regular expression <re>.\n\
+                                        Shell style patterns like enp3s0f* can be used with -i/--interface too.\n\
+\n\
+    --all                               Check every interface except loopback. With --all-physical-only interfaces\n\
+    --all-physical-only                 without a device (bridges, VLANs, tunnels, ...) are skipped as well. The\n\
+                                        status line reports how many interfaces were checked and names only the\n\
+                                        interfaces with problems.\n\
+\n\
+    --exclude=<if>[,<if>,...]           Don't check interfaces selected by --all, shell style patterns or\n\
+                                        --interface-regex if their name matches one of the shell style patterns,\n\
+                                        e.g. docker*,veth*. Interfaces listed by name are always checked.\n\
+\n\
+    --min-matches=<n>                   Report CRITICAL if less than <n> interfaces match the interface\n\
+                                        patterns or regular expression.\n\
+\n\
+    -m <mtu>                            Expceted MTU value for interface. <mtu> can be a single value, a range like\n\
+    --mtu=<mtu>                         9000-9216 if jumbo frames are configured differently depending on the NIC, or a\n\
+                                        Nagios range, e.g. 1500:9000 for any MTU from 1500 to 9000 or @1:1499 to alert\n\
+                                        on MTUs below 1500. A plain number is an exact value, not a range from 0 to the\n\
+                                        number. The MTU is also compared with related devices: Report WARNING if it\n\
+                                        exceeds the MTU of a lower device (e.g. the parent of a VLAN) or, for a member\n\
+                                        of a bond or bridge, differs from the MTU of its master.\n\
+\n\
+    --no-mtu-consistency                Don't compare the MTU with lower devices and the master for -m/--mtu\n\
+\n\
+    --effective-mtu-to=<ip>             Compare the expected MTU against the MTU of the route to <ip> actually in effect\n\
+                                        (route MTU metric, e.g. \"mtu lock 1500\", or the interface MTU if unset)\n\
+\n\
+    -s <state>                          Expceted state. <state> is consists of <speed>[:<mode>] where <speed> is the\n\
+    --state=<state>                     expected negotiated link speed in MBit/s and <mode> is the negotiated link mode.\n\
+                                        <mode> can be one of \"half\", \"full\" or \"any\". Default: 1000:full\n\
+                                        <speed> can be a Nagios range like 1000: for at least 1000 MBit/s or\n\
+                                        @100:999 to alert on speeds from 100 to 999 MBit/s. A speed of 0 disables the check\n\
+                                        of speed and link mode, unless a link mode is given. A comma separated list like\n\
+                                        1000,2500,10000 accepts any of the listed speeds. An empty <speed>, e.g. -s :full,\n\
+                                        checks the link mode only. Speeds can be given with a m (MBit/s) or g (GBit/s)\n\
+                                        suffix, e.g. -s 10g:full, -s 2.5g: or --min-speed 2.5g\n\
+\n\
+    --duplex=<mode>                     Expected negotiated link mode, \"half\", \"full\" or \"any\" to not check it. The\n\
+                                        link mode is checked even if the link speed isn't. Can't be combined with a link\n\
+                                        mode in -s/--state\n\
+\n\
+    --min-speed=<mbit>                  Accept any negotiated link speed of at least <mbit> MBit/s, a lower speed is\n\
+                                        reported as WARNING (CRITICAL if -C/--critical is used). Same as -s <mbit>:\n\
+                                        and can't be combined with a link speed in -s/--state.\n\
+\n\
+    --speed-warn=<mbit>                 Report WARNING if the negotiated link speed is below <mbit> MBit/s\n\
+\n\
+    --speed-crit=<mbit>                 Report CRITICAL if the negotiated link speed is below <mbit> MBit/s. If\n\
+                                        --speed-warn or --speed-crit is used, the link speed is checked against them\n\
+                                        only and -C/--critical doesn't apply to it. They can't be combined with a\n\
+                                        link speed in -s/--state, use -s :<mode> to set the expected link mode.\n\
+\n\
+    --virtual                           Don't check speed and duplex, e.g. for veth, wireguard, tun or bridge devices.\n\
+                                        Without -s/--state, --duplex, --min-speed, --speed-warn or --speed-crit this is\n\
+                                        done for every interface without a device directory in sysfs. Can't be\n\
+                                        combined with any of these options.\n\
+\n\
+    --require-autoneg                   Report WARNING (CRITICAL if -C/--critical is used) if auto-negotiation of the\n\
+    --require-no-autoneg                link mode is off (on). Report UNKNOWN if the interface doesn't support the\n\
+                                        ethtool ioctl, e.g. virtual devices.\n\
+\n\
+    --fec=<mode>                        Expected forward error correction of the link, \"rs\", \"baser\", \"off\" or \"auto\"\n\
+                                        if the NIC follows the link partner. Report WARNING (CRITICAL if -C/--critical is\n\
+                                        used) if the active mode differs, UNKNOWN if the NIC doesn't report its FEC mode.\n\
+                                        The active mode is shown with -v/--verbose even without this option.\n\
+\n\
+    --no-promisc                        Report WARNING (CRITICAL if -C/--critical is used) if the interface is in\n\
+    --require-promisc                   promiscuous mode, e.g. left behind by tcpdump (is not in promiscuous mode,\n\
+                                        e.g. on sensors). The flags are shown with -v/--verbose.\n\
+\n\
+    --flags-set=<flag>,...              Report WARNING (CRITICAL if -C/--critical is used) if any of the interface flags\n\
+    --flags-clear=<flag>,...            is not set (is set), e.g. --flags-set=UP,RUNNING,MULTICAST --flags-clear=NOARP.\n\
+                                        Flags are UP, BROADCAST, DEBUG, LOOPBACK, POINTOPOINT, NOTRAILERS, RUNNING,\n\
+                                        NOARP, PROMISC, ALLMULTI, MASTER, SLAVE, MULTICAST, PORTSEL, AUTOMEDIA, DYNAMIC,\n\
+                                        LOWER_UP, DORMANT and ECHO.\n\
+\n\
+    --check-napi-balance                Check if threaded NAPI polling of the interface is only allowed on CPUs local\n\
+                                        to the network device (NUMA node). Report WARNING if non-local CPUs are allowed.\n\
+\n\
+    --check-wq-balance                  Check if unbound workqueues with a cpumask narrowed from the default, as found\n\
+                                        in /sys/bus/workqueue/devices/*/cpumask, are only allowed on CPUs local to the\n\
+                                        checked interfaces. Report WARNING if non-local CPUs are allowed. The\n\
+                                        workqueues are checked once for all interfaces.\n\
+\n\
+    --enrich-from-lldp                  If a problem was found, listen for LLDP announcements on the interface and add\n\
+                                        the name and port of the connected switch to the output. Listens for at most\n\
+                                        half of the time left of -t/--timeout, a neighbor announcing less often is\n\
+                                        only learned if it was cached by an earlier run.\n\
+\n\
+    --state-file=<file>                 File to keep information between two runs of the check, %i will be replaced\n\
+                                        by the interface name. Default: /var/tmp/check_ethernet_%i.state\n\
+\n\
+    --sysfs-root=<dir>                  Read interface information from <dir> instead of the kernel's sysfs tree,\n\
+                                        e.g. a copy taken from another host. Report UNKNOWN if <dir> is not a\n\
+                                        directory. Default: /sys/class/net, the state is read from netlink if it\n\
+                                        can't be read, like on a host without /sys.\n\
+\n\
+    -C                                  Report CRITICAL condition if state is below requested speed or duplex (or both) or MTU size\n\
+    --critical                          does not match. Default: Report WARNING state\n\
+\n\
+    --operstate-unknown-is=<state>      State to report if the kernel reports the operational state \"unknown\", which\n\
+                                        many virtual drivers do while working. With ok the interface is checked like\n\
+                                        an interface which is up. \"lowerlayerdown\" is always reported as CRITICAL\n\
+                                        and \"dormant\" as WARNING. One of ok, warning or critical. Default: UNKNOWN\n\
+\n\
+    --missing-state=<state>             State to report if the interface is not present, e.g. for hot-pluggable\n\
+                                        interfaces like USB modems, or vanishes while it is read. No other check\n\
+                                        (including -a) runs on a missing interface, whatever the state.\n\
+                                        One of ok, warning, critical or unknown. Default: CRITICAL\n\
+\n\
+    --lock-wait=<sec>                   Checks updating the state file or sending probes lock the interface against\n\
+    --lock-policy=wait|fail             overlapping check instances. \"wait\" waits up to <sec> seconds for the other\n\
+                                        instance, \"fail\" reports UNKNOWN at once. Default: wait, 10 seconds\n\
+\n\
+    --output=plain|json|checkmk         Output format. \"json\" prints a structured document with the interface\n\
+                                        state and all findings including their stable identifiers. \"checkmk\" prints\n\
+                                        a single line in the check_mk local check format, the state is part of the\n\
+                                        line and the exit code is always 0. The exit code is the same for all other\n\
+                                        output formats. Default: plain\n\
+\n\
+    --output-version=<n>                Version of the JSON output, the version is reported as \"schema_version\". Older\n\
+                                        versions are kept for a while after an incompatible change so consumers can\n\
+                                        migrate. Supported: 1. Default: 1\n\
+\n\
+    --service-name=<name>               Service name of the check_mk local check, must not contain whitespace.\n\
+                                        Default: ethernet_<interface>, names joined by _ for several interfaces\n\
+\n\
+    --no-status-prefix                  Don't start the first line of the plain output with the state of the check,\n\
+                                        e.g. \"CRITICAL - \", for tools which parse the messages\n\
+\n\
+    --max-output-length=<bytes>         Maximal length of the first line of the plain output. Performance data and\n\
+                                        then the messages of the least severe state are cut first. \"|\", line\n\
+                                        breaks and other control characters are always removed from messages.\n\
+                                        At least 14, default: 4096\n\
+\n\
+    --probe-subnet-peers                Report WARNING for every assigned non-link local IPv4 subnet without a REACHABLE,\n\
+                                        STALE, DELAY or PROBE entry of another host in the neighbor table.\n\
+\n\
+    --peer-probe-limit=<n>              If the neighbor table has no peer in a subnet, send ARP requests to up to <n>\n\
+                                        addresses of the subnet (at most 64, 200ms each). Default: 0 (no ARP sweep)\n\
+\n\
+    --prometheus-textfile=<file>        In addition to the regular output write the link state, speed, MTU, number of\n\
+                                        addresses and check status as metrics for the node_exporter textfile collector\n\
+                                        to <file>. The file is replaced atomically.\n\
+\n\
+    -t <sec>                            Global timeout, a positive number of seconds. If reading the interface state\n\
+    --timeout=<sec>                     doesn't complete in time, the check reports UNKNOWN (\"Check timed out after\n\
+                                        <sec> seconds\"). Active probes (first hop, subnet peers, LLDP) run concurrently,\n\
+                                        each within its own timeout limited by the time left. Probes not completed in\n\
+                                        time are reported as UNKNOWN, results of all other checks are kept. Default: 10\n\
+\n\
+    -v                                  Show every threshold comparison (speed, MTU, addresses, rates, bandwidth)\n\
+    --verbose                           with the observed value, the threshold as parsed, the violated bound and\n\
+                                        the resulting state, one comparison per line after the status line.\n\
+                                        Every message, including OK messages, is listed on its own line.\n\
+                                        Without it, messages are listed one per line only if there is more than\n\
+                                        one problem, the status line then only counts them.\n\
+\n\
+    --config=<file>                     Read options from the TOML file <file>. Keys are the long option names, e.g.\n\
+                                        interface = \"eth0\", mtu = 9000, critical = true, address-assigned = \"ipv4:1\".\n\
+                                        Options given on the command line take precedence, unknown keys are an error.\n\
+\n\
+    --dry-run                           Print the resolved check plan and exit: the selected interfaces and why they\n\
+                                        were selected, the checks with their thresholds, data sources, probes and\n\
+                                        the state, lock and acknowledgment files. Nothing is read from the interfaces,\n\
+                                        no probe is sent and no file is written. The plan is printed as text or as\n\
+                                        JSON according to --output.\n\
+\n\
+    --capabilities                      Print a JSON document describing this build and host (version, compiled\n\
+                                        features, available backends, output formats and versions, whether raw\n\
+                                        sockets can be opened and all finding identifiers) and exit.\n\
+\n\
+    -h                                  This text\n\
+    --help\n\
+\n");
+}
+
+// parsing is shared by the configuration file merging and Configuration::new
+fn parse_command_line(argv: &[String], opts: &Options) -> Result<getopts::Matches, CheckError> {
+    match opts.parse(&argv[1..]) {
+        Ok(o) => { Ok(o) },
+        Err(e) => { Err(CheckError::InvalidArgument(format!("Failed to parse command line: {}", e))) },
+    }
+}
+
+// options accepted more than once, like --expect-address
+fn repeatable(opts: &Options, name: &str) -> bool {
+    let probe = format!("--{}=", name);
+    opts.parse(&[probe.clone(), probe]).is_ok()
+}
+
+// 00:1B:21:3a:4c:5d or 00-1b-21-3a-4c-5d, returned like the kernel prints them
+fn parse_mac(s: &str) -> Option<String> {
+    let octets: Vec<&str> = s.split([':', '-']).collect();
+    if octets.len() != 6 || octets.iter().any(|o| o.len() != 2 || u8::from_str_radix(o, 16).is_err()) {
+        return None;
+    }
+    Some(octets.join(":").to_lowercase())
+}
+
+fn config_value(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(s) => { Some(s.clone()) },
+        toml::Value::Integer(i) => { Some(i.to_string()) },
+        toml::Value::Float(f) => { Some(f.to_string()) },
+        _ => { None },
+    }
+}
+
+// all command line options, the configuration file accepts the same names
+pub fn options() -> Options {
+    let mut options = Options::new();
+
+    options.optflag("h", "help", "Usage information.");
+    options.optflag("", "capabilities", "Describe this build and host as JSON.");
+    options.optopt("i", "interface", "Ethernet interface to check.", "");
+    options.optopt("", "interface-regex", "Check all interfaces matching the regular expression.", "");
+    options.optflag("", "all", "Check every interface except loopback.");
+    options.optflag("", "all-physical-only", "Skip interfaces without a device with --all.");
+    options.optopt("", "exclude", "Interfaces not to check, shell style patterns.", "");
+    options.optopt("", "min-matches", "Minimal number of interfaces matching the interface patterns.", "");
+    options.optopt("m", "mtu", "Expceted MTU value for interface.", "");
+    options.optflag("", "no-mtu-consistency", "Don't compare the MTU with lower devices and the master.");
+    options.optopt("s", "state", "Expceted state.", "");
+    options.optopt("", "duplex", "Expected link mode, half, full or any.", "");
+    options.optopt("", "min-speed", "Minimal link speed in MBit/s.", "");
+    options.optopt("", "speed-warn", "Report WARNING if link speed is below <mbit>.", "");
+    options.optopt("", "speed-crit", "Report CRITICAL if link speed is below <mbit>.", "");
+    options.optflag("", "virtual", "Don't check speed and duplex of virtual interfaces.");
+    options.optflag("C", "critical", "Report CRITICAL condition if state is below requested speed or duplex (or both) or MTU size does not match.");
+    options.optopt("", "operstate-unknown-is", "State to report for the operational state unknown.", "");
+    options.optopt("", "missing-state", "State to report for a missing interface.", "");
+    options.optopt("", "effective-mtu-to", "Compare MTU against the route MTU in effect for the destination.", "");
+    options.optflag("", "require-autoneg", "Report WARNING if auto-negotiation is off.");
+    options.optflag("", "require-no-autoneg", "Report WARNING if auto-negotiation is on.");
+    options.optopt("", "fec", "Expected forward error correction mode.", "");
+    options.optflag("", "no-promisc", "Report WARNING if the interface is in promiscuous mode.");
+    options.optflag("", "require-promisc", "Report WARNING if the interface is not in promiscuous mode.");
+    options.optopt("", "flags-set", "Interface flags which must be set.", "");
+    options.optopt("", "flags-clear", "Interface flags which must not be set.", "");
+    options.optflag("", "check-napi-balance", "Check if threaded NAPI polling is only allowed on CPUs local to the network device.");
+    options.optflag("", "check-wq-balance", "Check if unbound workqueues are only allowed on CPUs local to the network device.");
+    options.optflag("", "enrich-from-lldp", "Add the connected switch port learned from LLDP to the output of problems.");
+    options.optopt("", "state-file", "File to keep information between two runs of the check.", "");
+    options.optopt("", "sysfs-root", "Directory to read interface information from.", "");
+    options.optopt("", "output", "Output format.", "");
+    options.optopt("", "output-version", "Version of the JSON output.", "");
+    options.optopt("", "service-name", "Service name of the check_mk local check.", "");
+    options.optflag("", "no-status-prefix", "Don't start the output with the state of the check.");
+    options.optopt("", "max-output-length", "Maximal length of the first output line.", "");
+    options.optopt("", "errors-warn", "Report WARNING if the rx or tx error rate exceeds this value (errors/s).", "");
+    options.optopt("", "errors-crit", "Report CRITICAL if the rx or tx error rate exceeds this value (errors/s).", "");
+    options.optflag("", "check-first-hop", "Check if the default gateway of the interface is alive.");
+    options.optopt("", "frame-size-histogram", "Sample sizes of received frames for <sec> seconds.", "");
+    options.optopt("", "first-hop-methods", "Methods and their order for the first hop check.", "");
+    options.optflagopt("", "probe-gateway", "Send an ARP or NDP probe to the gateway of the interface.", "");
+    options.optopt("", "probe-timeout", "Time to wait for the reply of the gateway in seconds.", "");
+    options.optopt("", "probe-warn-ms", "Round trip time of the gateway probe in milliseconds to report WARNING.", "");
+    options.optopt("", "first-hop-timeout", "Time for all steps of the first hop check in seconds.", "");
+    options.optopt("", "uplink-check", "Provider prefix of a point-to-point uplink to check.", "");
+    options.optflag("", "cross-check-backends", "Report WARNING if sysfs and netlink disagree.");
+    options.optopt("", "backend", "Read the interface state from sysfs or netlink.", "");
+    options.optopt("", "snmp-host", "Read the interface state from a remote device with SNMPv2c.", "");
+    options.optopt("", "snmp-community", "SNMP community of the remote device.", "");
+    options.optopt("", "drops-warn", "Report WARNING if the rx or tx drop rate exceeds this value (packets/s).", "");
+    options.optopt("", "drops-crit", "Report CRITICAL if the rx or tx drop rate exceeds this value (packets/s).", "");
+    options.optopt("", "flaps-warn", "Report WARNING if the link changed more often per hour.", "");
+    options.optopt("", "flaps-crit", "Report CRITICAL if the link changed more often per hour.", "");
+    options.optopt("", "collisions-warn", "Report WARNING if the collision rate exceeds this value (collisions/s).", "");
+    options.optopt("", "collisions-crit", "Report CRITICAL if the collision rate exceeds this value (collisions/s).", "");
+    options.optmulti("", "stat", "Rate thresholds of a statistics counter, <name>=<warn>,<crit>. Can be repeated.", "");
+    options.optopt("", "lock-wait", "Seconds to wait for another instance checking the same interface.", "");
+    options.optopt("", "lock-policy", "Wait for or fail on a running instance checking the same interface.", "");
+    options.optopt("", "bandwidth-warn", "Report WARNING if rx or tx throughput exceeds this percentage of the link speed.", "");
+    options.optopt("", "bandwidth-crit", "Report CRITICAL if rx or tx throughput exceeds this percentage of the link speed.", "");
+    options.optopt("", "bandwidth-max", "Link speed in MBit/s for interfaces not reporting a speed.", "");
+    options.optopt("", "signal-warn", "Report WARNING if the signal level of a wireless interface is below <dbm>.", "");
+    options.optopt("", "signal-crit", "Report CRITICAL if the signal level of a wireless interface is below <dbm>.", "");
+    options.optopt("", "hysteresis", "Percent of a threshold a value has to clear it by to recover.", "");
+    options.optopt("", "alert-on-change", "Report WARNING if one of these metrics changed since the last run.", "");
+    options.optopt("", "bond-min-slaves", "Minimal number of active slaves of a bond interface.", "");
+    options.optopt("", "ack-file", "TOML file with acknowledged findings.", "");
+    options.optopt("", "bond-mode", "Expected bonding mode, as name or number.", "");
+    options.optflag("", "bond-slave-status", "Report the MII status of every slave of a bond interface.");
+    options.optflag("", "check-lacp", "Check the LACP partner state of every slave of a 802.3ad bond.");
+    options.optopt("", "team-min-ports", "Minimal number of active ports of a team interface.", "");
+    options.optopt("", "team-runner", "Expected runner of a team interface.", "");
+    options.optflag("", "probe-subnet-peers", "Report WARNING for assigned IPv4 subnets without any other host alive.");
+    options.optopt("", "peer-probe-limit", "Number of addresses per subnet to probe by ARP if the neighbor table has no peer.", "");
+    options.optopt("", "bridge-ports", "Ports expected to be enslaved to the bridge.", "");
+    options.optflag("", "bridge-ports-min-match", "Allow additional ports not listed by --bridge-ports.");
+    options.optopt("", "vlan", "Expected VLAN id of the interface.", "");
+    options.optopt("", "vlan-parent", "Expected lower device of the VLAN interface.", "");
+    options.optopt("", "by-address", "Check the interface(s) the address is assigned to.", "");
+    options.optopt("", "expect-qdisc", "Expected qdisc of the interface.", "");
+    options.optopt("", "expect-runtime-pm", "Expected runtime power management of the device.", "");
+    options.optopt("", "driver", "Expected kernel driver of the device.", "");
+    options.optopt("", "txqueuelen", "Expected transmit queue length of the interface.", "");
+    options.optopt("", "ring-rx", "Expected size of the RX ring buffer.", "");
+    options.optopt("", "ring-tx", "Expected size of the TX ring buffer.", "");
+    options.optmulti("", "feature", "Expected state of an offload feature, can be repeated.", "");
+    options.optopt("", "pause", "Expected flow control settings.", "");
+    options.optopt("", "queues-rx", "Expected number of RX queues.", "");
+    options.optopt("", "queues-tx", "Expected number of TX queues.", "");
+    options.optopt("", "queues-combined", "Expected number of combined channels or auto for the number of online CPUs.", "");
+    options.optopt("", "numa-node", "Expected NUMA node of the device.", "");
+    options.optopt("", "sriov-vfs", "Expected number of enabled SR-IOV virtual functions.", "");
+    options.optopt("", "pcie-speed", "Minimal PCIe link speed in GT/s.", "");
+    options.optopt("", "pcie-width", "Minimal PCIe link width in lanes.", "");
+    options.optflag("", "pcie-check-max", "Report WARNING if the PCIe link runs below its maximum.");
+    options.optopt("", "firmware", "Expected firmware version of the NIC.", "");
+    options.optopt("", "firmware-min", "Minimal firmware version of the NIC.", "");
+    options.optopt("", "master", "Bond, bridge or VRF the interface must be enslaved to.", "");
+    options.optflag("", "no-master", "Report CRITICAL if the interface is enslaved.");
+    options.optopt("", "expect-altname", "Alternative name which must be configured on the interface.", "");
+    options.optopt("", "check-vlans-of", "Check all VLAN interfaces of this parent device.", "");
+    options.optopt("", "expect-vlan-set", "VLAN ids which must exist on the parent of --check-vlans-of.", "");
+    options.optopt("", "prometheus-textfile", "Write metrics for the node_exporter textfile collector to this file.", "");
+    options.optflag("", "ipv6-require-global", "Don't count IPv6 unique local addresses as assigned addresses.");
+    options.optflag("", "ipv6-accept-ula", "Count IPv6 unique local addresses as assigned addresses.");
+    options.optmulti("", "ignore-prefix", "Addresses inside the prefix don't count for address checks, can be repeated.", "");
+    options.optmulti("", "addr-count", "Exact number of non link local addresses of a family, can be repeated.", "");
+    options.optflag("", "strict-none", "With -a none, IPv6 link local addresses are not ignored.");
+    options.optflag("", "ipv6-require-stable", "At least one non-temporary IPv6 address must be assigned.");
+    options.optflag("", "require-ipv6-link-local", "At least one IPv6 link local address must be assigned.");
+    options.optopt("", "tentative-grace", "Seconds an IPv6 address may stay tentative.", "");
+    options.optmulti("", "expect-address", "Address which must be assigned to the interface, can be repeated.", "");
+    options.optmulti("", "expect-neighbor", "Neighbor which must be resolved on the interface, can be repeated.", "");
+    options.optflagopt("", "expect-default-route", "Report CRITICAL if no default route leaves through the interface.", "");
+    options.optflag("4", "", "Check the IPv4 default route.");
+    options.optflag("6", "", "Check the IPv6 default route.");
+    options.optflag("", "expect-no-address", "Report CRITICAL if any address is assigned to the interface.");
+    options.optflag("", "strict", "Don't ignore IPv6 link local addresses for --expect-no-address.");
+    options.optopt("t", "timeout", "Global timeout in seconds.", "");
+    options.optflag("v", "verbose", "Show every threshold comparison.");
+    options.optopt("", "config", "Read options from a TOML configuration file.", "");
+    options.optflag("", "dry-run", "Print the resolved check plan without checking anything.");
+    options.optopt("a", "address-assigned", "Check if non-link local address has been assigned to the interface.", "");
+    options
+}
+
+
+// keys are the long option names, "_" may be used instead of "-". A value of true
+// enables a flag, arrays are repeated options or comma separated lists
+pub fn config_file_args(path: &str, opts: &Options) -> Result<Vec<(String, Vec<String>)>, CheckError> {
+    let content = match fs::read_to_string(path) {
+        Ok(s) => { s },
+        Err(e) => { return Err(CheckError::io(path, e)); },
+    };
+    let table = match content.parse::<toml::Value>() {
+        Ok(toml::Value::Table(t)) => { t },
+        Ok(_) => { return Err(CheckError::InvalidArgument(format!("Can't parse configuration file {}: not a table", path))); },
+        Err(e) => { return Err(CheckError::InvalidArgument(format!("Can't parse configuration file {}: {}", path, e))); },
+    };
+
+    let mut result: Vec<(String, Vec<String>)> = Vec::new();
+    for (key, value) in &table {
+        let name = key.replace('_', "-");
+        if name == "config" {
+            return Err(CheckError::InvalidArgument(format!("Key {} is not allowed in configuration file {}", key, path)));
+        }
+
+        let args: Vec<String> = match value {
+            toml::Value::Boolean(true) => { vec![format!("--{}", name)] },
+            toml::Value::Boolean(false) => { Vec::new() },
+            toml::Value::Array(a) => {
+                let values: Option<Vec<String>> = a.iter().map(config_value).collect();
+                match values {
+                    Some(v) if repeatable(opts, &name) => { v.iter().map(|v| format!("--{}={}", name, v)).collect() },
+                    Some(v) => { vec![format!("--{}={}", name, v.join(","))] },
+                    None => { return Err(CheckError::InvalidArgument(format!("Invalid value for key {} in configuration file {}", key, path))); },
+                }
+            },
+            v => {
+                match config_value(v) {
+                    Some(v) => { vec![format!("--{}={}", name, v)] },
+                    None => { return Err(CheckError::InvalidArgument(format!("Invalid value for key {} in configuration file {}", key, path))); },
+                }
+            },
+        };
+
+        // a false flag is validated like every other key
+        let probe = if args.is_empty() { vec![format!("--{}", name)] } else { args.clone() };
+        match opts.parse(&probe) {
+            Ok(_) => {},
+            Err(getopts::Fail::UnrecognizedOption(_)) => { return Err(CheckError::InvalidArgument(format!("Unknown key {} in configuration file {}", key, path))); },
+            Err(_) => { return Err(CheckError::InvalidArgument(format!("Invalid value for key {} in configuration file {}", key, path))); },
+        };
+        result.push((name, args));
+    }
+    Ok(result)
+}
+
+// options of the configuration file are placed in front of the command line, unless
+// the command line has the option itself
+pub fn merge_config_file(argv: &[String], opts: &Options) -> Result<Vec<String>, CheckError> {
+    // errors of the command line are reported by Configuration::new
+    let cli = match parse_command_line(argv, opts) {
+        Ok(o) => { o },
+        Err(_) => { return Ok(argv.to_vec()); },
+    };
+    let path = match cli.opt_str("config") {
+        Some(p) => { p },
+        None => { return Ok(argv.to_vec()); },
+    };
+
+    let mut result: Vec<String> = vec![argv[0].clone()];
+    for (name, args) in config_file_args(&path, opts)? {
+        if !cli.opt_present(&name) {
+            result.extend(args);
+        }
+    }
+    result.extend(argv[1..].iter().cloned());
+    Ok(result)
+}
+
+fn parse_rate_threshold(opt_match: &getopts::Matches, name: &str) -> Result<Option<f64>, CheckError> {
+    match opt_match.opt_str(name) {
+        Some(a) => {
+            match a.parse::<f64>() {
+                Ok(v) if v >= 0.0 => { Ok(Some(v)) },
+                _ => { Err(CheckError::InvalidArgument(format!("Invalid rate threshold \"{}\" of --{}", a, name))) },
+            }
+        },
+        None => { Ok(None) },
+    }
+}
+
+fn parse_signal_threshold(opt_match: &getopts::Matches, name: &str) -> Result<Option<f64>, CheckError> {
+    match opt_match.opt_str(name) {
+        Some(a) => {
+            match a.parse::<f64>() {
+                Ok(v) if v <= 0.0 => { Ok(Some(v)) },
+                _ => { Err(CheckError::InvalidArgument(format!("Invalid signal level threshold \"{}\" of --{}, must be a dBm value of 0 or below", a, name))) },
+            }
+        },
+        None => { Ok(None) },
+    }
+}
+
+impl Configuration {
+    // statistics counters which have to be tracked in the state file
+    pub fn counter_names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        if self.errors_warn.is_some() || self.errors_crit.is_some() {
+            names.push("rx_errors");
+            names.push("tx_errors");
+        }
+        if self.drops_warn.is_some() || self.drops_crit.is_some() {
+            names.push("rx_dropped");
+            names.push("tx_dropped");
+        }
+        if self.collisions_warn.is_some() || self.collisions_crit.is_some() {
+            names.push("collisions");
+        }
+        if self.bandwidth_warn.is_some() || self.bandwidth_crit.is_some() {
+            names.push("rx_bytes");
+            names.push("tx_bytes");
+        }
+        for stat in &self.stats {
+            if !names.contains(&stat.name.as_str()) {
+                names.push(&stat.name);
+            }
+        }
+        names
+    }
+
+    // state file updates and active probes must not run concurrently for an interface
+    pub fn needs_lock(&self) -> bool {
+        !self.counter_names().is_empty() || !self.alert_on_change.is_empty() || self.hysteresis.is_some() || self.check_first_hop || self.enrich_from_lldp
+            || (self.probe_subnet_peers && self.peer_probe_limit > 0) || self.bond_slave_status
+            || self.flaps_warn.is_some() || self.flaps_crit.is_some()
+    }
+
+    pub fn new(argv: &[String], opts: &Options) -> Result<Configuration, CheckError> {
+        let opt_match = parse_command_line(argv, opts)?;
+
+        if opt_match.opt_present("h") {
+            usage();
+            process::exit(STATE_OK);
+        }
+
+        if opt_match.opt_present("capabilities") {
+            let ids: Vec<&'static str> = FindingId::ALL.iter().map(|id| id.as_str()).collect();
+            match serde_json::to_string(&capabilities::probe(ids)) {
+                Ok(v) => { println!("{}", v); },
+                Err(_) => { return Err(CheckError::invalid("Can't encode capabilities as JSON")); },
+            };
+            process::exit(STATE_OK);
+        }
+
+        let interface: Vec<String> = match opt_match.opt_str("i") {
+            Some(a) => { a.split(',').filter(|i| !i.is_empty()).map(|i| i.to_string()).collect() },
+            None => { Vec::new() },
+        };
+
+        let mtu: Option<threshold::Range> = match opt_match.opt_str("m") {
+            Some(a) => {
+                match mtu_range(&a) {
+                    Some(Ok(v)) => { Some(v) },
+                    Some(Err(e)) => { return Err(e); },
+                    None => {
+                        match threshold::Range::parse(&a) {
+                            Ok(v) => { enabled_range(v) },
+                            Err(_) => { return Err(CheckError::invalid("Invalid MTU, must be an integer or a range")); },
+                        }
+                    },
+                }
+            },
+            None => { None },
+        };
+
+        let mtu_consistency = mtu.is_some() && !opt_match.opt_present("no-mtu-consistency");
+
+        let state_ = match opt_match.opt_str("s") {
+            Some(a) => { a },
+            // full duplex is the default link mode, see below
+            None => { "1000".to_string() },
+        };
+        // the link mode is separated by the last colon, everything in front of it
+        // is the speed or a range of speeds
+        let mut mode_: Option<&str> = None;
+        let mut speed_ = state_.as_str();
+        if let Some(pos) = state_.rfind(':') {
+            let mode = &state_[pos + 1..];
+            if mode == "half" || mode == "full" || mode == "any" {
+                mode_ = Some(mode);
+                speed_ = &state_[..pos];
+            }
+        }
+
+        // a list of speeds replaces the single speed or range, -s :<mode> checks the
+        // link mode only
+        let mut allowed_speeds: Vec<i32> = Vec::new();
+        let speed: Option<threshold::Range> = if speed_.is_empty() {
+            None
+        } else if speed_.contains(',') {
+            for s in speed_.split(',') {
+                match parse_speed(s) {
+                    Some(v) if v > 0.0 => { allowed_speeds.push(v as i32); },
+                    _ => { return Err(CheckError::invalid("Invalid list of link speeds, must be a comma separated list of positive speeds")); },
+                };
+            }
+            None
+        } else {
+            match speed_range(speed_).map(|r| threshold::Range::parse(&r)) {
+                Some(Ok(v)) => { enabled_range(v) },
+                _ => { return Err(CheckError::invalid("Invalid link speed, must be a speed like 1000 or 10g or a range")); },
+            }
+        };
+
+        // -s 0 without a link mode disables speed and link mode like it always did
+        let speed_disabled = !speed_.is_empty() && speed.is_none() && allowed_speeds.is_empty();
+
+        let speed_warn: Option<u32> = match opt_match.opt_str("speed-warn") {
+            Some(a) => {
+                match parse_speed(&a) {
+                    Some(v) => { Some(v as u32) },
+                    None => { return Err(CheckError::invalid("Can't convert warning threshold for link speed to a speed like 1000 or 10g")); },
+                }
+            },
+            None => { None },
+        };
+        let speed_crit: Option<u32> = match opt_match.opt_str("speed-crit") {
+            Some(a) => {
+                match parse_speed(&a) {
+                    Some(v) => { Some(v as u32) },
+                    None => { return Err(CheckError::invalid("Can't convert critical threshold for link speed to a speed like 1000 or 10g")); },
+                }
+            },
+            None => { None },
+        };
+
+        let min_speed: Option<u32> = match opt_match.opt_str("min-speed") {
+            Some(a) => {
+                match parse_speed(&a) {
+                    Some(v) => { Some(v as u32) },
+                    None => { return Err(CheckError::invalid("Can't convert minimal link speed to a speed like 1000 or 10g")); },
+                }
+            },
+            None => { None },
+        };
+        if min_speed.is_some() && (speed_warn.is_some() || speed_crit.is_some()) {
+            return Err(CheckError::invalid("--min-speed can't be combined with --speed-warn or --speed-crit"));
+        }
+
+        // the thresholds replace the speed of -s, only the link mode can be given with -s then
+        let speed = if let Some(v) = min_speed {
+            if opt_match.opt_present("s") && !speed_.is_empty() {
+                return Err(CheckError::invalid("--min-speed can't be combined with a link speed in -s/--state, use -s :<mode> for the link mode"));
+            }
+            // any speed at or above the minimum is OK
+            Some(threshold::Range{ start: Some(v as f64), end: None, inside: false })
+        } else if speed_warn.is_some() || speed_crit.is_some() {
+            if opt_match.opt_present("s") && !speed_.is_empty() {
+                return Err(CheckError::invalid("--speed-warn and --speed-crit can't be combined with a link speed in -s/--state, use -s :<mode> for the link mode"));
+            }
+            if let (Some(w), Some(c)) = (speed_warn, speed_crit) {
+                if c > w {
+                    return Err(CheckError::invalid("Critical threshold for link speed must not be above the warning threshold"));
+                }
+            }
+            None
+        } else {
+            speed
+        };
+
+        // the link mode is checked independent of the speed
+        let duplex: Option<String> = match (opt_match.opt_str("duplex"), mode_) {
+            (Some(_), Some(_)) => { return Err(CheckError::invalid("--duplex can't be combined with a link mode in -s/--state")); },
+            (Some(a), None) => {
+                match a.as_str() {
+                    "half" | "full" => { Some(a) },
+                    "any" => { None },
+                    _ => { return Err(CheckError::invalid("Invalid link mode, must be half, full or any")); },
+                }
+            },
+            (None, Some("any")) => { None },
+            (None, Some(m)) => { Some(m.to_string()) },
+            (None, None) if speed_disabled => { None },
+            (None, None) => { Some("full".to_string()) },
+        };
+
+        let speed_requested = opt_match.opt_present("s") || opt_match.opt_present("duplex") || min_speed.is_some() || speed_warn.is_some() || speed_crit.is_some();
+        let virtual_interface = if opt_match.opt_present("virtual") {
+            if speed_requested {
+                return Err(CheckError::invalid("--virtual can't be combined with -s/--state, --duplex, --min-speed, --speed-warn or --speed-crit"));
+            }
+            Some(true)
+        } else if speed_requested {
+            Some(false)
+        } else {
+            None
+        };
+
+        let mut report_critical: bool = false;
+
+        if opt_match.opt_present("C") {
+            report_critical = true;
+        };
+
+
+        let autoneg = match (opt_match.opt_present("require-autoneg"), opt_match.opt_present("require-no-autoneg")) {
+            (true, true) => { return Err(CheckError::invalid("--require-autoneg and --require-no-autoneg can't be combined")); },
+            (true, false) => { Some(true) },
+            (false, true) => { Some(false) },
+            (false, false) => { None },
+        };
+
+        let fec = match opt_match.opt_str("fec") {
+            Some(a) => {
+                match a.as_str() {
+                    "rs" | "baser" | "off" | "auto" => { Some(a) },
+                    _ => { return Err(CheckError::invalid("Invalid FEC mode, must be rs, baser, off or auto")); },
+                }
+            },
+            None => { None },
+        };
+
+        let promisc = match (opt_match.opt_present("require-promisc"), opt_match.opt_present("no-promisc")) {
+            (true, true) => { return Err(CheckError::invalid("--require-promisc and --no-promisc can't be combined")); },
+            (true, false) => { Some(true) },
+            (false, true) => { Some(false) },
+            (false, false) => { None },
+        };
+
+        let mut flags_set: u32 = 0;
+        let mut flags_clear: u32 = 0;
+        for (option, mask) in [("flags-set", &mut flags_set), ("flags-clear", &mut flags_clear)] {
+            if let Some(a) = opt_match.opt_str(option) {
+                for name in a.split(',').filter(|n| !n.is_empty()) {
+                    match interface::flag_bit(name) {
+                        Some(bit) => { *mask |= bit; },
+                        None => { return Err(CheckError::invalid("Unknown interface flag, supported flags are UP, BROADCAST, DEBUG, LOOPBACK, POINTOPOINT, NOTRAILERS, RUNNING, NOARP, PROMISC, ALLMULTI, MASTER, SLAVE, MULTICAST, PORTSEL, AUTOMEDIA, DYNAMIC, LOWER_UP, DORMANT and ECHO")); },
+                    };
+                }
+            }
+        }
+        if flags_set & flags_clear != 0 {
+            return Err(CheckError::invalid("A flag can't be required to be set and cleared at the same time"));
+        }
+
+        let check_napi_balance = opt_match.opt_present("check-napi-balance");
+        let check_wq_balance = opt_match.opt_present("check-wq-balance");
+
+        let effective_mtu_to: Option<IpAddr> = match opt_match.opt_str("effective-mtu-to") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Some(v) },
+                    Err(_) => { return Err(CheckError::invalid("Invalid destination address for effective MTU check")); },
+                }
+            },
+            None => { None },
+        };
+
+        // ip, ipv4 or ipv6, optionally with the minimal number of addresses like ipv4:1,ipv6:2,
+        // none is the same as --expect-no-address
+        let mut address_minimums: Vec<AddressMinimum> = Vec::new();
+        let mut address_none = false;
+        if let Some(a) = opt_match.opt_str("a") {
+            for part in a.split(',').filter(|p| !p.is_empty()) {
+                if part == "none" {
+                    address_none = true;
+                    continue;
+                }
+                let mut fields = part.splitn(2, ':');
+                let family = match fields.next() {
+                    Some("ip") => { AddressFamily::Any },
+                    Some("ipv4") => { AddressFamily::Ipv4 },
+                    Some("ipv6") => { AddressFamily::Ipv6 },
+                    _ => { return Err(CheckError::invalid("Invalid parameter for address assignment check")); },
+                };
+                let (count, explicit) = match fields.next() {
+                    Some(c) => {
+                        match c.parse() {
+                            Ok(v) if v > 0 => { (v, true) },
+                            _ => { return Err(CheckError::invalid("Invalid minimal number of addresses for address assignment check")); },
+                        }
+                    },
+                    None => { (1, false) },
+                };
+                address_minimums.push(AddressMinimum{ family, count, explicit });
+            }
+        }
+
+        // fewer addresses than expected are CRITICAL, more are WARNING unless -C is used
+        let mut address_counts: Vec<AddressCount> = Vec::new();
+        for a in opt_match.opt_strs("addr-count") {
+            let (family, count) = match a.split_once('=') {
+                Some(v) => { v },
+                None => { return Err(CheckError::invalid("Invalid parameter for address count check, expected <family>=<n>")); },
+            };
+            let family = match family {
+                "ip" => { AddressFamily::Any },
+                "ipv4" => { AddressFamily::Ipv4 },
+                "ipv6" => { AddressFamily::Ipv6 },
+                _ => { return Err(CheckError::invalid("Invalid address family for address count check")); },
+            };
+            let count = match count.parse() {
+                Ok(v) => { v },
+                Err(_) => { return Err(CheckError::invalid("Can't convert number of addresses for address count check to an integer")); },
+            };
+            if address_counts.iter().any(|c| c.family == family) {
+                return Err(CheckError::invalid("Address count check given twice for the same address family"));
+            }
+            address_counts.push(AddressCount{ family, count });
+        }
+
+        let interface_regex = match opt_match.opt_str("interface-regex") {
+            Some(a) => {
+                match regex::Regex::new(&a) {
+                    Ok(v) => { Some(v) },
+                    Err(_) => { return Err(CheckError::invalid("Invalid regular expression for interface names")); },
+                }
+            },
+            None => { None },
+        };
+
+        let all = opt_match.opt_present("all");
+        let all_physical_only = opt_match.opt_present("all-physical-only");
+        if all_physical_only && !all {
+            return Err(CheckError::invalid("--all-physical-only requires --all"));
+        }
+
+        let mut exclude: Vec<glob::Pattern> = Vec::new();
+        if let Some(a) = opt_match.opt_str("exclude") {
+            for p in a.split(',').filter(|p| !p.is_empty()) {
+                match glob::Pattern::new(p) {
+                    Ok(v) => { exclude.push(v); },
+                    Err(_) => { return Err(CheckError::invalid("Invalid pattern for excluded interfaces")); },
+                };
+            }
+        }
+
+        let min_matches: usize = match opt_match.opt_str("min-matches") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { v },
+                    Err(_) => { return Err(CheckError::invalid("Can't convert minimal number of matching interfaces to an integer")); },
+                }
+            },
+            None => { 0 },
+        };
+
+        let state_file = match opt_match.opt_str("state-file") {
+            Some(a) => { a },
+            None => { DEFAULT_STATE_FILE.to_string() },
+        };
+
+        // a missing /sys/class/net falls back to netlink, a missing fixture tree is a typo
+        let sysfs_root = match opt_match.opt_str("sysfs-root") {
+            Some(a) => {
+                if !Path::new(&a).is_dir() {
+                    return Err(CheckError::InvalidArgument(format!("Sysfs root {} is not a directory", a)));
+                }
+                a.trim_end_matches('/').to_string()
+            },
+            None => { DEFAULT_SYSFS_ROOT.to_string() },
+        };
+
+        let enrich_from_lldp = opt_match.opt_present("enrich-from-lldp");
+
+        let output = match opt_match.opt_str("output") {
+            Some(a) => {
+                match a.as_str() {
+                    "plain" => { OutputFormat::Plain },
+                    "json" => { OutputFormat::Json },
+                    "checkmk" => { OutputFormat::Checkmk },
+                    _ => { return Err(CheckError::invalid("Invalid output format")); },
+                }
+            },
+            None => { OutputFormat::Plain },
+        };
+
+        // consumers can pin an older shape of the JSON output during migrations
+        let output_version: u32 = match opt_match.opt_str("output-version") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) if (output::MIN_SCHEMA_VERSION..=output::SCHEMA_VERSION).contains(&v) => { v },
+                    _ => { return Err(CheckError::invalid("Unsupported output version")); },
+                }
+            },
+            None => { output::SCHEMA_VERSION },
+        };
+
+        // fields of a local check line are separated by spaces
+        let status_prefix = !opt_match.opt_present("no-status-prefix");
+        let max_output_length: usize = match opt_match.opt_str("max-output-length") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) if v >= MIN_OUTPUT_LENGTH => { v },
+                    _ => { return Err(CheckError::InvalidArgument(format!("Invalid maximal output length, must be at least {} bytes", MIN_OUTPUT_LENGTH))); },
+                }
+            },
+            None => { DEFAULT_MAX_OUTPUT_LENGTH },
+        };
+
+        let service_name = match opt_match.opt_str("service-name") {
+            Some(a) => {
+                if a.is_empty() || a.contains(char::is_whitespace) {
+                    return Err(CheckError::invalid("Invalid service name, must not be empty or contain whitespace"));
+                }
+                if output != OutputFormat::Checkmk {
+                    return Err(CheckError::invalid("Service name requires --output=checkmk"));
+                }
+                Some(a)
+            },
+            None => { None },
+        };
+
+        let expect_no_address = opt_match.opt_present("expect-no-address") || address_none;
+
+        let ipv6_require_global = opt_match.opt_present("ipv6-require-global");
+        if ipv6_require_global && opt_match.opt_present("ipv6-accept-ula") {
+            return Err(CheckError::invalid("--ipv6-require-global and --ipv6-accept-ula are mutually exclusive"));
+        }
+
+        let mut expect_address: Vec<(IpAddr, Option<u8>)> = Vec::new();
+        for a in opt_match.opt_strs("expect-address") {
+            if a.contains('/') {
+                match a.parse::<ipnetwork::IpNetwork>() {
+                    Ok(n) => { expect_address.push((n.ip(), Some(n.prefix()))); },
+                    Err(_) => { return Err(CheckError::invalid("Invalid expected address")); },
+                };
+            } else {
+                match a.parse() {
+                    Ok(v) => { expect_address.push((v, None)); },
+                    Err(_) => { return Err(CheckError::invalid("Invalid expected address")); },
+                };
+            }
+        }
+        let strict = opt_match.opt_present("strict") || opt_match.opt_present("strict-none");
+
+        let mut expect_neighbors: Vec<(IpAddr, Option<String>)> = Vec::new();
+        for a in opt_match.opt_strs("expect-neighbor") {
+            let (ip, mac) = match a.split_once('=') {
+                Some((ip, mac)) => {
+                    match parse_mac(mac) {
+                        Some(m) => { (ip, Some(m)) },
+                        None => { return Err(CheckError::invalid("Invalid MAC address for --expect-neighbor")); },
+                    }
+                },
+                None => { (a.as_str(), None) },
+            };
+            match ip.parse() {
+                Ok(v) => { expect_neighbors.push((v, mac)); },
+                Err(_) => { return Err(CheckError::invalid("Invalid address for --expect-neighbor")); },
+            };
+        }
+
+        let mut ignore_prefixes: Vec<ipnetwork::IpNetwork> = Vec::new();
+        for a in opt_match.opt_strs("ignore-prefix") {
+            match a.parse::<ipnetwork::IpNetwork>() {
+                Ok(n) => { ignore_prefixes.push(n); },
+                Err(_) => { return Err(CheckError::invalid("Invalid prefix to ignore for address checks")); },
+            };
+        }
+
+        let expect_default_route = opt_match.opt_present("expect-default-route");
+        let default_gateway: Option<IpAddr> = match opt_match.opt_str("expect-default-route") {
+            Some(v) => {
+                match v.parse() {
+                    Ok(v) => { Some(v) },
+                    Err(_) => { return Err(CheckError::invalid("Invalid gateway for --expect-default-route")); },
+                }
+            },
+            None => { None },
+        };
+
+        let mut default_route_families: Vec<AddressFamily> = Vec::new();
+        if opt_match.opt_present("4") {
+            default_route_families.push(AddressFamily::Ipv4);
+        }
+        if opt_match.opt_present("6") {
+            default_route_families.push(AddressFamily::Ipv6);
+        }
+        if !default_route_families.is_empty() && !expect_default_route {
+            return Err(CheckError::invalid("-4 and -6 require --expect-default-route"));
+        }
+        // the family of the gateway if one was given, IPv4 otherwise
+        if default_route_families.is_empty() {
+            match default_gateway {
+                Some(IpAddr::V6(_)) => { default_route_families.push(AddressFamily::Ipv6); },
+                _ => { default_route_families.push(AddressFamily::Ipv4); },
+            };
+        }
+        if let Some(gw) = default_gateway {
+            let family = if gw.is_ipv4() { AddressFamily::Ipv4 } else { AddressFamily::Ipv6 };
+            if default_route_families != [family] {
+                return Err(CheckError::invalid("Expected default gateway doesn't match the address family selected by -4/-6"));
+            }
+        }
+
+        let prometheus_textfile = opt_match.opt_str("prometheus-textfile");
+
+        let errors_warn = parse_rate_threshold(&opt_match, "errors-warn")?;
+        let errors_crit = parse_rate_threshold(&opt_match, "errors-crit")?;
+        let drops_warn = parse_rate_threshold(&opt_match, "drops-warn")?;
+        let drops_crit = parse_rate_threshold(&opt_match, "drops-crit")?;
+        let flaps_warn = parse_rate_threshold(&opt_match, "flaps-warn")?;
+        let flaps_crit = parse_rate_threshold(&opt_match, "flaps-crit")?;
+        let collisions_warn = parse_rate_threshold(&opt_match, "collisions-warn")?;
+        let collisions_crit = parse_rate_threshold(&opt_match, "collisions-crit")?;
+
+        let mut stats: Vec<StatThreshold> = Vec::new();
+        for a in opt_match.opt_strs("stat") {
+            let (name, warn, crit) = match a.split_once('=').and_then(|(n, t)| t.split_once(',').map(|(w, c)| (n, w, c))) {
+                Some(v) => { v },
+                None => { return Err(CheckError::invalid("Invalid parameter for --stat, expected <name>=<warn>,<crit>")); },
+            };
+            // the name becomes part of a path below statistics/
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(CheckError::InvalidArgument(format!("Invalid counter name \"{}\" of --stat", name)));
+            }
+            if stats.iter().any(|s| s.name == name) {
+                return Err(CheckError::InvalidArgument(format!("--stat given twice for counter {}", name)));
+            }
+            let mut thresholds = [None, None];
+            for (t, raw) in thresholds.iter_mut().zip([warn, crit].iter()) {
+                if raw.is_empty() {
+                    continue;
+                }
+                match raw.parse::<f64>() {
+                    Ok(v) if v >= 0.0 => { *t = Some(v); },
+                    _ => { return Err(CheckError::InvalidArgument(format!("Invalid rate threshold \"{}\" of --stat {}", raw, name))); },
+                };
+            }
+            if thresholds == [None, None] {
+                return Err(CheckError::InvalidArgument(format!("No threshold given for --stat {}", name)));
+            }
+            let dedicated = [
+                (&["rx_errors", "tx_errors"][..], errors_warn.is_some() || errors_crit.is_some(), "--errors-warn/--errors-crit"),
+                (&["rx_dropped", "tx_dropped"][..], drops_warn.is_some() || drops_crit.is_some(), "--drops-warn/--drops-crit"),
+                (&["collisions"][..], collisions_warn.is_some() || collisions_crit.is_some(), "--collisions-warn/--collisions-crit"),
+            ];
+            if let Some((_, _, options)) = dedicated.iter().find(|(names, used, _)| *used && names.contains(&name)) {
+                return Err(CheckError::InvalidArgument(format!("Counter {} of --stat is already checked by {}", name, options)));
+            }
+            stats.push(StatThreshold{ name: name.to_string(), warn: thresholds[0], crit: thresholds[1] });
+        }
+        let bandwidth_warn = parse_rate_threshold(&opt_match, "bandwidth-warn")?;
+        let bandwidth_crit = parse_rate_threshold(&opt_match, "bandwidth-crit")?;
+
+        let bandwidth_max = match opt_match.opt_str("bandwidth-max") {
+            Some(a) => {
+                match a.parse::<f64>() {
+                    Ok(v) if v > 0.0 => { Some(v) },
+                    _ => { return Err(CheckError::invalid("Invalid maximal bandwidth")); },
+                }
+            },
+            None => { None },
+        };
+
+        let signal_warn = parse_signal_threshold(&opt_match, "signal-warn")?;
+        let signal_crit = parse_signal_threshold(&opt_match, "signal-crit")?;
+        if let (Some(w), Some(c)) = (signal_warn, signal_crit) {
+            if c > w {
+                return Err(CheckError::invalid("Critical threshold for the signal level must not be above the warning threshold"));
+            }
+        }
+
+        let hysteresis = match opt_match.opt_str("hysteresis") {
+            Some(a) => {
+                match a.parse::<f64>() {
+                    Ok(v) if (0.0..100.0).contains(&v) => { Some(v) },
+                    _ => { return Err(CheckError::invalid("Invalid hysteresis, must be a percentage below 100")); },
+                }
+            },
+            None => { None },
+        };
+
+        let check_first_hop = opt_match.opt_present("check-first-hop");
+        let cross_check_backends = opt_match.opt_present("cross-check-backends");
+
+        let backend = match opt_match.opt_str("backend") {
+            Some(a) => {
+                match a.as_str() {
+                    "sysfs" => { Backend::Sysfs },
+                    "netlink" => { Backend::Netlink },
+                    _ => { return Err(CheckError::invalid("Invalid backend")); },
+                }
+            },
+            None => { Backend::Sysfs },
+        };
+
+        let snmp_host = opt_match.opt_str("snmp-host");
+        let snmp_community = opt_match.opt_str("snmp-community").unwrap_or_else(|| "public".to_string());
+        if snmp_host.is_some() {
+            if let Some(name) = SNMP_UNSUPPORTED.iter().find(|n| opt_match.opt_present(n)) {
+                return Err(CheckError::InvalidArgument(format!("--{} can't be used with --snmp-host", name)));
+            }
+            if interface.iter().any(|i| i.contains('*') || i.contains('?') || i.contains('[')) {
+                return Err(CheckError::invalid("Interface name patterns can't be used with --snmp-host"));
+            }
+        } else if opt_match.opt_present("snmp-community") {
+            return Err(CheckError::invalid("--snmp-community requires --snmp-host"));
+        }
+
+        let bond_min_slaves: Option<usize> = match opt_match.opt_str("bond-min-slaves") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Some(v) },
+                    Err(_) => { return Err(CheckError::invalid("Can't convert minimal number of bond slaves to an integer")); },
+                }
+            },
+            None => { None },
+        };
+
+        // mode names and numbers are both accepted
+        let bond_mode = match opt_match.opt_str("bond-mode") {
+            Some(a) => {
+                match bonding::Mode::parse(&a) {
+                    Some(v) => { Some(v) },
+                    None => { return Err(CheckError::invalid("Invalid bonding mode")); },
+                }
+            },
+            None => { None },
+        };
+
+        let bond_slave_status = opt_match.opt_present("bond-slave-status");
+        let check_lacp = opt_match.opt_present("check-lacp");
+
+        let team_min_ports: Option<usize> = match opt_match.opt_str("team-min-ports") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Some(v) },
+                    Err(_) => { return Err(CheckError::invalid("Can't convert minimal number of team ports to an integer")); },
+                }
+            },
+            None => { None },
+        };
+
+        let team_runner = match opt_match.opt_str("team-runner") {
+            Some(a) => {
+                if !team::RUNNERS.contains(&a.as_str()) {
+                    return Err(CheckError::invalid("Invalid team runner"));
+                }
+                Some(a)
+            },
+            None => { None },
+        };
+
+        let mut alert_on_change: Vec<String> = Vec::new();
+        if let Some(a) = opt_match.opt_str("alert-on-change") {
+            for m in a.split(',').filter(|m| !m.is_empty()) {
+                if !changes::METRICS.contains(&m) {
+                    return Err(CheckError::invalid("Invalid metric for change detection"));
+                }
+                if !alert_on_change.iter().any(|x| x == m) {
+                    alert_on_change.push(m.to_string());
+                }
+            }
+        }
+
+        let ack_file = opt_match.opt_str("ack-file");
+
+        let probe_subnet_peers = opt_match.opt_present("probe-subnet-peers");
+
+        let bridge_ports: Vec<String> = match opt_match.opt_str("bridge-ports") {
+            Some(a) => { a.split(',').filter(|p| !p.is_empty()).map(|p| p.to_string()).collect() },
+            None => { Vec::new() },
+        };
+        let bridge_ports_min_match = opt_match.opt_present("bridge-ports-min-match");
+
+        let vlan_id: Option<u16> = match opt_match.opt_str("vlan") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) if (1..=4094).contains(&v) => { Some(v) },
+                    _ => { return Err(CheckError::invalid("Invalid VLAN id")); },
+                }
+            },
+            None => { None },
+        };
+        let vlan_parent = opt_match.opt_str("vlan-parent");
+
+        let check_vlans_of = opt_match.opt_str("check-vlans-of");
+
+        let expect_qdisc = opt_match.opt_str("expect-qdisc");
+
+        let expect_runtime_pm = match opt_match.opt_str("expect-runtime-pm") {
+            Some(a) => {
+                match a.as_str() {
+                    "on" => { Some(true) },
+                    "off" => { Some(false) },
+                    _ => { return Err(CheckError::invalid("Invalid runtime power management state, must be on or off")); },
+                }
+            },
+            None => { None },
+        };
+
+        let driver = match opt_match.opt_str("driver") {
+            Some(ref a) if a.is_empty() => { return Err(CheckError::invalid("Driver name must not be empty")); },
+            v => { v },
+        };
+
+        let txqueuelen: Option<u64> = match opt_match.opt_str("txqueuelen") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Some(v) },
+                    Err(_) => { return Err(CheckError::invalid("Invalid transmit queue length")); },
+                }
+            },
+            None => { None },
+        };
+
+        let ring_rx: Option<u32> = match opt_match.opt_str("ring-rx") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Some(v) },
+                    Err(_) => { return Err(CheckError::invalid("Invalid RX ring buffer size")); },
+                }
+            },
+            None => { None },
+        };
+        let ring_tx: Option<u32> = match opt_match.opt_str("ring-tx") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Some(v) },
+                    Err(_) => { return Err(CheckError::invalid("Invalid TX ring buffer size")); },
+                }
+            },
+            None => { None },
+        };
+
+        let mut features: Vec<(String, bool)> = Vec::new();
+        for a in opt_match.opt_strs("feature") {
+            let (name, state) = match a.rsplit_once('=') {
+                Some(v) => { v },
+                None => { return Err(CheckError::invalid("Invalid parameter for feature check, expected <name>=on|off")); },
+            };
+            let on = match state {
+                "on" => { true },
+                "off" => { false },
+                _ => { return Err(CheckError::invalid("Invalid state for feature check, must be on or off")); },
+            };
+            if name.is_empty() {
+                return Err(CheckError::invalid("Feature name must not be empty"));
+            }
+            if features.iter().any(|(n, _)| ethtool::feature_name(n) == ethtool::feature_name(name)) {
+                return Err(CheckError::invalid("Feature check given twice for the same feature"));
+            }
+            features.push((name.to_string(), on));
+        }
+
+        let mut pause: Vec<(String, bool)> = Vec::new();
+        if let Some(a) = opt_match.opt_str("pause") {
+            for p in a.split(',') {
+                let (setting, on) = match p.split_once('=') {
+                    Some((s, "on")) => { (s, true) },
+                    Some((s, "off")) => { (s, false) },
+                    _ => { return Err(CheckError::invalid("Invalid parameter for pause frame check, expected <setting>=on|off")); },
+                };
+                if setting != "rx" && setting != "tx" && setting != "autoneg" {
+                    return Err(CheckError::invalid("Invalid pause frame setting, must be rx, tx or autoneg"));
+                }
+                if pause.iter().any(|(s, _)| s == setting) {
+                    return Err(CheckError::invalid("Pause frame setting given twice"));
+                }
+                pause.push((setting.to_string(), on));
+            }
+        }
+
+        let queues_rx: Option<u32> = match opt_match.opt_str("queues-rx") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Some(v) },
+                    Err(_) => { return Err(CheckError::invalid("Invalid number of RX queues")); },
+                }
+            },
+            None => { None },
+        };
+        let queues_tx: Option<u32> = match opt_match.opt_str("queues-tx") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Some(v) },
+                    Err(_) => { return Err(CheckError::invalid("Invalid number of TX queues")); },
+                }
+            },
+            None => { None },
+        };
+        let queues_combined: Option<QueueCount> = match opt_match.opt_str("queues-combined") {
+            Some(ref a) if a == "auto" => { Some(QueueCount::OnlineCpus) },
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Some(QueueCount::Exact(v)) },
+                    Err(_) => { return Err(CheckError::invalid("Invalid number of combined channels, must be an integer or auto")); },
+                }
+            },
+            None => { None },
+        };
+
+        let numa_node: Option<u32> = match opt_match.opt_str("numa-node") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Some(v) },
+                    Err(_) => { return Err(CheckError::invalid("Invalid NUMA node")); },
+                }
+            },
+            None => { None },
+        };
+
+        let sriov_vfs: Option<u32> = match opt_match.opt_str("sriov-vfs") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Some(v) },
+                    Err(_) => { return Err(CheckError::invalid("Invalid number of SR-IOV virtual functions")); },
+                }
+            },
+            None => { None },
+        };
+
+        let pcie_speed: Option<f64> = match opt_match.opt_str("pcie-speed") {
+            Some(a) => {
+                let raw = a.trim_end_matches("GT/s").trim();
+                match raw.parse::<f64>() {
+                    Ok(v) if v > 0.0 && v.is_finite() => { Some(v) },
+                    _ => { return Err(CheckError::invalid("Invalid PCIe link speed, must be a positive number of GT/s")); },
+                }
+            },
+            None => { None },
+        };
+        let pcie_width: Option<u32> = match opt_match.opt_str("pcie-width") {
+            Some(a) => {
+                match a.trim_start_matches('x').parse() {
+                    Ok(v) if v > 0 => { Some(v) },
+                    _ => { return Err(CheckError::invalid("Invalid PCIe link width, must be a positive number of lanes")); },
+                }
+            },
+            None => { None },
+        };
+        let pcie_check_max = opt_match.opt_present("pcie-check-max");
+
+        let firmware = opt_match.opt_str("firmware");
+        let firmware_min = opt_match.opt_str("firmware-min");
+        if firmware.is_some() && firmware_min.is_some() {
+            return Err(CheckError::invalid("--firmware and --firmware-min can't be combined"));
+        }
+        if firmware.as_deref() == Some("") || firmware_min.as_deref() == Some("") {
+            return Err(CheckError::invalid("Firmware version must not be empty"));
+        }
+
+        let master = match opt_match.opt_str("master") {
+            Some(ref a) if a.is_empty() => { return Err(CheckError::invalid("Master interface name must not be empty")); },
+            v => { v },
+        };
+        let no_master = opt_match.opt_present("no-master");
+        if master.is_some() && no_master {
+            return Err(CheckError::invalid("--master and --no-master can't be combined"));
+        }
+
+        let expect_altname = match opt_match.opt_str("expect-altname") {
+            Some(ref a) if a.is_empty() => { return Err(CheckError::invalid("Alternative name must not be empty")); },
+            v => { v },
+        };
+
+        let by_address: Option<IpAddr> = match opt_match.opt_str("by-address") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Some(v) },
+                    Err(_) => { return Err(CheckError::invalid("Invalid address for --by-address")); },
+                }
+            },
+            None => { None },
+        };
+
+        let expect_vlan_set: Option<Vec<u16>> = match opt_match.opt_str("expect-vlan-set") {
+            Some(a) => {
+                let mut ids: Vec<u16> = Vec::new();
+                for i in a.split(',').filter(|i| !i.is_empty()) {
+                    match i.trim().parse() {
+                        Ok(v) if (1..=4094).contains(&v) => { ids.push(v); },
+                        _ => { return Err(CheckError::invalid("Invalid VLAN id in expected VLAN set")); },
+                    };
+                }
+                ids.sort_unstable();
+                ids.dedup();
+                Some(ids)
+            },
+            None => { None },
+        };
+
+        let verbose = opt_match.opt_present("v");
+        let dry_run = opt_match.opt_present("dry-run");
+
+        let timeout = match opt_match.opt_str("t") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) if v > 0 => { Duration::from_secs(v) },
+                    _ => { return Err(CheckError::invalid("Invalid timeout")); },
+                }
+            },
+            None => { Duration::from_secs(DEFAULT_TIMEOUT) },
+        };
+
+        // the ARP sweep is off by default
+        let peer_probe_limit: usize = match opt_match.opt_str("peer-probe-limit") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) if v <= MAX_PEER_PROBE_LIMIT => { v },
+                    _ => { return Err(CheckError::invalid("Invalid number of addresses for the subnet peer ARP sweep")); },
+                }
+            },
+            None => { 0 },
+        };
+
+        let ipv6_require_stable = opt_match.opt_present("ipv6-require-stable");
+        let require_ipv6_link_local = opt_match.opt_present("require-ipv6-link-local");
+
+        let tentative_grace = match opt_match.opt_str("tentative-grace") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Duration::from_secs(v) },
+                    Err(_) => { return Err(CheckError::invalid("Can't convert grace period for tentative addresses to an integer")); },
+                }
+            },
+            None => { Duration::from_secs(DEFAULT_TENTATIVE_GRACE) },
+        };
+
+        let lock_wait = match opt_match.opt_str("lock-wait") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Duration::from_secs(v) },
+                    Err(_) => { return Err(CheckError::invalid("Can't convert lock wait time to an integer")); },
+                }
+            },
+            None => { Duration::from_secs(DEFAULT_LOCK_WAIT) },
+        };
+
+        let operstate_unknown = match opt_match.opt_str("operstate-unknown-is") {
+            Some(a) => {
+                match a.as_str() {
+                    "ok" => { STATE_OK },
+                    "warning" => { STATE_WARNING },
+                    "critical" => { STATE_CRITICAL },
+                    _ => { return Err(CheckError::invalid("Invalid state for operational state unknown, must be ok, warning or critical")); },
+                }
+            },
+            None => { STATE_UNKNOWN },
+        };
+
+        let missing_state = match opt_match.opt_str("missing-state") {
+            Some(a) => {
+                match a.as_str() {
+                    "ok" => { STATE_OK },
+                    "warning" => { STATE_WARNING },
+                    "critical" => { STATE_CRITICAL },
+                    "unknown" => { STATE_UNKNOWN },
+                    _ => { return Err(CheckError::invalid("Invalid state for missing interface, must be ok, warning, critical or unknown")); },
+                }
+            },
+            None => { STATE_CRITICAL },
+        };
+
+        let lock_policy = match opt_match.opt_str("lock-policy") {
+            Some(a) => {
+                match a.as_str() {
+                    "wait" => { LockPolicy::Wait },
+                    "fail" => { LockPolicy::Fail },
+                    _ => { return Err(CheckError::invalid("Invalid lock policy")); },
+                }
+            },
+            None => { LockPolicy::Wait },
+        };
+
+        let first_hop_methods: Vec<firsthop::Method> = match opt_match.opt_str("first-hop-methods") {
+            Some(a) => {
+                let mut methods = Vec::new();
+                for m in a.split(',').filter(|m| !m.is_empty()) {
+                    match firsthop::Method::parse(m) {
+                        Some(v) => { methods.push(v); },
+                        None => { return Err(CheckError::invalid("Invalid method for first hop check")); },
+                    };
+                }
+                if methods.is_empty() {
+                    return Err(CheckError::invalid("At least one method for first hop check is required"));
+                }
+                methods
+            },
+            None => { firsthop::DEFAULT_METHODS.to_vec() },
+        };
+
+        let first_hop_timeout = match opt_match.opt_str("first-hop-timeout") {
+            Some(a) => {
+                match a.parse::<f64>() {
+                    Ok(v) if v > 0.0 => { Duration::from_millis((v * 1000.0) as u64) },
+                    _ => { return Err(CheckError::invalid("Invalid timeout for first hop check")); },
+                }
+            },
+            None => { Duration::from_millis((DEFAULT_FIRST_HOP_TIMEOUT * 1000.0) as u64) },
+        };
+
+        let probe_gateway = opt_match.opt_present("probe-gateway");
+        let probe_gateway_address: Option<IpAddr> = match opt_match.opt_str("probe-gateway") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Some(v) },
+                    Err(_) => { return Err(CheckError::invalid("Invalid gateway address for --probe-gateway")); },
+                }
+            },
+            None => { None },
+        };
+
+        let probe_timeout = match opt_match.opt_str("probe-timeout") {
+            Some(a) => {
+                match a.parse::<f64>() {
+                    Ok(v) if v > 0.0 => { Duration::from_millis((v * 1000.0) as u64) },
+                    _ => { return Err(CheckError::invalid("Invalid timeout for gateway probe")); },
+                }
+            },
+            None => { Duration::from_millis((DEFAULT_PROBE_TIMEOUT * 1000.0) as u64) },
+        };
+
+        let probe_warn_ms: Option<f64> = match opt_match.opt_str("probe-warn-ms") {
+            Some(a) => {
+                match a.parse::<f64>() {
+                    Ok(v) if v > 0.0 => { Some(v) },
+                    _ => { return Err(CheckError::invalid("Invalid round trip time for --probe-warn-ms")); },
+                }
+            },
+            None => { None },
+        };
+
+        if !probe_gateway && (opt_match.opt_present("probe-timeout") || probe_warn_ms.is_some()) {
+            return Err(CheckError::invalid("--probe-timeout and --probe-warn-ms require --probe-gateway"));
+        }
+
+        let uplink_check: Option<ipnetwork::Ipv4Network> = match opt_match.opt_str("uplink-check") {
+            Some(a) => {
+                match a.parse() {
+                    Ok(v) => { Some(v) },
+                    Err(_) => { return Err(CheckError::invalid("Invalid provider prefix for --uplink-check, must be an IPv4 network")); },
+                }
+            },
+            None => { None },
+        };
+
+        let frame_size_window = match opt_match.opt_str("frame-size-histogram") {
+            Some(a) => {
+                match a.parse::<f64>() {
+                    Ok(v) if v > 0.0 => { Some(Duration::from_millis((v * 1000.0) as u64)) },
+                    _ => { return Err(CheckError::invalid("Invalid sampling window for frame size histogram")); },
+                }
+            },
+            None => { None },
+        };
+
+        if expect_no_address && (!address_minimums.is_empty() || !expect_address.is_empty() || !address_counts.is_empty()
+            || require_ipv6_link_local || ipv6_require_global || ipv6_require_stable) {
+            return Err(CheckError::invalid("Checking for absence of addresses can't be combined with address assignment checks"));
+        }
+
+        if expect_vlan_set.is_some() && check_vlans_of.is_none() {
+            return Err(CheckError::invalid("Expected VLAN set requires --check-vlans-of"));
+        }
+
+        if interface.is_empty() && interface_regex.is_none() && !all && check_vlans_of.is_none() && by_address.is_none() {
+            return Err(CheckError::invalid("Interface to check is mandatory"));
+        };
+
+        Ok(Configuration{
+            interface,
+            mtu,
+            mtu_consistency,
+            speed,
+            allowed_speeds,
+            speed_warn,
+            speed_crit,
+            duplex,
+            virtual_interface,
+            autoneg,
+            fec,
+            promisc,
+            flags_set,
+            flags_clear,
+            report_critical,
+            operstate_unknown,
+            missing_state,
+            address_minimums,
+            address_counts,
+            check_napi_balance,
+            check_wq_balance,
+            effective_mtu_to,
+            interface_regex,
+            all,
+            all_physical_only,
+            exclude,
+            min_matches,
+            state_file,
+            sysfs_root,
+            enrich_from_lldp,
+            output,
+            output_version,
+            service_name,
+            status_prefix,
+            max_output_length,
+            expect_no_address,
+            ipv6_require_global,
+            require_ipv6_link_local,
+            ipv6_require_stable,
+            tentative_grace,
+            strict,
+            expect_address,
+            expect_neighbors,
+            ignore_prefixes,
+            expect_default_route,
+            default_route_families,
+            default_gateway,
+            prometheus_textfile,
+            errors_warn,
+            errors_crit,
+            drops_warn,
+            drops_crit,
+            flaps_warn,
+            flaps_crit,
+            collisions_warn,
+            collisions_crit,
+            stats,
+            bandwidth_warn,
+            bandwidth_crit,
+            bandwidth_max,
+            signal_warn,
+            signal_crit,
+            hysteresis,
+            check_first_hop,
+            first_hop_methods,
+            first_hop_timeout,
+            probe_gateway,
+            probe_gateway_address,
+            probe_timeout,
+            probe_warn_ms,
+            uplink_check,
+            frame_size_window,
+            cross_check_backends,
+            backend,
+            snmp_host,
+            snmp_community,
+            lock_wait,
+            lock_policy,
+            alert_on_change,
+            bond_min_slaves,
+            bond_mode,
+            bond_slave_status,
+            check_lacp,
+            team_min_ports,
+            team_runner,
+            ack_file,
+            probe_subnet_peers,
+            peer_probe_limit,
+            bridge_ports,
+            bridge_ports_min_match,
+            vlan_id,
+            vlan_parent,
+            check_vlans_of,
+            by_address,
+            expect_qdisc,
+            expect_runtime_pm,
+            driver,
+            txqueuelen,
+            ring_rx,
+            ring_tx,
+            features,
+            pause,
+            queues_rx,
+            queues_tx,
+            queues_combined,
+            numa_node,
+            sriov_vfs,
+            pcie_speed,
+            pcie_width,
+            pcie_check_max,
+            firmware,
+            firmware_min,
+            master,
+            no_master,
+            expect_altname,
+            expect_vlan_set,
+            timeout,
+            verbose,
+            dry_run,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Configuration, CheckError> {
+        let argv: Vec<String> = ["check_ethernet"].iter().chain(args.iter()).map(|a| a.to_string()).collect();
+        Configuration::new(&argv, &options())
+    }
+
+    fn rejected(args: &[&str]) -> String {
+        match parse(args) {
+            Ok(_) => { panic!("{:?} was accepted", args); },
+            Err(e) => { e.to_string() },
+        }
+    }
+
+    // a configuration file in the temporary directory, removed again by the caller
+    fn config_file(name: &str, content: &str) -> String {
+        let path = std::env::temp_dir().join(format!("check_ethernet_test_{}_{}.toml", name, std::process::id())).to_string_lossy().to_string();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn merged(name: &str, content: &str, args: &[&str]) -> Result<Vec<String>, CheckError> {
+        let path = config_file(name, content);
+        let argv: Vec<String> = ["check_ethernet", "--config", &path].iter().chain(args.iter()).map(|a| a.to_string()).collect();
+        let result = merge_config_file(&argv, &options());
+        let _ = fs::remove_file(&path);
+        result.map(|v| v.into_iter().filter(|a| !a.starts_with("--config") && !a.ends_with(".toml")).collect())
+    }
+
+    #[test]
+    fn config_file_keys_become_options() {
+        let argv = merged("keys", "interface = \"eth0\"\nmtu = 9000\nno_mtu_consistency = true\nverbose = false\nexclude = [\"eth1\", \"eth2\"]\n", &[]).unwrap();
+        assert_eq!(argv, vec!["check_ethernet", "--exclude=eth1,eth2", "--interface=eth0", "--mtu=9000", "--no-mtu-consistency"]);
+    }
+
+    #[test]
+    fn command_line_overrides_config_file() {
+        let argv = merged("override", "interface = \"eth0\"\nmtu = 9000\n", &["-m", "1500"]).unwrap();
+        assert_eq!(argv, vec!["check_ethernet", "--interface=eth0", "-m", "1500"]);
+        let cfg = Configuration::new(&argv, &options()).unwrap();
+        assert_eq!(cfg.interface, vec!["eth0".to_string()]);
+        assert!(cfg.mtu.unwrap() == threshold::Range::exact(1500.0));
+    }
+
+    #[test]
+    fn unknown_config_file_key_is_rejected() {
+        let err = merged("unknown", "interface = \"eth0\"\nmtu_size = 9000\n", &[]).unwrap_err().to_string();
+        assert!(err.starts_with("Unknown key mtu_size in configuration file "), "{}", err);
+        let err = merged("nested", "config = \"/etc/other.toml\"\n", &[]).unwrap_err().to_string();
+        assert!(err.starts_with("Key config is not allowed in configuration file "), "{}", err);
+    }
+
+    #[test]
+    fn invalid_config_file_value_types() {
+        for content in &["mtu = true\n", "mtu = { min = 1500 }\n", "exclude = [[\"eth1\"]]\n", "critical = \"yes\"\n", "mtu = \n"] {
+            let err = merged("types", content, &[]).unwrap_err().to_string();
+            assert!(err.contains("configuration file"), "{}: {}", content, err);
+        }
+    }
+
+    #[test]
+    fn mtu_ranges() {
+        let cfg = parse(&["-i", "eth0", "-m", "9000-9216"]).unwrap();
+        let mtu = cfg.mtu.unwrap();
+        assert_eq!((mtu.start, mtu.end), (Some(9000.0), Some(9216.0)));
+        assert!(parse(&["-i", "eth0", "-m", "9000"]).unwrap().mtu.unwrap() == threshold::Range::exact(9000.0));
+        assert_eq!(rejected(&["-i", "eth0", "-m", "9216-9000"]), "Invalid MTU range, minimum is greater than maximum");
+        assert_eq!(rejected(&["-i", "eth0", "-m", "9000-"]), "Invalid MTU, must be an integer or a range");
+        assert_eq!(rejected(&["-i", "eth0", "-m", "jumbo"]), "Invalid MTU, must be an integer or a range");
+    }
+
+    #[test]
+    fn interface_flags() {
+        let cfg = parse(&["-i", "eth0", "--flags-set", "up,IFF_RUNNING", "--flags-clear", "NOARP"]).unwrap();
+        assert_eq!(cfg.flags_set, (libc::IFF_UP | libc::IFF_RUNNING) as u32);
+        assert_eq!(cfg.flags_clear, libc::IFF_NOARP as u32);
+        assert!(rejected(&["-i", "eth0", "--flags-set", "FOO"]).starts_with("Unknown interface flag, supported flags are UP, "));
+        assert_eq!(rejected(&["-i", "eth0", "--flags-set", "UP", "--flags-clear", "UP"]), "A flag can't be required to be set and cleared at the same time");
+    }
+
+    #[test]
+    fn duplex_without_speed() {
+        let cfg = parse(&["-i", "eth0", "-s", "0", "--duplex", "full"]).unwrap();
+        assert!(cfg.speed.is_none());
+        assert_eq!(cfg.duplex.as_deref(), Some("full"));
+        assert_eq!(rejected(&["-i", "eth0", "--duplex", "sideways"]), "Invalid link mode, must be half, full or any");
+    }
+
+    #[test]
+    fn speed_units() {
+        let cfg = parse(&["-i", "eth0", "-s", "10g:full"]).unwrap();
+        assert!(cfg.speed.unwrap() == threshold::Range::exact(10000.0));
+        // at least 2.5 GBit/s
+        let speed = parse(&["-i", "eth0", "-s", "2.5G:"]).unwrap().speed.unwrap();
+        assert_eq!((speed.start, speed.end), (Some(2500.0), None));
+        for garbage in &["10gx", "2.5x:full", "g", "10gg"] {
+            assert_eq!(rejected(&["-i", "eth0", "-s", garbage]), "Invalid link speed, must be a speed like 1000 or 10g or a range", "{}", garbage);
+        }
+        assert_eq!(rejected(&["-i", "eth0", "--min-speed", "2.5q"]), "Can't convert minimal link speed to a speed like 1000 or 10g");
+    }
+
+    #[test]
+    fn no_address_conflicts_with_address_requirements() {
+        let conflicts: &[&[&str]] = &[
+            &["-a", "ipv4"], &["--expect-address", "192.0.2.1"], &["--addr-count", "ipv4=1"],
+            &["--require-ipv6-link-local"], &["--ipv6-require-global"], &["--ipv6-require-stable"],
+        ];
+        for conflict in conflicts {
+            for none in &[&["--expect-no-address"][..], &["-a", "none"][..]] {
+                // -a can't be given twice
+                if conflict[0] == "-a" && none[0] == "-a" {
+                    continue;
+                }
+                let mut args = vec!["-i", "eth0"];
+                args.extend_from_slice(none);
+                args.extend_from_slice(conflict);
+                assert_eq!(rejected(&args), "Checking for absence of addresses can't be combined with address assignment checks");
+            }
+        }
+        assert!(parse(&["-i", "eth0", "--expect-no-address", "--strict"]).is_ok());
+    }
+
+    #[test]
+    fn output_length_has_room_for_the_state() {
+        assert_eq!(parse(&["-i", "eth0", "--max-output-length", "14"]).unwrap().max_output_length, 14);
+        for length in &["13", "2", "0", "-1", "many"] {
+            assert_eq!(rejected(&["-i", "eth0", "--max-output-length", length]), "Invalid maximal output length, must be at least 14 bytes");
+        }
+    }
+}
@@ -0,0 +1,186 @@
+// Rates of interface statistics counters, computed from the values stored in the
+// state file by the previous run.
+use super::error::CheckError;
+use super::interface::{SysfsReader, SOURCE_SYSFS};
+use super::state::State;
+
+// width of the counters as read, netlink (IFLA_STATS64) is 64 bit, SNMP Counter32 is 32 bit
+pub const WIDTH_64: u64 = 64;
+pub const WIDTH_32: u64 = 32;
+
+// sysfs shows an unsigned long of the kernel, but older kernels and some drivers keep
+// 32 bit counters even on 64 bit hosts
+pub const SYSFS_WIDTH: u64 = if cfg!(target_pointer_width = "32") { WIDTH_32 } else { WIDTH_64 };
+
+const WRAP_32: u64 = 1 << 32;
+
+#[derive(Serialize)]
+pub struct Counter {
+    pub name: String,
+    pub value: u64,
+    // per second, None on the first run, after a reset or after the source changed
+    pub rate: Option<f64>,
+    // bits of the counter as read
+    pub width: u64,
+    // the counter wrapped at 32 bit since the previous run and the rate was corrected
+    pub wrapped: bool,
+}
+
+pub fn read_statistic(sysfs: &dyn SysfsReader, sysfs_path: &str, name: &str) -> Result<u64, CheckError> {
+    let path = format!("{}/statistics/{}", sysfs_path, name);
+    let raw = match sysfs.read_to_string(&path) {
+        Ok(s) => { s },
+        Err(e) => { return Err(CheckError::io(&path, e)); },
+    };
+
+    match raw.trim().parse() {
+        Ok(v) => { Ok(v) },
+        Err(_) => { Err(CheckError::parse(&format!("statistics counter {}", path), &raw)) },
+    }
+}
+
+// names of the counters in statistics/, sorted
+pub fn available_statistics(sysfs: &dyn SysfsReader, sysfs_path: &str) -> Vec<String> {
+    sysfs.read_dir(&format!("{}/statistics", sysfs_path)).unwrap_or_default()
+}
+
+// store the current value and return the rate since the previous run and whether
+// the counter wrapped. Rates above max_rate are not plausible for the link.
+pub fn update(state: &mut State, name: &str, value: u64, width: u64, source: &str, max_rate: Option<f64>, now_ms: u64) -> (Option<f64>, bool) {
+    let value_key = format!("counter.{}.value", name);
+    let time_key = format!("counter.{}.time", name);
+    let width_key = format!("counter.{}.width", name);
+    let source_key = format!("counter.{}.source", name);
+
+    let previous = match (state.get_u64(&value_key), state.get_u64(&time_key), state.get_u64(&width_key), state.get(&source_key)) {
+        (Some(v), Some(t), Some(w), Some(s)) => { Some((v, t, w, s == source)) },
+        _ => { None },
+    };
+
+    state.set(&value_key, value);
+    state.set(&time_key, now_ms);
+    state.set(&width_key, width);
+    state.set(&source_key, source);
+
+    let (last_value, last_time, last_width, same_source) = match previous {
+        Some(v) => { v },
+        None => { return (None, false); },
+    };
+
+    // values of different sources can't be compared, start with a new baseline
+    if !same_source || last_width != width || now_ms <= last_time {
+        return (None, false);
+    }
+    let elapsed = (now_ms - last_time) as f64 / 1000.0;
+
+    if value >= last_value {
+        return (Some((value - last_value) as f64 / elapsed), false);
+    }
+
+    // a 32 bit counter wrapped if both values fit into 32 bit and the rate including
+    // the wrap is plausible, anything else means the driver was reloaded or the host
+    // rebooted. Treat it as a new baseline instead of reporting a negative rate.
+    if (width == WIDTH_32 || source == SOURCE_SYSFS) && last_value < WRAP_32 && value < WRAP_32 {
+        let rate = (value + WRAP_32 - last_value) as f64 / elapsed;
+        // without a link speed only a counter known to be 32 bit wraps
+        let plausible = match max_rate {
+            Some(m) => { rate <= m },
+            None => { width == WIDTH_32 },
+        };
+        if plausible {
+            return (Some(rate), true);
+        }
+    }
+
+    (None, false)
+}
+
+pub fn find<'a>(counters: &'a [Counter], name: &str) -> Option<&'a Counter> {
+    counters.iter().find(|c| c.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // never saved, a missing file is an empty state
+    fn empty_state() -> State {
+        State::load("/nonexistent/check_ethernet/counters.state").unwrap()
+    }
+
+    #[test]
+    fn first_run_has_no_rate() {
+        let mut state = empty_state();
+        assert_eq!(update(&mut state, "rx_bytes", 1000, WIDTH_64, "netlink", None, 10_000), (None, false));
+        assert_eq!(state.get_u64("counter.rx_bytes.value"), Some(1000));
+    }
+
+    #[test]
+    fn rate_per_second() {
+        let mut state = empty_state();
+        update(&mut state, "rx_bytes", 1000, WIDTH_64, "netlink", None, 10_000);
+        assert_eq!(update(&mut state, "rx_bytes", 3000, WIDTH_64, "netlink", None, 12_000), (Some(1000.0), false));
+    }
+
+    #[test]
+    fn counter32_wrap_is_corrected() {
+        let mut state = empty_state();
+        update(&mut state, "rx_bytes", WRAP_32 - 100, WIDTH_32, "snmp", None, 10_000);
+        assert_eq!(update(&mut state, "rx_bytes", 100, WIDTH_32, "snmp", None, 11_000), (Some(200.0), true));
+    }
+
+    #[test]
+    fn counter32_wrap_above_max_rate_is_a_reset() {
+        let mut state = empty_state();
+        update(&mut state, "rx_bytes", 4_000_000_000, WIDTH_32, "snmp", None, 10_000);
+        assert_eq!(update(&mut state, "rx_bytes", 100, WIDTH_32, "snmp", Some(1000.0), 11_000), (None, false));
+    }
+
+    #[test]
+    fn sysfs_wrap_is_corrected() {
+        // a driver keeping 32 bit counters behind the 64 bit sysfs file
+        let mut state = empty_state();
+        update(&mut state, "rx_bytes", WRAP_32 - 100, SYSFS_WIDTH, "sysfs", Some(1000.0), 10_000);
+        assert_eq!(update(&mut state, "rx_bytes", 100, SYSFS_WIDTH, "sysfs", Some(1000.0), 11_000), (Some(200.0), true));
+    }
+
+    #[test]
+    fn smaller_sysfs_value_above_32_bit_is_a_reset() {
+        let mut state = empty_state();
+        update(&mut state, "rx_bytes", WRAP_32 + 100, WIDTH_64, "sysfs", None, 10_000);
+        assert_eq!(update(&mut state, "rx_bytes", 100, WIDTH_64, "sysfs", None, 11_000), (None, false));
+        // the new value is the baseline of the next run
+        assert_eq!(update(&mut state, "rx_bytes", 600, WIDTH_64, "sysfs", None, 12_000), (Some(500.0), false));
+    }
+
+    #[test]
+    fn netlink_counters_dont_wrap_at_32_bit() {
+        let mut state = empty_state();
+        update(&mut state, "rx_bytes", WRAP_32 - 100, WIDTH_64, "netlink", None, 10_000);
+        assert_eq!(update(&mut state, "rx_bytes", 100, WIDTH_64, "netlink", None, 11_000), (None, false));
+    }
+
+    #[test]
+    fn source_switch_starts_a_new_baseline() {
+        let mut state = empty_state();
+        update(&mut state, "rx_bytes", 1000, WIDTH_64, "netlink", None, 10_000);
+        assert_eq!(update(&mut state, "rx_bytes", 2000, WIDTH_64, "sysfs", None, 11_000), (None, false));
+        assert_eq!(update(&mut state, "rx_bytes", 2500, WIDTH_64, "sysfs", None, 12_000), (Some(500.0), false));
+    }
+
+    #[test]
+    fn state_without_source_starts_a_new_baseline() {
+        let mut state = empty_state();
+        state.set("counter.rx_bytes.value", 1000);
+        state.set("counter.rx_bytes.time", 10_000);
+        state.set("counter.rx_bytes.width", WIDTH_32);
+        assert_eq!(update(&mut state, "rx_bytes", 2000, WIDTH_64, "sysfs", None, 11_000), (None, false));
+    }
+
+    #[test]
+    fn clock_going_backwards_has_no_rate() {
+        let mut state = empty_state();
+        update(&mut state, "rx_bytes", 1000, WIDTH_64, "netlink", None, 10_000);
+        assert_eq!(update(&mut state, "rx_bytes", 2000, WIDTH_64, "netlink", None, 9_000), (None, false));
+    }
+}
@@ -0,0 +1,102 @@
+// Errors of parsing the command line and reading the state of an interface. The
+// underlying cause is kept, so the message tells which file or value was the problem.
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum CheckError {
+    // a file or directory can't be read or written
+    Io{ path: String, source: io::Error },
+    // a value reported by the kernel can't be parsed
+    Parse{ field: String, value: String },
+    // an invalid option or option value
+    InvalidArgument(String),
+    // a rtnetlink request failed
+    Netlink{ request: String, source: io::Error },
+    // a SNMP request to a remote device failed
+    Snmp{ host: String, request: String, source: io::Error },
+    // the data was read but is incomplete, e.g. a VLAN without lower device
+    Missing(String),
+}
+
+impl CheckError {
+    pub fn io(path: &str, source: io::Error) -> CheckError {
+        CheckError::Io{ path: path.to_string(), source }
+    }
+
+    pub fn parse(field: &str, value: &str) -> CheckError {
+        CheckError::Parse{ field: field.to_string(), value: value.trim().to_string() }
+    }
+
+    pub fn invalid(message: &str) -> CheckError {
+        CheckError::InvalidArgument(message.to_string())
+    }
+
+    pub fn netlink(request: &str, source: io::Error) -> CheckError {
+        CheckError::Netlink{ request: request.to_string(), source }
+    }
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CheckError::Io{ path, source } => { write!(f, "Can't access {}: {}", path, source) },
+            CheckError::Parse{ field, value } => { write!(f, "Can't parse {} from \"{}\"", field, value) },
+            CheckError::InvalidArgument(message) => { write!(f, "{}", message) },
+            CheckError::Netlink{ request, source } => { write!(f, "Can't read {} from netlink: {}", request, source) },
+            CheckError::Snmp{ host, request, source } => { write!(f, "Can't read {} from {} via SNMP: {}", request, host, source) },
+            CheckError::Missing(message) => { write!(f, "{}", message) },
+        }
+    }
+}
+
+impl Error for CheckError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CheckError::Io{ source, .. } | CheckError::Netlink{ source, .. } | CheckError::Snmp{ source, .. } => { Some(source) },
+            _ => { None },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_error() {
+        let err = CheckError::io("/sys/class/net/eth0/mtu", io::Error::new(io::ErrorKind::PermissionDenied, "Permission denied"));
+        assert_eq!(err.to_string(), "Can't access /sys/class/net/eth0/mtu: Permission denied");
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn parse_error_trims_the_value() {
+        let err = CheckError::parse("MTU in /sys/class/net/eth0/mtu", "15x0\n");
+        assert_eq!(err.to_string(), "Can't parse MTU in /sys/class/net/eth0/mtu from \"15x0\"");
+        assert!(err.source().is_none());
+    }
+
+    #[test]
+    fn invalid_argument() {
+        assert_eq!(CheckError::invalid("Invalid timeout").to_string(), "Invalid timeout");
+    }
+
+    #[test]
+    fn netlink_error() {
+        let err = CheckError::netlink("link eth0", io::Error::from_raw_os_error(libc::ENODEV));
+        assert!(err.to_string().starts_with("Can't read link eth0 from netlink: No such device"), "{}", err);
+    }
+
+    #[test]
+    fn snmp_error() {
+        let err = CheckError::Snmp{ host: "switch01:161".to_string(), request: "ifOperStatus.3".to_string(), source: io::Error::new(io::ErrorKind::TimedOut, "timed out") };
+        assert_eq!(err.to_string(), "Can't read ifOperStatus.3 from switch01:161 via SNMP: timed out");
+    }
+
+    #[test]
+    fn missing() {
+        assert_eq!(CheckError::Missing("VLAN eth0.10 has no lower device".to_string()).to_string(), "VLAN eth0.10 has no lower device");
+    }
+}
@@ -0,0 +1,382 @@
+// Negotiated speed and duplex mode from the SIOCETHTOOL ioctl, for drivers whose
+// sysfs speed and duplex files fail with EINVAL, and settings sysfs doesn't know at
+// all like the FEC mode. Reading requires no privileges.
+use std::cmp::Ordering;
+use std::io;
+use std::mem;
+
+const SIOCETHTOOL: libc::c_ulong = 0x8946;
+
+const ETHTOOL_GSET: u32 = 0x0000_0001;
+const ETHTOOL_GLINKSETTINGS: u32 = 0x0000_004c;
+
+// struct ethtool_cmd
+const ETHTOOL_CMD_LEN: usize = 44;
+// struct ethtool_link_settings without the link mode masks
+const LINK_SETTINGS_LEN: usize = 48;
+
+const SPEED_UNKNOWN: u32 = 0xffff_ffff;
+const DUPLEX_HALF: u8 = 0;
+const DUPLEX_FULL: u8 = 1;
+
+const AUTONEG_ENABLE: u8 = 1;
+
+#[repr(C)]
+struct IfReq {
+    name: [libc::c_char; libc::IFNAMSIZ],
+    data: *mut libc::c_void,
+    // rest of the union in struct ifreq
+    _pad: [u8; 16],
+}
+
+struct Socket {
+    fd: libc::c_int,
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+impl Socket {
+    fn new() -> io::Result<Socket> {
+        let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Socket{ fd })
+    }
+
+    // the command is the first u32 of buf, the kernel writes its answer into buf
+    fn ioctl(&self, interface: &str, buf: &mut [u8]) -> io::Result<()> {
+        let mut ifr: IfReq = unsafe { mem::zeroed() };
+        if interface.len() >= ifr.name.len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "interface name too long"));
+        }
+        for (dst, src) in ifr.name.iter_mut().zip(interface.bytes()) {
+            *dst = src as libc::c_char;
+        }
+        ifr.data = buf.as_mut_ptr() as *mut libc::c_void;
+
+        let rc = unsafe { libc::ioctl(self.fd, SIOCETHTOOL as _, &mut ifr as *mut IfReq) };
+        if rc < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+pub struct LinkSettings {
+    // MBit/s, -1 if unknown
+    pub speed: i32,
+    // "half", "full" or "unknown" like /sys/class/net/<if>/duplex
+    pub duplex: &'static str,
+    pub autoneg: bool,
+}
+
+fn speed_value(speed: u32) -> i32 {
+    if speed == SPEED_UNKNOWN || speed > i32::MAX as u32 {
+        return -1;
+    }
+    speed as i32
+}
+
+fn duplex_name(duplex: u8) -> &'static str {
+    match duplex {
+        DUPLEX_HALF => { "half" },
+        DUPLEX_FULL => { "full" },
+        _ => { "unknown" },
+    }
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes([buf[offset], buf[offset + 1], buf[offset + 2], buf[offset + 3]])
+}
+
+// the kernel answers a request without link mode masks with the number of 32 bit
+// words it needs (negated), the second request gets the settings
+fn glinksettings(sock: &Socket, interface: &str) -> io::Result<LinkSettings> {
+    let mut buf = vec![0u8; LINK_SETTINGS_LEN];
+    buf[0..4].copy_from_slice(&ETHTOOL_GLINKSETTINGS.to_ne_bytes());
+    sock.ioctl(interface, &mut buf)?;
+
+    let nwords = buf[15] as i8;
+    if nwords >= 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected answer to link mode mask handshake"));
+    }
+    let nwords = -(nwords as i32) as usize;
+
+    // supported, advertising and link partner advertising masks
+    let mut buf = vec![0u8; LINK_SETTINGS_LEN + 3 * nwords * 4];
+    buf[0..4].copy_from_slice(&ETHTOOL_GLINKSETTINGS.to_ne_bytes());
+    buf[15] = nwords as u8;
+    sock.ioctl(interface, &mut buf)?;
+
+    Ok(LinkSettings{ speed: speed_value(u32_at(&buf, 4)), duplex: duplex_name(buf[8]), autoneg: buf[11] == AUTONEG_ENABLE })
+}
+
+// deprecated interface, still the only one of kernels before 4.6
+fn gset(sock: &Socket, interface: &str) -> io::Result<LinkSettings> {
+    let mut buf = vec![0u8; ETHTOOL_CMD_LEN];
+    buf[0..4].copy_from_slice(&ETHTOOL_GSET.to_ne_bytes());
+    sock.ioctl(interface, &mut buf)?;
+
+    let speed = (u16::from_ne_bytes([buf[28], buf[29]]) as u32) << 16 | u16::from_ne_bytes([buf[12], buf[13]]) as u32;
+    Ok(LinkSettings{ speed: speed_value(speed), duplex: duplex_name(buf[14]), autoneg: buf[18] == AUTONEG_ENABLE })
+}
+
+pub fn link_settings(interface: &str) -> io::Result<LinkSettings> {
+    let sock = Socket::new()?;
+    match glinksettings(&sock, interface) {
+        Ok(v) => { Ok(v) },
+        Err(_) => { gset(&sock, interface) },
+    }
+}
+
+const ETHTOOL_GFECPARAM: u32 = 0x0000_0050;
+// struct ethtool_fecparam
+const FECPARAM_LEN: usize = 16;
+
+// ETHTOOL_FEC_* bits, in the order of the names
+const FEC_MODES: &[(u32, &str)] = &[(1 << 0, "none"), (1 << 1, "auto"), (1 << 2, "off"), (1 << 3, "rs"), (1 << 4, "baser"), (1 << 5, "llrs")];
+
+#[derive(Serialize)]
+pub struct Fec {
+    // "off" if the link runs without FEC
+    pub active: String,
+    // modes the NIC may choose from, "auto" if it follows the link partner
+    pub configured: Vec<String>,
+}
+
+fn fec_names(mask: u32) -> Vec<String> {
+    FEC_MODES.iter().filter(|(bit, _)| mask & bit != 0).map(|(_, name)| name.to_string()).collect()
+}
+
+// Err(Unsupported) from drivers without get_fecparam
+pub fn fec(interface: &str) -> io::Result<Fec> {
+    let sock = Socket::new()?;
+    let mut buf = vec![0u8; FECPARAM_LEN];
+    buf[0..4].copy_from_slice(&ETHTOOL_GFECPARAM.to_ne_bytes());
+    sock.ioctl(interface, &mut buf)?;
+
+    // "none" means there is no FEC on the link, which is the same as off
+    let active = match fec_names(u32_at(&buf, 4)).into_iter().next() {
+        Some(a) if a == "none" => { "off".to_string() },
+        Some(a) => { a },
+        None => { "off".to_string() },
+    };
+    Ok(Fec{ active, configured: fec_names(u32_at(&buf, 8)) })
+}
+
+const ETHTOOL_GRINGPARAM: u32 = 0x0000_0010;
+// struct ethtool_ringparam
+const RINGPARAM_LEN: usize = 36;
+
+#[derive(Serialize)]
+pub struct Rings {
+    pub rx: u32,
+    pub tx: u32,
+    // largest sizes the hardware supports
+    pub rx_max: u32,
+    pub tx_max: u32,
+}
+
+pub fn rings(interface: &str) -> io::Result<Rings> {
+    let sock = Socket::new()?;
+    let mut buf = vec![0u8; RINGPARAM_LEN];
+    buf[0..4].copy_from_slice(&ETHTOOL_GRINGPARAM.to_ne_bytes());
+    sock.ioctl(interface, &mut buf)?;
+
+    Ok(Rings{ rx: u32_at(&buf, 20), tx: u32_at(&buf, 32), rx_max: u32_at(&buf, 4), tx_max: u32_at(&buf, 16) })
+}
+
+const ETHTOOL_GSTRINGS: u32 = 0x0000_001b;
+const ETHTOOL_GSSET_INFO: u32 = 0x0000_0037;
+const ETHTOOL_GFEATURES: u32 = 0x0000_003a;
+const ETH_SS_FEATURES: u32 = 4;
+const ETH_GSTRING_LEN: usize = 32;
+// struct ethtool_sset_info with a single data word
+const SSET_INFO_LEN: usize = 20;
+// struct ethtool_gstrings and struct ethtool_gfeatures without their data
+const GSTRINGS_LEN: usize = 12;
+const GFEATURES_LEN: usize = 8;
+// struct ethtool_get_features_block: available, requested, active, never_changed
+const FEATURES_BLOCK_LEN: usize = 16;
+
+// short names of ethtool -K for the kernel's feature strings
+const FEATURE_ALIASES: &[(&str, &str)] = &[("gro", "rx-gro"), ("lro", "rx-lro"), ("tso", "tx-tcp-segmentation"), ("gso", "tx-generic-segmentation"),
+    ("sg", "tx-scatter-gather"), ("rx", "rx-checksum"), ("rxvlan", "rx-vlan-hw-parse"), ("txvlan", "tx-vlan-hw-insert"), ("ntuple", "rx-ntuple-filter"),
+    ("rxhash", "rx-hashing")];
+
+#[derive(Serialize)]
+pub struct Feature {
+    pub name: String,
+    pub active: bool,
+    // the driver doesn't allow to change it
+    pub fixed: bool,
+}
+
+// kernel name of a feature, e.g. rx-gro for gro
+pub fn feature_name(name: &str) -> &str {
+    match FEATURE_ALIASES.iter().find(|(alias, _)| *alias == name) {
+        Some((_, kernel)) => { kernel },
+        None => { name },
+    }
+}
+
+fn feature_count(sock: &Socket, interface: &str) -> io::Result<usize> {
+    let mut buf = vec![0u8; SSET_INFO_LEN];
+    buf[0..4].copy_from_slice(&ETHTOOL_GSSET_INFO.to_ne_bytes());
+    buf[8..16].copy_from_slice(&(1u64 << ETH_SS_FEATURES).to_ne_bytes());
+    sock.ioctl(interface, &mut buf)?;
+
+    // the kernel clears the bits of string sets it doesn't know
+    if u64::from_ne_bytes([buf[8], buf[9], buf[10], buf[11], buf[12], buf[13], buf[14], buf[15]]) == 0 {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "no feature strings"));
+    }
+    Ok(u32_at(&buf, 16) as usize)
+}
+
+// names from the ETH_SS_FEATURES string set, the index is the bit in the feature blocks
+pub fn features(interface: &str) -> io::Result<Vec<Feature>> {
+    let sock = Socket::new()?;
+    let count = feature_count(&sock, interface)?;
+
+    let mut strings = vec![0u8; GSTRINGS_LEN + count * ETH_GSTRING_LEN];
+    strings[0..4].copy_from_slice(&ETHTOOL_GSTRINGS.to_ne_bytes());
+    strings[4..8].copy_from_slice(&ETH_SS_FEATURES.to_ne_bytes());
+    strings[8..12].copy_from_slice(&(count as u32).to_ne_bytes());
+    sock.ioctl(interface, &mut strings)?;
+
+    let blocks = count.div_ceil(32);
+    let mut buf = vec![0u8; GFEATURES_LEN + blocks * FEATURES_BLOCK_LEN];
+    buf[0..4].copy_from_slice(&ETHTOOL_GFEATURES.to_ne_bytes());
+    buf[4..8].copy_from_slice(&(blocks as u32).to_ne_bytes());
+    sock.ioctl(interface, &mut buf)?;
+
+    let mut features = Vec::with_capacity(count);
+    for i in 0..count {
+        let raw = &strings[GSTRINGS_LEN + i * ETH_GSTRING_LEN..GSTRINGS_LEN + (i + 1) * ETH_GSTRING_LEN];
+        let name = String::from_utf8_lossy(raw.split(|b| *b == 0).next().unwrap_or_default()).to_string();
+        if name.is_empty() {
+            continue;
+        }
+        let block = GFEATURES_LEN + (i / 32) * FEATURES_BLOCK_LEN;
+        let bit = 1u32 << (i % 32);
+        let available = u32_at(&buf, block) & bit != 0;
+        let never_changed = u32_at(&buf, block + 12) & bit != 0;
+        features.push(Feature{ name, active: u32_at(&buf, block + 8) & bit != 0, fixed: !available || never_changed });
+    }
+    Ok(features)
+}
+
+const ETHTOOL_GPAUSEPARAM: u32 = 0x0000_0012;
+// struct ethtool_pauseparam
+const PAUSEPARAM_LEN: usize = 16;
+
+#[derive(Serialize)]
+pub struct Pause {
+    pub autoneg: bool,
+    pub rx: bool,
+    pub tx: bool,
+}
+
+pub fn pause(interface: &str) -> io::Result<Pause> {
+    let sock = Socket::new()?;
+    let mut buf = vec![0u8; PAUSEPARAM_LEN];
+    buf[0..4].copy_from_slice(&ETHTOOL_GPAUSEPARAM.to_ne_bytes());
+    sock.ioctl(interface, &mut buf)?;
+
+    Ok(Pause{ autoneg: u32_at(&buf, 4) != 0, rx: u32_at(&buf, 8) != 0, tx: u32_at(&buf, 12) != 0 })
+}
+
+const ETHTOOL_GCHANNELS: u32 = 0x0000_003c;
+// struct ethtool_channels
+const CHANNELS_LEN: usize = 36;
+
+#[derive(Serialize)]
+pub struct Channels {
+    pub rx: u32,
+    pub tx: u32,
+    pub other: u32,
+    pub combined: u32,
+    // most combined channels the hardware supports
+    pub max_combined: u32,
+}
+
+pub fn channels(interface: &str) -> io::Result<Channels> {
+    let sock = Socket::new()?;
+    let mut buf = vec![0u8; CHANNELS_LEN];
+    buf[0..4].copy_from_slice(&ETHTOOL_GCHANNELS.to_ne_bytes());
+    sock.ioctl(interface, &mut buf)?;
+
+    Ok(Channels{ rx: u32_at(&buf, 20), tx: u32_at(&buf, 24), other: u32_at(&buf, 28), combined: u32_at(&buf, 32), max_combined: u32_at(&buf, 16) })
+}
+
+const ETHTOOL_GDRVINFO: u32 = 0x0000_0003;
+// struct ethtool_drvinfo
+const DRVINFO_LEN: usize = 196;
+const DRVINFO_STRING_LEN: usize = 32;
+
+#[derive(Serialize)]
+pub struct DriverInfo {
+    pub driver: String,
+    pub version: String,
+    // empty if the driver doesn't report it
+    pub fw_version: String,
+    pub bus_info: String,
+}
+
+fn string_at(buf: &[u8], offset: usize) -> String {
+    let raw = &buf[offset..offset + DRVINFO_STRING_LEN];
+    String::from_utf8_lossy(raw.split(|b| *b == 0).next().unwrap_or_default()).trim().to_string()
+}
+
+pub fn driver_info(interface: &str) -> io::Result<DriverInfo> {
+    let sock = Socket::new()?;
+    let mut buf = vec![0u8; DRVINFO_LEN];
+    buf[0..4].copy_from_slice(&ETHTOOL_GDRVINFO.to_ne_bytes());
+    sock.ioctl(interface, &mut buf)?;
+
+    Ok(DriverInfo{ driver: string_at(&buf, 4), version: string_at(&buf, 36), fw_version: string_at(&buf, 68), bus_info: string_at(&buf, 100) })
+}
+
+// runs of digits compare as numbers, everything else as text, so 14.32.1010 is newer
+// than 14.9.2000 and 1.2829.0 newer than 1.2800
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a, b) = (version_parts(a), version_parts(b));
+    for (x, y) in a.iter().zip(b.iter()) {
+        let order = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => { x.cmp(&y) },
+            (_, _) => { x.cmp(y) },
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+// digits and other characters, separators like dots and blanks are dropped
+fn version_parts(version: &str) -> Vec<String> {
+    let mut parts: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for c in version.chars() {
+        let boundary = match current.chars().last() {
+            Some(last) => { last.is_ascii_digit() != c.is_ascii_digit() },
+            None => { false },
+        };
+        if (boundary || !c.is_ascii_alphanumeric()) && !current.is_empty() {
+            parts.push(current.clone());
+            current.clear();
+        }
+        if c.is_ascii_alphanumeric() {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
@@ -0,0 +1,231 @@
+// First hop health: consult the neighbor cache and fall back to active ARP/NDP
+// probes before declaring the default gateway of an interface dead.
+use super::error::CheckError;
+use super::netlink;
+use super::probe;
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Method {
+    Neighbor,
+    Arp,
+    Ndp,
+}
+
+impl Method {
+    pub fn parse(name: &str) -> Option<Method> {
+        match name {
+            "neighbor" => { Some(Method::Neighbor) },
+            "arp" => { Some(Method::Arp) },
+            "ndp" => { Some(Method::Ndp) },
+            _ => { None },
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Neighbor => "neighbor",
+            Method::Arp => "arp",
+            Method::Ndp => "ndp",
+        }
+    }
+
+    fn applies_to(&self, gateway: &IpAddr) -> bool {
+        match self {
+            Method::Neighbor => { true },
+            Method::Arp => { gateway.is_ipv4() },
+            Method::Ndp => { gateway.is_ipv6() },
+        }
+    }
+}
+
+pub const DEFAULT_METHODS: &[Method] = &[Method::Neighbor, Method::Arp, Method::Ndp];
+// --probe-gateway always asks the gateway itself
+const PROBE_METHODS: &[Method] = &[Method::Arp, Method::Ndp];
+
+pub const VERDICT_ALIVE: &str = "alive";
+// only a stale neighbor cache entry, no probe could confirm or refute it
+pub const VERDICT_STALE: &str = "stale";
+pub const VERDICT_DEAD: &str = "dead";
+// no method could be used at all, e.g. raw sockets are not permitted
+pub const VERDICT_UNKNOWN: &str = "unknown";
+
+#[derive(Serialize)]
+pub struct FirstHop {
+    pub gateway: IpAddr,
+    pub verdict: &'static str,
+    // method and result which produced the verdict, e.g. "ARP probe 2.1ms"
+    pub detail: String,
+    // outcome of every method tried, in chain order
+    pub attempts: Vec<String>,
+    pub rtt_ms: Option<f64>,
+}
+
+enum Outcome {
+    Alive(String, Option<f64>),
+    Stale(String),
+    Failed(String),
+    Error(String),
+}
+
+// default gateways of the main routing table using this interface
+pub fn gateways(ifindex: u32) -> Result<Vec<IpAddr>, CheckError> {
+    let routes = match netlink::routes() {
+        Ok(v) => { v },
+        Err(e) => { return Err(CheckError::netlink("routing table", e)); },
+    };
+
+    let mut result: Vec<IpAddr> = Vec::new();
+    for r in routes {
+        if r.dst_len != 0 || r.oif != Some(ifindex) {
+            continue;
+        }
+        if let Some(gw) = r.gateway {
+            if !result.contains(&gw) {
+                result.push(gw);
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn neighbor_cache(ifindex: u32, gateway: &IpAddr) -> Outcome {
+    let entries = match netlink::neighbors() {
+        Ok(v) => { v },
+        Err(e) => { return Outcome::Error(format!("neighbor cache: {}", e)); },
+    };
+
+    match entries.iter().find(|n| n.ifindex == ifindex && n.ip == *gateway) {
+        Some(n) => {
+            let name = netlink::neighbor_state_name(n.state);
+            match n.state {
+                netlink::NUD_REACHABLE | netlink::NUD_PERMANENT | netlink::NUD_NOARP => {
+                    Outcome::Alive(format!("neighbor cache {}", name), None)
+                },
+                netlink::NUD_STALE | netlink::NUD_DELAY | netlink::NUD_PROBE => {
+                    Outcome::Stale(format!("neighbor cache {}", name))
+                },
+                _ => { Outcome::Failed(format!("neighbor cache {}", name)) },
+            }
+        },
+        None => { Outcome::Failed("neighbor cache: no entry".to_string()) },
+    }
+}
+
+fn probe_outcome(kind: &str, result: Result<Option<Duration>, String>, timeout: Duration) -> Outcome {
+    match result {
+        Ok(Some(rtt)) => {
+            let ms = rtt.as_secs() as f64 * 1000.0 + rtt.subsec_micros() as f64 / 1000.0;
+            Outcome::Alive(format!("{} probe {:.1}ms", kind, ms), Some(ms))
+        },
+        Ok(None) => {
+            Outcome::Failed(format!("{} probe: no reply within {}ms", kind, timeout.as_millis()))
+        },
+        Err(e) => { Outcome::Error(format!("{} probe: {}", kind, e)) },
+    }
+}
+
+// an address of the interface in the same network as the gateway, 0.0.0.0 otherwise
+fn arp_source(ips: &[ipnetwork::IpNetwork], gateway: Ipv4Addr) -> Ipv4Addr {
+    for n in ips {
+        if let ipnetwork::IpNetwork::V4(net) = n {
+            if net.contains(gateway) {
+                return net.ip();
+            }
+        }
+    }
+    Ipv4Addr::new(0, 0, 0, 0)
+}
+
+fn ndp_source(ips: &[ipnetwork::IpNetwork]) -> Option<Ipv6Addr> {
+    let link_local: ipnetwork::Ipv6Network = "fe80::/10".parse().unwrap();
+    for n in ips {
+        if let ipnetwork::IpNetwork::V6(net) = n {
+            if link_local.contains(net.ip()) {
+                return Some(net.ip());
+            }
+        }
+    }
+    None
+}
+
+// run the permitted methods in order until one of them finds the gateway alive,
+// the probes share whatever is left of the timeout
+pub fn check(interface: &str, ifindex: u32, ips: &[ipnetwork::IpNetwork], gateway: IpAddr, methods: &[Method], timeout: Duration) -> FirstHop {
+    let start = Instant::now();
+    let chain: Vec<Method> = methods.iter().filter(|m| m.applies_to(&gateway)).cloned().collect();
+    let mut attempts: Vec<String> = Vec::new();
+    let mut stale: Option<String> = None;
+    let mut failed = false;
+
+    for (i, method) in chain.iter().enumerate() {
+        let remaining = timeout.checked_sub(start.elapsed()).unwrap_or_default();
+        let probes_left = chain[i..].iter().filter(|m| **m != Method::Neighbor).count() as u32;
+        let step_timeout = if probes_left > 0 { remaining / probes_left } else { remaining };
+
+        let outcome = match (method, gateway) {
+            (Method::Neighbor, _) => { neighbor_cache(ifindex, &gateway) },
+            (Method::Arp, IpAddr::V4(gw)) => {
+                probe_outcome("ARP", probe::arp(interface, arp_source(ips, gw), gw, step_timeout), step_timeout)
+            },
+            (Method::Ndp, IpAddr::V6(gw)) => {
+                match ndp_source(ips) {
+                    Some(src) => { probe_outcome("NDP", probe::ndp(interface, src, gw, step_timeout), step_timeout) },
+                    None => { Outcome::Error("NDP probe: no link local address on interface".to_string()) },
+                }
+            },
+            _ => { continue; },
+        };
+
+        match outcome {
+            Outcome::Alive(detail, rtt_ms) => {
+                attempts.push(detail.clone());
+                return FirstHop{ gateway, verdict: VERDICT_ALIVE, detail, attempts, rtt_ms };
+            },
+            Outcome::Stale(detail) => {
+                attempts.push(detail.clone());
+                stale = Some(detail);
+            },
+            Outcome::Failed(detail) => {
+                attempts.push(detail);
+                failed = true;
+            },
+            Outcome::Error(detail) => { attempts.push(detail); },
+        };
+    }
+
+    // a probe without reply outweighs a stale cache entry
+    let (verdict, detail) = if failed {
+        (VERDICT_DEAD, attempts.join(", "))
+    } else if let Some(detail) = stale {
+        (VERDICT_STALE, detail)
+    } else if attempts.is_empty() {
+        (VERDICT_UNKNOWN, "no permitted method applies to this gateway".to_string())
+    } else {
+        (VERDICT_UNKNOWN, attempts.join(", "))
+    };
+
+    FirstHop{ gateway, verdict, detail, attempts, rtt_ms: None }
+}
+
+// check all default gateways of the interface, they share the timeout
+pub fn check_all(interface: &str, ifindex: u32, ips: &[ipnetwork::IpNetwork], methods: &[Method], timeout: Duration) -> Result<Vec<FirstHop>, CheckError> {
+    let gateways = gateways(ifindex)?;
+    let share = if gateways.is_empty() { timeout } else { timeout / gateways.len() as u32 };
+
+    Ok(gateways.into_iter().map(|gw| check(interface, ifindex, ips, gw, methods, share)).collect())
+}
+
+// ARP or NDP probe of the given gateway or of all default gateways of the interface,
+// they share the timeout
+pub fn probe_gateways(interface: &str, ifindex: u32, ips: &[ipnetwork::IpNetwork], gateway: Option<IpAddr>, timeout: Duration) -> Result<Vec<FirstHop>, CheckError> {
+    let gateways = match gateway {
+        Some(gw) => { vec![gw] },
+        None => { gateways(ifindex)? },
+    };
+    let share = if gateways.is_empty() { timeout } else { timeout / gateways.len() as u32 };
+
+    Ok(gateways.into_iter().map(|gw| check(interface, ifindex, ips, gw, PROBE_METHODS, share)).collect())
+}
@@ -0,0 +1,83 @@
+// Passive sampling of the sizes of received ethernet frames, e.g. to find out if jumbo
+// frames are actually used. Only the length of a frame is looked at, the payload is
+// never copied.
+use pnet::datalink;
+
+use std::time::{Duration, Instant};
+
+// upper bound of every bucket, frame sizes include the ethernet header and the FCS
+pub const BUCKETS: [(&str, usize); 5] = [
+    ("64", 64),
+    ("65_511", 511),
+    ("512_1023", 1023),
+    ("1024_1518", 1518),
+    ("1519_9216", 9216),
+];
+
+// largest frame without jumbo frames
+pub const MAX_STANDARD_FRAME: usize = 1518;
+
+// the kernel strips the frame check sequence before handing frames to packet sockets
+const FCS_LENGTH: usize = 4;
+
+#[derive(Serialize)]
+pub struct Histogram {
+    // number of frames per bucket, in the order of BUCKETS
+    pub frames: Vec<u64>,
+    // seconds actually spent listening
+    pub window: f64,
+}
+
+impl Histogram {
+    fn new() -> Histogram {
+        Histogram{ frames: vec![0; BUCKETS.len()], window: 0.0 }
+    }
+
+    // frames above the largest bucket, e.g. merged by GRO, count as the largest bucket
+    fn add(&mut self, captured_length: usize) {
+        let size = captured_length + FCS_LENGTH;
+        let bucket = BUCKETS.iter().position(|(_, max)| size <= *max).unwrap_or(BUCKETS.len() - 1);
+        self.frames[bucket] += 1;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.frames.iter().sum()
+    }
+
+    pub fn jumbo(&self) -> u64 {
+        BUCKETS.iter().zip(&self.frames).filter(|((_, max), _)| *max > MAX_STANDARD_FRAME).map(|(_, n)| n).sum()
+    }
+}
+
+// listen on the interface for the whole window
+pub fn capture(interface: &str, window: Duration) -> Result<Histogram, String> {
+    let iface = match datalink::interfaces().into_iter().find(|i| i.name == interface) {
+        Some(v) => { v },
+        None => { return Err(format!("Interface {} not found", interface)); },
+    };
+
+    let config = datalink::Config{ read_timeout: Some(Duration::from_millis(500)), ..Default::default() };
+    let mut rx = match datalink::channel(&iface, config) {
+        Ok(datalink::Channel::Ethernet(_, rx)) => { rx },
+        Ok(_) => { return Err("Unsupported channel type".to_string()); },
+        Err(e) => { return Err(e.to_string()); },
+    };
+
+    // the packet socket sees frames sent by this host as well
+    let own_mac: Option<[u8; 6]> = iface.mac.map(|m| [m.0, m.1, m.2, m.3, m.4, m.5]);
+
+    let mut histogram = Histogram::new();
+    let start = Instant::now();
+    while start.elapsed() < window {
+        // read timeouts are expected, just try again until the window is over
+        if let Ok(frame) = rx.next() {
+            if frame.len() >= 12 && own_mac.map(|m| frame[6..12] == m).unwrap_or(false) {
+                continue;
+            }
+            histogram.add(frame.len());
+        }
+    }
+    histogram.window = start.elapsed().as_secs_f64();
+
+    Ok(histogram)
+}
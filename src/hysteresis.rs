@@ -0,0 +1,29 @@
+// State reported for every threshold by the previous run, --hysteresis keeps a
+// violated threshold violated until the value clears it by a margin.
+use super::state::State;
+use super::threshold::Comparison;
+
+use std::collections::HashMap;
+
+const PREFIX: &str = "hysteresis.";
+
+// metric names contain spaces, e.g. "rx_errors rate"
+pub fn key(metric: &str) -> String {
+    metric.replace(' ', "_")
+}
+
+pub fn load(state: &State) -> HashMap<String, i32> {
+    let mut result = HashMap::new();
+    for (k, v) in state.with_prefix(PREFIX) {
+        if let Ok(s) = v.parse() {
+            result.insert(k.to_string(), s);
+        }
+    }
+    result
+}
+
+pub fn store(state: &mut State, comparisons: &[&Comparison]) {
+    for cmp in comparisons {
+        state.set(&format!("{}{}", PREFIX, key(&cmp.metric)), cmp.state);
+    }
+}
@@ -0,0 +1,1330 @@
+// State of a single interface as read from sysfs, rtnetlink and the state file.
+// All reads below /sys go through a SysfsReader so the state can be built from
+// fixtures instead of a real interface.
+use super::{bonding, changes, counters, ethtool, firsthop, framesize, hysteresis, netlink, pcie, peers, route, snmp, state, team, uplink, vlan, wireless};
+use super::error::CheckError;
+use super::config::{state_file_path, Backend, Configuration, DEFAULT_SYSFS_ROOT};
+use super::unix_now_ms;
+
+use pnet::datalink;
+
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::net::{IpAddr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+
+pub trait SysfsReader {
+    fn read_to_string(&self, path: &str) -> io::Result<String>;
+    fn read_link(&self, path: &str) -> io::Result<PathBuf>;
+    // names of the entries of a directory, sorted
+    fn read_dir(&self, path: &str) -> io::Result<Vec<String>>;
+}
+
+// the real /sys
+pub struct Sysfs {}
+
+impl SysfsReader for Sysfs {
+    fn read_to_string(&self, path: &str) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn read_link(&self, path: &str) -> io::Result<PathBuf> {
+        fs::read_link(path)
+    }
+
+    fn read_dir(&self, path: &str) -> io::Result<Vec<String>> {
+        let mut names: Vec<String> = fs::read_dir(path)?.flatten().map(|e| e.file_name().to_string_lossy().to_string()).collect();
+        names.sort();
+        Ok(names)
+    }
+}
+
+// files and symbolic links kept in memory, missing paths fail with NotFound
+#[derive(Default)]
+pub struct SysfsFixture {
+    files: HashMap<String, String>,
+    links: HashMap<String, PathBuf>,
+}
+
+impl SysfsFixture {
+    pub fn new() -> SysfsFixture {
+        SysfsFixture::default()
+    }
+
+    pub fn file(mut self, path: &str, content: &str) -> SysfsFixture {
+        self.files.insert(path.to_string(), content.to_string());
+        self
+    }
+
+    pub fn link(mut self, path: &str, target: &str) -> SysfsFixture {
+        self.links.insert(path.to_string(), PathBuf::from(target));
+        self
+    }
+}
+
+impl SysfsReader for SysfsFixture {
+    fn read_to_string(&self, path: &str) -> io::Result<String> {
+        match self.files.get(path) {
+            Some(v) => { Ok(v.clone()) },
+            None => { Err(io::Error::new(io::ErrorKind::NotFound, path.to_string())) },
+        }
+    }
+
+    fn read_link(&self, path: &str) -> io::Result<PathBuf> {
+        match self.links.get(path) {
+            Some(v) => { Ok(v.clone()) },
+            None => { Err(io::Error::new(io::ErrorKind::NotFound, path.to_string())) },
+        }
+    }
+
+    // a directory exists if it contains at least one file or link
+    fn read_dir(&self, path: &str) -> io::Result<Vec<String>> {
+        let mut names: Vec<String> = self.files.keys().chain(self.links.keys())
+            .filter_map(|p| Path::new(p).strip_prefix(path).ok())
+            .filter_map(|p| p.components().next())
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        if names.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, path.to_string()));
+        }
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+}
+
+// where the state of an interface was read from
+pub const SOURCE_SYSFS: &str = "sysfs";
+pub const SOURCE_NETLINK: &str = "netlink";
+pub const SOURCE_SNMP: &str = "snmp";
+
+#[derive(Default, Serialize)]
+pub struct InterfaceState {
+    pub name: String,
+    pub index: u32,
+    pub present: bool,
+    // unreadable values are reported as -1
+    #[serde(serialize_with = "negative_as_null")]
+    pub speed: i32,
+    #[serde(serialize_with = "negative_as_null")]
+    pub mtu: i32,
+    pub operstate: String,
+    pub duplex: String,
+    // None if the interface doesn't support the ethtool ioctl
+    pub autoneg: Option<bool>,
+    // active and configured FEC modes, None if not requested or not supported
+    pub fec: Option<ethtool::Fec>,
+    // ring buffer sizes, None if not requested or not supported
+    pub rings: Option<ethtool::Rings>,
+    // offload features, None if not requested or not supported
+    pub features: Option<Vec<ethtool::Feature>>,
+    // flow control, None if not requested or not supported
+    pub pause: Option<ethtool::Pause>,
+    // entries rx-* and tx-* in the queues directory, None if not requested
+    pub rx_queues: Option<u32>,
+    pub tx_queues: Option<u32>,
+    // None if not requested or not supported
+    pub channels: Option<ethtool::Channels>,
+    // -1 if the device has no NUMA affinity, None without a device directory
+    pub numa_node: Option<i32>,
+    // enabled and supported SR-IOV virtual functions, None if not requested or no SR-IOV
+    pub sriov_numvfs: Option<u32>,
+    pub sriov_totalvfs: Option<u32>,
+    // None if not requested or the device has no PCIe link
+    pub pcie: Option<pcie::Link>,
+    // driver and firmware version, None if not requested or not supported
+    pub driver_info: Option<ethtool::DriverInfo>,
+    // lower devices and a bond or bridge master, empty if not requested
+    pub related_mtus: Vec<RelatedMtu>,
+    // alternative names, None if not requested
+    pub altnames: Option<Vec<String>>,
+    // IFF_* flags from netlink or the flags file in sysfs
+    pub flags: Option<u32>,
+    // hardware address, None for devices without one like tunnels
+    pub mac: Option<String>,
+    pub ips: Vec<ipnetwork::IpNetwork>,
+    pub local_cpus: Option<Vec<u32>>,
+    pub napi_threads: Vec<NapiThread>,
+    #[serde(serialize_with = "negative_as_null")]
+    pub effective_mtu: i32,
+    pub counters: Vec<counters::Counter>,
+    pub first_hop: Vec<firsthop::FirstHop>,
+    // empty unless --probe-gateway is used
+    pub gateway_probes: Vec<firsthop::FirstHop>,
+    pub subnet_peers: Vec<peers::SubnetPeers>,
+    // None unless --uplink-check is used
+    pub uplink: Option<uplink::Uplink>,
+    // None unless --frame-size-histogram is used
+    pub frame_sizes: Option<framesize::Histogram>,
+    // None if the interface is administratively down, the kernel doesn't report a carrier then
+    pub carrier: Option<bool>,
+    pub carrier_changes: Option<u64>,
+    pub carrier_up_count: Option<u64>,
+    pub carrier_down_count: Option<u64>,
+    // carrier_changes with the rate since the previous run, None unless --flaps-warn or
+    // --flaps-crit is used or if the kernel doesn't report carrier changes
+    pub flaps: Option<counters::Counter>,
+    // statistics/collisions, None if the driver doesn't count them
+    pub collisions: Option<u64>,
+    // bond or bridge the interface is enslaved to
+    pub master: Option<String>,
+    pub driver: Option<String>,
+    // None unless --txqueuelen is used
+    pub tx_queue_len: Option<u64>,
+    pub changes: Vec<changes::Change>,
+    // threshold states reported by the previous run, only loaded for --hysteresis
+    #[serde(skip)]
+    pub reported_states: HashMap<String, i32>,
+    // None if the interface is not a bond
+    pub bond_slaves: Option<Vec<bonding::Slave>>,
+    pub bond_mode: Option<bonding::Mode>,
+    // None unless --bond-slave-status or --check-lacp is used
+    pub bond_slave_status: Option<Vec<bonding::SlaveStatus>>,
+    // None if the interface is not a team or neither --team-min-ports nor --team-runner is used
+    pub team_ports: Option<Vec<team::Port>>,
+    pub team_runner: Option<String>,
+    // None if the interface is not a bridge
+    pub bridge_ports: Option<Vec<String>>,
+    // None if the interface is not a VLAN device
+    pub vlan: Option<vlan::Vlan>,
+    pub vlan_parent_operstate: Option<String>,
+    // None unless --expect-qdisc is used
+    pub qdisc: Option<Qdiscs>,
+    // None unless --expect-neighbor is used
+    pub neighbors: Option<Vec<Neighbor>>,
+    // runtime power management of the underlying device, None for virtual devices
+    pub runtime_pm: Option<RuntimePm>,
+    // attributes below power/ of the interface itself, only read with --verbose
+    pub power: BTreeMap<String, String>,
+    // the same data as seen by rtnetlink, only collected for --cross-check-backends
+    pub netlink: Option<NetlinkState>,
+    // sources of the same value which disagree, shown with --verbose
+    pub discrepancies: Vec<String>,
+    // attributes of an existing interface which couldn't be read, with the error of the read
+    pub read_errors: BTreeMap<String, String>,
+    // no device directory in sysfs, e.g. veth, wireguard, tun or bridge devices
+    pub virtual_device: bool,
+    // IFF_LOOPBACK is set
+    pub loopback: bool,
+    // None if the interface is not a wireless device
+    pub wireless: Option<wireless::Wireless>,
+    // addresses in or after a failed duplicate address detection
+    pub unusable_addresses: Vec<UnusableAddress>,
+    // IPv6 privacy extension addresses, None unless --ipv6-require-stable is used and
+    // netlink reports the flags of the addresses
+    pub temporary_addresses: Option<Vec<IpAddr>>,
+    // None unless --expect-default-route is used and the routing table could be read
+    pub default_routes: Option<route::DefaultRoutes>,
+    pub source: &'static str,
+}
+
+#[derive(Serialize)]
+pub struct UnusableAddress {
+    pub ip: IpAddr,
+    // "tentative" or "dadfailed"
+    pub state: &'static str,
+    // seconds since the address was added, None if unknown
+    pub age: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct Qdiscs {
+    pub root: String,
+    // qdiscs of the transmit queues in queue order, only if the root qdisc is mq
+    pub queues: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct Neighbor {
+    pub ip: IpAddr,
+    // REACHABLE, STALE, FAILED, ...
+    pub state: &'static str,
+    // None if the neighbor is not resolved
+    pub mac: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct RuntimePm {
+    // "on" keeps the device powered, "auto" allows the kernel to suspend it when idle
+    pub control: String,
+    // "active", "suspended", "suspending", "resuming" or "unsupported"
+    pub runtime_status: String,
+}
+
+impl RuntimePm {
+    pub fn enabled(&self) -> bool {
+        self.control == "auto"
+    }
+}
+
+#[derive(Serialize)]
+pub struct NetlinkState {
+    pub operstate: String,
+    pub flags: u32,
+    #[serde(serialize_with = "negative_as_null")]
+    pub mtu: i32,
+    pub ips: Vec<ipnetwork::IpNetwork>,
+}
+
+fn negative_as_null<S: serde::Serializer>(value: &i32, serializer: S) -> Result<S::Ok, S::Error> {
+    if *value < 0 {
+        serializer.serialize_none()
+    } else {
+        serializer.serialize_i32(*value)
+    }
+}
+
+// MTU of a lower device or of the master of the interface
+#[derive(Serialize)]
+pub struct RelatedMtu {
+    pub name: String,
+    // "lower" or the kind of master, "bond" or "bridge"
+    pub relation: &'static str,
+    pub mtu: i32,
+}
+
+#[derive(Serialize)]
+pub struct NapiThread {
+    pub name: String,
+    pub cpus: Vec<u32>,
+}
+
+// unbound workqueue from /sys/bus/workqueue/devices, the kworkers of it run on these CPUs
+#[derive(Serialize)]
+pub struct Workqueue {
+    pub name: String,
+    pub cpus: Vec<u32>,
+}
+
+// unbound workqueues use the default cpumask unless it was narrowed for the workqueue
+pub struct Workqueues {
+    pub default_cpus: Vec<u32>,
+    pub queues: Vec<Workqueue>,
+}
+
+// parse kernel CPU lists like "0-3,8,10-11"
+fn parse_cpu_list(raw: &str) -> Result<Vec<u32>, CheckError> {
+    let mut cpus = Vec::new();
+
+    for part in raw.trim().split(',') {
+        if part.is_empty() {
+            continue;
+        }
+
+        let range: Vec<&str> = part.split('-').collect();
+        let start: u32 = match range[0].parse() {
+            Ok(v) => { v },
+            Err(_) => { return Err(CheckError::parse("CPU number", part)); },
+        };
+        let end: u32 = match range.len() {
+            1 => { start },
+            2 => {
+                match range[1].parse() {
+                    Ok(v) => { v },
+                    Err(_) => { return Err(CheckError::parse("CPU number", part)); },
+                }
+            },
+            _ => { return Err(CheckError::parse("CPU range", part)); },
+        };
+
+        for c in start..=end {
+            cpus.push(c);
+        }
+    }
+
+    Ok(cpus)
+}
+
+// parse kernel CPU masks like "ffffffff,00000003", 32 bit groups with the highest CPUs first
+fn parse_cpu_mask(raw: &str) -> Result<Vec<u32>, CheckError> {
+    let mut cpus = Vec::new();
+
+    for (i, c) in raw.trim().chars().filter(|c| *c != ',').rev().enumerate() {
+        let nibble = match c.to_digit(16) {
+            Some(v) => { v },
+            None => { return Err(CheckError::parse("CPU mask", raw)); },
+        };
+        for bit in 0..4 {
+            if nibble & (1 << bit) != 0 {
+                cpus.push(i as u32 * 4 + bit);
+            }
+        }
+    }
+
+    Ok(cpus)
+}
+
+// IFF_* flags with their names as shown by ip link, in bit order
+pub const FLAG_NAMES: [(u32, &str); 19] = [
+    (libc::IFF_UP as u32, "UP"), (libc::IFF_BROADCAST as u32, "BROADCAST"), (libc::IFF_DEBUG as u32, "DEBUG"),
+    (libc::IFF_LOOPBACK as u32, "LOOPBACK"), (libc::IFF_POINTOPOINT as u32, "POINTOPOINT"), (libc::IFF_NOTRAILERS as u32, "NOTRAILERS"),
+    (libc::IFF_RUNNING as u32, "RUNNING"), (libc::IFF_NOARP as u32, "NOARP"), (libc::IFF_PROMISC as u32, "PROMISC"),
+    (libc::IFF_ALLMULTI as u32, "ALLMULTI"), (libc::IFF_MASTER as u32, "MASTER"), (libc::IFF_SLAVE as u32, "SLAVE"),
+    (libc::IFF_MULTICAST as u32, "MULTICAST"), (libc::IFF_PORTSEL as u32, "PORTSEL"), (libc::IFF_AUTOMEDIA as u32, "AUTOMEDIA"),
+    (libc::IFF_DYNAMIC as u32, "DYNAMIC"), (libc::IFF_LOWER_UP as u32, "LOWER_UP"), (libc::IFF_DORMANT as u32, "DORMANT"),
+    (libc::IFF_ECHO as u32, "ECHO"),
+];
+
+pub fn flag_names(flags: u32) -> Vec<&'static str> {
+    FLAG_NAMES.iter().filter(|(bit, _)| flags & bit != 0).map(|(_, name)| *name).collect()
+}
+
+// case doesn't matter, IFF_ may be used as prefix
+pub fn flag_bit(name: &str) -> Option<u32> {
+    let name = name.to_uppercase();
+    let name = name.trim_start_matches("IFF_");
+    FLAG_NAMES.iter().find(|(_, n)| *n == name).map(|(bit, _)| *bit)
+}
+
+// the kernel adds these to the flags of netlink, but not to the flags file in sysfs
+fn operational_flags(flags: u32, operstate: &str, carrier: bool) -> u32 {
+    if flags & libc::IFF_UP as u32 == 0 {
+        return 0;
+    }
+    let mut result = 0;
+    if operstate == "up" || operstate == "unknown" {
+        result |= libc::IFF_RUNNING as u32;
+    }
+    if carrier {
+        result |= libc::IFF_LOWER_UP as u32;
+    }
+    if operstate == "dormant" {
+        result |= libc::IFF_DORMANT as u32;
+    }
+    result
+}
+
+pub fn format_cpu_list(cpus: &[u32]) -> String {
+    let mut ranges: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while i < cpus.len() {
+        let start = cpus[i];
+        while i + 1 < cpus.len() && cpus[i + 1] == cpus[i] + 1 {
+            i += 1;
+        }
+        if cpus[i] == start {
+            ranges.push(start.to_string());
+        } else {
+            ranges.push(format!("{}-{}", start, cpus[i]));
+        }
+        i += 1;
+    }
+
+    ranges.join(",")
+}
+
+// None if the device has no power management, e.g. virtual devices
+fn read_runtime_pm(sysfs: &dyn SysfsReader, sysfs_path: &str) -> Result<Option<RuntimePm>, CheckError> {
+    let control = match sysfs.read_to_string(&format!("{}/device/power/control", sysfs_path)) {
+        Ok(s) => { s.trim().to_string() },
+        Err(_) => { return Ok(None); },
+    };
+    let status_file = format!("{}/device/power/runtime_status", sysfs_path);
+    let runtime_status = match sysfs.read_to_string(&status_file) {
+        Ok(s) => { s.trim().to_string() },
+        Err(e) => { return Err(CheckError::io(&status_file, e)); },
+    };
+    Ok(Some(RuntimePm{ control, runtime_status }))
+}
+
+// unreadable attributes are left out
+fn read_power_attributes(sysfs: &dyn SysfsReader, sysfs_path: &str) -> BTreeMap<String, String> {
+    let mut attributes = BTreeMap::new();
+    let dir = format!("{}/power", sysfs_path);
+    for name in sysfs.read_dir(&dir).unwrap_or_default() {
+        if let Ok(s) = sysfs.read_to_string(&format!("{}/{}", dir, name)) {
+            attributes.insert(name, s.trim().to_string());
+        }
+    }
+    attributes
+}
+
+// threaded NAPI polling runs in kernel threads named napi/<interface>-<napi_id>
+fn read_napi_threads(sysfs: &dyn SysfsReader, interface: &str) -> Result<Vec<NapiThread>, CheckError> {
+    let mut threads = Vec::new();
+    let prefix = format!("napi/{}-", interface);
+
+    let procs = match sysfs.read_dir("/proc") {
+        Ok(v) => { v },
+        Err(e) => { return Err(CheckError::io("/proc", e)); },
+    };
+
+    for pid in procs {
+        if !pid.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+
+        // processes may vanish while we are looking at them
+        let name = match sysfs.read_to_string(&format!("/proc/{}/comm", pid)) {
+            Ok(s) => { s.trim().to_string() },
+            Err(_) => { continue; },
+        };
+        if !name.starts_with(&prefix) {
+            continue;
+        }
+
+        let status = match sysfs.read_to_string(&format!("/proc/{}/status", pid)) {
+            Ok(s) => { s },
+            Err(_) => { continue; },
+        };
+        for line in status.lines() {
+            if let Some(list) = line.strip_prefix("Cpus_allowed_list:") {
+                let cpus = parse_cpu_list(list)?;
+                threads.push(NapiThread{ name: name.clone(), cpus });
+            }
+        }
+    }
+
+    Ok(threads)
+}
+
+// only unbound workqueues have a cpumask, per CPU workqueues run wherever the work was queued
+pub fn read_workqueues(sysfs: &dyn SysfsReader, sysfs_mount: &str) -> Result<Workqueues, CheckError> {
+    let default_file = format!("{}/devices/virtual/workqueue/cpumask", sysfs_mount);
+    let default_cpus = match sysfs.read_to_string(&default_file) {
+        Ok(s) => { parse_cpu_mask(&s)? },
+        Err(e) => { return Err(CheckError::io(&default_file, e)); },
+    };
+
+    let mut queues = Vec::new();
+    let dir = format!("{}/bus/workqueue/devices", sysfs_mount);
+    for name in sysfs.read_dir(&dir).unwrap_or_default() {
+        if let Ok(s) = sysfs.read_to_string(&format!("{}/{}/cpumask", dir, name)) {
+            let cpus = parse_cpu_mask(&s)?;
+            queues.push(Workqueue{ name, cpus });
+        }
+    }
+
+    Ok(Workqueues{ default_cpus, queues })
+}
+
+// where sysfs is mounted, for the files outside of class/net. A copied tree only has it if
+// it was copied from the mount point down.
+pub fn sysfs_mount(sysfs_root: &str) -> Option<String> {
+    if sysfs_root == DEFAULT_SYSFS_ROOT {
+        return Some("/sys".to_string());
+    }
+    sysfs_root.strip_suffix("/class/net").filter(|m| !m.is_empty()).map(|m| m.to_string())
+}
+
+impl InterfaceState {
+    pub fn new(cfg: &Configuration, interface: &str, sysfs: &dyn SysfsReader) -> Result<InterfaceState, CheckError> {
+        let mut ifs = InterfaceState::not_present(interface, SOURCE_SYSFS);
+        let sysfs_path = format!("{}/{}", cfg.sysfs_root, interface);
+        // netlink, ethtool and /proc only know the interfaces of the running kernel, not those
+        // of a copied tree, even if the names are the same
+        let host = cfg.sysfs_root == DEFAULT_SYSFS_ROOT;
+
+        let mut operstate_file = sysfs_path.clone();
+        operstate_file.push_str("/operstate");
+
+        let mut duplex_file = sysfs_path.clone();
+        duplex_file.push_str("/duplex");
+
+        let mut mtu_file = sysfs_path.clone();
+        mtu_file.push_str("/mtu");
+
+        let mut speed_file = sysfs_path.clone();
+        speed_file.push_str("/speed");
+
+        match cfg.backend {
+            Backend::Netlink if host => {
+                // operstate, MTU, flags and addresses are read together instead of from
+                // separate files at different moments
+                ifs.index = interface_index(interface);
+                if ifs.index == 0 {
+                    return Ok(ifs);
+                }
+                ifs.present = true;
+                let nl = read_netlink_state(ifs.index)?;
+                ifs.mtu = nl.mtu;
+                ifs.ips = nl.ips;
+                ifs.flags = Some(nl.flags);
+                ifs.operstate = nl.operstate;
+            },
+            _ => {
+                if host {
+                    for interface in datalink::interfaces() {
+                        if interface.name == ifs.name {
+                            ifs.ips = interface.ips;
+                            ifs.index = interface.index;
+                        }
+                    }
+                }
+
+                // only a missing interface has no directory, anything unreadable below it
+                // is reported for the attribute alone
+                ifs.present = sysfs.read_dir(&sysfs_path).is_ok() || ifs.index != 0;
+                if !ifs.present {
+                    return Ok(ifs);
+                }
+
+                match sysfs.read_to_string(&operstate_file) {
+                    Ok(s) => { ifs.operstate = s.trim().to_string(); },
+                    Err(e) => { ifs.read_errors.insert("operstate".to_string(), e.to_string()); },
+                };
+
+                match sysfs.read_to_string(&mtu_file) {
+                    Ok(s) => {
+                        ifs.mtu = match s.trim().parse() {
+                            Ok(v) => { v },
+                            Err(_) => {
+                                return Err(CheckError::parse(&format!("MTU in {}", mtu_file), &s));
+                            },
+                        };
+                    },
+                    Err(e) => { ifs.read_errors.insert("mtu".to_string(), e.to_string()); },
+                };
+            },
+        };
+
+        ifs.virtual_device = match sysfs.read_dir(&format!("{}/device", sysfs_path)) {
+            Err(ref e) => { e.kind() == io::ErrorKind::NotFound },
+            Ok(_) => { false },
+        };
+
+        if host && wireless::is_wireless(sysfs, &sysfs_path) {
+            ifs.wireless = Some(wireless::read(interface, ifs.index));
+        }
+
+        // flags are written as hex number, e.g. 0x1003
+        if ifs.flags.is_none() {
+            if let Ok(s) = sysfs.read_to_string(&format!("{}/flags", sysfs_path)) {
+                let up = sysfs.read_to_string(&format!("{}/carrier", sysfs_path)).map(|c| c.trim() == "1").unwrap_or(false);
+                ifs.flags = u32::from_str_radix(s.trim().trim_start_matches("0x"), 16).ok().map(|f| f | operational_flags(f, &ifs.operstate, up));
+            }
+        }
+        if let Some(f) = ifs.flags {
+            ifs.loopback = f & libc::IFF_LOOPBACK as u32 != 0;
+        }
+
+        // neither pnet nor sysfs know the flags of an address
+        if host && (ifs.ips.iter().any(|n| n.is_ipv6()) || cfg.ipv6_require_stable) {
+            let (unusable, temporary) = read_address_flags(interface, ifs.index);
+            ifs.unusable_addresses = unusable;
+            if cfg.ipv6_require_stable {
+                ifs.temporary_addresses = temporary;
+            }
+        }
+
+        if cfg.expect_default_route && host {
+            match route::default_routes() {
+                Ok(v) => { ifs.default_routes = Some(v); },
+                Err(e) => { ifs.read_errors.insert("default_routes".to_string(), e.to_string()); },
+            };
+        }
+
+        // some drivers fail to report speed and duplex in sysfs but answer the ethtool
+        // ioctl, which only knows the interfaces of the running kernel
+        let link_settings = if host { ethtool::link_settings(interface).ok() } else { None };
+        ifs.autoneg = link_settings.as_ref().map(|l| l.autoneg);
+
+        // the FEC mode is also shown with --verbose, NICs below 25G usually don't support the query
+        ifs.fec = if (cfg.fec.is_some() || cfg.verbose) && host {
+            match ethtool::fec(interface) {
+                Ok(v) => { Some(v) },
+                Err(e) if cfg.fec.is_some() => {
+                    ifs.read_errors.insert("fec".to_string(), e.to_string());
+                    None
+                },
+                Err(_) => { None },
+            }
+        } else {
+            None
+        };
+
+        let ring_check = cfg.ring_rx.is_some() || cfg.ring_tx.is_some();
+        ifs.rings = if (ring_check || cfg.verbose) && host {
+            match ethtool::rings(interface) {
+                Ok(v) => { Some(v) },
+                Err(e) if ring_check => {
+                    ifs.read_errors.insert("rings".to_string(), e.to_string());
+                    None
+                },
+                Err(_) => { None },
+            }
+        } else {
+            None
+        };
+
+        ifs.features = if !cfg.features.is_empty() && host {
+            match ethtool::features(interface) {
+                Ok(v) => { Some(v) },
+                Err(e) => {
+                    ifs.read_errors.insert("features".to_string(), e.to_string());
+                    None
+                },
+            }
+        } else {
+            None
+        };
+
+        // virtual devices don't support pause frames, that's only an error if asked for
+        ifs.pause = if (!cfg.pause.is_empty() || cfg.verbose) && host {
+            match ethtool::pause(interface) {
+                Ok(v) => { Some(v) },
+                Err(e) if !cfg.pause.is_empty() => {
+                    ifs.read_errors.insert("pause".to_string(), e.to_string());
+                    None
+                },
+                Err(_) => { None },
+            }
+        } else {
+            None
+        };
+
+        ifs.altnames = if cfg.expect_altname.is_some() && host {
+            match netlink::link_get(ifs.index) {
+                Ok(l) => { Some(l.altnames) },
+                Err(e) => {
+                    ifs.read_errors.insert("altnames".to_string(), e.to_string());
+                    None
+                },
+            }
+        } else {
+            None
+        };
+
+        let firmware_check = cfg.firmware.is_some() || cfg.firmware_min.is_some();
+        ifs.driver_info = if (firmware_check || cfg.verbose) && host {
+            match ethtool::driver_info(interface) {
+                Ok(v) => { Some(v) },
+                Err(e) if firmware_check => {
+                    ifs.read_errors.insert("driver_info".to_string(), e.to_string());
+                    None
+                },
+                Err(_) => { None },
+            }
+        } else {
+            None
+        };
+
+        ifs.channels = if cfg.queues_combined.is_some() && host {
+            match ethtool::channels(interface) {
+                Ok(v) => { Some(v) },
+                Err(e) => {
+                    ifs.read_errors.insert("channels".to_string(), e.to_string());
+                    None
+                },
+            }
+        } else {
+            None
+        };
+
+        let sysfs_duplex = sysfs.read_to_string(&duplex_file).map(|s| s.trim().to_string());
+        match (&sysfs_duplex, &link_settings) {
+            (Ok(s), _) => { ifs.duplex = s.clone(); },
+            (Err(_), Some(l)) => { ifs.duplex = l.duplex.to_string(); },
+            (Err(e), None) => { ifs.read_errors.insert("duplex".to_string(), e.to_string()); },
+        };
+
+        ifs.effective_mtu = ifs.mtu;
+        if let (Some(dst), true) = (cfg.effective_mtu_to, host) {
+            let route = match netlink::route_get(dst) {
+                Ok(v) => { v },
+                Err(e) => { return Err(CheckError::netlink(&format!("route to {}", dst), e)); },
+            };
+            // routes without a MTU metric use the MTU of the interface
+            if let Some(route_mtu) = route.mtu {
+                if route_mtu > 0 && (route_mtu as i32) < ifs.effective_mtu {
+                    ifs.effective_mtu = route_mtu as i32;
+                }
+            }
+        }
+
+        let sysfs_speed: Result<i32, String> = match sysfs.read_to_string(&speed_file) {
+            Ok(s) => {
+                match s.trim().parse() {
+                    Ok(v) => { Ok(v) },
+                    Err(_) => { return Err(CheckError::parse(&format!("link speed in {}", speed_file), &s)); },
+                }
+            },
+            Err(e) => { Err(e.to_string()) },
+        };
+        match (&sysfs_speed, &link_settings) {
+            (Ok(v), _) => { ifs.speed = *v; },
+            (Err(_), Some(l)) => { ifs.speed = l.speed; },
+            (Err(e), None) => { ifs.read_errors.insert("speed".to_string(), e.clone()); },
+        };
+
+        // ethtool is asked directly, it wins if both sources answered and disagree
+        if let (Ok(_), Ok(_), Some(l)) = (&sysfs_duplex, &sysfs_speed, &link_settings) {
+            if l.speed != ifs.speed || l.duplex != ifs.duplex {
+                ifs.discrepancies.push(format!("sysfs reports speed {} MBit/s and duplex {}, ethtool reports speed {} MBit/s and duplex {}, using ethtool", ifs.speed, ifs.duplex, l.speed, l.duplex));
+                ifs.speed = l.speed;
+                ifs.duplex = l.duplex.to_string();
+            }
+        }
+
+        // reading carrier fails with EINVAL if the interface is administratively down,
+        // carrier_up_count/carrier_down_count require Linux 4.16
+        if let Ok(s) = sysfs.read_to_string(&format!("{}/carrier", sysfs_path)) {
+            ifs.carrier = Some(s.trim() == "1");
+        }
+        ifs.carrier_changes = read_sysfs_u64(sysfs, &format!("{}/carrier_changes", sysfs_path));
+        ifs.carrier_up_count = read_sysfs_u64(sysfs, &format!("{}/carrier_up_count", sysfs_path));
+        ifs.carrier_down_count = read_sysfs_u64(sysfs, &format!("{}/carrier_down_count", sysfs_path));
+        ifs.collisions = read_sysfs_u64(sysfs, &format!("{}/statistics/collisions", sysfs_path));
+
+        ifs.master = read_sysfs_link_name(sysfs, &format!("{}/master", sysfs_path));
+        ifs.driver = read_sysfs_link_name(sysfs, &format!("{}/device/driver", sysfs_path));
+        ifs.mac = sysfs.read_to_string(&format!("{}/address", sysfs_path)).ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        if cfg.txqueuelen.is_some() {
+            ifs.tx_queue_len = read_sysfs_u64(sysfs, &format!("{}/tx_queue_len", sysfs_path));
+        }
+        if cfg.mtu_consistency {
+            ifs.related_mtus = read_related_mtus(sysfs, &cfg.sysfs_root, &sysfs_path, ifs.master.as_deref());
+        }
+        if cfg.numa_node.is_some() || cfg.verbose {
+            ifs.numa_node = sysfs.read_to_string(&format!("{}/device/numa_node", sysfs_path)).ok().and_then(|s| s.trim().parse().ok());
+        }
+        if cfg.sriov_vfs.is_some() {
+            ifs.sriov_numvfs = read_sysfs_u64(sysfs, &format!("{}/device/sriov_numvfs", sysfs_path)).map(|v| v as u32);
+            ifs.sriov_totalvfs = read_sysfs_u64(sysfs, &format!("{}/device/sriov_totalvfs", sysfs_path)).map(|v| v as u32);
+        }
+        if cfg.pcie_speed.is_some() || cfg.pcie_width.is_some() || cfg.pcie_check_max {
+            ifs.pcie = pcie::read_link(sysfs, &format!("{}/device", sysfs_path));
+        }
+        if cfg.queues_rx.is_some() || cfg.queues_tx.is_some() {
+            if let Ok(queues) = sysfs.read_dir(&format!("{}/queues", sysfs_path)) {
+                ifs.rx_queues = Some(queues.iter().filter(|q| q.starts_with("rx-")).count() as u32);
+                ifs.tx_queues = Some(queues.iter().filter(|q| q.starts_with("tx-")).count() as u32);
+            }
+        }
+
+        if cfg.bond_min_slaves.is_some() {
+            ifs.bond_slaves = bonding::read_slaves(sysfs, &cfg.sysfs_root, &sysfs_path)?;
+        }
+        if cfg.bond_mode.is_some() {
+            ifs.bond_mode = bonding::read_mode(sysfs, interface, &sysfs_path)?;
+        }
+        if cfg.bond_slave_status || cfg.check_lacp {
+            ifs.bond_slave_status = bonding::read_slave_status(sysfs, interface, &sysfs_path)?;
+        }
+        if (cfg.team_min_ports.is_some() || cfg.team_runner.is_some()) && team::is_team(sysfs, &sysfs_path, ifs.index) {
+            if cfg.team_min_ports.is_some() {
+                ifs.team_ports = Some(team::read_ports(sysfs, &cfg.sysfs_root, interface)?);
+            }
+            if cfg.team_runner.is_some() && host {
+                ifs.team_runner = Some(team::read_runner(interface)?);
+            }
+        }
+        if !cfg.bridge_ports.is_empty() {
+            ifs.bridge_ports = read_bridge_ports(sysfs, &sysfs_path)?;
+        }
+
+        if cfg.vlan_id.is_some() || cfg.vlan_parent.is_some() {
+            ifs.vlan = vlan::read(sysfs, &cfg.sysfs_root, interface)?;
+            if let Some(ref v) = ifs.vlan {
+                ifs.vlan_parent_operstate = sysfs.read_to_string(&format!("{}/{}/operstate", cfg.sysfs_root, v.parent)).ok().map(|s| s.trim().to_string());
+            }
+        }
+
+        if cfg.expect_qdisc.is_some() && host {
+            ifs.qdisc = Some(read_qdiscs(ifs.index)?);
+        }
+        if !cfg.expect_neighbors.is_empty() && host {
+            ifs.neighbors = Some(read_neighbors(ifs.index)?);
+        }
+
+        if cfg.expect_runtime_pm.is_some() || cfg.verbose {
+            ifs.runtime_pm = read_runtime_pm(sysfs, &sysfs_path)?;
+        }
+        if cfg.verbose {
+            ifs.power = read_power_attributes(sysfs, &sysfs_path);
+        }
+
+        if cfg.check_napi_balance || cfg.check_wq_balance {
+            let mut local_cpulist_file = sysfs_path.clone();
+            local_cpulist_file.push_str("/device/local_cpulist");
+
+            // virtual devices have no device directory and therefore no CPU locality
+            if let Ok(s) = sysfs.read_to_string(&local_cpulist_file) {
+                ifs.local_cpus = Some(parse_cpu_list(&s)?);
+            }
+        }
+        if cfg.check_napi_balance && host {
+            ifs.napi_threads = read_napi_threads(sysfs, interface)?;
+        }
+
+        let counter_names = cfg.counter_names();
+        if !counter_names.is_empty() || !cfg.alert_on_change.is_empty() || cfg.hysteresis.is_some() || (cfg.bond_slave_status && ifs.bond_slave_status.is_some())
+            || cfg.flaps_warn.is_some() || cfg.flaps_crit.is_some() {
+            let state_file = state_file_path(cfg, interface);
+            let mut state = match state::State::load(&state_file) {
+                Ok(v) => { v },
+                Err(e) => { return Err(CheckError::io(&state_file, e)); },
+            };
+            let now = unix_now_ms();
+
+            if cfg.hysteresis.is_some() {
+                ifs.reported_states = hysteresis::load(&state);
+            }
+
+            // 64 bit counters from netlink never wrap in practice, sysfs is the fallback
+            let link = if host { netlink::link_get(ifs.index).ok() } else { None };
+            for counter in counter_names {
+                let (value, source, width) = match link.as_ref().and_then(|l| l.stat64(counter)) {
+                    Some(v) => { (v, SOURCE_NETLINK, counters::WIDTH_64) },
+                    None => {
+                        // a counter of --stat the driver doesn't provide is reported, not fatal
+                        if cfg.stats.iter().any(|s| s.name == counter) {
+                            let available = counters::available_statistics(sysfs, &sysfs_path);
+                            if !available.iter().any(|a| a == counter) {
+                                ifs.read_errors.insert(format!("stat.{}", counter), format!("No statistics counter {}, available: {}", counter, available.join(", ")));
+                                continue;
+                            }
+                        }
+                        (counters::read_statistic(sysfs, &sysfs_path, counter)?, SOURCE_SYSFS, counters::SYSFS_WIDTH)
+                    },
+                };
+                // at most line rate, packets and errors are limited by the size of the smallest frame
+                // on the wire (64 bytes plus preamble and inter frame gap)
+                let max_rate = if ifs.speed > 0 {
+                    let bytes = ifs.speed as f64 * 1_000_000.0 / 8.0;
+                    if counter.ends_with("_bytes") { Some(bytes) } else { Some(bytes / 84.0) }
+                } else {
+                    None
+                };
+                let (rate, wrapped) = counters::update(&mut state, counter, value, width, source, max_rate, now);
+                ifs.counters.push(counters::Counter{ name: counter.to_string(), value, rate, width, wrapped });
+            }
+
+            // a reboot resets carrier_changes, counters::update starts a new baseline then
+            if let (true, Some(value)) = (cfg.flaps_warn.is_some() || cfg.flaps_crit.is_some(), ifs.carrier_changes) {
+                let (rate, _) = counters::update(&mut state, "carrier_changes", value, counters::SYSFS_WIDTH, SOURCE_SYSFS, None, now);
+                ifs.flaps = Some(counters::Counter{ name: "carrier_changes".to_string(), value, rate, width: counters::SYSFS_WIDTH, wrapped: false });
+            }
+
+            // only selected metrics are stored
+            for metric in &cfg.alert_on_change {
+                let value = match metric.as_str() {
+                    "speed" => { ifs.speed.to_string() },
+                    "duplex" => { ifs.duplex.clone() },
+                    "mtu" => { ifs.mtu.to_string() },
+                    "ipv4_count" => { ifs.ips.iter().filter(|n| n.is_ipv4()).count().to_string() },
+                    "ipv6_count" => { ifs.ips.iter().filter(|n| n.is_ipv6()).count().to_string() },
+                    "master" => { ifs.master.clone().unwrap_or_else(|| "none".to_string()) },
+                    "driver" => { ifs.driver.clone().unwrap_or_else(|| "none".to_string()) },
+                    _ => { continue; },
+                };
+                if let Some(c) = changes::update(&mut state, metric, &value, now / 1000) {
+                    ifs.changes.push(c);
+                }
+            }
+
+            // link failures are stored for --bond-slave-status only
+            match ifs.bond_slave_status {
+                Some(ref mut slaves) if cfg.bond_slave_status => { bonding::update_link_failures(&mut state, slaves); },
+                _ => {},
+            };
+
+            if let Err(e) = state.save() {
+                return Err(CheckError::io(&state_file, e));
+            }
+        }
+
+        if cfg.cross_check_backends {
+            ifs.netlink = read_netlink_state(ifs.index).ok();
+        }
+
+        Ok(ifs)
+    }
+
+    // an interface without any state, nothing else is checked for it
+    pub fn not_present(interface: &str, source: &'static str) -> InterfaceState {
+        InterfaceState{
+            name: interface.to_string(),
+            speed: -1,
+            mtu: -1,
+            operstate: "unknown".to_string(),
+            duplex: "unknown".to_string(),
+            effective_mtu: -1,
+            source,
+            ..Default::default()
+        }
+    }
+
+    // operstate, speed, MTU, hardware address and addresses of a remote device from
+    // IF-MIB and IP-MIB, the interface is selected by ifName or ifDescr
+    pub fn from_snmp(cfg: &Configuration, interface: &str) -> Result<InterfaceState, CheckError> {
+        let host = cfg.snmp_host.clone().unwrap_or_default();
+        let snmp_error = |request: &str, e: io::Error| { CheckError::Snmp{ host: host.clone(), request: request.to_string(), source: e } };
+
+        let mut session = match snmp::Session::new(&host, &cfg.snmp_community) {
+            Ok(v) => { v },
+            Err(e) => { return Err(CheckError::InvalidArgument(format!("Can't open SNMP session to {}: {}", host, e))); },
+        };
+        let mut ifs = InterfaceState::not_present(interface, SOURCE_SNMP);
+        ifs.index = match snmp::find_interface(&mut session, interface) {
+            Ok(Some(v)) => { v },
+            Ok(None) => { return Ok(ifs); },
+            Err(e) => { return Err(snmp_error("interface table", e)); },
+        };
+        ifs.present = true;
+
+        let columns = [snmp::IF_OPER_STATUS, snmp::IF_MTU, snmp::IF_HIGH_SPEED, snmp::IF_SPEED, snmp::IF_PHYS_ADDRESS];
+        let oids: Vec<Vec<u32>> = columns.iter().map(|c| { let mut o = c.to_vec(); o.push(ifs.index); o }).collect();
+        let values: Vec<snmp::Value> = match session.get(&oids) {
+            Ok(v) => { v.into_iter().map(|(_, v)| v).collect() },
+            Err(e) => { return Err(snmp_error("interface state", e)); },
+        };
+        if values.len() != columns.len() {
+            return Err(CheckError::Missing(format!("SNMP agent {} returned {} of {} requested values", host, values.len(), columns.len())));
+        }
+
+        if let snmp::Value::Integer(status) = values[0] {
+            ifs.operstate = snmp::oper_status_name(status).to_string();
+        }
+        match values[1].as_u64() {
+            Some(v) => {
+                ifs.mtu = v as i32;
+                ifs.effective_mtu = v as i32;
+            },
+            None => { ifs.read_errors.insert("mtu".to_string(), "ifMtu not reported by agent".to_string()); },
+        };
+        // ifSpeed is a Gauge32 in bit/s and saturates above 4 GBit/s, ifHighSpeed is in MBit/s
+        match (values[2].as_u64(), values[3].as_u64()) {
+            (Some(v), _) => { ifs.speed = v as i32; },
+            (None, Some(v)) => { ifs.speed = (v / 1_000_000) as i32; },
+            (None, None) => { ifs.read_errors.insert("speed".to_string(), "neither ifHighSpeed nor ifSpeed reported by agent".to_string()); },
+        };
+        if let snmp::Value::OctetString(ref a) = values[4] {
+            if !a.is_empty() {
+                ifs.mac = Some(format_mac(a));
+            }
+        }
+
+        ifs.ips = match snmp::interface_addresses(&mut session, ifs.index) {
+            Ok(v) => { v },
+            Err(e) => { return Err(snmp_error("address table", e)); },
+        };
+        Ok(ifs)
+    }
+
+    // operstate, MTU and addresses from rtnetlink if sysfs is not mounted, everything
+    // else is only available from sysfs
+    pub fn from_netlink(cfg: &Configuration, interface: &str) -> Result<InterfaceState, CheckError> {
+        let index = interface_index(interface);
+
+        let mut ifs = InterfaceState::not_present(interface, SOURCE_NETLINK);
+        ifs.index = index;
+        if index == 0 {
+            return Ok(ifs);
+        }
+
+        let nl = read_netlink_state(index)?;
+        ifs.present = true;
+        ifs.operstate = nl.operstate;
+        ifs.mtu = nl.mtu;
+        ifs.effective_mtu = nl.mtu;
+        ifs.ips = nl.ips;
+        let (unusable, temporary) = read_address_flags(interface, index);
+        ifs.unusable_addresses = unusable;
+        if cfg.ipv6_require_stable {
+            ifs.temporary_addresses = temporary;
+        }
+        if cfg.expect_default_route {
+            match route::default_routes() {
+                Ok(v) => { ifs.default_routes = Some(v); },
+                Err(e) => { ifs.read_errors.insert("default_routes".to_string(), e.to_string()); },
+            };
+        }
+        ifs.flags = Some(nl.flags);
+        ifs.loopback = nl.flags & libc::IFF_LOOPBACK as u32 != 0;
+        if cfg.expect_qdisc.is_some() {
+            ifs.qdisc = Some(read_qdiscs(index)?);
+        }
+        if !cfg.expect_neighbors.is_empty() {
+            ifs.neighbors = Some(read_neighbors(index)?);
+        }
+        Ok(ifs)
+    }
+}
+
+pub fn format_mac(lladdr: &[u8]) -> String {
+    lladdr.iter().map(|b| format!("{:02x}", b)).collect::<Vec<String>>().join(":")
+}
+
+// ARP and NDP neighbor table entries of the interface
+fn read_neighbors(ifindex: u32) -> Result<Vec<Neighbor>, CheckError> {
+    let entries = match netlink::neighbors() {
+        Ok(v) => { v },
+        Err(e) => { return Err(CheckError::netlink("neighbor table", e)); },
+    };
+
+    Ok(entries.iter().filter(|n| n.ifindex == ifindex).map(|n| {
+        let mac = if n.lladdr.is_empty() { None } else { Some(format_mac(&n.lladdr)) };
+        Neighbor{ ip: n.ip, state: netlink::neighbor_state_name(n.state), mac }
+    }).collect())
+}
+
+// the root qdisc, for mq the qdiscs attached to its classes (one per transmit queue)
+fn read_qdiscs(ifindex: u32) -> Result<Qdiscs, CheckError> {
+    let qdiscs = match netlink::qdiscs(ifindex) {
+        Ok(v) => { v },
+        Err(e) => { return Err(CheckError::netlink("qdiscs", e)); },
+    };
+
+    let root = match qdiscs.iter().find(|q| q.parent == netlink::TC_H_ROOT) {
+        Some(v) => { v },
+        None => { return Ok(Qdiscs{ root: "none".to_string(), queues: Vec::new() }); },
+    };
+
+    let mut queues: Vec<(u32, String)> = Vec::new();
+    if root.kind == "mq" {
+        // classes of mq are <major>:<queue + 1>
+        let major = root.handle & 0xffff_0000;
+        for q in &qdiscs {
+            if q.parent != netlink::TC_H_ROOT && q.parent & 0xffff_0000 == major {
+                queues.push((q.parent & 0xffff, q.kind.clone()));
+            }
+        }
+        queues.sort();
+    }
+
+    Ok(Qdiscs{ root: root.kind.clone(), queues: queues.into_iter().map(|(_, k)| k).collect() })
+}
+
+// None if the interface is not a bridge
+fn read_bridge_ports(sysfs: &dyn SysfsReader, sysfs_path: &str) -> Result<Option<Vec<String>>, CheckError> {
+    // a bridge without ports still has the bridge directory
+    if sysfs.read_dir(&format!("{}/bridge", sysfs_path)).is_err() {
+        return Ok(None);
+    }
+
+    let brif = format!("{}/brif", sysfs_path);
+    match sysfs.read_dir(&brif) {
+        Ok(v) => { Ok(Some(v)) },
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => { Ok(Some(Vec::new())) },
+        Err(e) => { Err(CheckError::io(&brif, e)) },
+    }
+}
+
+// lower_<if> links point to the devices below, e.g. the parent of a VLAN or the slaves
+// of a bond. The master only counts if it's a bond or a bridge, a VRF has its own MTU.
+fn read_related_mtus(sysfs: &dyn SysfsReader, sysfs_root: &str, sysfs_path: &str, master: Option<&str>) -> Vec<RelatedMtu> {
+    let mut related = Vec::new();
+    let read_mtu = |name: &str| read_sysfs_u64(sysfs, &format!("{}/{}/mtu", sysfs_root, name)).map(|v| v as i32);
+
+    for entry in sysfs.read_dir(sysfs_path).unwrap_or_default() {
+        if let Some(name) = entry.strip_prefix("lower_") {
+            if let Some(mtu) = read_mtu(name) {
+                related.push(RelatedMtu{ name: name.to_string(), relation: "lower", mtu });
+            }
+        }
+    }
+
+    if let Some(master) = master {
+        let relation = if sysfs.read_dir(&format!("{}/{}/bonding", sysfs_root, master)).is_ok() {
+            Some("bond")
+        } else if sysfs.read_dir(&format!("{}/{}/bridge", sysfs_root, master)).is_ok() {
+            Some("bridge")
+        } else {
+            None
+        };
+        if let (Some(relation), Some(mtu)) = (relation, read_mtu(master)) {
+            related.push(RelatedMtu{ name: master.to_string(), relation, mtu });
+        }
+    }
+    related
+}
+
+// name of the target of a symbolic link like <if>/master -> ../bond0
+fn read_sysfs_link_name(sysfs: &dyn SysfsReader, path: &str) -> Option<String> {
+    match sysfs.read_link(path) {
+        Ok(p) => { p.file_name().map(|n| n.to_string_lossy().to_string()) },
+        Err(_) => { None },
+    }
+}
+
+fn read_sysfs_u64(sysfs: &dyn SysfsReader, path: &str) -> Option<u64> {
+    match sysfs.read_to_string(path) {
+        Ok(s) => { s.trim().parse().ok() },
+        Err(_) => { None },
+    }
+}
+
+// 0 if the interface doesn't exist
+fn interface_index(interface: &str) -> u32 {
+    match CString::new(interface) {
+        Ok(c) => { unsafe { libc::if_nametoindex(c.as_ptr()) } },
+        Err(_) => { 0 },
+    }
+}
+
+fn address_state(flags: u32) -> Option<&'static str> {
+    if flags & netlink::IFA_F_DADFAILED != 0 {
+        return Some("dadfailed");
+    }
+    if flags & netlink::IFA_F_TENTATIVE != 0 {
+        return Some("tentative");
+    }
+    None
+}
+
+// hundredths of a second since boot like the timestamps of addresses, wraps like them
+fn monotonic_centis() -> u32 {
+    let mut ts = libc::timespec{ tv_sec: 0, tv_nsec: 0 };
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) };
+    (ts.tv_sec as u64 * 100 + ts.tv_nsec as u64 / 10_000_000) as u32
+}
+
+// "20010db8000000000000000000000005 02 40 00 c0     eth0", only the lower 8 bits of the flags
+fn parse_if_inet6(content: &str, interface: &str) -> Vec<(IpAddr, u32)> {
+    let mut result: Vec<(IpAddr, u32)> = Vec::new();
+    for line in content.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 6 || fields[5] != interface {
+            continue;
+        }
+        if let (Ok(addr), Ok(flags)) = (u128::from_str_radix(fields[0], 16), u32::from_str_radix(fields[4], 16)) {
+            result.push((IpAddr::V6(Ipv6Addr::from(addr)), flags));
+        }
+    }
+    result
+}
+
+// addresses still in or failed duplicate address detection and temporary IPv6 addresses
+// from netlink. The fallback /proc/net/if_inet6 doesn't know when an address was added,
+// it is only used for duplicate address detection and the temporary addresses are None
+fn read_address_flags(interface: &str, ifindex: u32) -> (Vec<UnusableAddress>, Option<Vec<IpAddr>>) {
+    match netlink::addresses(ifindex) {
+        Ok(addresses) => {
+            let now = monotonic_centis();
+            let unusable = addresses.iter().filter_map(|a| {
+                address_state(a.flags).map(|state| UnusableAddress{ ip: a.ip, state, age: a.created.map(|c| (now.wrapping_sub(c) / 100) as u64) })
+            }).collect();
+            let temporary = addresses.iter().filter(|a| a.ip.is_ipv6() && a.flags & netlink::IFA_F_TEMPORARY != 0).map(|a| a.ip).collect();
+            (unusable, Some(temporary))
+        },
+        Err(_) => {
+            let content = fs::read_to_string("/proc/net/if_inet6").unwrap_or_default();
+            let unusable = parse_if_inet6(&content, interface).into_iter().filter_map(|(ip, flags)| {
+                address_state(flags).map(|state| UnusableAddress{ ip, state, age: None })
+            }).collect();
+            (unusable, None)
+        },
+    }
+}
+
+fn read_netlink_state(ifindex: u32) -> Result<NetlinkState, CheckError> {
+    let link = match netlink::link_get(ifindex) {
+        Ok(v) => { v },
+        Err(e) => { return Err(CheckError::netlink("interface state", e)); },
+    };
+    let addresses = match netlink::addresses(ifindex) {
+        Ok(v) => { v },
+        Err(e) => { return Err(CheckError::netlink("interface addresses", e)); },
+    };
+
+    let mut ips: Vec<ipnetwork::IpNetwork> = Vec::new();
+    for a in addresses {
+        if let Ok(n) = ipnetwork::IpNetwork::new(a.ip, a.prefix_len) {
+            ips.push(n);
+        }
+    }
+
+    Ok(NetlinkState{
+        operstate: link.operstate.unwrap_or("unknown").to_string(),
+        flags: link.flags,
+        mtu: match link.mtu {
+            Some(v) => { v as i32 },
+            None => { -1 },
+        },
+        ips,
+    })
+}
+
+pub fn sysfs_available(sysfs_root: &str) -> bool {
+    fs::read_dir(sysfs_root).is_ok()
+}
+
+pub fn sysfs_unavailable(sysfs_root: &str) -> String {
+    if sysfs_root == DEFAULT_SYSFS_ROOT {
+        format!("sysfs not available at {} (is /sys mounted in this container?)", sysfs_root)
+    } else {
+        format!("sysfs not available at {}", sysfs_root)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::config;
+
+    #[test]
+    fn sysfs_unavailable_names_the_root() {
+        assert_eq!(sysfs_unavailable(DEFAULT_SYSFS_ROOT), "sysfs not available at /sys/class/net (is /sys mounted in this container?)");
+        assert_eq!(sysfs_unavailable("/nonexistent/sysfs"), "sysfs not available at /nonexistent/sysfs");
+    }
+
+    #[test]
+    fn bogus_sysfs_root_is_unavailable() {
+        assert!(!sysfs_available("/nonexistent/check_ethernet/sysfs"));
+    }
+
+    #[test]
+    fn copied_tree_ignores_the_host() {
+        // lo exists on the host, but the copy has no addresses
+        let root = std::env::temp_dir().to_string_lossy().trim_end_matches('/').to_string();
+        let argv: Vec<String> = ["check_ethernet", "-i", "lo", "-s", "0", "--sysfs-root", &root].iter().map(|a| a.to_string()).collect();
+        let cfg = Configuration::new(&argv, &config::options()).unwrap();
+        let sysfs = SysfsFixture::new()
+            .file(&format!("{}/lo/operstate", root), "unknown\n")
+            .file(&format!("{}/lo/mtu", root), "65536\n");
+        let ifs = InterfaceState::new(&cfg, "lo", &sysfs).unwrap();
+        assert!(ifs.present);
+        assert_eq!(ifs.index, 0);
+        assert!(ifs.ips.is_empty());
+        assert_eq!(ifs.mtu, 65536);
+    }
+
+    #[test]
+    fn cpu_masks() {
+        assert_eq!(parse_cpu_mask("f\n").unwrap(), vec![0, 1, 2, 3]);
+        assert_eq!(parse_cpu_mask("00000001,00000030").unwrap(), vec![4, 5, 32]);
+        assert!(parse_cpu_mask("0000000g").is_err());
+    }
+
+    #[test]
+    fn unbound_workqueues_only() {
+        let sysfs = SysfsFixture::new()
+            .file("/sys/devices/virtual/workqueue/cpumask", "ff\n")
+            .file("/sys/bus/workqueue/devices/writeback/cpumask", "0f\n")
+            .file("/sys/bus/workqueue/devices/writeback/per_cpu", "0\n")
+            .file("/sys/bus/workqueue/devices/netns/per_cpu", "1\n");
+        let workqueues = read_workqueues(&sysfs, "/sys").unwrap();
+        assert_eq!(workqueues.default_cpus, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(workqueues.queues.len(), 1);
+        assert_eq!(workqueues.queues[0].name, "writeback");
+        assert_eq!(workqueues.queues[0].cpus, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn sysfs_mount_of_the_root() {
+        assert_eq!(sysfs_mount(DEFAULT_SYSFS_ROOT).as_deref(), Some("/sys"));
+        assert_eq!(sysfs_mount("/srv/copy/sys/class/net").as_deref(), Some("/srv/copy/sys"));
+        assert_eq!(sysfs_mount("/srv/copy/net"), None);
+    }
+
+    #[test]
+    fn napi_threads_of_the_interface() {
+        let sysfs = SysfsFixture::new()
+            .file("/proc/1/comm", "systemd\n")
+            .file("/proc/1/status", "Name:\tsystemd\nCpus_allowed_list:\t0-7\n")
+            .file("/proc/812/comm", "napi/eth0-8193\n")
+            .file("/proc/812/status", "Name:\tnapi/eth0-8193\nCpus_allowed_list:\t2-3\n")
+            .file("/proc/813/comm", "napi/eth1-8194\n")
+            .file("/proc/813/status", "Name:\tnapi/eth1-8194\nCpus_allowed_list:\t4\n")
+            .file("/proc/self/comm", "check_ethernet\n");
+        let threads = read_napi_threads(&sysfs, "eth0").unwrap();
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].name, "napi/eth0-8193");
+        assert_eq!(threads[0].cpus, vec![2, 3]);
+    }
+}
@@ -0,0 +1,64 @@
+extern crate getopts;
+extern crate pnet;
+extern crate ipnetwork;
+extern crate libc;
+extern crate glob;
+extern crate regex;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate toml;
+
+pub mod ack;
+pub mod bonding;
+pub mod capabilities;
+pub mod changes;
+pub mod config;
+pub mod counters;
+pub mod error;
+pub mod ethtool;
+pub mod firsthop;
+pub mod framesize;
+pub mod hysteresis;
+pub mod interface;
+pub mod lldp;
+pub mod lock;
+pub mod netlink;
+pub mod output;
+pub mod pcie;
+pub mod peers;
+pub mod plan;
+pub mod probe;
+pub mod route;
+pub mod scheduler;
+pub mod snmp;
+pub mod state;
+pub mod status;
+pub mod team;
+pub mod threshold;
+pub mod uplink;
+pub mod vlan;
+pub mod wireless;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const STATE_OK: i32 = 0;
+pub const STATE_WARNING: i32 = 1;
+pub const STATE_CRITICAL: i32 = 2;
+pub const STATE_UNKNOWN: i32 = 3;
+
+pub fn unix_now() -> u64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(d) => { d.as_secs() },
+        Err(_) => { 0 },
+    }
+}
+
+pub fn unix_now_ms() -> u64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(d) => { d.as_secs() * 1000 + d.subsec_millis() as u64 },
+        Err(_) => { 0 },
+    }
+}
+
@@ -0,0 +1,135 @@
+// Passive LLDP capture to find out which switch port an interface is connected to.
+use pnet::datalink;
+
+use std::time::{Duration, Instant};
+
+// advertisements are sent every 30 seconds by default
+pub const CAPTURE_TIMEOUT: u64 = 31;
+
+const ETHERTYPE_LLDP: u16 = 0x88cc;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+
+const TLV_END: u8 = 0;
+const TLV_PORT_ID: u8 = 2;
+const TLV_TTL: u8 = 3;
+const TLV_PORT_DESCRIPTION: u8 = 4;
+const TLV_SYSTEM_NAME: u8 = 5;
+
+// port id subtypes carrying printable strings
+const PORT_ID_INTERFACE_ALIAS: u8 = 1;
+const PORT_ID_INTERFACE_NAME: u8 = 5;
+const PORT_ID_LOCAL: u8 = 7;
+const PORT_ID_MAC_ADDRESS: u8 = 3;
+
+pub struct Neighbor {
+    pub system_name: String,
+    pub port: String,
+    pub ttl: u64,
+}
+
+impl Neighbor {
+    pub fn describe(&self) -> String {
+        format!("connected to {} port {}", self.system_name, self.port)
+    }
+}
+
+fn printable(data: &[u8]) -> String {
+    String::from_utf8_lossy(data).chars().filter(|c| !c.is_control()).collect::<String>().trim().to_string()
+}
+
+fn parse_port_id(data: &[u8]) -> Option<String> {
+    if data.len() < 2 {
+        return None;
+    }
+
+    match data[0] {
+        PORT_ID_INTERFACE_ALIAS | PORT_ID_INTERFACE_NAME | PORT_ID_LOCAL => {
+            Some(printable(&data[1..]))
+        },
+        PORT_ID_MAC_ADDRESS => {
+            Some(data[1..].iter().map(|b| format!("{:02x}", b)).collect::<Vec<String>>().join(":"))
+        },
+        _ => { None },
+    }
+}
+
+// parse an ethernet frame, returns None if it isn't a LLDP frame
+fn parse_frame(frame: &[u8]) -> Option<Neighbor> {
+    if frame.len() < 14 {
+        return None;
+    }
+
+    let mut pos = 12;
+    let mut ethertype = u16::from_be_bytes([frame[pos], frame[pos + 1]]);
+    if ethertype == ETHERTYPE_VLAN && frame.len() >= 18 {
+        pos += 4;
+        ethertype = u16::from_be_bytes([frame[pos], frame[pos + 1]]);
+    }
+    if ethertype != ETHERTYPE_LLDP {
+        return None;
+    }
+    pos += 2;
+
+    let mut system_name: Option<String> = None;
+    let mut port_id: Option<String> = None;
+    let mut port_description: Option<String> = None;
+    let mut ttl: u64 = 0;
+
+    while pos + 2 <= frame.len() {
+        let kind = frame[pos] >> 1;
+        let len = (((frame[pos] & 0x01) as usize) << 8) | frame[pos + 1] as usize;
+        pos += 2;
+        if kind == TLV_END || pos + len > frame.len() {
+            break;
+        }
+        let value = &frame[pos..pos + len];
+
+        match kind {
+            TLV_PORT_ID => { port_id = parse_port_id(value); },
+            TLV_TTL => {
+                if len >= 2 {
+                    ttl = u16::from_be_bytes([value[0], value[1]]) as u64;
+                }
+            },
+            TLV_PORT_DESCRIPTION => { port_description = Some(printable(value)); },
+            TLV_SYSTEM_NAME => { system_name = Some(printable(value)); },
+            _ => {},
+        };
+        pos += len;
+    }
+
+    // the port id is usually the switch interface name, the description is the fallback
+    let port = match port_id {
+        Some(p) if !p.is_empty() => { p },
+        _ => { port_description.unwrap_or_else(|| "unknown".to_string()) },
+    };
+
+    Some(Neighbor{ system_name: system_name.unwrap_or_else(|| "unknown system".to_string()), port, ttl })
+}
+
+// listen on the interface until a LLDP frame arrives or the timeout expires
+pub fn capture(interface: &str, timeout: Duration) -> Result<Option<Neighbor>, String> {
+    let iface = match datalink::interfaces().into_iter().find(|i| i.name == interface) {
+        Some(v) => { v },
+        None => { return Err(format!("Interface {} not found", interface)); },
+    };
+
+    let config = datalink::Config{ read_timeout: Some(Duration::from_millis(500)), ..Default::default() };
+    let mut rx = match datalink::channel(&iface, config) {
+        Ok(datalink::Channel::Ethernet(_, rx)) => { rx },
+        Ok(_) => { return Err("Unsupported channel type".to_string()); },
+        Err(e) => { return Err(e.to_string()); },
+    };
+
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        // read timeouts are expected, just try again until the deadline is reached
+        if let Ok(frame) = rx.next() {
+            if let Some(neighbor) = parse_frame(frame) {
+                return Ok(Some(neighbor));
+            }
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,93 @@
+// Advisory lock to keep overlapping check instances from probing the same interface
+// and rewriting its state file at the same time.
+use std::fs;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const RETRY_INTERVAL: u64 = 100;
+
+// the lock is held as long as the file is open, the kernel releases it when the file
+// is closed, including on process exit and panics
+pub struct InstanceLock {
+    _file: fs::File,
+}
+
+// Ok(None) if the lock is still held by another process after waiting for `wait`
+pub fn acquire(path: &str, wait: Duration) -> io::Result<Option<InstanceLock>> {
+    let file = fs::OpenOptions::new().create(true).write(true).truncate(false).open(path)?;
+    let start = Instant::now();
+
+    loop {
+        let rc = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+        if rc == 0 {
+            return Ok(Some(InstanceLock{ _file: file }));
+        }
+
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::EWOULDBLOCK) && err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+        if start.elapsed() >= wait {
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(RETRY_INTERVAL));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn lock_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("check_ethernet_test_{}_{}.lock", name, std::process::id())).to_string_lossy().to_string()
+    }
+
+    // hold the lock on another thread until `hold` has passed, returns once it is held
+    fn hold_lock(path: &str, hold: Duration) -> thread::JoinHandle<()> {
+        let path = path.to_string();
+        let (tx, rx) = mpsc::channel();
+        let holder = thread::spawn(move || {
+            let lock = acquire(&path, Duration::from_secs(0)).unwrap();
+            assert!(lock.is_some());
+            tx.send(()).unwrap();
+            thread::sleep(hold);
+        });
+        rx.recv().unwrap();
+        holder
+    }
+
+    #[test]
+    fn second_instance_fails_without_waiting() {
+        let path = lock_path("fail");
+        let holder = hold_lock(&path, Duration::from_millis(500));
+        let start = Instant::now();
+        assert!(acquire(&path, Duration::from_secs(0)).unwrap().is_none());
+        assert!(start.elapsed() < Duration::from_millis(500));
+        holder.join().unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn second_instance_waits_for_the_first() {
+        let path = lock_path("wait");
+        let holder = hold_lock(&path, Duration::from_millis(300));
+        let start = Instant::now();
+        assert!(acquire(&path, Duration::from_secs(5)).unwrap().is_some());
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        holder.join().unwrap();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn released_on_drop() {
+        let path = lock_path("drop");
+        let first = acquire(&path, Duration::from_secs(0)).unwrap();
+        assert!(first.is_some());
+        drop(first);
+        assert!(acquire(&path, Duration::from_secs(0)).unwrap().is_some());
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -1,410 +1,605 @@
+extern crate check_ethernet;
 extern crate getopts;
+extern crate glob;
+extern crate libc;
 extern crate pnet;
-extern crate ipnetwork;
+extern crate regex;
 
-use getopts::Options;
+use check_ethernet::{ack, hysteresis, lldp, lock, netlink, output, plan, scheduler, state, vlan};
+use check_ethernet::{firsthop, framesize, peers, threshold, uplink};
+use check_ethernet::{unix_now, STATE_OK, STATE_UNKNOWN};
+use check_ethernet::error::CheckError;
+use check_ethernet::config::{self, state_file_path, Configuration, LockPolicy, OutputFormat};
+use check_ethernet::interface::{read_workqueues, sysfs_available, sysfs_mount, sysfs_unavailable, InterfaceState, Sysfs, SOURCE_NETLINK, SOURCE_SYSFS};
+use check_ethernet::status::{probe_timeout, vlan_status, wq_balance_status, Finding, FindingId, NagiosStatus};
+
+
+use output::OutputRenderer;
 
 use pnet::datalink;
 
 use std::env;
-use std::process;
+use std::ffi::CStr;
 use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// reads of sysfs can block for seconds on a wedged driver, the state of the interfaces
+// is collected on a worker thread which is abandoned at the deadline
+fn collect_states(cfg: &Arc<Configuration>, interfaces: &[String], deadline: Instant) -> Option<(Vec<InterfaceState>, Vec<Finding>)> {
+    let cfg = Arc::clone(cfg);
+    let interfaces = interfaces.to_vec();
+    let timeout = cfg.timeout;
+
+    let task = scheduler::Task::new("data collection".to_string(), timeout, move |_| {
+        let mut states: Vec<InterfaceState> = Vec::new();
+        let mut unknown: Vec<Finding> = Vec::new();
+
+        // without sysfs every interface would look missing, netlink can stand in for the basics
+        let sysfs = sysfs_available(&cfg.sysfs_root);
+        for interface in &interfaces {
+            if cfg.snmp_host.is_some() {
+                match InterfaceState::from_snmp(&cfg, interface) {
+                    Ok(ifstate) => { states.push(ifstate); },
+                    Err(err) => { unknown.push(Finding::new(FindingId::InterfaceError, interface, err.to_string())); },
+                };
+                continue;
+            }
 
-const STATE_OK: i32 = 0;
-const STATE_WARNING: i32 = 1;
-const STATE_CRITICAL: i32 = 2;
-const STATE_UNKNOWN: i32 = 3;
-
-const ADDR_IPV4: u32 = 0x01;
-const ADDR_IPV6: u32 = 0x02;
-
-struct Configuration {
-    interface: String,
-    mtu: i32,
-    speed: i32,
-    duplex: String,
-    report_critical: bool,
-    address_type: u32,
-}
+            if !sysfs {
+                match InterfaceState::from_netlink(&cfg, interface) {
+                    Ok(ifstate) => { states.push(ifstate); },
+                    Err(_) => { unknown.push(Finding::new(FindingId::SysfsUnavailable, interface, sysfs_unavailable(&cfg.sysfs_root))); },
+                };
+                continue;
+            }
 
-struct InterfaceState {
-    present: bool,
-    speed: i32,
-    mtu: i32,
-    operstate: String,
-    duplex: String,
-    ips: Vec<ipnetwork::IpNetwork>,
-}
+            // a broken interface must not prevent checking the remaining interfaces
+            match InterfaceState::new(&cfg, interface, &Sysfs{}) {
+                Ok(ifstate) => { states.push(ifstate); },
+                // the interface was removed while it was read, e.g. a USB modem unplugged
+                Err(_) if !Path::new(&format!("{}/{}", cfg.sysfs_root, interface)).exists() => { states.push(InterfaceState::not_present(interface, SOURCE_SYSFS)); },
+                Err(err) => { unknown.push(Finding::new(FindingId::InterfaceError, interface, err.to_string())); },
+            };
+        }
+        (states, unknown)
+    });
 
-struct NagiosStatus {
-    critical: Vec<String>,
-    warning: Vec<String>,
-    ok: Vec<String>,
-    unknown: Vec<String>,
+    scheduler::run(vec![task], deadline).completed.pop().map(|(_, v)| v)
 }
 
-impl NagiosStatus {
-    fn new(cfg: &Configuration, ifs: &InterfaceState) -> NagiosStatus {
-        let mut critical = Vec::new();
-        let mut warning = Vec::new();
-        let mut ok = Vec::new();
-        let mut unknown = Vec::new();
-        let link_local_ipv4: ipnetwork::Ipv4Network = "169.254.0.0/16".parse().unwrap();
-        let link_local_ipv6: ipnetwork::Ipv6Network = "fe80::/10".parse().unwrap();
-        let mut link_local_4 = 0;
-        let mut non_link_local_4 = 0;
-        let mut link_local_6 = 0;
-        let mut non_link_local_6 = 0;
-        let mut non_link_local = 0;
-        let mut link_local = 0;
-        
-        if !ifs.present {
-            critical.push("Interface is not present".to_string());
-            // no need to check futher parameters
-            return NagiosStatus{ critical, warning, ok, unknown };
+// names of all interfaces the address is assigned to
+fn interfaces_by_address(ip: IpAddr) -> Result<Vec<String>, CheckError> {
+    let addresses = match netlink::all_addresses() {
+        Ok(v) => { v },
+        Err(e) => { return Err(CheckError::netlink("addresses", e)); },
+    };
+
+    let mut result: Vec<String> = Vec::new();
+    for a in addresses.iter().filter(|a| a.ip == ip) {
+        let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+        let name = unsafe { libc::if_indextoname(a.ifindex, buf.as_mut_ptr()) };
+        if name.is_null() {
+            continue;
         }
-
-        if ifs.operstate == "down" {
-            critical.push("Interface is DOWN".to_string());
-            // no need to check futher parameters
-            return NagiosStatus{ critical, warning, ok, unknown };
+        let name = unsafe { CStr::from_ptr(name) }.to_string_lossy().to_string();
+        if !result.contains(&name) {
+            result.push(name);
         }
+    }
 
-        if ifs.operstate == "up" {
-            ok.push("Interface is up".to_string());
-        } else {
-            // should never happen!
-            unknown.push(format!("Interface is {}", ifs.operstate));
-            // no need to check futher parameters
-            return NagiosStatus{ critical, warning, ok, unknown };
+    Ok(result)
+}
+
+// all interface names known to the kernel, sorted and without duplicates
+fn list_interfaces(sysfs_root: &str) -> Vec<String> {
+    let mut names: Vec<String> = datalink::interfaces().into_iter().map(|i| i.name).collect();
+
+    if let Ok(entries) = fs::read_dir(sysfs_root) {
+        for entry in entries.flatten() {
+            names.push(entry.file_name().to_string_lossy().to_string());
         }
+    }
 
-        // check negotiated interface speed and duplex mode
-        if cfg.speed > 0 {
-            if ifs.speed > cfg.speed {
-                warning.push(format!("Negotiated interface speed ({} MBit/s) is greater than requested interface speed ({} MBit/s)", ifs.speed, cfg.speed));
-            } else if ifs.speed < cfg.speed {
-                if cfg.report_critical {
-                    critical.push(format!("Negotiated interface speed ({} MBit/s) is below requested interface speed ({} MBit/s)", ifs.speed, cfg.speed));
-                } else {
-                    warning.push(format!("Negotiated interface speed ({} MBit/s) is below requested interface speed ({} MBit/s)", ifs.speed, cfg.speed));
-                }
-            } else {
-                ok.push(format!("Negotiated interface speed is {} MBit/s", ifs.speed));
-            }
+    names.sort();
+    names.dedup();
+    names
+}
 
-            // check negotiated duplex mode
-            if ifs.duplex != "half" && ifs.duplex != "full" {
-                unknown.push(format!("Unknown duplex mode {}", ifs.duplex));
-            } else if ifs.duplex != cfg.duplex {
-                if cfg.report_critical {
-                    critical.push(format!("Negotiated duplex mode is {} instead of {}", ifs.duplex, cfg.duplex));
-                } else {
-                    warning.push(format!("Negotiated duplex mode is {} instead of {}", ifs.duplex, cfg.duplex));
-                }
-            } else {
-                ok.push(format!("Negotiated duplex mode is {}", ifs.duplex));
-            }
+fn is_glob(name: &str) -> bool {
+    name.contains('*') || name.contains('?') || name.contains('[')
+}
+
+fn excluded(cfg: &Configuration, name: &str) -> bool {
+    cfg.exclude.iter().any(|p| p.matches(name))
+}
+
+// every interface of --all, loopback is never checked and without a device symlink
+// an interface is virtual
+fn all_interfaces(cfg: &Configuration) -> Vec<String> {
+    list_interfaces(&cfg.sysfs_root).into_iter().filter(|name| {
+        let path = format!("{}/{}", cfg.sysfs_root, name);
+        // bonding_masters is a file in the same directory
+        if !Path::new(&path).is_dir() {
+            return false;
         }
+        let loopback = match fs::read_to_string(format!("{}/flags", path)) {
+            Ok(s) => { u32::from_str_radix(s.trim().trim_start_matches("0x"), 16).map(|f| f & libc::IFF_LOOPBACK as u32 != 0).unwrap_or(false) },
+            Err(_) => { name == "lo" },
+        };
+        !loopback && (!cfg.all_physical_only || Path::new(&format!("{}/device", path)).exists())
+    }).collect()
+}
 
-        // check MTU
-        if cfg.mtu > 0 {
-            if ifs.mtu != cfg.mtu {
-                if cfg.report_critical {
-                    critical.push(format!("MTU size of {} does not match requested MTU size of {}", ifs.mtu, cfg.mtu));
-                } else {
-                    warning.push(format!("MTU size of {} does not match requested MTU size of {}", ifs.mtu, cfg.mtu));
-                }
-            } else {
-                ok.push(format!("MTU size is {}", ifs.mtu));
+// -i names without a directory in sysfs which are an alternative name of an interface,
+// as (altname, primary name). Only the running kernel knows them.
+fn resolve_altnames(cfg: &Configuration) -> Vec<(String, String)> {
+    let mut altnames = Vec::new();
+    if cfg.sysfs_root != config::DEFAULT_SYSFS_ROOT {
+        return altnames;
+    }
+
+    for name in cfg.interface.iter().filter(|n| !is_glob(n)) {
+        if Path::new(&format!("{}/{}", cfg.sysfs_root, name)).exists() {
+            continue;
+        }
+        if let Ok(Some(primary)) = netlink::link_by_name(name).map(|l| l.name) {
+            if &primary != name {
+                altnames.push((name.clone(), primary));
             }
         }
+    }
+    altnames
+}
 
-        // check assigned addresses
-        if cfg.address_type != 0 {
-            for n in &ifs.ips {
-                match n {
-                    ipnetwork::IpNetwork::V4(addr) => {
-                        if link_local_ipv4.contains(addr.ip()) {
-                            link_local_4 += 1;
-                        } else {
-                            non_link_local_4 += 1;
-                        }
-                    },
-                    ipnetwork::IpNetwork::V6(addr) => {
-                        if link_local_ipv6.contains(addr.ip()) {
-                            link_local_6 += 1;
-                        } else {
-                            non_link_local_6 += 1;
-                        }
-                    },
-                };
-                    
-            }
+// expand glob patterns, the interface regex and --all into the list of interfaces to check,
+// plain interface names are always kept so a missing interface is reported as such
+fn resolve_interfaces(cfg: &Configuration, discovered: &[String], altnames: &[(String, String)]) -> Result<Vec<String>, CheckError> {
+    let mut result: Vec<String> = Vec::new();
+    let available: Vec<String> = list_interfaces(&cfg.sysfs_root).into_iter().filter(|a| !excluded(cfg, a)).collect();
 
-            if cfg.address_type & ADDR_IPV4 == ADDR_IPV4 {
-                link_local += link_local_4;
-                non_link_local += non_link_local_4;
+    for name in &cfg.interface {
+        if is_glob(name) {
+            let pattern = match glob::Pattern::new(name) {
+                Ok(v) => { v },
+                Err(_) => { return Err(CheckError::InvalidArgument(format!("Invalid interface name pattern \"{}\"", name))); },
+            };
+            for a in &available {
+                if pattern.matches(a) && !result.contains(a) {
+                    result.push(a.clone());
+                }
             }
-            if cfg.address_type & ADDR_IPV6 == ADDR_IPV6 {
-                link_local += link_local_6;
-                non_link_local += non_link_local_6;
+        } else {
+            // sysfs and every check only know the primary name
+            let name = match altnames.iter().find(|(alt, _)| alt == name) {
+                Some((_, primary)) => { primary },
+                None => { name },
+            };
+            if !result.contains(name) {
+                result.push(name.clone());
             }
+        }
+    }
 
-            if non_link_local == 0 && link_local == 0 {
-                // no address assigned
-                critical.push("No IP address assigned".to_string());
-            } else if non_link_local == 0 && link_local > 0 {
-                // only link local addresses assigned
-                critical.push("Only link local address(es) are assigned".to_string());
-            } else {
-                // OK: non-link local address(es) and zero ore more link local addresses
-                ok.push("Non link local address(es) assigned".to_string());
+    if let Some(ref re) = cfg.interface_regex {
+        for a in &available {
+            if re.is_match(a) && !result.contains(a) {
+                result.push(a.clone());
             }
         }
+    }
 
-        NagiosStatus{ critical, warning, ok, unknown }
+    if cfg.all {
+        for a in all_interfaces(cfg) {
+            if !excluded(cfg, &a) && !result.contains(&a) {
+                result.push(a);
+            }
+        }
     }
 
-    fn print(&self) -> i32 {
-        if self.unknown.len() > 0 {
-            println!("{}", self.unknown.join(", "));
-            return STATE_UNKNOWN;
-        };
+    for d in discovered {
+        if !result.contains(d) {
+            result.push(d.clone());
+        }
+    }
 
-        if self.critical.len() > 0 {
-            println!("{}", self.critical.join(", "));
-            return STATE_CRITICAL;
-        };
+    Ok(result)
+}
 
-        if self.warning.len() > 0 {
-            println!("{}", self.warning.join(", "));
-            return STATE_WARNING;
-        };
-        if self.ok.len() > 0 {
-            println!("{}", self.ok.join(", "));
-            return STATE_OK;
-        };
-        return STATE_UNKNOWN;
-    }
+enum ProbeResult {
+    FirstHop(usize, Result<Vec<firsthop::FirstHop>, CheckError>),
+    GatewayProbe(usize, Result<Vec<firsthop::FirstHop>, CheckError>),
+    SubnetPeers(usize, Result<Vec<peers::SubnetPeers>, CheckError>),
+    FrameSizes(usize, Result<framesize::Histogram, String>),
+    Uplink(usize, Result<uplink::Uplink, CheckError>),
 }
 
-impl InterfaceState {
-    fn new(cfg: &Configuration) -> Result<InterfaceState, &'static str> {
-        let mut mtu: i32 = -1;
-        let mut speed: i32 = -1;
-        let operstate: String = "unknown".to_string();
-        let duplex: String = "unknown".to_string();
-        let mut present: bool = false;
-        let mut ips: Vec<ipnetwork::IpNetwork> = Vec::new();
-        let mut sysfs_path = "/sys/class/net/".to_owned();
-        sysfs_path.push_str(cfg.interface.as_str());
+// active probes of all interfaces, run concurrently by the scheduler
+fn probe_tasks(cfg: &Configuration, states: &[InterfaceState]) -> Vec<scheduler::Task<ProbeResult>> {
+    let mut tasks = Vec::new();
 
-        let mut operstate_file = sysfs_path.clone();
-        operstate_file.push_str("/operstate");
+    for (i, ifs) in states.iter().enumerate() {
+        if !ifs.present {
+            continue;
+        }
+
+        if cfg.check_first_hop {
+            let (name, index, ips, methods) = (ifs.name.clone(), ifs.index, ifs.ips.clone(), cfg.first_hop_methods.clone());
+            tasks.push(scheduler::Task::new(format!("first hop check of {}", ifs.name), cfg.first_hop_timeout, move |timeout| {
+                ProbeResult::FirstHop(i, firsthop::check_all(&name, index, &ips, &methods, timeout))
+            }));
+        }
 
-        let mut duplex_file = sysfs_path.clone();
-        duplex_file.push_str("/duplex");
+        if cfg.probe_gateway {
+            let (name, index, ips, gateway) = (ifs.name.clone(), ifs.index, ifs.ips.clone(), cfg.probe_gateway_address);
+            tasks.push(scheduler::Task::new(format!("gateway probe of {}", ifs.name), cfg.probe_timeout, move |timeout| {
+                ProbeResult::GatewayProbe(i, firsthop::probe_gateways(&name, index, &ips, gateway, timeout))
+            }));
+        }
 
-        let mut mtu_file = sysfs_path.clone();
-        mtu_file.push_str("/mtu");
+        if let Some(provider) = cfg.uplink_check {
+            let (name, index, ips, methods) = (ifs.name.clone(), ifs.index, ifs.ips.clone(), cfg.first_hop_methods.clone());
+            tasks.push(scheduler::Task::new(format!("uplink check of {}", ifs.name), cfg.first_hop_timeout, move |timeout| {
+                ProbeResult::Uplink(i, uplink::check(&name, index, &ips, provider, &methods, timeout))
+            }));
+        }
 
-        let mut speed_file = sysfs_path.clone();
-        speed_file.push_str("/speed");
+        if cfg.probe_subnet_peers {
+            let (name, index, ips, limit) = (ifs.name.clone(), ifs.index, ifs.ips.clone(), cfg.peer_probe_limit);
+            tasks.push(scheduler::Task::new(format!("subnet peer probe of {}", ifs.name), cfg.timeout, move |timeout| {
+                ProbeResult::SubnetPeers(i, peers::check(&name, index, &ips, limit, timeout))
+            }));
+        }
 
-        for interface in datalink::interfaces() {
-            if interface.name == cfg.interface {
-                ips = interface.ips;
+        if let Some(window) = cfg.frame_size_window {
+            let name = ifs.name.clone();
+            tasks.push(scheduler::Task::new(format!("frame size sampling of {}", ifs.name), window, move |timeout| {
+                ProbeResult::FrameSizes(i, framesize::capture(&name, timeout))
+            }));
+        }
+    }
+
+    tasks
+}
+
+// why every interface was selected, for --dry-run
+fn selections(cfg: &Configuration, interfaces: &[String], altnames: &[(String, String)]) -> Vec<plan::Selection> {
+    let available = list_interfaces(&cfg.sysfs_root);
+    let all = if cfg.all { all_interfaces(cfg) } else { Vec::new() };
+
+    interfaces.iter().map(|interface| {
+        let mut reasons: Vec<String> = Vec::new();
+        for name in &cfg.interface {
+            if name == interface {
+                reasons.push("listed by -i/--interface".to_string());
+            } else if altnames.iter().any(|(alt, primary)| alt == name && primary == interface) {
+                reasons.push(format!("listed by -i/--interface as altname {}", name));
+            } else if is_glob(name) && glob::Pattern::new(name).map(|p| p.matches(interface)).unwrap_or(false) {
+                reasons.push(format!("matches {}", name));
+            }
+        }
+        if let Some(ref re) = cfg.interface_regex {
+            if re.is_match(interface) {
+                reasons.push(format!("matches --interface-regex {}", re.as_str()));
             }
         }
+        if cfg.all && all.contains(interface) {
+            reasons.push("selected by --all".to_string());
+        }
+        if !available.contains(interface) {
+            reasons.push("doesn't exist".to_string());
+        }
+        plan::Selection{ interface: interface.clone(), reasons }
+    }).collect()
+}
 
-        let operstate = match fs::read_to_string(operstate_file) {
-            Ok(s) => { s.trim().to_string() },
-            Err(_) => { return Ok(InterfaceState{ present, speed, mtu, operstate, duplex, ips }) },
-        };
+// describe the switch port the interface is connected to, neighbors are cached in the
+// state file for the TTL announced by the switch
+fn lldp_neighbor(state_path: &str, interface: &str, timeout: Duration) -> Option<String> {
+    let mut state = match state::State::load(state_path) {
+        Ok(v) => { v },
+        Err(_) => { return None; },
+    };
+    let now = unix_now();
+
+    if let Some(expires) = state.get_u64("lldp.expires") {
+        if now < expires {
+            if let Some(neighbor) = state.get("lldp.neighbor") {
+                return Some(neighbor.to_string());
+            }
+        }
+    }
 
-        let duplex = match fs::read_to_string(duplex_file) {
-            Ok(s) => { s.trim().to_string() },
-            Err(_) => { return Ok(InterfaceState{ present, speed, mtu, operstate, duplex, ips }) },
-        };
+    match lldp::capture(interface, timeout) {
+        Ok(Some(neighbor)) => {
+            let description = neighbor.describe();
+            state.set("lldp.neighbor", &description);
+            state.set("lldp.expires", now + neighbor.ttl);
+            // caching is best effort
+            let _ = state.save();
+            Some(description)
+        },
+        _ => { None },
+    }
+}
 
-        let raw_mtu = match fs::read_to_string(mtu_file) {
-            Ok(s) => { s.trim().to_string() },
-            Err(_) => { return Ok(InterfaceState{ present, speed, mtu, operstate, duplex, ips }) },
+fn main() {
+    let start = Instant::now();
+    let argv: Vec<String> = env::args().collect();
+    let options = config::options();
+
+    // options of the configuration file are used unless given on the command line
+    let argv = config::merge_config_file(&argv, &options).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        process::exit(STATE_UNKNOWN);
+    });
+    let cfg = Arc::new(Configuration::new(&argv, &options).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        process::exit(STATE_UNKNOWN);
+    }));
+
+    // resolving the interfaces only needs their names, nothing else is read. VLANs and
+    // holders of an address are only known after reading sysfs and netlink.
+    if cfg.dry_run {
+        let altnames = resolve_altnames(&cfg);
+        let interfaces = resolve_interfaces(&cfg, &[], &altnames).unwrap_or_else(|err| {
+            eprintln!("Error: {}", err);
+            process::exit(STATE_UNKNOWN);
+        });
+        let plan = plan::Plan::new(&cfg, selections(&cfg, &interfaces, &altnames));
+        let rendered = match cfg.output {
+            OutputFormat::Plain | OutputFormat::Checkmk => { Ok(plan.to_plain()) },
+            OutputFormat::Json => { plan.to_json() },
         };
-        mtu = match raw_mtu.trim().parse() {
-            Ok(v) => { v },
-            Err(_) => { 
-                return Err("Can't convert reported MTU to an integer");
+        match rendered {
+            Ok(v) => { println!("{}", v); },
+            Err(e) => {
+                println!("{}", e);
+                process::exit(STATE_UNKNOWN);
             },
         };
+        process::exit(STATE_OK);
+    }
 
-        let raw_speed = match fs::read_to_string(speed_file) {
-            Ok(s) => { s.trim().to_string() },
-            Err(_) => { return Ok(InterfaceState{ present, speed, mtu, operstate, duplex, ips }) },
-        };
-        speed = match raw_speed.parse() {
-            Ok(v) => { v },
-            Err(_) => { return Err("Can't convert reported link speed to an integer"); },
-        };
+    // VLANs are discovered on every run, new VLANs are checked without changing the configuration
+    let vlans = match cfg.check_vlans_of {
+        Some(ref parent) => {
+            vlan::discover(&Sysfs{}, &cfg.sysfs_root, parent).unwrap_or_else(|err| {
+                println!("{}", err);
+                process::exit(STATE_UNKNOWN);
+            })
+        },
+        None => { Vec::new() },
+    };
+
+    // a service address can move to another interface between two runs
+    let holders = match cfg.by_address {
+        Some(ip) => {
+            interfaces_by_address(ip).unwrap_or_else(|err| {
+                println!("{}", err);
+                process::exit(STATE_UNKNOWN);
+            })
+        },
+        None => { Vec::new() },
+    };
+
+    let mut discovered: Vec<String> = vlans.iter().map(|v| v.name.clone()).collect();
+    discovered.extend(holders.iter().cloned());
+
+    let altnames = resolve_altnames(&cfg);
+    let interfaces = resolve_interfaces(&cfg, &discovered, &altnames).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        process::exit(STATE_UNKNOWN);
+    });
 
-        // if we are at this point we are pretty sure the interface exists
-        present = true;
+    let mut patterns: Vec<String> = cfg.interface.iter().filter(|i| is_glob(i)).cloned().collect();
+    if let Some(ref re) = cfg.interface_regex {
+        patterns.push(re.as_str().to_string());
+    }
+    if cfg.all {
+        patterns.push("all interfaces".to_string());
+    }
+    if let Some(ref parent) = cfg.check_vlans_of {
+        patterns.push(format!("VLANs of {}", parent));
+    }
+    if let Some(ip) = cfg.by_address {
+        patterns.push(format!("holders of {}", ip));
+    }
 
-        Ok(InterfaceState{ present, speed, mtu, operstate, duplex, ips })
+    // a missing VLAN of the expected set is a finding of its own
+    if interfaces.is_empty() && cfg.expect_vlan_set.is_none() && cfg.by_address.is_none() {
+        println!("No interface matches {}", patterns.join(", "));
+        process::exit(STATE_UNKNOWN);
     }
-}
 
+    let mut states: Vec<InterfaceState> = Vec::new();
+    let mut nag_status = NagiosStatus::default();
 
-fn usage() {
-    println!("check_ethernet version 0.2.1\n\
-Copyright (C) by Andreas Maus <maus@ypbind.de>\n\
-This program comes with ABSOLUTELY NO WARRANTY.\n\
-\n\
-check_ethernet is distributed under the Terms of the GNU General\n\
-Public License Version 3. (http://www.gnu.org/copyleft/gpl.html)\n\
-\n\
-Usage: check_ethernet -i <if>|--interface=<if> [-m <mtu>|--mtu=<mtu>] [-s <state>|--state=<state>]   [-C|--critical] [-h|--help] [-a=[ip|ipv4|ipv6]|--address-assigned=[ip|ipv4|ipv6]\n\
-\n\
-    -a =[ip|ipv4|ipv6]                  Check if non-link local address has been assigned to the interface\n\
-    --address-assigned=[ip|ipv4|ipv6]   ip   - IPv4 (169.254.0.0/16) and IPv6 (fe80::/10)
-                                        ipv4 - IPv4 (169.254.0.0/16) only
-                                        ipv6 - IPv6 (fe80::/10) only
-
-    -i <if>                             Ethernet interface to check.\n\
-    --interface=<if>\n\
-\n\
-    -m <mtu>                            Expceted MTU value for interface.\n\
-    --mtu=<mtu>\n\
-\n\
-    -s <state>                          Expceted state. <state> is consists of <speed>[:<mode>] where <speed> is the\n\
-    --state=<state>                     expected negotiated link speed in MBit/s and <mode> is the negotiated link mode.\n\
-                                        <mode> can be one of \"half\" or \"full\". Default: 1000:full\n\
-\n\
-    -C                                  Report CRITICAL condition if state is below requested speed or duplex (or both) or MTU size\n\
-    --critical                          does not match. Default: Report WARNING state\n\
-\n\
-    -h                                  This text\n\
-    --help\n\
-\n");
-}
+    if let Some(ip) = cfg.by_address {
+        let address = ip.to_string();
+        match holders.len() {
+            0 => { nag_status.critical.push(Finding::new(FindingId::AddrNotAssigned, &address, format!("address {} is not assigned to any interface", ip))); },
+            1 => { nag_status.ok.push(Finding::new(FindingId::AddrHolder, &address, format!("address {} is assigned to {}", ip, holders[0]))); },
+            // duplicated addresses in a broadcast domain are usually a problem of their own
+            n => { nag_status.warning.push(Finding::with_values(FindingId::AddrMultipleHolders, &address, format!("address {} is assigned to {} interfaces: {}", ip, n, holders.join(", ")), 1, n)); },
+        };
+    }
 
-impl Configuration {
-    fn new(argv: &[String], opts: &Options) -> Result<Configuration, &'static str> {
-        let address_type: u32;
-        let opt_match = match opts.parse(&argv[1..]) {
-            Ok(o) => { o },
-            Err(_) => {
-                return Err("Failed to parse command line");
-            },
+    // only interfaces which actually exist count
+    let available = list_interfaces(&cfg.sysfs_root);
+    let found = interfaces.iter().filter(|i| available.contains(i)).count();
+    if found < cfg.min_matches {
+        nag_status.critical.push(Finding::with_values(FindingId::InterfaceTooFewMatches, &patterns.join(","), format!("Only {} interface(s) found, at least {} are required", found, cfg.min_matches), cfg.min_matches, found));
+    }
+
+    // held until the process exits, in the same order by every instance
+    let mut locks: Vec<lock::InstanceLock> = Vec::new();
+    if cfg.needs_lock() {
+        let mut lock_files: Vec<String> = interfaces.iter().map(|i| format!("{}.lock", state_file_path(&cfg, i))).collect();
+        lock_files.sort();
+        lock_files.dedup();
+
+        let wait = match cfg.lock_policy {
+            LockPolicy::Wait => { cfg.lock_wait },
+            LockPolicy::Fail => { Duration::from_secs(0) },
         };
 
-        if opt_match.opt_present("h") {
-            usage();
-            process::exit(STATE_OK);
+        for path in lock_files {
+            match lock::acquire(&path, wait) {
+                Ok(Some(l)) => { locks.push(l); },
+                Ok(None) => {
+                    println!("Another check instance is running (lock file {} is held)", path);
+                    process::exit(STATE_UNKNOWN);
+                },
+                Err(e) => {
+                    println!("Can't lock {}: {}", path, e);
+                    process::exit(STATE_UNKNOWN);
+                },
+            };
         }
+    }
 
-        let interface = match opt_match.opt_str("i") {
-            Some(a) => { a },
-            None => { "".to_string() },
-        };
+    let deadline = start + cfg.timeout;
+
+    match collect_states(&cfg, &interfaces, deadline) {
+        Some((collected, unknown)) => {
+            states.extend(collected);
+            nag_status.unknown.extend(unknown);
+        },
+        None => {
+            println!("Check timed out after {} seconds", cfg.timeout.as_secs());
+            process::exit(STATE_UNKNOWN);
+        },
+    };
+    if states.iter().any(|s| s.source == SOURCE_NETLINK) {
+        nag_status.notes.push(format!("{}, state read from netlink, speed, duplex and counters not checked", sysfs_unavailable(&cfg.sysfs_root)));
+    }
+    if let Some(ref host) = cfg.snmp_host {
+        nag_status.notes.push(format!("state read from {} via SNMP, duplex not checked", host));
+    }
+    for (alt, primary) in &altnames {
+        nag_status.notes.push(format!("{} is an altname of {}", alt, primary));
+    }
 
-        let mtu_ = match opt_match.opt_str("m") {
-            Some(a) => { a },
-            None => { "-1".to_string() },
-        };
-        let mtu: i32 = match mtu_.parse() {
-            Ok(v) => { v },
-            Err(_) => { 
-                return Err("Can't convert MTU to an integer");
+    // probes not finishing in time are reported, the results of all others are kept
+    let probes = scheduler::run(probe_tasks(&cfg, &states), deadline);
+    for (_, result) in probes.completed {
+        match result {
+            ProbeResult::FirstHop(i, Ok(v)) => { states[i].first_hop = v; },
+            ProbeResult::GatewayProbe(i, Ok(v)) => { states[i].gateway_probes = v; },
+            ProbeResult::SubnetPeers(i, Ok(v)) => { states[i].subnet_peers = v; },
+            ProbeResult::FrameSizes(i, Ok(v)) => { states[i].frame_sizes = Some(v); },
+            ProbeResult::Uplink(i, Ok(v)) => { states[i].uplink = Some(v); },
+            ProbeResult::FirstHop(i, Err(e)) | ProbeResult::GatewayProbe(i, Err(e)) | ProbeResult::SubnetPeers(i, Err(e)) | ProbeResult::Uplink(i, Err(e)) => {
+                nag_status.unknown.push(Finding::new(FindingId::InterfaceError, &states[i].name, e.to_string()));
+            },
+            ProbeResult::FrameSizes(i, Err(e)) => {
+                nag_status.unknown.push(Finding::new(FindingId::InterfaceError, &states[i].name, format!("Can't sample frame sizes: {}", e)));
             },
         };
+    }
+    if let Some(finding) = probe_timeout(&interfaces.join(","), cfg.timeout, &probes.incomplete) {
+        nag_status.unknown.push(finding);
+    }
 
-        let state_ = match opt_match.opt_str("s") {
-            Some(a) => { a },
-            None => { "1000:full".to_string() },
-        };
-        let state_vec_: Vec<&str> = state_.split(":").collect();
-        let mut speed: i32 = 1000;
-        let mut duplex = "full".to_string();
-
-        if state_vec_.len() > 2 || state_vec_.len() == 0 {
-            return Err("Invalid link mode");
-        }
+    for ifstate in &states {
+        nag_status.merge(NagiosStatus::new(&cfg, &ifstate.name, ifstate));
+    }
 
-        if state_vec_.len() == 1 {
-            speed = match state_vec_[0].parse() {
-                Ok(v) => { v },
-                Err(_) => { return Err("Can't convert link speed to an integer"); },
-            };
-        } else {
-            if state_vec_[0] != "" {
-                speed = match state_vec_[0].parse() {
-                    Ok(v) => { v },
-                    Err(_) => { return Err("Can't convert link speed to an integer"); },
-                };
+    // the next run needs the reported state of every threshold
+    if cfg.hysteresis.is_some() {
+        for ifstate in states.iter().filter(|s| s.source != SOURCE_NETLINK) {
+            let comparisons: Vec<&threshold::Comparison> = nag_status.audit.iter().filter(|c| c.interface == ifstate.name).collect();
+            let saved = state::State::load(&state_file_path(&cfg, &ifstate.name)).and_then(|mut s| {
+                hysteresis::store(&mut s, &comparisons);
+                s.save()
+            });
+            if saved.is_err() {
+                nag_status.unknown.push(Finding::new(FindingId::InterfaceError, &ifstate.name, "Can't write state file".to_string()));
             }
-            duplex = state_vec_[1].clone().to_string();
         }
+    }
 
-        let mut report_critical: bool = false;
+    if let Some(ref parent) = cfg.check_vlans_of {
+        nag_status.merge(vlan_status(&cfg, parent, &vlans, &states));
+    }
 
-        if opt_match.opt_present("C") {
-            report_critical = true;
+    if cfg.check_wq_balance {
+        let workqueues = match sysfs_mount(&cfg.sysfs_root) {
+            Some(mount) => { read_workqueues(&Sysfs{}, &mount).map_err(|e| e.to_string()) },
+            None => { Err(format!("no sysfs mount point above {}", cfg.sysfs_root)) },
         };
+        nag_status.merge(wq_balance_status(&workqueues, &states));
+    }
 
-
-        let raw_address_type = match opt_match.opt_str("a") {
-            Some(a) => { a },
-            None => { "".to_string() },
+    // errors in the acknowledgment file must not hide problems
+    if let Some(ref path) = cfg.ack_file {
+        match ack::load(path) {
+            Ok(acks) => { nag_status.apply_acks(&acks, (unix_now() / 86400) as i64); },
+            Err(e) => { nag_status.unknown.push(Finding::new(FindingId::AckFileInvalid, path, e)); },
         };
+    }
 
-        if raw_address_type != "" && raw_address_type != "ip" && raw_address_type != "ipv4" && raw_address_type != "ipv6" {
+    // purely informational, failing to learn the neighbor never changes the result
+    if cfg.enrich_from_lldp {
+        // half of the time left at most, the result must still be printed before the
+        // caller kills the check
+        let left = deadline.checked_duration_since(Instant::now()).unwrap_or_default();
+        let budget = (left / 2).min(Duration::from_secs(lldp::CAPTURE_TIMEOUT));
+        let mut tasks = Vec::new();
+        for interface in nag_status.problem_interfaces() {
+            let state_path = state_file_path(&cfg, &interface);
+            tasks.push(scheduler::Task::new(format!("LLDP capture on {}", interface), budget, move |timeout| {
+                lldp_neighbor(&state_path, &interface, timeout).map(|n| format!("{} {}", interface, n))
+            }));
         }
 
-        if raw_address_type == "ip" {
-            address_type = ADDR_IPV4 | ADDR_IPV6;
-        } else if raw_address_type == "ipv4" {
-            address_type = ADDR_IPV4;
-        } else if raw_address_type == "ipv6" {
-            address_type = ADDR_IPV6;
-        } else if raw_address_type == "" {
-            address_type = 0;
-        } else {
-            return Err("Invalid parameter for address assignment check");
+        let captures = scheduler::run(tasks, Instant::now() + budget);
+        for (_, note) in captures.completed {
+            if let Some(n) = note {
+                nag_status.notes.push(n);
+            }
+        }
+        if !captures.incomplete.is_empty() {
+            nag_status.notes.push(format!("timeout, not completed: {}", captures.incomplete.join(", ")));
         }
+    }
 
-        if interface == "" {
-            return Err("Interface to check is mandatory");
+    // metrics are written in addition to the regular output
+    if let Some(ref path) = cfg.prometheus_textfile {
+        let check_result = output::CheckResult{ schema_version: cfg.output_version, status: &nag_status, interfaces: &states, exit_code: nag_status.state(), prefix_interface: true, count_interfaces: false, status_prefix: cfg.status_prefix, max_length: cfg.max_output_length, audit: None };
+        let renderer = output::PrometheusRenderer{};
+        let written = match renderer.render(&check_result) {
+            Ok(v) => { state::write_atomic(path, &v).map_err(|e| e.to_string()) },
+            Err(e) => { Err(e) },
         };
-
-        Ok(Configuration{ interface, mtu, speed, duplex, report_critical, address_type })
+        if let Err(e) = written {
+            nag_status.unknown.push(Finding::new(FindingId::PrometheusWriteFailed, path, format!("Can't write Prometheus textfile {}: {}", path, e)));
+        }
     }
-}
-
-fn main() {
-    let argv: Vec<String> = env::args().collect();
-    let mut options = Options::new();
-
-    options.optflag("h", "help", "Usage information.");
-    options.optopt("i", "interface", "Ethernet interface to check.", "");
-    options.optopt("m", "mtu", "Expceted MTU value for interface.", "");
-    options.optopt("s", "state", "Expceted state.", "");
-    options.optflag("C", "critical", "Report CRITICAL condition if state is below requested speed or duplex (or both) or MTU size does not match.");
-    options.optopt("a", "address-assigned", "Check if non-link local address has been assigned to the interface.", "");
 
-    let cfg = Configuration::new(&argv, &options).unwrap_or_else(|err| {
-        eprintln!("Error: {}", err);
-        process::exit(STATE_UNKNOWN);
-    });
-
-    let ifstate = InterfaceState::new(&cfg).unwrap_or_else(|err| {
-        eprintln!("{}", err);
-        process::exit(STATE_UNKNOWN);
-    });
-
-    let nag_status = NagiosStatus::new(&cfg, &ifstate);
-    let result = nag_status.print();
+    let result = nag_status.state();
+    let check_result = output::CheckResult{ schema_version: cfg.output_version, status: &nag_status, interfaces: &states, exit_code: result, prefix_interface: interfaces.len() > 1 || !patterns.is_empty(), count_interfaces: cfg.all, status_prefix: cfg.status_prefix, max_length: cfg.max_output_length, audit: if cfg.verbose { Some(&nag_status.audit) } else { None } };
+    let service_name = match cfg.service_name {
+        Some(ref v) => { v.clone() },
+        None => { format!("ethernet_{}", interfaces.join("_")) },
+    };
+    match output::renderer(cfg.output, &service_name).render(&check_result) {
+        Ok(v) => { println!("{}", v); },
+        Err(e) => {
+            println!("{}", e);
+            process::exit(STATE_UNKNOWN);
+        },
+    };
+
+    // the check_mk agent takes the state from the output line
+    if cfg.output == OutputFormat::Checkmk {
+        process::exit(STATE_OK);
+    }
     process::exit(result);
 }
 
+
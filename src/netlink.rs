@@ -0,0 +1,646 @@
+// Minimal rtnetlink client, just enough to query the kernel for the few
+// attributes sysfs doesn't expose. Generic netlink requests share the same
+// message handling.
+use std::io;
+use std::mem;
+use std::net::IpAddr;
+
+const NLMSG_ERROR: u16 = 0x02;
+const NLMSG_DONE: u16 = 0x03;
+
+const NLM_F_REQUEST: u16 = 0x01;
+const NLM_F_DUMP: u16 = 0x300;
+
+const NLMSG_HDRLEN: usize = 16;
+const RTA_HDRLEN: usize = 4;
+
+pub const RTM_GETLINK: u16 = 18;
+pub const RTM_GETADDR: u16 = 22;
+pub const RTM_GETROUTE: u16 = 26;
+pub const RTM_GETNEIGH: u16 = 30;
+pub const RTM_GETQDISC: u16 = 38;
+
+const IFINFOMSG_LEN: usize = 16;
+pub const IFLA_IFNAME: u16 = 3;
+pub const IFLA_MTU: u16 = 4;
+pub const IFLA_LINK: u16 = 5;
+pub const IFLA_OPERSTATE: u16 = 16;
+pub const IFLA_LINKINFO: u16 = 18;
+pub const IFLA_STATS64: u16 = 23;
+pub const IFLA_PROP_LIST: u16 = 52;
+pub const IFLA_ALT_IFNAME: u16 = 53;
+pub const IFLA_INFO_KIND: u16 = 1;
+pub const IFLA_INFO_DATA: u16 = 2;
+pub const IFLA_VLAN_ID: u16 = 1;
+
+const IFADDRMSG_LEN: usize = 8;
+pub const IFA_ADDRESS: u16 = 1;
+pub const IFA_LOCAL: u16 = 2;
+pub const IFA_CACHEINFO: u16 = 6;
+pub const IFA_FLAGS: u16 = 8;
+pub const IFA_F_TEMPORARY: u32 = 0x01;
+pub const IFA_F_DADFAILED: u32 = 0x08;
+pub const IFA_F_TENTATIVE: u32 = 0x40;
+
+const RTMSG_LEN: usize = 12;
+pub const RTA_DST: u16 = 1;
+pub const RTA_OIF: u16 = 4;
+pub const RTA_GATEWAY: u16 = 5;
+pub const RTA_METRICS: u16 = 8;
+pub const RTA_TABLE: u16 = 15;
+pub const RTAX_MTU: u16 = 2;
+
+const RT_TABLE_MAIN: u32 = 254;
+
+const TCMSG_LEN: usize = 20;
+pub const TCA_KIND: u16 = 1;
+pub const TC_H_ROOT: u32 = 0xffff_ffff;
+
+const NDMSG_LEN: usize = 12;
+pub const NDA_DST: u16 = 1;
+pub const NDA_LLADDR: u16 = 2;
+
+pub const NUD_INCOMPLETE: u16 = 0x01;
+pub const NUD_REACHABLE: u16 = 0x02;
+pub const NUD_STALE: u16 = 0x04;
+pub const NUD_DELAY: u16 = 0x08;
+pub const NUD_PROBE: u16 = 0x10;
+pub const NUD_FAILED: u16 = 0x20;
+pub const NUD_NOARP: u16 = 0x40;
+pub const NUD_PERMANENT: u16 = 0x80;
+
+const GENL_ID_CTRL: u16 = 0x10;
+const GENL_HDRLEN: usize = 4;
+const CTRL_CMD_GETFAMILY: u8 = 3;
+const CTRL_ATTR_FAMILY_ID: u16 = 1;
+const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+
+const RECV_BUFFER_SIZE: usize = 65536;
+
+pub struct Attribute {
+    pub kind: u16,
+    pub data: Vec<u8>,
+}
+
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+// parse a block of rtattr structures
+pub fn parse_attributes(buf: &[u8]) -> Vec<Attribute> {
+    let mut result = Vec::new();
+    let mut pos = 0;
+
+    while pos + RTA_HDRLEN <= buf.len() {
+        let len = u16::from_ne_bytes([buf[pos], buf[pos + 1]]) as usize;
+        // the upper bits carry the NLA_F_NESTED and NLA_F_NET_BYTEORDER flags
+        let kind = u16::from_ne_bytes([buf[pos + 2], buf[pos + 3]]) & 0x3fff;
+        if len < RTA_HDRLEN || pos + len > buf.len() {
+            break;
+        }
+        result.push(Attribute{ kind, data: buf[pos + RTA_HDRLEN..pos + len].to_vec() });
+        pos += align(len);
+    }
+
+    result
+}
+
+pub fn push_attribute(buf: &mut Vec<u8>, kind: u16, data: &[u8]) {
+    let len = (RTA_HDRLEN + data.len()) as u16;
+    buf.extend_from_slice(&len.to_ne_bytes());
+    buf.extend_from_slice(&kind.to_ne_bytes());
+    buf.extend_from_slice(data);
+    while buf.len() % 4 != 0 {
+        buf.push(0);
+    }
+}
+
+pub fn attribute_u32(data: &[u8]) -> Option<u32> {
+    if data.len() < 4 {
+        return None;
+    }
+    Some(u32::from_ne_bytes([data[0], data[1], data[2], data[3]]))
+}
+
+pub fn attribute_ip(data: &[u8]) -> Option<IpAddr> {
+    match data.len() {
+        4 => {
+            Some(IpAddr::from([data[0], data[1], data[2], data[3]]))
+        },
+        16 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(data);
+            Some(IpAddr::from(octets))
+        },
+        _ => { None },
+    }
+}
+
+struct Socket {
+    fd: libc::c_int,
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+impl Socket {
+    fn new(protocol: libc::c_int) -> io::Result<Socket> {
+        let fd = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_RAW | libc::SOCK_CLOEXEC, protocol) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let sock = Socket{ fd };
+
+        let mut addr: libc::sockaddr_nl = unsafe { mem::zeroed() };
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        let rc = unsafe { libc::bind(sock.fd, &addr as *const libc::sockaddr_nl as *const libc::sockaddr, mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t) };
+        if rc < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(sock)
+    }
+
+    fn send(&self, buf: &[u8]) -> io::Result<()> {
+        let rc = unsafe { libc::send(self.fd, buf.as_ptr() as *const libc::c_void, buf.len(), 0) };
+        if rc < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        let rc = unsafe { libc::recv(self.fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+        if rc < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(rc as usize)
+    }
+}
+
+// send a single request and collect all answers; dump requests are read until NLMSG_DONE
+pub fn request(kind: u16, dump: bool, payload: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+    request_protocol(libc::NETLINK_ROUTE, kind, dump, payload)
+}
+
+fn request_protocol(protocol: libc::c_int, kind: u16, dump: bool, payload: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+    let sock = Socket::new(protocol)?;
+    let mut flags = NLM_F_REQUEST;
+    if dump {
+        flags |= NLM_F_DUMP;
+    }
+
+    let mut buf: Vec<u8> = Vec::new();
+    buf.extend_from_slice(&((NLMSG_HDRLEN + payload.len()) as u32).to_ne_bytes());
+    buf.extend_from_slice(&kind.to_ne_bytes());
+    buf.extend_from_slice(&flags.to_ne_bytes());
+    // sequence number and port id
+    buf.extend_from_slice(&1u32.to_ne_bytes());
+    buf.extend_from_slice(&0u32.to_ne_bytes());
+    buf.extend_from_slice(payload);
+    sock.send(&buf)?;
+
+    let mut result = Vec::new();
+    let mut rbuf = vec![0u8; RECV_BUFFER_SIZE];
+
+    loop {
+        let len = sock.recv(&mut rbuf)?;
+        let mut pos = 0;
+
+        while pos + NLMSG_HDRLEN <= len {
+            let msg_len = u32::from_ne_bytes([rbuf[pos], rbuf[pos + 1], rbuf[pos + 2], rbuf[pos + 3]]) as usize;
+            let msg_kind = u16::from_ne_bytes([rbuf[pos + 4], rbuf[pos + 5]]);
+            if msg_len < NLMSG_HDRLEN || pos + msg_len > len {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated netlink message"));
+            }
+            let body = &rbuf[pos + NLMSG_HDRLEN..pos + msg_len];
+
+            match msg_kind {
+                NLMSG_DONE => { return Ok(result); },
+                NLMSG_ERROR => {
+                    let errno = match attribute_u32(body) {
+                        Some(v) => { v as i32 },
+                        None => { 0 },
+                    };
+                    if errno != 0 {
+                        return Err(io::Error::from_raw_os_error(-errno));
+                    }
+                    // a plain acknowledgement
+                    return Ok(result);
+                },
+                _ => {
+                    result.push(body.to_vec());
+                },
+            };
+            pos += align(msg_len);
+        }
+
+        if !dump && !result.is_empty() {
+            return Ok(result);
+        }
+    }
+}
+
+// generic netlink request to the family, the answers are returned without the
+// generic netlink header
+pub fn genl_request(family: u16, cmd: u8, dump: bool, attributes: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+    let mut payload: Vec<u8> = vec![cmd, 1, 0, 0];
+    payload.extend_from_slice(attributes);
+
+    let answers = request_protocol(libc::NETLINK_GENERIC, family, dump, &payload)?;
+    Ok(answers.into_iter().filter(|a| a.len() >= GENL_HDRLEN).map(|a| a[GENL_HDRLEN..].to_vec()).collect())
+}
+
+// id of a generic netlink family, ENOENT if the family isn't registered
+pub fn genl_family(name: &str) -> io::Result<u16> {
+    let mut attributes: Vec<u8> = Vec::new();
+    let mut raw_name = name.as_bytes().to_vec();
+    raw_name.push(0);
+    push_attribute(&mut attributes, CTRL_ATTR_FAMILY_NAME, &raw_name);
+
+    for answer in genl_request(GENL_ID_CTRL, CTRL_CMD_GETFAMILY, false, &attributes)? {
+        for attr in parse_attributes(&answer) {
+            if attr.kind == CTRL_ATTR_FAMILY_ID && attr.data.len() >= 2 {
+                return Ok(u16::from_ne_bytes([attr.data[0], attr.data[1]]));
+            }
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::NotFound, "generic netlink family not found"))
+}
+
+pub struct Route {
+    pub mtu: Option<u32>,
+}
+
+// ask the kernel which route it would use to reach dst (like "ip route get")
+pub fn route_get(dst: IpAddr) -> io::Result<Route> {
+    let mut payload = vec![0u8; RTMSG_LEN];
+    let octets = match dst {
+        IpAddr::V4(a) => {
+            payload[0] = libc::AF_INET as u8;
+            payload[1] = 32;
+            a.octets().to_vec()
+        },
+        IpAddr::V6(a) => {
+            payload[0] = libc::AF_INET6 as u8;
+            payload[1] = 128;
+            a.octets().to_vec()
+        },
+    };
+    push_attribute(&mut payload, RTA_DST, &octets);
+
+    let answer = request(RTM_GETROUTE, false, &payload)?;
+    let msg = match answer.first() {
+        Some(m) => { m },
+        None => { return Err(io::Error::new(io::ErrorKind::NotFound, "no route to destination")); },
+    };
+    if msg.len() < RTMSG_LEN {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated route message"));
+    }
+
+    let mut route = Route{ mtu: None };
+    for attr in parse_attributes(&msg[RTMSG_LEN..]) {
+        match attr.kind {
+            RTA_METRICS => {
+                for metric in parse_attributes(&attr.data) {
+                    if metric.kind == RTAX_MTU {
+                        route.mtu = attribute_u32(&metric.data);
+                    }
+                }
+            },
+            _ => {},
+        };
+    }
+
+    Ok(route)
+}
+
+pub struct RouteEntry {
+    // AF_INET or AF_INET6
+    pub family: u8,
+    pub dst: Option<IpAddr>,
+    pub dst_len: u8,
+    pub gateway: Option<IpAddr>,
+    pub oif: Option<u32>,
+}
+
+// routes of the main routing table
+pub fn routes() -> io::Result<Vec<RouteEntry>> {
+    let payload = vec![0u8; RTMSG_LEN];
+    let mut result = Vec::new();
+
+    for msg in request(RTM_GETROUTE, true, &payload)? {
+        if msg.len() < RTMSG_LEN {
+            continue;
+        }
+
+        let mut table = msg[4] as u32;
+        let mut entry = RouteEntry{ family: msg[0], dst: None, dst_len: msg[1], gateway: None, oif: None };
+        for attr in parse_attributes(&msg[RTMSG_LEN..]) {
+            match attr.kind {
+                RTA_DST => { entry.dst = attribute_ip(&attr.data); },
+                RTA_OIF => { entry.oif = attribute_u32(&attr.data); },
+                RTA_GATEWAY => { entry.gateway = attribute_ip(&attr.data); },
+                RTA_TABLE => {
+                    if let Some(t) = attribute_u32(&attr.data) {
+                        table = t;
+                    }
+                },
+                _ => {},
+            };
+        }
+
+        if table == RT_TABLE_MAIN {
+            result.push(entry);
+        }
+    }
+
+    Ok(result)
+}
+
+pub struct NeighborEntry {
+    pub ifindex: u32,
+    pub ip: IpAddr,
+    pub state: u16,
+    // empty if the neighbor is not resolved
+    pub lladdr: Vec<u8>,
+}
+
+pub fn neighbor_state_name(state: u16) -> &'static str {
+    match state {
+        NUD_INCOMPLETE => { "INCOMPLETE" },
+        NUD_REACHABLE => { "REACHABLE" },
+        NUD_STALE => { "STALE" },
+        NUD_DELAY => { "DELAY" },
+        NUD_PROBE => { "PROBE" },
+        NUD_FAILED => { "FAILED" },
+        NUD_NOARP => { "NOARP" },
+        NUD_PERMANENT => { "PERMANENT" },
+        _ => { "NONE" },
+    }
+}
+
+// the ARP and NDP neighbor tables of all interfaces
+pub fn neighbors() -> io::Result<Vec<NeighborEntry>> {
+    let payload = vec![0u8; NDMSG_LEN];
+    let mut result = Vec::new();
+
+    for msg in request(RTM_GETNEIGH, true, &payload)? {
+        if msg.len() < NDMSG_LEN {
+            continue;
+        }
+
+        let ifindex = u32::from_ne_bytes([msg[4], msg[5], msg[6], msg[7]]);
+        let state = u16::from_ne_bytes([msg[8], msg[9]]);
+        let mut ip: Option<IpAddr> = None;
+        let mut lladdr: Vec<u8> = Vec::new();
+
+        for attr in parse_attributes(&msg[NDMSG_LEN..]) {
+            match attr.kind {
+                NDA_DST => { ip = attribute_ip(&attr.data); },
+                NDA_LLADDR => { lladdr = attr.data.clone(); },
+                _ => {},
+            };
+        }
+
+        if let Some(ip) = ip {
+            result.push(NeighborEntry{ ifindex, ip, state, lladdr });
+        }
+    }
+
+    Ok(result)
+}
+
+// fields of struct rtnl_link_stats64, named like /sys/class/net/<if>/statistics/
+const STATS64_FIELDS: [&str; 10] = [
+    "rx_packets", "tx_packets", "rx_bytes", "tx_bytes", "rx_errors",
+    "tx_errors", "rx_dropped", "tx_dropped", "multicast", "collisions",
+];
+
+pub struct Link {
+    // IFF_* flags of the interface
+    pub flags: u32,
+    pub mtu: Option<u32>,
+    pub operstate: Option<&'static str>,
+    // None if the kernel doesn't report 64 bit statistics
+    pub stats64: Option<Vec<u64>>,
+    // IFLA_INFO_KIND like "vlan", "bond" or "team", None for physical devices
+    pub kind: Option<String>,
+    pub name: Option<String>,
+    // alternative names from IFLA_PROP_LIST, like "ip link property add" sets them
+    pub altnames: Vec<String>,
+}
+
+impl Link {
+    pub fn stat64(&self, name: &str) -> Option<u64> {
+        let pos = STATS64_FIELDS.iter().position(|f| *f == name)?;
+        self.stats64.as_ref()?.get(pos).copied()
+    }
+}
+
+// RFC 2863 operational states, named like /sys/class/net/<if>/operstate
+fn operstate_name(state: u8) -> &'static str {
+    match state {
+        1 => { "notpresent" },
+        2 => { "down" },
+        3 => { "lowerlayerdown" },
+        4 => { "testing" },
+        5 => { "dormant" },
+        6 => { "up" },
+        _ => { "unknown" },
+    }
+}
+
+pub fn link_get(ifindex: u32) -> io::Result<Link> {
+    let mut payload = vec![0u8; IFINFOMSG_LEN];
+    payload[4..8].copy_from_slice(&ifindex.to_ne_bytes());
+    link_request(&payload)
+}
+
+// the kernel looks up the primary name and the alternative names of all links
+pub fn link_by_name(name: &str) -> io::Result<Link> {
+    let mut payload = vec![0u8; IFINFOMSG_LEN];
+    let mut attribute = name.as_bytes().to_vec();
+    attribute.push(0);
+    push_attribute(&mut payload, IFLA_ALT_IFNAME, &attribute);
+    link_request(&payload)
+}
+
+fn link_request(payload: &[u8]) -> io::Result<Link> {
+    let answer = request(RTM_GETLINK, false, payload)?;
+    let msg = match answer.first() {
+        Some(m) => { m },
+        None => { return Err(io::Error::new(io::ErrorKind::NotFound, "no such interface")); },
+    };
+    if msg.len() < IFINFOMSG_LEN {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated link message"));
+    }
+
+    let flags = u32::from_ne_bytes([msg[8], msg[9], msg[10], msg[11]]);
+    let mut link = Link{ flags, mtu: None, operstate: None, stats64: None, kind: None, name: None, altnames: Vec::new() };
+    for attr in parse_attributes(&msg[IFINFOMSG_LEN..]) {
+        match attr.kind {
+            IFLA_IFNAME => { link.name = Some(String::from_utf8_lossy(&attr.data).trim_end_matches('\0').to_string()); },
+            IFLA_MTU => { link.mtu = attribute_u32(&attr.data); },
+            IFLA_PROP_LIST => {
+                for prop in parse_attributes(&attr.data).into_iter().filter(|p| p.kind == IFLA_ALT_IFNAME) {
+                    link.altnames.push(String::from_utf8_lossy(&prop.data).trim_end_matches('\0').to_string());
+                }
+            },
+            IFLA_OPERSTATE => {
+                if let Some(state) = attr.data.first() {
+                    link.operstate = Some(operstate_name(*state));
+                }
+            },
+            IFLA_STATS64 => {
+                link.stats64 = Some(attr.data.chunks_exact(8).map(|c| u64::from_ne_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]])).collect());
+            },
+            IFLA_LINKINFO => {
+                if let Some(info) = parse_attributes(&attr.data).into_iter().find(|i| i.kind == IFLA_INFO_KIND) {
+                    link.kind = Some(String::from_utf8_lossy(&info.data).trim_end_matches('\0').to_string());
+                }
+            },
+            _ => {},
+        };
+    }
+
+    Ok(link)
+}
+
+pub struct VlanLink {
+    pub name: String,
+    pub id: u16,
+    // interface index of the parent device
+    pub parent: u32,
+}
+
+// all VLAN interfaces, from IFLA_LINKINFO of a link dump
+pub fn vlans() -> io::Result<Vec<VlanLink>> {
+    let payload = vec![0u8; IFINFOMSG_LEN];
+    let mut result = Vec::new();
+
+    for msg in request(RTM_GETLINK, true, &payload)? {
+        if msg.len() < IFINFOMSG_LEN {
+            continue;
+        }
+
+        let mut name: Option<String> = None;
+        let mut parent: Option<u32> = None;
+        let mut is_vlan = false;
+        let mut id: Option<u16> = None;
+
+        for attr in parse_attributes(&msg[IFINFOMSG_LEN..]) {
+            match attr.kind {
+                IFLA_IFNAME => { name = Some(String::from_utf8_lossy(&attr.data).trim_end_matches('\0').to_string()); },
+                IFLA_LINK => { parent = attribute_u32(&attr.data); },
+                IFLA_LINKINFO => {
+                    for info in parse_attributes(&attr.data) {
+                        match info.kind {
+                            IFLA_INFO_KIND => { is_vlan = info.data.starts_with(b"vlan\0"); },
+                            IFLA_INFO_DATA => {
+                                for data in parse_attributes(&info.data) {
+                                    if data.kind == IFLA_VLAN_ID && data.data.len() >= 2 {
+                                        id = Some(u16::from_ne_bytes([data.data[0], data.data[1]]));
+                                    }
+                                }
+                            },
+                            _ => {},
+                        };
+                    }
+                },
+                _ => {},
+            };
+        }
+
+        if let (true, Some(name), Some(id), Some(parent)) = (is_vlan, name, id, parent) {
+            result.push(VlanLink{ name, id, parent });
+        }
+    }
+
+    Ok(result)
+}
+
+pub struct Address {
+    pub ifindex: u32,
+    pub ip: IpAddr,
+    pub prefix_len: u8,
+    // IFA_F_*, the header only has room for the lower 8 bits
+    pub flags: u32,
+    // creation time in hundredths of a second since boot
+    pub created: Option<u32>,
+}
+
+pub fn addresses(ifindex: u32) -> io::Result<Vec<Address>> {
+    Ok(all_addresses()?.into_iter().filter(|a| a.ifindex == ifindex).collect())
+}
+
+// the addresses of all interfaces
+pub fn all_addresses() -> io::Result<Vec<Address>> {
+    let payload = vec![0u8; IFADDRMSG_LEN];
+    let mut result = Vec::new();
+
+    for msg in request(RTM_GETADDR, true, &payload)? {
+        if msg.len() < IFADDRMSG_LEN {
+            continue;
+        }
+        let ifindex = u32::from_ne_bytes([msg[4], msg[5], msg[6], msg[7]]);
+
+        // IFA_ADDRESS is the peer address on point-to-point links, IFA_LOCAL
+        // is the address of the interface if present
+        let mut address: Option<IpAddr> = None;
+        let mut local: Option<IpAddr> = None;
+        let mut flags = msg[2] as u32;
+        let mut created: Option<u32> = None;
+        for attr in parse_attributes(&msg[IFADDRMSG_LEN..]) {
+            match attr.kind {
+                IFA_ADDRESS => { address = attribute_ip(&attr.data); },
+                IFA_LOCAL => { local = attribute_ip(&attr.data); },
+                IFA_FLAGS => { flags = attribute_u32(&attr.data).unwrap_or(flags); },
+                // struct ifa_cacheinfo: preferred and valid lifetime, cstamp, tstamp
+                IFA_CACHEINFO if attr.data.len() >= 12 => { created = attribute_u32(&attr.data[8..12]); },
+                _ => {},
+            };
+        }
+
+        if let Some(ip) = local.or(address) {
+            result.push(Address{ ifindex, ip, prefix_len: msg[1], flags, created });
+        }
+    }
+
+    Ok(result)
+}
+
+pub struct Qdisc {
+    pub kind: String,
+    pub handle: u32,
+    pub parent: u32,
+}
+
+// all qdiscs of the interface, the kernel dumps the qdiscs of every interface
+pub fn qdiscs(ifindex: u32) -> io::Result<Vec<Qdisc>> {
+    let payload = vec![0u8; TCMSG_LEN];
+    let mut result = Vec::new();
+
+    for msg in request(RTM_GETQDISC, true, &payload)? {
+        if msg.len() < TCMSG_LEN {
+            continue;
+        }
+        if u32::from_ne_bytes([msg[4], msg[5], msg[6], msg[7]]) != ifindex {
+            continue;
+        }
+
+        let handle = u32::from_ne_bytes([msg[8], msg[9], msg[10], msg[11]]);
+        let parent = u32::from_ne_bytes([msg[12], msg[13], msg[14], msg[15]]);
+        for attr in parse_attributes(&msg[TCMSG_LEN..]) {
+            if attr.kind == TCA_KIND {
+                let kind = String::from_utf8_lossy(&attr.data).trim_end_matches('\0').to_string();
+                result.push(Qdisc{ kind, handle, parent });
+            }
+        }
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,463 @@
+// Rendering of check results. Every output format consumes the same CheckResult,
+// so escaping and ordering rules live in exactly one place per format.
+use super::config::OutputFormat;
+use super::interface::{self, InterfaceState};
+use super::status::{Finding, NagiosStatus};
+use super::threshold::{self, Comparison};
+use super::{STATE_CRITICAL, STATE_OK, STATE_UNKNOWN, STATE_WARNING};
+
+use std::fmt::Write;
+
+// version of the JSON output, raised on every incompatible change. The shape of
+// every version is described by schema/output-v<n>.json
+pub const SCHEMA_VERSION: u32 = 1;
+// oldest version still available with --output-version
+pub const MIN_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+pub struct PerfData {
+    pub label: String,
+    pub value: f64,
+    pub uom: String,
+    pub warn: Option<String>,
+    pub crit: Option<String>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl PerfData {
+    pub fn new(label: &str, value: f64, uom: &str) -> PerfData {
+        PerfData{ label: label.to_string(), value, uom: uom.to_string(), warn: None, crit: None, min: None, max: None }
+    }
+
+    // label=value[uom];[warn];[crit];[min];[max] as defined by the plugin development guidelines
+    pub fn to_nagios(&self) -> String {
+        let opt_f64 = |v: &Option<f64>| match v {
+            Some(v) => { v.to_string() },
+            None => { String::new() },
+        };
+
+        let mut result = format!("{}={}{};{};{};{};{}", quote_label(&self.label), self.value, self.uom,
+                                 self.warn.clone().unwrap_or_default(), self.crit.clone().unwrap_or_default(),
+                                 opt_f64(&self.min), opt_f64(&self.max));
+        // trailing empty fields can be omitted
+        while result.ends_with(';') {
+            result.pop();
+        }
+        result
+    }
+}
+
+// labels containing spaces, '=' or quotes must be enclosed in single quotes,
+// a single quote inside the label is written as two single quotes
+fn quote_label(label: &str) -> String {
+    let label = sanitize(label);
+    if label.contains(' ') || label.contains('=') || label.contains('\'') {
+        return format!("'{}'", label.replace('\'', "''"));
+    }
+    label
+}
+
+#[derive(Serialize)]
+pub struct CheckResult<'a> {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub status: &'a NagiosStatus,
+    pub interfaces: &'a [InterfaceState],
+    pub exit_code: i32,
+    // prefix messages with the interface name if several interfaces were checked
+    #[serde(skip)]
+    pub prefix_interface: bool,
+    // summarise as number of interfaces checked, e.g. for --all
+    #[serde(skip)]
+    pub count_interfaces: bool,
+    // start the first line with the state, e.g. "CRITICAL - Interface is down"
+    #[serde(skip)]
+    pub status_prefix: bool,
+    // bytes of the first line of the plain output
+    #[serde(skip)]
+    pub max_length: usize,
+    // threshold comparisons, only with --verbose
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audit: Option<&'a [Comparison]>,
+}
+
+pub trait OutputRenderer {
+    fn render(&self, result: &CheckResult) -> Result<String, String>;
+}
+
+pub fn renderer(format: OutputFormat, service_name: &str) -> Box<dyn OutputRenderer> {
+    match format {
+        OutputFormat::Plain => { Box::new(PlainRenderer{}) },
+        OutputFormat::Json => { Box::new(JsonRenderer{}) },
+        OutputFormat::Checkmk => { Box::new(CheckmkRenderer{ service_name: service_name.to_string() }) },
+    }
+}
+
+// a "|" starts the performance data and NRPE only passes the first line, interface
+// names and values read from the system may contain both
+pub fn sanitize(text: &str) -> String {
+    text.chars().filter_map(|c| {
+        match c {
+            '|' => { Some('/') },
+            '\n' | '\r' | '\t' => { Some(' ') },
+            c if c.is_control() => { None },
+            c => { Some(c) },
+        }
+    }).collect()
+}
+
+// at most max bytes, cut at a character boundary and marked with "..." if it fits
+fn truncate(text: &str, max: usize) -> String {
+    if text.len() <= max {
+        return text.to_string();
+    }
+    let mark = if max < 3 { "" } else { "..." };
+    let mut end = max - mark.len();
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{}", &text[..end], mark)
+}
+
+fn message(f: &Finding, prefix_interface: bool) -> String {
+    if prefix_interface {
+        sanitize(&format!("{}: {}", f.interface, f.message))
+    } else {
+        sanitize(&f.message)
+    }
+}
+
+fn join_messages(findings: &[Finding], prefix_interface: bool) -> String {
+    findings.iter().map(|f| message(f, prefix_interface)).collect::<Vec<String>>().join(", ")
+}
+
+// the first line starts with the state as recommended by the plugin development
+// guidelines, e.g. "CRITICAL - Interface is down". Several problems are counted and
+// those of lower states follow the worst ones, "CRITICAL - 2 problems on eth0:
+// Interface is down; WARNING - MTU size is 1500 ...", and are listed one per line
+// below. With count_interfaces the first line is "CRITICAL - 12 interfaces checked,
+// problems on ..."
+pub struct PlainRenderer {}
+
+impl PlainRenderer {
+    fn problems(status: &NagiosStatus) -> usize {
+        status.critical.len() + status.warning.len() + status.unknown.len()
+    }
+
+    // the messages of the state of the check first, then those of the other states
+    // labelled with their state
+    fn grouped(result: &CheckResult) -> String {
+        let status = result.status;
+        let mut groups = [(STATE_UNKNOWN, &status.unknown), (STATE_CRITICAL, &status.critical), (STATE_WARNING, &status.warning)];
+        groups.sort_by_key(|(state, _)| *state != result.exit_code);
+
+        groups.iter().filter(|(_, findings)| !findings.is_empty()).enumerate().map(|(i, (state, findings))| {
+            if i == 0 {
+                join_messages(findings, result.prefix_interface)
+            } else {
+                format!("{} - {}", threshold::state_name(*state), join_messages(findings, result.prefix_interface))
+            }
+        }).collect::<Vec<String>>().join("; ")
+    }
+
+    // first line, performance data must stay on it. Messages come worst first, a line
+    // longer than max_length loses performance data from the end and then the end of
+    // the messages
+    fn summary(result: &CheckResult) -> String {
+        let status = result.status;
+        let problems = PlainRenderer::problems(status);
+
+        let mut line = if result.count_interfaces {
+            let checked = result.interfaces.len();
+            if problems == 0 {
+                format!("{} interfaces checked", checked)
+            } else {
+                format!("{} interfaces checked, problems on {}", checked, sanitize(&status.problem_interfaces().join(", ")))
+            }
+        } else if problems > 1 {
+            format!("{} problems on {}: {}", problems, sanitize(&status.problem_interfaces().join(", ")), PlainRenderer::grouped(result))
+        } else {
+            let findings = match result.exit_code {
+                STATE_CRITICAL => { &status.critical },
+                STATE_WARNING => { &status.warning },
+                STATE_OK => { &status.ok },
+                _ => { &status.unknown },
+            };
+            join_messages(findings, result.prefix_interface)
+        };
+        if result.status_prefix {
+            line = format!("{} - {}", threshold::state_name(result.exit_code), line);
+        }
+        if !status.notes.is_empty() {
+            line = format!("{} ({})", line, sanitize(&status.notes.join("; ")));
+        }
+        let line = truncate(&line, result.max_length);
+
+        let mut perfdata: Vec<String> = status.perfdata.iter().map(|p| p.to_nagios()).collect();
+        while !perfdata.is_empty() && line.len() + 3 + perfdata.join(" ").len() > result.max_length {
+            perfdata.pop();
+        }
+        if perfdata.is_empty() {
+            line
+        } else {
+            format!("{} | {}", line, perfdata.join(" "))
+        }
+    }
+
+    // one line per message, worst state first, OK messages only with --verbose
+    fn details(result: &CheckResult) -> Vec<String> {
+        let status = result.status;
+        let verbose = result.audit.is_some();
+        if PlainRenderer::problems(status) <= 1 && !result.count_interfaces && !verbose {
+            return Vec::new();
+        }
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut groups = vec![(STATE_UNKNOWN, &status.unknown), (STATE_CRITICAL, &status.critical), (STATE_WARNING, &status.warning)];
+        if verbose {
+            groups.push((STATE_OK, &status.ok));
+        }
+        for (state, findings) in groups {
+            for f in findings.iter() {
+                lines.push(format!("{}: {}", threshold::state_name(state), message(f, result.prefix_interface)));
+            }
+        }
+        lines
+    }
+}
+
+impl OutputRenderer for PlainRenderer {
+    fn render(&self, result: &CheckResult) -> Result<String, String> {
+        let mut lines = PlainRenderer::details(result);
+
+        if let Some(audit) = result.audit {
+            lines.extend(audit.iter().map(|cmp| cmp.to_string()));
+            lines.extend(result.status.details.iter().cloned());
+            for ifs in result.interfaces.iter().filter(|i| i.present) {
+                match ifs.driver {
+                    Some(ref d) => { lines.push(format!("{} driver: {}", ifs.name, d)); },
+                    // IF-MIB doesn't tell the driver
+                    None if ifs.source == interface::SOURCE_SNMP => {},
+                    None => { lines.push(format!("{} driver: none (virtual interface)", ifs.name)); },
+                };
+                if let Some(ref mac) = ifs.mac {
+                    lines.push(format!("{} address: {}", ifs.name, mac));
+                }
+                if let Some(flags) = ifs.flags {
+                    lines.push(format!("{} flags: {:#x} ({})", ifs.name, flags, interface::flag_names(flags).join(", ")));
+                }
+                if let Some(ref pm) = ifs.runtime_pm {
+                    lines.push(format!("{} device/power: control {}, runtime_status {}", ifs.name, pm.control, pm.runtime_status));
+                }
+                for (name, value) in &ifs.power {
+                    lines.push(format!("{} power/{}: {}", ifs.name, name, value));
+                }
+                for d in &ifs.discrepancies {
+                    lines.push(format!("{} {}", ifs.name, d));
+                }
+            }
+        }
+
+        let mut out = PlainRenderer::summary(result);
+        for line in lines {
+            out = format!("{}\n{}", out, sanitize(&line));
+        }
+        Ok(out)
+    }
+}
+
+// node_exporter textfile collector format
+pub struct PrometheusRenderer {}
+
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+impl OutputRenderer for PrometheusRenderer {
+    fn render(&self, result: &CheckResult) -> Result<String, String> {
+        let mut link_up = String::new();
+        let mut speed = String::new();
+        let mut mtu = String::new();
+        let mut addresses = String::new();
+        let mut status = String::new();
+
+        for ifs in result.interfaces {
+            let label = format!("interface=\"{}\"", escape_label_value(&ifs.name));
+            let up = if ifs.present && ifs.operstate == "up" { 1 } else { 0 };
+            let _ = writeln!(link_up, "ethernet_link_up{{{}}} {}", label, up);
+
+            // missing or unreadable values are not exported at all
+            if ifs.speed >= 0 {
+                let _ = writeln!(speed, "ethernet_speed_mbits{{{}}} {}", label, ifs.speed);
+            }
+            if ifs.mtu >= 0 {
+                let _ = writeln!(mtu, "ethernet_mtu_bytes{{{}}} {}", label, ifs.mtu);
+            }
+
+            let ipv4 = ifs.ips.iter().filter(|n| n.is_ipv4()).count();
+            let ipv6 = ifs.ips.iter().filter(|n| n.is_ipv6()).count();
+            let _ = writeln!(addresses, "ethernet_addresses{{{},family=\"ipv4\"}} {}", label, ipv4);
+            let _ = writeln!(addresses, "ethernet_addresses{{{},family=\"ipv6\"}} {}", label, ipv6);
+
+            let _ = writeln!(status, "ethernet_check_status{{{}}} {}", label, result.status.interface_state(&ifs.name));
+        }
+
+        let mut out = String::new();
+        for (name, help, values) in &[
+            ("ethernet_link_up", "Interface is present and operational state is up", link_up),
+            ("ethernet_speed_mbits", "Negotiated link speed in MBit/s", speed),
+            ("ethernet_mtu_bytes", "MTU of the interface", mtu),
+            ("ethernet_addresses", "Number of addresses assigned to the interface", addresses),
+            ("ethernet_check_status", "Nagios state of the interface check (0 - OK, 1 - WARNING, 2 - CRITICAL, 3 - UNKNOWN)", status),
+        ] {
+            if values.is_empty() {
+                continue;
+            }
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} gauge", name);
+            out.push_str(values);
+        }
+
+        Ok(out)
+    }
+}
+
+// check_mk local check: "<state> <service> <metric>=<value>;<warn>;<crit>;<min>;<max>|... <text>"
+pub struct CheckmkRenderer {
+    pub service_name: String,
+}
+
+impl CheckmkRenderer {
+    // check_mk only knows upper levels, Nagios ranges like "-70:" or "@1:10" are dropped
+    fn level(range: &Option<String>) -> String {
+        match range {
+            Some(ref r) => {
+                let r = r.strip_prefix("0:").unwrap_or(r);
+                match r.parse::<f64>() {
+                    Ok(v) => { v.to_string() },
+                    Err(_) => { String::new() },
+                }
+            },
+            None => { String::new() },
+        }
+    }
+
+    fn metric(p: &PerfData) -> String {
+        let opt_f64 = |v: &Option<f64>| match v {
+            Some(v) => { v.to_string() },
+            None => { String::new() },
+        };
+
+        // metric names are neither quoted nor may they contain the field separators
+        let name: String = p.label.chars().map(|c| if c.is_whitespace() || c == '|' || c == '=' || c == ';' { '_' } else { c }).collect();
+        let mut result = format!("{}={};{};{};{};{}", name, p.value, CheckmkRenderer::level(&p.warn), CheckmkRenderer::level(&p.crit),
+                                 opt_f64(&p.min), opt_f64(&p.max));
+        while result.ends_with(';') {
+            result.pop();
+        }
+        result
+    }
+}
+
+impl OutputRenderer for CheckmkRenderer {
+    fn render(&self, result: &CheckResult) -> Result<String, String> {
+        let status = result.status;
+        let findings = match result.exit_code {
+            STATE_CRITICAL => { &status.critical },
+            STATE_WARNING => { &status.warning },
+            STATE_OK => { &status.ok },
+            _ => { &status.unknown },
+        };
+
+        let perfdata = if status.perfdata.is_empty() {
+            "-".to_string()
+        } else {
+            status.perfdata.iter().map(CheckmkRenderer::metric).collect::<Vec<String>>().join("|")
+        };
+
+        let mut text = join_messages(findings, result.prefix_interface);
+        if !status.notes.is_empty() {
+            text = format!("{} ({})", text, status.notes.join("; "));
+        }
+        // the line is the whole record, everything after the perfdata is the text
+        text = sanitize(&text);
+
+        Ok(format!("{} {} {} {}", result.exit_code, self.service_name, perfdata, text))
+    }
+}
+
+pub struct JsonRenderer {}
+
+impl OutputRenderer for JsonRenderer {
+    fn render(&self, result: &CheckResult) -> Result<String, String> {
+        match serde_json::to_string(result) {
+            Ok(v) => { Ok(v) },
+            Err(e) => { Err(format!("Can't encode result as JSON: {}", e)) },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::status::FindingId;
+
+    fn result<'a>(status: &'a NagiosStatus, max_length: usize) -> CheckResult<'a> {
+        CheckResult{ schema_version: SCHEMA_VERSION, status, interfaces: &[], exit_code: status.state(), prefix_interface: true, count_interfaces: false, status_prefix: true, max_length, audit: None }
+    }
+
+    #[test]
+    fn sanitize_hostile_names() {
+        assert_eq!(sanitize("eth0|rm -rf /"), "eth0/rm -rf /");
+        assert_eq!(sanitize("eth0\nOK - all fine"), "eth0 OK - all fine");
+        assert_eq!(sanitize("eth0\r\n\tx"), "eth0   x");
+        assert_eq!(sanitize("eth0\u{1b}[31m\u{7}"), "eth0[31m");
+        assert_eq!(sanitize("büro-lan"), "büro-lan");
+    }
+
+    #[test]
+    fn perfdata_label_of_a_hostile_name() {
+        assert_eq!(PerfData::new("eth0|x_mtu", 1500.0, "").to_nagios(), "eth0/x_mtu=1500");
+        assert_eq!(PerfData::new("eth0\nx_mtu", 1500.0, "").to_nagios(), "'eth0 x_mtu'=1500");
+        assert_eq!(PerfData::new("it's_mtu", 1500.0, "").to_nagios(), "'it''s_mtu'=1500");
+    }
+
+    #[test]
+    fn plain_output_of_a_hostile_name_stays_on_one_line() {
+        let mut status = NagiosStatus::default();
+        status.critical.push(Finding::new(FindingId::InterfaceMissing, "eth0|rm -rf", "Interface is not present".to_string()));
+        status.notes.push("eth0\nOK - injected".to_string());
+        status.perfdata.push(PerfData::new("eth0|rm -rf_mtu", 1500.0, ""));
+        let out = PlainRenderer{}.render(&result(&status, 4096)).unwrap();
+        assert_eq!(out, "CRITICAL - eth0/rm -rf: Interface is not present (eth0 OK - injected) | 'eth0/rm -rf_mtu'=1500");
+    }
+
+    #[test]
+    fn checkmk_output_of_a_hostile_name_stays_on_one_line() {
+        let mut status = NagiosStatus::default();
+        status.critical.push(Finding::new(FindingId::InterfaceMissing, "eth0\n2 Fake - x", "Interface is not present".to_string()));
+        let out = CheckmkRenderer{ service_name: "Ethernet".to_string() }.render(&result(&status, 4096)).unwrap();
+        assert_eq!(out, "2 Ethernet - eth0 2 Fake - x: Interface is not present");
+    }
+
+    #[test]
+    fn long_first_line_loses_perfdata_first() {
+        let mut status = NagiosStatus::default();
+        status.critical.push(Finding::new(FindingId::LinkDown, "eth0", "Interface is DOWN".to_string()));
+        status.perfdata.push(PerfData::new("eth0_mtu", 1500.0, ""));
+        let out = PlainRenderer{}.render(&result(&status, 40)).unwrap();
+        assert_eq!(out, "CRITICAL - eth0: Interface is DOWN");
+        let out = PlainRenderer{}.render(&result(&status, 20)).unwrap();
+        assert_eq!(out, "CRITICAL - eth0: ...");
+    }
+
+    #[test]
+    fn truncate_at_a_character_boundary() {
+        assert_eq!(truncate("Grüße", 6), "Gr...");
+        assert_eq!(truncate("Grüße", 100), "Grüße");
+        assert_eq!(truncate("Grüße", 3), "...");
+        assert_eq!(truncate("Grüße", 2), "Gr");
+        assert_eq!(truncate("Grüße", 0), "");
+    }
+}
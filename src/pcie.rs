@@ -0,0 +1,59 @@
+// PCIe link of the device behind an interface from the current_link_* and max_link_*
+// files in sysfs. Virtual functions have no link of their own, the link of their
+// physical function is read instead.
+use super::interface::SysfsReader;
+
+#[derive(Serialize)]
+pub struct Link {
+    // GT/s
+    pub speed: f64,
+    pub width: u32,
+    // what the device and the slot support, None if not reported
+    pub max_speed: Option<f64>,
+    pub max_width: Option<u32>,
+}
+
+// "8.0 GT/s PCIe", older kernels print "8.0 GT/s" or "8 GT/s" and devices without
+// a link "Unknown"
+pub fn parse_speed(raw: &str) -> Option<f64> {
+    let mut fields = raw.split_whitespace();
+    let value = fields.next()?;
+    let value = match value.strip_suffix("GT/s") {
+        Some(v) => { v },
+        None => {
+            if fields.next() != Some("GT/s") {
+                return None;
+            }
+            value
+        },
+    };
+    match value.parse::<f64>() {
+        Ok(v) if v > 0.0 && v.is_finite() => { Some(v) },
+        _ => { None },
+    }
+}
+
+// "8", some tools and older kernels use "x8"
+pub fn parse_width(raw: &str) -> Option<u32> {
+    let raw = raw.trim();
+    match raw.strip_prefix('x').unwrap_or(raw).parse::<u32>() {
+        Ok(v) if v > 0 => { Some(v) },
+        _ => { None },
+    }
+}
+
+// device is the device directory of the interface, None for devices without a PCIe link
+pub fn read_link(sysfs: &dyn SysfsReader, device: &str) -> Option<Link> {
+    let device = if sysfs.read_link(&format!("{}/physfn", device)).is_ok() {
+        format!("{}/physfn", device)
+    } else {
+        device.to_string()
+    };
+    let read = |name: &str| sysfs.read_to_string(&format!("{}/{}", device, name)).ok();
+
+    let speed = parse_speed(&read("current_link_speed")?)?;
+    let width = parse_width(&read("current_link_width")?)?;
+    let max_speed = read("max_link_speed").and_then(|s| parse_speed(&s));
+    let max_width = read("max_link_width").and_then(|s| parse_width(&s));
+    Some(Link{ speed, width, max_speed, max_width })
+}
@@ -0,0 +1,89 @@
+// Evidence that the IPv4 subnets assigned to an interface have at least one other
+// host alive, from the neighbor cache or an optional, strictly bounded ARP sweep.
+use super::error::CheckError;
+use super::netlink;
+use super::probe;
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::{Duration, Instant};
+
+// per address of the ARP sweep
+const SWEEP_PROBE_TIMEOUT: u64 = 200;
+
+#[derive(Serialize)]
+pub struct SubnetPeers {
+    pub network: String,
+    // first peer found, None if there is no evidence of any peer
+    pub peer: Option<Ipv4Addr>,
+    pub method: Option<String>,
+}
+
+// neighbor cache entries confirming a peer was alive recently
+fn is_alive(state: u16) -> bool {
+    matches!(state, netlink::NUD_REACHABLE | netlink::NUD_STALE | netlink::NUD_DELAY | netlink::NUD_PROBE)
+}
+
+// host addresses of the network, the network and broadcast address are only usable on /31
+fn sweep_targets(net: &ipnetwork::Ipv4Network, limit: usize) -> Vec<Ipv4Addr> {
+    let prefix = net.prefix();
+    net.iter()
+        .filter(|a| prefix >= 31 || (*a != net.network() && *a != net.broadcast()))
+        .filter(|a| *a != net.ip())
+        .take(limit)
+        .collect()
+}
+
+// subnets with a /32 prefix can't have peers and are skipped, the ARP sweep stops at the timeout
+pub fn check(interface: &str, ifindex: u32, ips: &[ipnetwork::IpNetwork], probe_limit: usize, timeout: Duration) -> Result<Vec<SubnetPeers>, CheckError> {
+    let start = Instant::now();
+    let neighbors = match netlink::neighbors() {
+        Ok(v) => { v },
+        Err(e) => { return Err(CheckError::netlink("neighbor table", e)); },
+    };
+
+    let link_local: ipnetwork::Ipv4Network = "169.254.0.0/16".parse().unwrap();
+    let mut result = Vec::new();
+
+    for n in ips {
+        let net = match n {
+            ipnetwork::IpNetwork::V4(v) if v.prefix() < 32 && !link_local.contains(v.ip()) => { v },
+            _ => { continue; },
+        };
+
+        let cached = neighbors.iter().filter(|e| e.ifindex == ifindex && is_alive(e.state)).find_map(|e| {
+            match e.ip {
+                IpAddr::V4(a) if a != net.ip() && net.contains(a) => { Some((a, netlink::neighbor_state_name(e.state))) },
+                _ => { None },
+            }
+        });
+
+        let mut peers = SubnetPeers{ network: format!("{}/{}", net.network(), net.prefix()), peer: None, method: None };
+        match cached {
+            Some((a, state)) => {
+                peers.peer = Some(a);
+                peers.method = Some(format!("neighbor cache {}", state));
+            },
+            None => {
+                // the sweep stops at the first answer, probe errors (e.g. no raw socket
+                // permission) end the sweep for this subnet
+                for target in sweep_targets(net, probe_limit) {
+                    if start.elapsed() + Duration::from_millis(SWEEP_PROBE_TIMEOUT) > timeout {
+                        break;
+                    }
+                    match probe::arp(interface, net.ip(), target, Duration::from_millis(SWEEP_PROBE_TIMEOUT)) {
+                        Ok(Some(_)) => {
+                            peers.peer = Some(target);
+                            peers.method = Some("ARP sweep".to_string());
+                            break;
+                        },
+                        Ok(None) => {},
+                        Err(_) => { break; },
+                    };
+                }
+            },
+        };
+        result.push(peers);
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,541 @@
+// Resolved check plan for --dry-run: which interfaces are checked and why, which checks
+// run with which thresholds and what would be read, probed and written. Building the
+// plan only looks at the configuration and the selection of interfaces.
+use super::{interface, lldp};
+use super::config::{state_file_path, AddressFamily, Backend, Configuration, LockPolicy, QueueCount, DEFAULT_SYSFS_ROOT};
+use super::status::speed_threshold;
+use super::threshold::Threshold;
+use super::{STATE_CRITICAL, STATE_OK, STATE_UNKNOWN, STATE_WARNING};
+
+use std::fmt::Write;
+
+#[derive(Serialize)]
+pub struct Selection {
+    pub interface: String,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct Check {
+    pub name: String,
+    pub threshold: String,
+}
+
+#[derive(Serialize)]
+pub struct Probe {
+    pub name: String,
+    // seconds
+    pub timeout: f64,
+    pub methods: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+pub struct StateFile {
+    pub interface: String,
+    pub path: String,
+    pub lock: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct Plan {
+    pub interfaces: Vec<Selection>,
+    pub checks: Vec<Check>,
+    pub backend: &'static str,
+    pub sources: Vec<String>,
+    pub probes: Vec<Probe>,
+    pub state_files: Vec<StateFile>,
+    pub lock_policy: &'static str,
+    pub ack_file: Option<String>,
+    pub prometheus_textfile: Option<String>,
+    // seconds
+    pub timeout: u64,
+}
+
+fn severity(cfg: &Configuration) -> &'static str {
+    if cfg.report_critical { "critical" } else { "warning" }
+}
+
+fn add(checks: &mut Vec<Check>, name: &str, threshold: String) {
+    checks.push(Check{ name: name.to_string(), threshold });
+}
+
+fn checks(cfg: &Configuration) -> Vec<Check> {
+    let mut checks: Vec<Check> = Vec::new();
+
+    let unknown_state = match cfg.operstate_unknown {
+        STATE_OK => { "ok" },
+        STATE_WARNING => { "warning" },
+        STATE_CRITICAL => { "critical" },
+        _ => { "unknown" },
+    };
+    add(&mut checks, "link state", format!("critical if down or lowerlayerdown, warning if dormant, {} if unknown", unknown_state));
+    let missing_state = match cfg.missing_state {
+        STATE_OK => { "ok" },
+        STATE_WARNING => { "warning" },
+        STATE_UNKNOWN => { "unknown" },
+        _ => { "critical" },
+    };
+    add(&mut checks, "presence", format!("{} if the interface is missing, no other check runs then", missing_state));
+    if cfg.min_matches > 0 {
+        add(&mut checks, "interface matches", format!("critical if < {}", cfg.min_matches));
+    }
+
+    let check_speed = cfg.speed.is_some() || cfg.speed_warn.is_some() || cfg.speed_crit.is_some() || !cfg.allowed_speeds.is_empty();
+    if cfg.virtual_interface == Some(true) {
+        add(&mut checks, "speed and duplex", "not checked (--virtual)".to_string());
+    } else if check_speed || cfg.duplex.is_some() {
+        if check_speed {
+            let (t, _) = speed_threshold(cfg);
+            let hysteresis = if cfg.speed_warn.is_some() || cfg.speed_crit.is_some() { cfg.hysteresis } else { None };
+            add(&mut checks, "speed", with_hysteresis(&t, "MBit/s", hysteresis));
+        }
+        if let Some(ref duplex) = cfg.duplex {
+            add(&mut checks, "duplex", format!("{} if not {}", severity(cfg), duplex));
+        }
+        if cfg.virtual_interface.is_none() {
+            add(&mut checks, "speed and duplex", "not checked for virtual interfaces without a device directory".to_string());
+        }
+    }
+    if let Some(expected) = cfg.promisc {
+        add(&mut checks, "promiscuous mode", format!("{} if {}", severity(cfg), if expected { "off" } else { "on" }));
+    }
+    if cfg.flags_set != 0 || cfg.flags_clear != 0 {
+        let mut expected: Vec<String> = interface::flag_names(cfg.flags_set).iter().map(|f| f.to_string()).collect();
+        expected.extend(interface::flag_names(cfg.flags_clear).iter().map(|f| format!("not {}", f)));
+        add(&mut checks, "interface flags", format!("{} unless {}", severity(cfg), expected.join(", ")));
+    }
+    if let Some(expected) = cfg.autoneg {
+        add(&mut checks, "autoneg", format!("{} if not {}", severity(cfg), if expected { "on" } else { "off" }));
+    }
+    if let Some(ref fec) = cfg.fec {
+        add(&mut checks, "FEC", format!("{} if not {}", severity(cfg), fec));
+    }
+    if let Some(mtu) = cfg.mtu {
+        let t = Threshold::range(mtu, cfg.report_critical).to_string();
+        match cfg.effective_mtu_to {
+            Some(dst) => { add(&mut checks, "mtu", format!("{} (effective MTU towards {})", t, dst)); },
+            None => { add(&mut checks, "mtu", t); },
+        };
+    }
+    if cfg.mtu_consistency {
+        add(&mut checks, "mtu consistency", "warning if above the MTU of a lower device or not the MTU of the bond or bridge master".to_string());
+    }
+
+    for m in &cfg.address_minimums {
+        let state = if m.explicit { severity(cfg) } else { "critical" };
+        let ula = if cfg.ipv6_require_global && m.family != AddressFamily::Ipv4 { ", IPv6 ULA not counted" } else { "" };
+        add(&mut checks, &format!("{} addresses", m.family.as_str()), format!("{} if < {}{}", state, m.count, ula));
+    }
+    add(&mut checks, "duplicate address detection", format!("critical if failed, warning if tentative for more than {}s", cfg.tentative_grace.as_secs()));
+    for c in &cfg.address_counts {
+        let above = if cfg.report_critical { "critical" } else { "warning" };
+        add(&mut checks, &format!("number of {} addresses", c.family.as_str()), format!("critical if < {}, {} if > {}", c.count, above, c.count));
+    }
+    if !cfg.ignore_prefixes.is_empty() && (!cfg.address_minimums.is_empty() || !cfg.address_counts.is_empty()) {
+        let prefixes: Vec<String> = cfg.ignore_prefixes.iter().map(|p| p.to_string()).collect();
+        add(&mut checks, "ignored prefixes", format!("addresses inside {} don't count", prefixes.join(", ")));
+    }
+    if cfg.require_ipv6_link_local {
+        add(&mut checks, "IPv6 link local address", "critical if none is assigned".to_string());
+    }
+    if cfg.ipv6_require_stable {
+        add(&mut checks, "stable IPv6 address", "critical if only temporary addresses are assigned, unknown without netlink address flags".to_string());
+    }
+    if !cfg.expect_address.is_empty() {
+        let expected: Vec<String> = cfg.expect_address.iter().map(|(ip, prefix)| match prefix {
+            Some(p) => { format!("{}/{}", ip, p) },
+            None => { ip.to_string() },
+        }).collect();
+        add(&mut checks, "expected addresses", format!("critical if missing, warning on wrong prefix length: {}", expected.join(", ")));
+    }
+    if !cfg.expect_neighbors.is_empty() {
+        let expected: Vec<String> = cfg.expect_neighbors.iter().map(|(ip, mac)| match mac {
+            Some(m) => { format!("{} ({})", ip, m) },
+            None => { ip.to_string() },
+        }).collect();
+        add(&mut checks, "expected neighbors", format!("critical if missing, unresolved or with another MAC address: {}", expected.join(", ")));
+    }
+    if cfg.expect_default_route {
+        for family in &cfg.default_route_families {
+            let gateway = match cfg.default_gateway {
+                Some(gw) => { format!(", warning if the gateway isn't {}", gw) },
+                None => { String::new() },
+            };
+            add(&mut checks, &format!("{} default route", family.as_str()), format!("critical if it doesn't leave through the interface{}", gateway));
+        }
+    }
+    if cfg.expect_no_address {
+        let scope = if cfg.strict { "any address" } else { "any address except IPv6 link local" };
+        add(&mut checks, "no address", format!("critical if {} is assigned", scope));
+    }
+
+    if cfg.errors_warn.is_some() || cfg.errors_crit.is_some() {
+        add(&mut checks, "rx/tx error rate", with_hysteresis(&Threshold::above(cfg.errors_warn, cfg.errors_crit), "errors/s", cfg.hysteresis));
+    }
+    if cfg.drops_warn.is_some() || cfg.drops_crit.is_some() {
+        add(&mut checks, "rx/tx drop rate", with_hysteresis(&Threshold::above(cfg.drops_warn, cfg.drops_crit), "packets/s", cfg.hysteresis));
+    }
+    if cfg.collisions_warn.is_some() || cfg.collisions_crit.is_some() {
+        add(&mut checks, "collision rate", with_hysteresis(&Threshold::above(cfg.collisions_warn, cfg.collisions_crit), "collisions/s", cfg.hysteresis));
+    }
+    for stat in &cfg.stats {
+        add(&mut checks, &format!("{} rate", stat.name), with_hysteresis(&Threshold::above(stat.warn, stat.crit), "per second", cfg.hysteresis));
+    }
+    if cfg.flaps_warn.is_some() || cfg.flaps_crit.is_some() {
+        add(&mut checks, "link flaps", with_hysteresis(&Threshold::above(cfg.flaps_warn, cfg.flaps_crit), "changes/h", cfg.hysteresis));
+    }
+    if cfg.bandwidth_warn.is_some() || cfg.bandwidth_crit.is_some() {
+        let unit = match cfg.bandwidth_max {
+            Some(max) => { format!("percent of the link speed ({} MBit/s without link speed)", max) },
+            None => { "percent of the link speed".to_string() },
+        };
+        add(&mut checks, "rx/tx throughput", with_hysteresis(&Threshold::above(cfg.bandwidth_warn, cfg.bandwidth_crit), &unit, cfg.hysteresis));
+    }
+    if cfg.signal_warn.is_some() || cfg.signal_crit.is_some() {
+        add(&mut checks, "wireless signal", with_hysteresis(&Threshold::below(cfg.signal_warn, cfg.signal_crit), "dBm", cfg.hysteresis));
+    }
+    if !cfg.alert_on_change.is_empty() {
+        add(&mut checks, "changes", format!("warning if changed: {}", cfg.alert_on_change.join(", ")));
+    }
+
+    if let Some(n) = cfg.bond_min_slaves {
+        add(&mut checks, "bond slaves", format!("warning if < {} active, critical if none", n));
+    }
+    if let Some(ref mode) = cfg.bond_mode {
+        add(&mut checks, "bond mode", format!("{} if not {}", severity(cfg), mode));
+    }
+    if cfg.bond_slave_status {
+        add(&mut checks, "bond slave status", "warning for every slave not up or not in the active aggregator".to_string());
+    }
+    if cfg.check_lacp {
+        add(&mut checks, "lacp", "critical for every slave without synchronized partner, warning if the slaves are in different aggregators".to_string());
+    }
+    if let Some(n) = cfg.team_min_ports {
+        add(&mut checks, "team ports", format!("warning if < {} active, critical if none", n));
+    }
+    if let Some(ref runner) = cfg.team_runner {
+        add(&mut checks, "team runner", format!("{} if not {}", severity(cfg), runner));
+    }
+    if !cfg.bridge_ports.is_empty() {
+        let extra = if cfg.bridge_ports_min_match { "" } else { ", warning on additional ports" };
+        add(&mut checks, "bridge ports", format!("critical if missing: {}{}", cfg.bridge_ports.join(", "), extra));
+    }
+    if let Some(id) = cfg.vlan_id {
+        add(&mut checks, "vlan id", format!("critical if not {}", id));
+    }
+    if let Some(ref parent) = cfg.vlan_parent {
+        add(&mut checks, "vlan parent", format!("critical if not {}", parent));
+    }
+    if let Some(ref expected) = cfg.expect_vlan_set {
+        let ids: Vec<String> = expected.iter().map(|i| i.to_string()).collect();
+        add(&mut checks, "vlan set", format!("critical if missing: {}", ids.join(", ")));
+    }
+    if let Some(ref qdisc) = cfg.expect_qdisc {
+        add(&mut checks, "qdisc", format!("warning if not {}", qdisc));
+    }
+    if let Some(expected) = cfg.expect_runtime_pm {
+        add(&mut checks, "runtime pm", format!("warning if not {}", if expected { "on" } else { "off" }));
+    }
+    if let Some(ref driver) = cfg.driver {
+        add(&mut checks, "driver", format!("warning if not {}", driver));
+    }
+    if let Some(len) = cfg.txqueuelen {
+        add(&mut checks, "txqueuelen", format!("{} if not {}", severity(cfg), len));
+    }
+    if let Some(size) = cfg.ring_rx {
+        add(&mut checks, "rx ring", format!("{} if not {}", severity(cfg), size));
+    }
+    if let Some(size) = cfg.ring_tx {
+        add(&mut checks, "tx ring", format!("{} if not {}", severity(cfg), size));
+    }
+    for (name, on) in &cfg.features {
+        add(&mut checks, &format!("feature {}", name), format!("{} if not {}", severity(cfg), if *on { "on" } else { "off" }));
+    }
+    if !cfg.pause.is_empty() {
+        let expected: Vec<String> = cfg.pause.iter().map(|(s, on)| format!("{}={}", s, if *on { "on" } else { "off" })).collect();
+        add(&mut checks, "pause frames", format!("{} unless {}", severity(cfg), expected.join(",")));
+    }
+    if let Some(n) = cfg.queues_rx {
+        add(&mut checks, "rx queues", format!("warning if not {}", n));
+    }
+    if let Some(n) = cfg.queues_tx {
+        add(&mut checks, "tx queues", format!("warning if not {}", n));
+    }
+    match cfg.queues_combined {
+        Some(QueueCount::Exact(n)) => { add(&mut checks, "combined channels", format!("warning if not {}", n)); },
+        Some(QueueCount::OnlineCpus) => { add(&mut checks, "combined channels", "warning if not the number of online CPUs".to_string()); },
+        None => {},
+    };
+    if let Some(node) = cfg.numa_node {
+        add(&mut checks, "numa node", format!("{} if not {}", severity(cfg), node));
+    }
+    if let Some(vfs) = cfg.sriov_vfs {
+        add(&mut checks, "sriov vfs", format!("critical if not {}", vfs));
+    }
+    if let Some(speed) = cfg.pcie_speed {
+        add(&mut checks, "pcie speed", format!("{} if below {} GT/s", severity(cfg), speed));
+    }
+    if let Some(width) = cfg.pcie_width {
+        add(&mut checks, "pcie width", format!("{} if below x{}", severity(cfg), width));
+    }
+    if cfg.pcie_check_max {
+        add(&mut checks, "pcie link", format!("{} if below the maximum of device and slot", severity(cfg)));
+    }
+    if let Some(ref version) = cfg.firmware {
+        add(&mut checks, "firmware", format!("warning if not {}", version));
+    }
+    if let Some(ref version) = cfg.firmware_min {
+        add(&mut checks, "firmware", format!("warning if older than {}", version));
+    }
+    if let Some(ref master) = cfg.master {
+        add(&mut checks, "master", format!("critical if not {}", master));
+    }
+    if cfg.no_master {
+        add(&mut checks, "master", "critical if enslaved".to_string());
+    }
+    if let Some(ref name) = cfg.expect_altname {
+        add(&mut checks, "altname", format!("warning if {} is missing", name));
+    }
+    if cfg.check_napi_balance {
+        add(&mut checks, "napi balance", "warning if NAPI threads may run on CPUs not local to the device".to_string());
+    }
+    if cfg.check_wq_balance {
+        add(&mut checks, "workqueue balance", "warning if narrowed unbound workqueues may run on CPUs not local to the devices".to_string());
+    }
+    if cfg.cross_check_backends {
+        add(&mut checks, "backend consistency", "warning if sysfs and netlink disagree".to_string());
+    }
+    if cfg.check_first_hop {
+        add(&mut checks, "first hop", "critical if the default gateway is dead".to_string());
+    }
+    if cfg.probe_gateway {
+        let gateway = match cfg.probe_gateway_address {
+            Some(gw) => { gw.to_string() },
+            None => { "the default gateway".to_string() },
+        };
+        let slow = match cfg.probe_warn_ms {
+            Some(ms) => { format!(", warning if slower than {}ms", ms) },
+            None => { String::new() },
+        };
+        add(&mut checks, "gateway probe", format!("critical if {} doesn't answer{}", gateway, slow));
+    }
+    if let Some(provider) = cfg.uplink_check {
+        add(&mut checks, "uplink", format!("critical if the point-to-point peer in {} is dead", provider));
+    }
+    if cfg.probe_subnet_peers {
+        add(&mut checks, "subnet peers", "warning if no other host of an IPv4 subnet is alive".to_string());
+    }
+
+    checks
+}
+
+fn with_hysteresis(t: &Threshold, unit: &str, hysteresis: Option<f64>) -> String {
+    match hysteresis {
+        Some(h) => { format!("{} {}, hysteresis {}%", t, unit, h) },
+        None => { format!("{} {}", t, unit) },
+    }
+}
+
+fn sources(cfg: &Configuration) -> Vec<String> {
+    let mut sources: Vec<String> = Vec::new();
+
+    match cfg.backend {
+        Backend::Sysfs => { sources.push(format!("sysfs {}: operational state, MTU, speed, duplex and counters", cfg.sysfs_root)); },
+        Backend::Netlink => {
+            sources.push("netlink: operational state, MTU and addresses".to_string());
+            sources.push(format!("sysfs {}: speed, duplex and counters", cfg.sysfs_root));
+        },
+    };
+    // a different root is a copy of sysfs, the ethtool ioctl would ask the running kernel
+    if cfg.sysfs_root == DEFAULT_SYSFS_ROOT {
+        sources.push("ethtool ioctl: speed, duplex and auto-negotiation".to_string());
+    }
+    sources.push("/proc/net/wireless and nl80211: signal, link quality, bitrate and SSID of wireless interfaces".to_string());
+    if let Some(dst) = cfg.effective_mtu_to {
+        sources.push(format!("netlink: route MTU towards {}", dst));
+    }
+    if cfg.expect_qdisc.is_some() {
+        sources.push("netlink: qdiscs".to_string());
+    }
+    if cfg.cross_check_backends {
+        sources.push("netlink: link state for the comparison with sysfs".to_string());
+    }
+    if !cfg.expect_neighbors.is_empty() {
+        sources.push("netlink: neighbor table".to_string());
+    }
+    if cfg.expect_default_route {
+        sources.push("netlink: routing table, /proc/net/route and /proc/net/ipv6_route as fallback".to_string());
+    }
+    if cfg.by_address.is_some() {
+        sources.push("netlink: addresses of all interfaces".to_string());
+    }
+    if cfg.check_vlans_of.is_some() {
+        sources.push("/proc/net/vlan/config: VLANs of the parent device".to_string());
+    }
+    if cfg.fec.is_some() {
+        sources.push("ethtool ioctl: forward error correction parameters".to_string());
+    }
+    if cfg.ring_rx.is_some() || cfg.ring_tx.is_some() {
+        sources.push("ethtool ioctl: ring buffer sizes".to_string());
+    }
+    if !cfg.features.is_empty() {
+        sources.push("ethtool ioctl: offload features".to_string());
+    }
+    if !cfg.pause.is_empty() {
+        sources.push("ethtool ioctl: pause frame parameters".to_string());
+    }
+    if cfg.queues_rx.is_some() || cfg.queues_tx.is_some() {
+        sources.push(format!("sysfs {}/<if>/queues: RX and TX queues", cfg.sysfs_root));
+    }
+    if cfg.queues_combined.is_some() {
+        sources.push("ethtool ioctl: channel parameters".to_string());
+    }
+    if cfg.firmware.is_some() || cfg.firmware_min.is_some() {
+        sources.push("ethtool ioctl: driver and firmware version".to_string());
+    }
+    if cfg.expect_altname.is_some() {
+        sources.push("netlink: alternative names".to_string());
+    }
+    if cfg.team_runner.is_some() {
+        sources.push("teamd control socket: runner of team interfaces".to_string());
+    }
+    if cfg.bond_slave_status || cfg.check_lacp {
+        sources.push("/proc/net/bonding: MII status, link failures, aggregator and LACP state of the bond slaves".to_string());
+    }
+
+    sources
+}
+
+fn probes(cfg: &Configuration) -> Vec<Probe> {
+    let mut probes: Vec<Probe> = Vec::new();
+    let methods: Vec<&'static str> = cfg.first_hop_methods.iter().map(|m| m.as_str()).collect();
+
+    if cfg.check_first_hop {
+        probes.push(Probe{ name: "first hop check".to_string(), timeout: cfg.first_hop_timeout.as_secs_f64(), methods: methods.clone() });
+    }
+    if cfg.probe_gateway {
+        probes.push(Probe{ name: "gateway probe".to_string(), timeout: cfg.probe_timeout.as_secs_f64(), methods: vec!["arp", "ndp"] });
+    }
+    if cfg.uplink_check.is_some() {
+        probes.push(Probe{ name: "uplink check".to_string(), timeout: cfg.first_hop_timeout.as_secs_f64(), methods });
+    }
+    if cfg.probe_subnet_peers {
+        let (name, methods) = if cfg.peer_probe_limit > 0 {
+            (format!("subnet peer probe (ARP to up to {} addresses per subnet)", cfg.peer_probe_limit), vec!["neighbor", "arp"])
+        } else {
+            ("subnet peer probe".to_string(), vec!["neighbor"])
+        };
+        probes.push(Probe{ name, timeout: cfg.timeout.as_secs_f64(), methods });
+    }
+    if let Some(window) = cfg.frame_size_window {
+        probes.push(Probe{ name: "frame size sampling".to_string(), timeout: window.as_secs_f64(), methods: Vec::new() });
+    }
+    if cfg.enrich_from_lldp {
+        probes.push(Probe{ name: "LLDP capture of interfaces with problems".to_string(), timeout: (cfg.timeout.as_secs_f64() / 2.0).min(lldp::CAPTURE_TIMEOUT as f64), methods: Vec::new() });
+    }
+
+    probes
+}
+
+// selections which need interface data, --dry-run doesn't read it
+fn resolved_at_run_time(cfg: &Configuration) -> Vec<Selection> {
+    let mut selections = Vec::new();
+    if let Some(ref parent) = cfg.check_vlans_of {
+        selections.push(Selection{ interface: format!("VLANs of {}", parent), reasons: vec!["resolved at run time".to_string()] });
+    }
+    if let Some(ip) = cfg.by_address {
+        selections.push(Selection{ interface: format!("holders of {}", ip), reasons: vec!["resolved at run time".to_string()] });
+    }
+    selections
+}
+
+impl Plan {
+    pub fn new(cfg: &Configuration, mut interfaces: Vec<Selection>) -> Plan {
+        let uses_state = cfg.needs_lock() || cfg.enrich_from_lldp;
+        let state_files = if uses_state {
+            interfaces.iter().map(|s| {
+                let path = state_file_path(cfg, &s.interface);
+                let lock = if cfg.needs_lock() { Some(format!("{}.lock", path)) } else { None };
+                StateFile{ interface: s.interface.clone(), path, lock }
+            }).collect()
+        } else {
+            Vec::new()
+        };
+        interfaces.extend(resolved_at_run_time(cfg));
+
+        Plan{
+            checks: checks(cfg),
+            backend: match cfg.backend {
+                Backend::Sysfs => { "sysfs" },
+                Backend::Netlink => { "netlink" },
+            },
+            sources: sources(cfg),
+            probes: probes(cfg),
+            state_files,
+            lock_policy: match cfg.lock_policy {
+                LockPolicy::Wait => { "wait" },
+                LockPolicy::Fail => { "fail" },
+            },
+            ack_file: cfg.ack_file.clone(),
+            prometheus_textfile: cfg.prometheus_textfile.clone(),
+            timeout: cfg.timeout.as_secs(),
+            interfaces,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| format!("Can't encode check plan as JSON: {}", e))
+    }
+
+    pub fn to_plain(&self) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(out, "Interfaces:");
+        if self.interfaces.is_empty() {
+            let _ = writeln!(out, "    none");
+        }
+        for s in &self.interfaces {
+            let _ = writeln!(out, "    {}: {}", s.interface, s.reasons.join(", "));
+        }
+
+        let _ = writeln!(out, "Checks:");
+        for c in &self.checks {
+            let _ = writeln!(out, "    {}: {}", c.name, c.threshold);
+        }
+
+        let _ = writeln!(out, "Sources (backend {}):", self.backend);
+        for s in &self.sources {
+            let _ = writeln!(out, "    {}", s);
+        }
+
+        if !self.probes.is_empty() {
+            let _ = writeln!(out, "Probes:");
+            for p in &self.probes {
+                if p.methods.is_empty() {
+                    let _ = writeln!(out, "    {}, {}s", p.name, p.timeout);
+                } else {
+                    let _ = writeln!(out, "    {} via {}, {}s", p.name, p.methods.join(", "), p.timeout);
+                }
+            }
+        }
+
+        if !self.state_files.is_empty() {
+            let _ = writeln!(out, "State files (lock policy {}):", self.lock_policy);
+            for f in &self.state_files {
+                match f.lock {
+                    Some(ref lock) => { let _ = writeln!(out, "    {}: {} (lock {})", f.interface, f.path, lock); },
+                    None => { let _ = writeln!(out, "    {}: {}", f.interface, f.path); },
+                };
+            }
+        }
+        if let Some(ref path) = self.ack_file {
+            let _ = writeln!(out, "Acknowledgments: {}", path);
+        }
+        if let Some(ref path) = self.prometheus_textfile {
+            let _ = writeln!(out, "Prometheus textfile: {}", path);
+        }
+        let _ = write!(out, "Timeout: {}s", self.timeout);
+
+        out
+    }
+}
@@ -0,0 +1,154 @@
+// Active ARP (IPv4) and NDP (IPv6) probes to check if a neighbor answers on the link.
+use pnet::datalink;
+
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::{Duration, Instant};
+
+const ETHERTYPE_ARP: u16 = 0x0806;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+
+const ARP_REQUEST: u16 = 1;
+const ARP_REPLY: u16 = 2;
+
+const IPPROTO_ICMPV6: u8 = 58;
+const ICMPV6_NEIGHBOR_SOLICITATION: u8 = 135;
+const ICMPV6_NEIGHBOR_ADVERTISEMENT: u8 = 136;
+const NDP_OPTION_SOURCE_LLADDR: u8 = 1;
+
+struct Link {
+    mac: [u8; 6],
+    tx: Box<dyn datalink::DataLinkSender>,
+    rx: Box<dyn datalink::DataLinkReceiver>,
+}
+
+fn open(interface: &str) -> Result<Link, String> {
+    let iface = match datalink::interfaces().into_iter().find(|i| i.name == interface) {
+        Some(v) => { v },
+        None => { return Err(format!("Interface {} not found", interface)); },
+    };
+
+    let mac = match iface.mac {
+        Some(m) => { [m.0, m.1, m.2, m.3, m.4, m.5] },
+        None => { return Err(format!("Interface {} has no hardware address", interface)); },
+    };
+
+    let config = datalink::Config{ read_timeout: Some(Duration::from_millis(100)), ..Default::default() };
+    match datalink::channel(&iface, config) {
+        Ok(datalink::Channel::Ethernet(tx, rx)) => { Ok(Link{ mac, tx, rx }) },
+        Ok(_) => { Err("Unsupported channel type".to_string()) },
+        Err(ref e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            Err(format!("Can't open raw socket on {}, CAP_NET_RAW is required", interface))
+        },
+        Err(e) => { Err(e.to_string()) },
+    }
+}
+
+// send the frame and return the round trip time of the first frame accepted by the matcher
+fn exchange<F>(link: &mut Link, frame: &[u8], timeout: Duration, matcher: F) -> Result<Option<Duration>, String>
+    where F: Fn(&[u8]) -> bool {
+    let start = Instant::now();
+    match link.tx.send_to(frame, None) {
+        Some(Ok(_)) => {},
+        Some(Err(e)) => { return Err(e.to_string()); },
+        None => { return Err("Can't send probe".to_string()); },
+    };
+
+    while start.elapsed() < timeout {
+        // read timeouts are expected, just try again until the deadline is reached
+        if let Ok(frame) = link.rx.next() {
+            if matcher(frame) {
+                return Ok(Some(start.elapsed()));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+fn ethernet_header(dst: &[u8; 6], src: &[u8; 6], ethertype: u16) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(86);
+    frame.extend_from_slice(dst);
+    frame.extend_from_slice(src);
+    frame.extend_from_slice(&ethertype.to_be_bytes());
+    frame
+}
+
+fn ethertype(frame: &[u8]) -> u16 {
+    u16::from_be_bytes([frame[12], frame[13]])
+}
+
+// a source of 0.0.0.0 makes the request an ARP probe (RFC 5227), which doesn't
+// update the neighbor caches of other hosts
+pub fn arp(interface: &str, source: Ipv4Addr, target: Ipv4Addr, timeout: Duration) -> Result<Option<Duration>, String> {
+    let mut link = open(interface)?;
+
+    let mut frame = ethernet_header(&[0xff; 6], &link.mac, ETHERTYPE_ARP);
+    frame.extend_from_slice(&1u16.to_be_bytes());
+    frame.extend_from_slice(&0x0800u16.to_be_bytes());
+    frame.push(6);
+    frame.push(4);
+    frame.extend_from_slice(&ARP_REQUEST.to_be_bytes());
+    frame.extend_from_slice(&link.mac);
+    frame.extend_from_slice(&source.octets());
+    frame.extend_from_slice(&[0; 6]);
+    frame.extend_from_slice(&target.octets());
+
+    exchange(&mut link, &frame, timeout, |reply| {
+        reply.len() >= 42 && ethertype(reply) == ETHERTYPE_ARP
+            && u16::from_be_bytes([reply[20], reply[21]]) == ARP_REPLY && reply[28..32] == target.octets()
+    })
+}
+
+fn icmpv6_checksum(src: &Ipv6Addr, dst: &Ipv6Addr, message: &[u8]) -> u16 {
+    let mut pseudo = Vec::with_capacity(40 + message.len());
+    pseudo.extend_from_slice(&src.octets());
+    pseudo.extend_from_slice(&dst.octets());
+    pseudo.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    pseudo.extend_from_slice(&[0, 0, 0, IPPROTO_ICMPV6]);
+    pseudo.extend_from_slice(message);
+    if pseudo.len() % 2 == 1 {
+        pseudo.push(0);
+    }
+
+    let mut sum: u32 = 0;
+    for chunk in pseudo.chunks(2) {
+        sum += u16::from_be_bytes([chunk[0], chunk[1]]) as u32;
+    }
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+// neighbor solicitation sent to the solicited-node multicast address of the target,
+// the source should be the link-local address of the interface
+pub fn ndp(interface: &str, source: Ipv6Addr, target: Ipv6Addr, timeout: Duration) -> Result<Option<Duration>, String> {
+    let mut link = open(interface)?;
+
+    let t = target.octets();
+    let dst = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 1, 0xff00 | t[13] as u16, u16::from_be_bytes([t[14], t[15]]));
+    let dst_mac = [0x33, 0x33, 0xff, t[13], t[14], t[15]];
+
+    let mut icmp = vec![ICMPV6_NEIGHBOR_SOLICITATION, 0, 0, 0, 0, 0, 0, 0];
+    icmp.extend_from_slice(&t);
+    icmp.push(NDP_OPTION_SOURCE_LLADDR);
+    icmp.push(1);
+    icmp.extend_from_slice(&link.mac);
+    let checksum = icmpv6_checksum(&source, &dst, &icmp);
+    icmp[2..4].copy_from_slice(&checksum.to_be_bytes());
+
+    let mut frame = ethernet_header(&dst_mac, &link.mac, ETHERTYPE_IPV6);
+    frame.extend_from_slice(&[0x60, 0, 0, 0]);
+    frame.extend_from_slice(&(icmp.len() as u16).to_be_bytes());
+    frame.push(IPPROTO_ICMPV6);
+    frame.push(255);
+    frame.extend_from_slice(&source.octets());
+    frame.extend_from_slice(&dst.octets());
+    frame.extend_from_slice(&icmp);
+
+    exchange(&mut link, &frame, timeout, |reply| {
+        reply.len() >= 78 && ethertype(reply) == ETHERTYPE_IPV6 && reply[20] == IPPROTO_ICMPV6
+            && reply[54] == ICMPV6_NEIGHBOR_ADVERTISEMENT && reply[62..78] == t
+    })
+}
@@ -0,0 +1,125 @@
+// Default routes of the main routing table from rtnetlink, or from /proc/net/route
+// and /proc/net/ipv6_route if netlink can't be used.
+use super::error::CheckError;
+use super::netlink;
+
+use std::ffi::CStr;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+pub const SOURCE_NETLINK: &str = "netlink";
+pub const SOURCE_PROCFS: &str = "procfs";
+
+// flags of the routes in procfs
+const RTF_GATEWAY: u32 = 0x0002;
+const RTF_REJECT: u32 = 0x0200;
+
+#[derive(Serialize)]
+pub struct DefaultRoute {
+    // "ipv4" or "ipv6"
+    pub family: &'static str,
+    // None for routes directly into the link, e.g. on point-to-point interfaces
+    pub gateway: Option<IpAddr>,
+    // output interface
+    pub interface: String,
+}
+
+#[derive(Serialize)]
+pub struct DefaultRoutes {
+    pub routes: Vec<DefaultRoute>,
+    pub source: &'static str,
+}
+
+// the index if the interface is gone
+fn interface_name(ifindex: u32) -> String {
+    let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+    let name = unsafe { libc::if_indextoname(ifindex, buf.as_mut_ptr()) };
+    if name.is_null() {
+        return ifindex.to_string();
+    }
+    unsafe { CStr::from_ptr(name) }.to_string_lossy().to_string()
+}
+
+// "eth0    00000000    0102000A    0003    0    0    100    00000000    0    0    0"
+fn parse_proc_route(content: &str) -> Vec<DefaultRoute> {
+    let mut result: Vec<DefaultRoute> = Vec::new();
+    for line in content.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 8 || fields[1] != "00000000" || fields[7] != "00000000" {
+            continue;
+        }
+        let flags = match u32::from_str_radix(fields[3], 16) {
+            Ok(v) => { v },
+            Err(_) => { continue; },
+        };
+        if flags & RTF_REJECT != 0 {
+            continue;
+        }
+
+        // the address is printed as number in host byte order
+        let gateway = match u32::from_str_radix(fields[2], 16) {
+            Ok(v) if flags & RTF_GATEWAY != 0 => { Some(IpAddr::V4(Ipv4Addr::from(v.to_ne_bytes()))) },
+            _ => { None },
+        };
+        result.push(DefaultRoute{ family: "ipv4", gateway, interface: fields[0].to_string() });
+    }
+    result
+}
+
+// "00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000400 00000001 00000000 00000003 eth0"
+fn parse_proc_ipv6_route(content: &str) -> Vec<DefaultRoute> {
+    let mut result: Vec<DefaultRoute> = Vec::new();
+    for line in content.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 || !fields[0].trim_start_matches('0').is_empty() || fields[1] != "00" {
+            continue;
+        }
+        let flags = match u32::from_str_radix(fields[8], 16) {
+            Ok(v) => { v },
+            Err(_) => { continue; },
+        };
+        // the kernel lists its unreachable catch all route on lo
+        if flags & RTF_REJECT != 0 {
+            continue;
+        }
+
+        let gateway = match u128::from_str_radix(fields[4], 16) {
+            Ok(v) if flags & RTF_GATEWAY != 0 => { Some(IpAddr::V6(Ipv6Addr::from(v))) },
+            _ => { None },
+        };
+        result.push(DefaultRoute{ family: "ipv6", gateway, interface: fields[9].to_string() });
+    }
+    result
+}
+
+// the error of /proc/net/route if neither netlink nor procfs can be read
+pub fn default_routes() -> Result<DefaultRoutes, CheckError> {
+    if let Ok(routes) = netlink::routes() {
+        let routes = routes.iter().filter(|r| r.dst_len == 0).filter_map(|r| {
+            let family = match r.family as i32 {
+                libc::AF_INET => { "ipv4" },
+                libc::AF_INET6 => { "ipv6" },
+                _ => { return None; },
+            };
+            r.oif.map(|oif| DefaultRoute{ family, gateway: r.gateway, interface: interface_name(oif) })
+        }).collect();
+        return Ok(DefaultRoutes{ routes, source: SOURCE_NETLINK });
+    }
+
+    let ipv4 = fs::read_to_string("/proc/net/route");
+    let ipv6 = fs::read_to_string("/proc/net/ipv6_route");
+
+    let mut routes: Vec<DefaultRoute> = Vec::new();
+    match ipv4 {
+        Ok(content) => { routes.extend(parse_proc_route(&content)); },
+        Err(e) => {
+            if ipv6.is_err() {
+                return Err(CheckError::io("/proc/net/route", e));
+            }
+        },
+    };
+    if let Ok(content) = ipv6 {
+        routes.extend(parse_proc_ipv6_route(&content));
+    }
+    Ok(DefaultRoutes{ routes, source: SOURCE_PROCFS })
+}
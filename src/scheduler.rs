@@ -0,0 +1,123 @@
+// Runs active probes concurrently, each one with its own deadline, and collects the
+// results of all probes finishing before the global deadline.
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// time granted to a probe to deliver its result after its own deadline expired
+const GRACE_PERIOD: u64 = 250;
+
+pub struct Task<T> {
+    pub name: String,
+    // the probe should finish within the timeout it is called with
+    pub timeout: Duration,
+    pub run: Box<dyn FnOnce(Duration) -> T + Send>,
+}
+
+impl<T> Task<T> {
+    pub fn new<F>(name: String, timeout: Duration, run: F) -> Task<T> where F: FnOnce(Duration) -> T + Send + 'static {
+        Task{ name, timeout, run: Box::new(run) }
+    }
+}
+
+pub struct Outcome<T> {
+    // in the order the probes finished
+    pub completed: Vec<(String, T)>,
+    pub incomplete: Vec<String>,
+}
+
+// probes still running at the deadline are abandoned, their threads end with the process
+pub fn run<T: Send + 'static>(tasks: Vec<Task<T>>, deadline: Instant) -> Outcome<T> {
+    let (tx, rx) = mpsc::channel();
+    let mut pending: Vec<String> = Vec::new();
+    let mut wait_until = Instant::now();
+
+    for task in tasks {
+        // every probe gets its own timeout, limited by what is left of the global budget
+        let remaining = deadline.checked_duration_since(Instant::now()).unwrap_or_default();
+        let timeout = if task.timeout < remaining { task.timeout } else { remaining };
+        let until = Instant::now() + timeout + Duration::from_millis(GRACE_PERIOD);
+        if until > wait_until {
+            wait_until = until;
+        }
+
+        let tx = tx.clone();
+        let name = task.name.clone();
+        let run = task.run;
+        pending.push(task.name);
+        thread::spawn(move || {
+            let result = run(timeout);
+            let _ = tx.send((name, result));
+        });
+    }
+    drop(tx);
+
+    if wait_until > deadline {
+        wait_until = deadline;
+    }
+
+    let mut completed = Vec::new();
+    while !pending.is_empty() {
+        let wait = wait_until.checked_duration_since(Instant::now()).unwrap_or_default();
+        match rx.recv_timeout(wait) {
+            Ok((name, result)) => {
+                pending.retain(|n| *n != name);
+                completed.push((name, result));
+            },
+            Err(_) => { break; },
+        };
+    }
+
+    Outcome{ completed, incomplete: pending }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::status::{probe_timeout, FindingId};
+
+    fn sleeping(name: &str, sleep: Duration, timeout: Duration) -> Task<u64> {
+        Task::new(name.to_string(), timeout, move |_| {
+            thread::sleep(sleep);
+            sleep.as_millis() as u64
+        })
+    }
+
+    #[test]
+    fn slow_probe_does_not_lose_the_other_results() {
+        let start = Instant::now();
+        let deadline = start + Duration::from_millis(500);
+        let tasks = vec![
+            sleeping("fast", Duration::from_millis(10), Duration::from_secs(5)),
+            sleeping("slow", Duration::from_secs(5), Duration::from_secs(5)),
+            sleeping("medium", Duration::from_millis(100), Duration::from_secs(5)),
+        ];
+        let outcome = run(tasks, deadline);
+
+        // abandoned at the deadline instead of waiting for the slow probe
+        assert!(start.elapsed() < Duration::from_secs(2));
+        let completed: Vec<(&str, u64)> = outcome.completed.iter().map(|(n, v)| (n.as_str(), *v)).collect();
+        assert_eq!(completed, vec![("fast", 10), ("medium", 100)]);
+        assert_eq!(outcome.incomplete, vec!["slow".to_string()]);
+
+        let finding = probe_timeout("eth0", Duration::from_secs(10), &outcome.incomplete).unwrap();
+        assert_eq!(finding.id, FindingId::ProbeTimeout);
+        assert_eq!(finding.message, "Timeout after 10s, probes not completed: slow");
+    }
+
+    #[test]
+    fn probe_timeout_is_limited_by_the_deadline() {
+        let deadline = Instant::now() + Duration::from_millis(200);
+        let tasks = vec![Task::new("limited".to_string(), Duration::from_secs(30), |timeout| timeout)];
+        let outcome = run(tasks, deadline);
+        assert_eq!(outcome.completed.len(), 1);
+        assert!(outcome.completed[0].1 <= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn all_probes_completed() {
+        let outcome = run(vec![sleeping("fast", Duration::from_millis(1), Duration::from_secs(1))], Instant::now() + Duration::from_secs(1));
+        assert!(outcome.incomplete.is_empty());
+        assert!(probe_timeout("eth0", Duration::from_secs(10), &outcome.incomplete).is_none());
+    }
+}
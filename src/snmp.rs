@@ -0,0 +1,440 @@
+// Minimal SNMPv2c client to read IF-MIB and IP-MIB of devices the check can't run
+// on. Only GetRequest and GetBulkRequest are implemented, BER is encoded by hand.
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+pub const DEFAULT_PORT: u16 = 161;
+
+// IF-MIB
+pub const IF_DESCR: &[u32] = &[1, 3, 6, 1, 2, 1, 2, 2, 1, 2];
+pub const IF_MTU: &[u32] = &[1, 3, 6, 1, 2, 1, 2, 2, 1, 4];
+pub const IF_SPEED: &[u32] = &[1, 3, 6, 1, 2, 1, 2, 2, 1, 5];
+pub const IF_PHYS_ADDRESS: &[u32] = &[1, 3, 6, 1, 2, 1, 2, 2, 1, 6];
+pub const IF_OPER_STATUS: &[u32] = &[1, 3, 6, 1, 2, 1, 2, 2, 1, 8];
+pub const IF_NAME: &[u32] = &[1, 3, 6, 1, 2, 1, 31, 1, 1, 1, 1];
+pub const IF_HIGH_SPEED: &[u32] = &[1, 3, 6, 1, 2, 1, 31, 1, 1, 1, 15];
+
+// IP-MIB, ipAddressTable and the deprecated IPv4 only ipAddrTable of older agents
+const IP_ADDRESS_IF_INDEX: &[u32] = &[1, 3, 6, 1, 2, 1, 4, 34, 1, 3];
+const IP_ADDRESS_PREFIX: &[u32] = &[1, 3, 6, 1, 2, 1, 4, 34, 1, 5];
+const IP_AD_ENT_IF_INDEX: &[u32] = &[1, 3, 6, 1, 2, 1, 4, 20, 1, 2];
+const IP_AD_ENT_NET_MASK: &[u32] = &[1, 3, 6, 1, 2, 1, 4, 20, 1, 3];
+
+const VERSION_2C: i64 = 1;
+// per attempt, the check timeout limits the whole collection
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+const RETRIES: u32 = 2;
+const MAX_REPETITIONS: i64 = 25;
+// a broken agent returning the same rows over and over must not keep the check busy
+const MAX_WALK_ROWS: usize = 100_000;
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_NULL: u8 = 0x05;
+const TAG_OID: u8 = 0x06;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_IP_ADDRESS: u8 = 0x40;
+const TAG_COUNTER32: u8 = 0x41;
+const TAG_GAUGE32: u8 = 0x42;
+const TAG_TIMETICKS: u8 = 0x43;
+const TAG_COUNTER64: u8 = 0x46;
+const TAG_NO_SUCH_OBJECT: u8 = 0x80;
+const TAG_NO_SUCH_INSTANCE: u8 = 0x81;
+const TAG_END_OF_MIB_VIEW: u8 = 0x82;
+
+const PDU_GET: u8 = 0xa0;
+const PDU_RESPONSE: u8 = 0xa2;
+const PDU_GET_BULK: u8 = 0xa5;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    OctetString(Vec<u8>),
+    Oid(Vec<u32>),
+    IpAddress([u8; 4]),
+    // Counter32, Gauge32, TimeTicks and Counter64
+    Unsigned(u64),
+    Null,
+    // noSuchObject and noSuchInstance
+    NoSuchObject,
+    EndOfMibView,
+    Other(u8),
+}
+
+impl Value {
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Integer(v) if *v >= 0 => { Some(*v as u64) },
+            Value::Unsigned(v) => { Some(*v) },
+            _ => { None },
+        }
+    }
+
+    pub fn as_string(&self) -> Option<String> {
+        match self {
+            Value::OctetString(v) => { Some(String::from_utf8_lossy(v).trim_end_matches('\0').to_string()) },
+            _ => { None },
+        }
+    }
+}
+
+pub type VarBind = (Vec<u32>, Value);
+
+pub struct Session {
+    socket: UdpSocket,
+    community: String,
+    request_id: i32,
+}
+
+// host, host:port, IPv6 address or [IPv6 address]:port
+fn resolve(host: &str) -> io::Result<SocketAddr> {
+    let mut addrs = match host.to_socket_addrs() {
+        Ok(v) => { v },
+        Err(_) => { (host.trim_start_matches('[').trim_end_matches(']'), DEFAULT_PORT).to_socket_addrs()? },
+    };
+    match addrs.next() {
+        Some(v) => { Ok(v) },
+        None => { Err(io::Error::new(io::ErrorKind::NotFound, format!("{} has no address", host))) },
+    }
+}
+
+impl Session {
+    pub fn new(host: &str, community: &str) -> io::Result<Session> {
+        let addr = resolve(host)?;
+        let local = if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(addr)?;
+        socket.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        Ok(Session{ socket, community: community.to_string(), request_id: std::process::id() as i32 & 0x7fff_ffff })
+    }
+
+    pub fn get(&mut self, oids: &[Vec<u32>]) -> io::Result<Vec<VarBind>> {
+        self.request(PDU_GET, oids, 0, 0)
+    }
+
+    // all objects below root in OID order
+    pub fn walk(&mut self, root: &[u32]) -> io::Result<Vec<VarBind>> {
+        let mut result: Vec<VarBind> = Vec::new();
+        let mut last = root.to_vec();
+
+        loop {
+            let rows = self.request(PDU_GET_BULK, &[last.clone()], 0, MAX_REPETITIONS)?;
+            if rows.is_empty() {
+                return Ok(result);
+            }
+            for (oid, value) in rows {
+                if !oid.starts_with(root) || value == Value::EndOfMibView {
+                    return Ok(result);
+                }
+                if oid <= last {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "agent returned OIDs out of order"));
+                }
+                last = oid.clone();
+                result.push((oid, value));
+            }
+            if result.len() > MAX_WALK_ROWS {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "too many rows returned by agent"));
+            }
+        }
+    }
+
+    // for GetBulkRequest non_repeaters and max_repetitions take the place of error
+    // status and error index
+    fn request(&mut self, pdu_type: u8, oids: &[Vec<u32>], non_repeaters: i64, max_repetitions: i64) -> io::Result<Vec<VarBind>> {
+        self.request_id = (self.request_id + 1) & 0x7fff_ffff;
+
+        let mut bindings: Vec<u8> = Vec::new();
+        for oid in oids {
+            let mut binding = encode(TAG_OID, &encode_oid(oid));
+            binding.extend(encode(TAG_NULL, &[]));
+            bindings.extend(encode(TAG_SEQUENCE, &binding));
+        }
+        let mut pdu = encode(TAG_INTEGER, &encode_integer(self.request_id as i64));
+        pdu.extend(encode(TAG_INTEGER, &encode_integer(non_repeaters)));
+        pdu.extend(encode(TAG_INTEGER, &encode_integer(max_repetitions)));
+        pdu.extend(encode(TAG_SEQUENCE, &bindings));
+        let mut message = encode(TAG_INTEGER, &encode_integer(VERSION_2C));
+        message.extend(encode(TAG_OCTET_STRING, self.community.as_bytes()));
+        message.extend(encode(pdu_type, &pdu));
+        let message = encode(TAG_SEQUENCE, &message);
+
+        let mut buf = [0u8; 65535];
+        for _ in 0..=RETRIES {
+            self.socket.send(&message)?;
+            loop {
+                let len = match self.socket.recv(&mut buf) {
+                    Ok(v) => { v },
+                    Err(ref e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => { break; },
+                    Err(e) => { return Err(e); },
+                };
+                // late answers to a previous attempt are dropped
+                let (request_id, error_status, error_index, bindings) = decode_response(&buf[..len])?;
+                if request_id != self.request_id as i64 {
+                    continue;
+                }
+                if error_status != 0 {
+                    return Err(io::Error::other(format!("agent returned error status {} for variable {}", error_status, error_index)));
+                }
+                return Ok(bindings);
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::TimedOut, "no response from agent"))
+    }
+}
+
+fn encode(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut result = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        result.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len.to_be_bytes().iter().skip_while(|b| **b == 0).cloned().collect();
+        result.push(0x80 | bytes.len() as u8);
+        result.extend(bytes);
+    }
+    result.extend_from_slice(content);
+    result
+}
+
+// two's complement with the fewest bytes
+fn encode_integer(value: i64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let mut start = 0;
+    while start < 7 && ((bytes[start] == 0 && bytes[start + 1] & 0x80 == 0) || (bytes[start] == 0xff && bytes[start + 1] & 0x80 != 0)) {
+        start += 1;
+    }
+    bytes[start..].to_vec()
+}
+
+// the first two arcs share a byte, every arc is written in base 128
+fn encode_oid(oid: &[u32]) -> Vec<u8> {
+    let mut result = Vec::new();
+    if oid.len() < 2 {
+        return result;
+    }
+    let mut arcs = vec![oid[0] * 40 + oid[1]];
+    arcs.extend_from_slice(&oid[2..]);
+    for arc in arcs {
+        let mut chunk = vec![(arc & 0x7f) as u8];
+        let mut rest = arc >> 7;
+        while rest > 0 {
+            chunk.push((rest & 0x7f) as u8 | 0x80);
+            rest >>= 7;
+        }
+        chunk.reverse();
+        result.extend(chunk);
+    }
+    result
+}
+
+fn malformed() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed SNMP response")
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn at_end(&self) -> bool {
+        self.pos >= self.buf.len()
+    }
+
+    fn next(&mut self) -> io::Result<(u8, &'a [u8])> {
+        let tag = *self.buf.get(self.pos).ok_or_else(malformed)?;
+        let first = *self.buf.get(self.pos + 1).ok_or_else(malformed)? as usize;
+        self.pos += 2;
+        let len = if first < 0x80 {
+            first
+        } else {
+            let count = first & 0x7f;
+            if count == 0 || count > 4 || self.pos + count > self.buf.len() {
+                return Err(malformed());
+            }
+            let len = self.buf[self.pos..self.pos + count].iter().fold(0usize, |l, b| l << 8 | *b as usize);
+            self.pos += count;
+            len
+        };
+        if self.pos + len > self.buf.len() {
+            return Err(malformed());
+        }
+        let content = &self.buf[self.pos..self.pos + len];
+        self.pos += len;
+        Ok((tag, content))
+    }
+
+    fn expect(&mut self, tag: u8) -> io::Result<&'a [u8]> {
+        match self.next()? {
+            (t, content) if t == tag => { Ok(content) },
+            _ => { Err(malformed()) },
+        }
+    }
+}
+
+fn decode_integer(content: &[u8]) -> io::Result<i64> {
+    if content.is_empty() || content.len() > 8 {
+        return Err(malformed());
+    }
+    let initial: i64 = if content[0] & 0x80 != 0 { -1 } else { 0 };
+    Ok(content.iter().fold(initial, |v, b| v << 8 | *b as i64))
+}
+
+fn decode_unsigned(content: &[u8]) -> io::Result<u64> {
+    if content.len() > 9 {
+        return Err(malformed());
+    }
+    Ok(content.iter().fold(0u64, |v, b| v << 8 | *b as u64))
+}
+
+fn decode_oid(content: &[u8]) -> io::Result<Vec<u32>> {
+    let mut arcs: Vec<u32> = Vec::new();
+    let mut arc: u32 = 0;
+    for b in content {
+        arc = arc.checked_mul(128).ok_or_else(malformed)? | (b & 0x7f) as u32;
+        if b & 0x80 == 0 {
+            arcs.push(arc);
+            arc = 0;
+        }
+    }
+    let first = match arcs.first() {
+        Some(v) => { *v },
+        None => { return Ok(Vec::new()); },
+    };
+    let mut result = if first < 80 { vec![first / 40, first % 40] } else { vec![2, first - 80] };
+    result.extend_from_slice(&arcs[1..]);
+    Ok(result)
+}
+
+fn decode_value(tag: u8, content: &[u8]) -> io::Result<Value> {
+    Ok(match tag {
+        TAG_INTEGER => { Value::Integer(decode_integer(content)?) },
+        TAG_OCTET_STRING => { Value::OctetString(content.to_vec()) },
+        TAG_NULL => { Value::Null },
+        TAG_OID => { Value::Oid(decode_oid(content)?) },
+        TAG_IP_ADDRESS if content.len() == 4 => { Value::IpAddress([content[0], content[1], content[2], content[3]]) },
+        TAG_COUNTER32 | TAG_GAUGE32 | TAG_TIMETICKS | TAG_COUNTER64 => { Value::Unsigned(decode_unsigned(content)?) },
+        TAG_NO_SUCH_OBJECT | TAG_NO_SUCH_INSTANCE => { Value::NoSuchObject },
+        TAG_END_OF_MIB_VIEW => { Value::EndOfMibView },
+        t => { Value::Other(t) },
+    })
+}
+
+// request id, error status, error index and the variable bindings
+fn decode_response(buf: &[u8]) -> io::Result<(i64, i64, i64, Vec<VarBind>)> {
+    let mut message = Reader{ buf: Reader{ buf, pos: 0 }.expect(TAG_SEQUENCE)?, pos: 0 };
+    if decode_integer(message.expect(TAG_INTEGER)?)? != VERSION_2C {
+        return Err(malformed());
+    }
+    message.expect(TAG_OCTET_STRING)?;
+
+    let mut pdu = Reader{ buf: message.expect(PDU_RESPONSE)?, pos: 0 };
+    let request_id = decode_integer(pdu.expect(TAG_INTEGER)?)?;
+    let error_status = decode_integer(pdu.expect(TAG_INTEGER)?)?;
+    let error_index = decode_integer(pdu.expect(TAG_INTEGER)?)?;
+
+    let mut bindings: Vec<VarBind> = Vec::new();
+    let mut list = Reader{ buf: pdu.expect(TAG_SEQUENCE)?, pos: 0 };
+    while !list.at_end() {
+        let mut binding = Reader{ buf: list.expect(TAG_SEQUENCE)?, pos: 0 };
+        let oid = decode_oid(binding.expect(TAG_OID)?)?;
+        let (tag, content) = binding.next()?;
+        bindings.push((oid, decode_value(tag, content)?));
+    }
+    Ok((request_id, error_status, error_index, bindings))
+}
+
+// ifIndex of the interface with the given ifName or ifDescr, ifName is preferred as
+// ifDescr is often a description like "Intel Corporation 82574L Gigabit Network Connection"
+pub fn find_interface(session: &mut Session, name: &str) -> io::Result<Option<u32>> {
+    for column in &[IF_NAME, IF_DESCR] {
+        for (oid, value) in session.walk(column)? {
+            if value.as_string().as_deref() == Some(name) && oid.len() == column.len() + 1 {
+                return Ok(Some(oid[column.len()]));
+            }
+        }
+    }
+    Ok(None)
+}
+
+// "up", "lowerlayerdown", ... like the kernel reports the operational state
+pub fn oper_status_name(status: i64) -> &'static str {
+    match status {
+        1 => { "up" },
+        2 => { "down" },
+        3 => { "testing" },
+        5 => { "dormant" },
+        6 => { "notpresent" },
+        7 => { "lowerlayerdown" },
+        _ => { "unknown" },
+    }
+}
+
+// the address from the index of ipAddressTable: type, length and the address bytes,
+// zoned addresses (ipv4z, ipv6z) carry the zone index in four additional bytes
+fn indexed_address(index: &[u32]) -> Option<IpAddr> {
+    let bytes: Vec<u8> = index.iter().skip(2).map(|b| *b as u8).collect();
+    match (index.first(), index.get(1)) {
+        (Some(1), Some(4)) | (Some(3), Some(8)) if bytes.len() >= 4 => { Some(IpAddr::V4(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]))) },
+        (Some(2), Some(16)) | (Some(4), Some(20)) if bytes.len() >= 16 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&bytes[..16]);
+            Some(IpAddr::V6(Ipv6Addr::from(octets)))
+        },
+        _ => { None },
+    }
+}
+
+// addresses of the interface from ipAddressTable, from ipAddrTable if the agent
+// doesn't implement it
+pub fn interface_addresses(session: &mut Session, ifindex: u32) -> io::Result<Vec<ipnetwork::IpNetwork>> {
+    let mut result: Vec<ipnetwork::IpNetwork> = Vec::new();
+
+    let owners = session.walk(IP_ADDRESS_IF_INDEX)?;
+    if !owners.is_empty() {
+        // ipAddressPrefix points into ipAddressPrefixTable, the last arc of its index
+        // is the prefix length, zeroDotZero if unknown
+        let prefixes = session.walk(IP_ADDRESS_PREFIX)?;
+        for (oid, value) in owners {
+            if value.as_u64() != Some(ifindex as u64) {
+                continue;
+            }
+            let index = &oid[IP_ADDRESS_IF_INDEX.len()..];
+            let ip = match indexed_address(index) {
+                Some(v) => { v },
+                None => { continue; },
+            };
+            let prefix = prefixes.iter().find(|(p, _)| &p[IP_ADDRESS_PREFIX.len()..] == index).and_then(|(_, v)| {
+                match v {
+                    Value::Oid(o) if o.len() > 2 => { o.last().map(|l| *l as u8) },
+                    _ => { None },
+                }
+            });
+            let prefix = prefix.unwrap_or(if ip.is_ipv4() { 32 } else { 128 });
+            if let Ok(n) = ipnetwork::IpNetwork::new(ip, prefix) {
+                result.push(n);
+            }
+        }
+        return Ok(result);
+    }
+
+    let masks = session.walk(IP_AD_ENT_NET_MASK)?;
+    for (oid, value) in session.walk(IP_AD_ENT_IF_INDEX)? {
+        if value.as_u64() != Some(ifindex as u64) || oid.len() != IP_AD_ENT_IF_INDEX.len() + 4 {
+            continue;
+        }
+        let index = &oid[IP_AD_ENT_IF_INDEX.len()..];
+        let ip = Ipv4Addr::new(index[0] as u8, index[1] as u8, index[2] as u8, index[3] as u8);
+        let prefix = masks.iter().find(|(m, _)| &m[IP_AD_ENT_NET_MASK.len()..] == index).and_then(|(_, v)| {
+            match v {
+                Value::IpAddress(m) => { Some(u32::from_be_bytes(*m).count_ones() as u8) },
+                _ => { None },
+            }
+        });
+        if let Ok(n) = ipnetwork::Ipv4Network::new(ip, prefix.unwrap_or(32)) {
+            result.push(ipnetwork::IpNetwork::V4(n));
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,97 @@
+// Persistent key/value state kept between two runs of the check.
+// The file consists of simple "key=value" lines.
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+
+pub struct State {
+    path: String,
+    values: BTreeMap<String, String>,
+}
+
+impl State {
+    // a missing state file is not an error, it just means this is the first run
+    pub fn load(path: &str) -> io::Result<State> {
+        let mut values = BTreeMap::new();
+
+        let content = match fs::read_to_string(path) {
+            Ok(s) => { s },
+            Err(e) => {
+                if e.kind() == io::ErrorKind::NotFound {
+                    return Ok(State{ path: path.to_string(), values });
+                }
+                return Err(e);
+            },
+        };
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(pos) = line.find('=') {
+                values.insert(line[..pos].to_string(), line[pos + 1..].to_string());
+            }
+        }
+
+        Ok(State{ path: path.to_string(), values })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|v| v.as_str())
+    }
+
+    pub fn get_u64(&self, key: &str) -> Option<u64> {
+        match self.get(key) {
+            Some(v) => { v.parse().ok() },
+            None => { None },
+        }
+    }
+
+    // all values with a key starting with prefix, the prefix removed from the key
+    pub fn with_prefix(&self, prefix: &str) -> Vec<(&str, &str)> {
+        self.values.iter().filter_map(|(k, v)| k.strip_prefix(prefix).map(|k| (k, v.as_str()))).collect()
+    }
+
+    pub fn set<V: ToString>(&mut self, key: &str, value: V) {
+        // line based format, newlines would corrupt the file
+        let value = value.to_string().replace('\n', " ");
+        self.values.insert(key.to_string(), value);
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let mut content = String::new();
+        for (key, value) in &self.values {
+            content.push_str(&format!("{}={}\n", key, value));
+        }
+        write_atomic(&self.path, &content)
+    }
+}
+
+// write to a temporary file in the same directory and rename it, so a concurrent
+// reader never sees a partially written file
+pub fn write_atomic(path: &str, content: &str) -> io::Result<()> {
+    let tmp_path = format!("{}.tmp.{}", path, std::process::id());
+
+    if let Some(dir) = Path::new(path).parent() {
+        if !dir.as_os_str().is_empty() && !dir.exists() {
+            fs::create_dir_all(dir)?;
+        }
+    }
+
+    {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+    }
+
+    match fs::rename(&tmp_path, path) {
+        Ok(_) => { Ok(()) },
+        Err(e) => {
+            let _ = fs::remove_file(&tmp_path);
+            Err(e)
+        },
+    }
+}
@@ -0,0 +1,2751 @@
+// Findings and the resulting Nagios state, decided from the interface states and
+// the configuration alone.
+use super::{threshold, vlan};
+use super::{STATE_CRITICAL, STATE_OK, STATE_UNKNOWN, STATE_WARNING};
+use super::config::{AddressFamily, Configuration, QueueCount};
+use super::interface::{flag_names, format_cpu_list, InterfaceState, Workqueue, Workqueues, SOURCE_NETLINK, SOURCE_SNMP, SOURCE_SYSFS};
+use super::output::PerfData;
+use super::{ack, bonding, changes, counters, ethtool, firsthop, framesize, hysteresis, route, uplink, unix_now};
+
+use std::cmp::Ordering;
+use std::fs;
+use std::net::IpAddr;
+use std::ops::ControlFlow;
+use std::time::Duration;
+
+// Stable machine readable identifiers for every finding the check can report.
+// Monitoring pipelines route on these, so existing identifiers must never be renamed.
+// The variants, ALL and as_str are generated from the one list below.
+macro_rules! finding_ids {
+    ($($variant:ident => $id:expr,)*) => {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        pub enum FindingId {
+            $($variant,)*
+        }
+
+        impl FindingId {
+            // every identifier, listed by --capabilities
+            pub const ALL: &'static [FindingId] = &[$(FindingId::$variant,)*];
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(FindingId::$variant => $id,)*
+                }
+            }
+        }
+    };
+}
+
+finding_ids! {
+    InterfaceMissing => "interface.missing",
+    AttributeUnreadable => "interface.attribute_unreadable",
+    InterfaceVirtual => "interface.virtual",
+    WirelessOk => "wireless.ok",
+    WirelessSignalLow => "wireless.signal_low",
+    WirelessSignalOk => "wireless.signal_ok",
+    WirelessSignalUnavailable => "wireless.signal_unavailable",
+    WirelessNotWireless => "wireless.not_wireless",
+    InterfaceError => "interface.error",
+    InterfaceTooFewMatches => "interface.too_few_matches",
+    PrometheusWriteFailed => "output.prometheus_write_failed",
+    SysfsUnavailable => "sysfs.unavailable",
+    ProbeTimeout => "probe.timeout",
+    AckFileInvalid => "ack.invalid_file",
+    AckExpired => "ack.expired",
+    LinkDown => "link.down",
+    LinkUp => "link.up",
+    LinkStateUnknown => "link.state_unknown",
+    LinkLowerLayerDown => "link.lower_layer_down",
+    LinkDormant => "link.dormant",
+    CarrierNone => "carrier.none",
+    CarrierOk => "carrier.ok",
+    FlapsAboveThreshold => "carrier.flaps_above_threshold",
+    FlapsOk => "carrier.flaps_ok",
+    FlapsUnavailable => "carrier.flaps_unavailable",
+    BondSlavesDown => "bond.slaves_down",
+    BondSlavesOk => "bond.slaves_ok",
+    BondNotABond => "bond.not_a_bond",
+    BondModeMismatch => "bond.mode_mismatch",
+    BondModeOk => "bond.mode_ok",
+    BondSlaveDown => "bond.slave_down",
+    BondSlaveNotAggregated => "bond.slave_not_aggregated",
+    BondSlaveOk => "bond.slave_ok",
+    LacpPartnerNotInSync => "lacp.partner_not_in_sync",
+    LacpPartnerOk => "lacp.partner_ok",
+    LacpSplitAggregation => "lacp.split_aggregation",
+    LacpNotInUse => "lacp.not_in_use",
+    TeamPortsDown => "team.ports_down",
+    TeamPortsOk => "team.ports_ok",
+    TeamNotATeam => "team.not_a_team",
+    TeamRunnerMismatch => "team.runner_mismatch",
+    TeamRunnerOk => "team.runner_ok",
+    BridgePortMissing => "bridge.port_missing",
+    BridgePortUnexpected => "bridge.port_unexpected",
+    BridgePortsOk => "bridge.ports_ok",
+    BridgeNotABridge => "bridge.not_a_bridge",
+    VlanNotAVlan => "vlan.not_a_vlan",
+    VlanIdMismatch => "vlan.id_mismatch",
+    VlanParentMismatch => "vlan.parent_mismatch",
+    VlanParentDown => "vlan.parent_down",
+    VlanOk => "vlan.ok",
+    VlanSetMismatch => "vlan.set_mismatch",
+    VlanSetOk => "vlan.set_ok",
+    VlanOperstateMismatch => "vlan.operstate_mismatch",
+    VlanOperstateOk => "vlan.operstate_ok",
+    QdiscMismatch => "qdisc.mismatch",
+    QdiscQueueMismatch => "qdisc.queue_mismatch",
+    QdiscOk => "qdisc.ok",
+    RuntimePmMismatch => "runtime_pm.mismatch",
+    RuntimePmUnavailable => "runtime_pm.unavailable",
+    RuntimePmOk => "runtime_pm.ok",
+    DriverMismatch => "driver.mismatch",
+    DriverOk => "driver.ok",
+    SpeedAboveExpected => "speed.above_expected",
+    SpeedBelowExpected => "speed.below_expected",
+    SpeedExcluded => "speed.excluded",
+    SpeedNotAllowed => "speed.not_allowed",
+    SpeedOk => "speed.ok",
+    DuplexUnknown => "duplex.unknown",
+    DuplexMismatch => "duplex.mismatch",
+    DuplexOk => "duplex.ok",
+    AutonegMismatch => "autoneg.mismatch",
+    AutonegUnknown => "autoneg.unknown",
+    AutonegOk => "autoneg.ok",
+    FecMismatch => "fec.mismatch",
+    FecUnknown => "fec.unknown",
+    FecOk => "fec.ok",
+    PromiscMismatch => "promisc.mismatch",
+    PromiscOk => "promisc.ok",
+    PromiscUnknown => "promisc.unknown",
+    FlagsMismatch => "flags.mismatch",
+    FlagsOk => "flags.ok",
+    FlagsUnknown => "flags.unknown",
+    MtuMismatch => "mtu.mismatch",
+    MtuOk => "mtu.ok",
+    MtuAboveLower => "mtu.above_lower",
+    MtuDiffersFromMaster => "mtu.differs_from_master",
+    MtuConsistent => "mtu.consistent",
+    TxQueueLenMismatch => "txqueuelen.mismatch",
+    TxQueueLenOk => "txqueuelen.ok",
+    TxQueueLenUnknown => "txqueuelen.unknown",
+    RingSizeMismatch => "ring.mismatch",
+    RingSizeOk => "ring.ok",
+    RingSizeUnknown => "ring.unknown",
+    FeatureMismatch => "feature.mismatch",
+    FeatureOk => "feature.ok",
+    FeatureUnknown => "feature.unknown",
+    PauseMismatch => "pause.mismatch",
+    PauseOk => "pause.ok",
+    PauseUnknown => "pause.unknown",
+    QueuesMismatch => "queues.mismatch",
+    QueuesOk => "queues.ok",
+    QueuesUnknown => "queues.unknown",
+    NumaNodeMismatch => "numa.mismatch",
+    NumaNodeNoAffinity => "numa.no_affinity",
+    NumaNodeOk => "numa.ok",
+    NumaNodeUnknown => "numa.unknown",
+    SriovVfsMismatch => "sriov.vfs_mismatch",
+    SriovVfsOk => "sriov.vfs_ok",
+    SriovUnsupported => "sriov.unsupported",
+    PcieLinkBelowExpected => "pcie.below_expected",
+    PcieLinkBelowMax => "pcie.below_max",
+    PcieLinkOk => "pcie.ok",
+    PcieLinkUnknown => "pcie.unknown",
+    FirmwareMismatch => "firmware.mismatch",
+    FirmwareTooOld => "firmware.too_old",
+    FirmwareOk => "firmware.ok",
+    FirmwareUnknown => "firmware.unknown",
+    MasterMismatch => "master.mismatch",
+    MasterMissing => "master.missing",
+    MasterOk => "master.ok",
+    MasterUnexpected => "master.unexpected",
+    AltnameMissing => "altname.missing",
+    AltnameOk => "altname.ok",
+    AltnameUnknown => "altname.unknown",
+    AddrNone => "addr.none",
+    AddrLinkLocalOnly => "addr.link_local_only",
+    AddrUlaOnly => "addr.ula_only",
+    AddrDadFailed => "addr.dad_failed",
+    AddrTentative => "addr.tentative",
+    AddrNoStable => "addr.no_stable",
+    AddrStableOk => "addr.stable_ok",
+    AddrFlagsUnavailable => "addr.flags_unavailable",
+    AddrNoLinkLocal => "addr.no_link_local",
+    AddrLinkLocalOk => "addr.link_local_ok",
+    AddrCountBelow => "addr.count_below",
+    AddrCountAbove => "addr.count_above",
+    AddrCountOk => "addr.count_ok",
+    AddrBelowMinimum => "addr.below_minimum",
+    AddrOk => "addr.ok",
+    AddrUnexpected => "addr.unexpected",
+    AddrAbsent => "addr.absent",
+    AddrLoopbackMissing => "addr.loopback_missing",
+    AddrLoopbackOk => "addr.loopback_ok",
+    AddrExpectedMissing => "addr.expected_missing",
+    AddrPrefixMismatch => "addr.prefix_mismatch",
+    AddrExpectedOk => "addr.expected_ok",
+    NeighborMissing => "neighbor.missing",
+    NeighborUnresolved => "neighbor.unresolved",
+    NeighborMacMismatch => "neighbor.mac_mismatch",
+    NeighborOk => "neighbor.ok",
+    RouteDefaultMissing => "route.default_missing",
+    RouteGatewayMismatch => "route.gateway_mismatch",
+    RouteDefaultOk => "route.default_ok",
+    RouteUnavailable => "route.unavailable",
+    AddrNotAssigned => "addr.not_assigned",
+    AddrMultipleHolders => "addr.multiple_holders",
+    AddrHolder => "addr.holder",
+    ErrorsAboveThreshold => "errors.above_threshold",
+    ErrorsOk => "errors.ok",
+    DropsAboveThreshold => "drops.above_threshold",
+    DropsOk => "drops.ok",
+    CollisionsAboveThreshold => "collisions.above_threshold",
+    CollisionsOk => "collisions.ok",
+    StatAboveThreshold => "stat.above_threshold",
+    StatOk => "stat.ok",
+    StatUnknown => "stat.unknown",
+    BandwidthAboveThreshold => "bandwidth.above_threshold",
+    BandwidthOk => "bandwidth.ok",
+    BandwidthNoSpeed => "bandwidth.no_speed",
+    CounterBaseline => "counter.baseline",
+    NapiRemoteCpu => "napi.remote_cpu",
+    NapiLocal => "napi.local",
+    NapiNotThreaded => "napi.not_threaded",
+    NapiNoLocality => "napi.no_locality",
+    WqRemoteCpu => "wq.remote_cpu",
+    WqLocal => "wq.local",
+    WqDefault => "wq.default",
+    WqNoLocality => "wq.no_locality",
+    WqUnavailable => "wq.unavailable",
+    SubnetPeer => "subnet.peer",
+    SubnetNoPeer => "subnet.no_peer",
+    FramesNoJumbo => "frames.no_jumbo",
+    FramesJumbo => "frames.jumbo",
+    FramesNoTraffic => "frames.no_traffic",
+    FirstHopAlive => "first_hop.alive",
+    FirstHopStale => "first_hop.stale",
+    FirstHopDead => "first_hop.dead",
+    FirstHopUnknown => "first_hop.unknown",
+    FirstHopNoGateway => "first_hop.no_gateway",
+    GatewayAlive => "gateway.alive",
+    GatewaySlow => "gateway.slow",
+    GatewayDead => "gateway.dead",
+    GatewayUnknown => "gateway.unknown",
+    GatewayNone => "gateway.none",
+    UplinkNoAddress => "uplink.no_address",
+    UplinkAmbiguous => "uplink.ambiguous",
+    UplinkNotPointToPoint => "uplink.not_point_to_point",
+    UplinkPeerStale => "uplink.peer_stale",
+    UplinkPeerDead => "uplink.peer_dead",
+    UplinkPeerUnknown => "uplink.peer_unknown",
+    UplinkNoDefaultRoute => "uplink.no_default_route",
+    UplinkOk => "uplink.ok",
+    MetricChanged => "change.detected",
+    MetricUnchanged => "change.none",
+    BackendMismatch => "backend.mismatch",
+    BackendAgree => "backend.agree",
+    BackendUnavailable => "backend.unavailable",
+}
+
+impl serde::Serialize for FindingId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[derive(Serialize)]
+pub struct Finding {
+    pub id: FindingId,
+    pub interface: String,
+    pub message: String,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+impl Finding {
+    pub fn new(id: FindingId, interface: &str, message: String) -> Finding {
+        Finding{ id, interface: interface.to_string(), message, expected: None, actual: None }
+    }
+
+    pub fn with_values<E: ToString, A: ToString>(id: FindingId, interface: &str, message: String, expected: E, actual: A) -> Finding {
+        Finding{ id, interface: interface.to_string(), message, expected: Some(expected.to_string()), actual: Some(actual.to_string()) }
+    }
+}
+
+#[derive(Default, Serialize)]
+pub struct NagiosStatus {
+    pub critical: Vec<Finding>,
+    pub warning: Vec<Finding>,
+    pub ok: Vec<Finding>,
+    pub unknown: Vec<Finding>,
+    // additional context, never changes the state
+    pub notes: Vec<String>,
+    pub perfdata: Vec<PerfData>,
+    // every threshold comparison made, only shown with --verbose
+    #[serde(skip)]
+    pub audit: Vec<threshold::Comparison>,
+    // explanations of what was left out, only shown with --verbose
+    #[serde(skip)]
+    pub details: Vec<String>,
+}
+
+impl NagiosStatus {
+    pub fn new(cfg: &Configuration, interface: &str, ifs: &InterfaceState) -> NagiosStatus {
+        let mut status = NagiosStatus::default();
+        // a stopping check leaves the findings collected so far
+        let _ = status.check_all(cfg, interface, ifs);
+        status
+    }
+
+    // run the checks in order, some of them make further checks meaningless
+    fn check_all(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) -> ControlFlow<()> {
+        self.check_presence(cfg, interface, ifs)?;
+        self.check_bond_slaves(cfg, interface, ifs)?;
+        self.check_bond_mode(cfg, interface, ifs);
+        self.check_team_ports(cfg, interface, ifs)?;
+        self.check_team_runner(cfg, interface, ifs);
+        self.check_bond_slave_status(cfg, interface, ifs);
+        self.check_lacp(cfg, interface, ifs);
+        self.check_bridge_ports(cfg, interface, ifs);
+        self.check_vlan(cfg, interface, ifs);
+        self.check_qdisc(cfg, interface, ifs);
+        self.check_runtime_pm(cfg, interface, ifs);
+        self.check_driver(cfg, interface, ifs);
+        self.check_link_state(cfg, interface, ifs)?;
+        self.check_link_type(cfg, interface, ifs);
+        self.check_signal(cfg, interface, ifs);
+        self.check_speed(cfg, interface, ifs);
+        self.check_duplex(cfg, interface, ifs);
+        self.check_autoneg(cfg, interface, ifs);
+        self.check_fec(cfg, interface, ifs);
+        self.check_flags(cfg, interface, ifs);
+        self.check_mtu(cfg, interface, ifs);
+        self.check_mtu_consistency(cfg, interface, ifs);
+        self.check_txqueuelen(cfg, interface, ifs);
+        self.check_rings(cfg, interface, ifs);
+        self.check_features(cfg, interface, ifs);
+        self.check_pause(cfg, interface, ifs);
+        self.check_queues(cfg, interface, ifs);
+        self.check_numa(cfg, interface, ifs);
+        self.check_sriov(cfg, interface, ifs);
+        self.check_pcie(cfg, interface, ifs);
+        self.check_firmware(cfg, interface, ifs);
+        self.check_master(cfg, interface, ifs);
+        self.check_altname(cfg, interface, ifs);
+        self.check_loopback_addresses(cfg, interface, ifs);
+        self.check_address_state(cfg, interface, ifs);
+        self.check_address_assignment(cfg, interface, ifs);
+        self.check_no_address(cfg, interface, ifs);
+        self.check_expected_addresses(cfg, interface, ifs);
+        self.check_neighbors(cfg, interface, ifs);
+        self.check_default_route(cfg, interface, ifs);
+        self.check_counter_rates(cfg, interface, ifs);
+        self.check_collisions(cfg, interface, ifs);
+        self.check_flaps(cfg, interface, ifs);
+        self.check_bandwidth(cfg, interface, ifs);
+        self.check_subnet_peers(cfg, interface, ifs);
+        self.check_first_hop(cfg, interface, ifs);
+        self.check_gateway_probe(cfg, interface, ifs);
+        self.check_uplink(cfg, interface, ifs);
+        self.check_frame_sizes(interface, ifs);
+        self.check_changes(cfg, interface, ifs);
+        self.check_backends(cfg, interface, ifs);
+        self.check_napi_balance(cfg, interface, ifs);
+        ControlFlow::Continue(())
+    }
+
+    fn check_presence(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) -> ControlFlow<()> {
+        if !ifs.present {
+            let missing = Finding::new(FindingId::InterfaceMissing, interface, "Interface is not present".to_string());
+            match cfg.missing_state {
+                STATE_OK => { self.ok.push(missing); },
+                STATE_WARNING => { self.warning.push(missing); },
+                STATE_UNKNOWN => { self.unknown.push(missing); },
+                _ => { self.critical.push(missing); },
+            };
+            // no need to check futher parameters, whatever the state
+            return ControlFlow::Break(());
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn check_bond_slaves(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) -> ControlFlow<()> {
+        // check number of active bond slaves, before the link state because a bond
+        // without active slaves is down
+        if let Some(min_slaves) = cfg.bond_min_slaves {
+            match ifs.bond_slaves {
+                Some(ref slaves) => {
+                    let active = slaves.iter().filter(|s| s.up).count();
+                    let mut messages: Vec<String> = slaves.iter().filter(|s| !s.up).map(|s| format!("slave {} is down", s.name)).collect();
+                    messages.push(format!("{}/{} slaves active", active, slaves.len()));
+
+                    let mut perf = PerfData::new(&format!("{}_bond_slaves_active", interface), active as f64, "");
+                    perf.warn = Some(format!("{}:", min_slaves));
+                    perf.crit = Some("1:".to_string());
+                    perf.min = Some(0.0);
+                    perf.max = Some(slaves.len() as f64);
+                    self.perfdata.push(perf);
+
+                    if active == 0 {
+                        self.critical.push(Finding::with_values(FindingId::BondSlavesDown, interface, messages.join(", "), min_slaves, active));
+                        // the bond itself is down too, the slaves are the more useful information
+                        return ControlFlow::Break(());
+                    } else if active < min_slaves {
+                        self.warning.push(Finding::with_values(FindingId::BondSlavesDown, interface, messages.join(", "), min_slaves, active));
+                    } else {
+                        self.ok.push(Finding::with_values(FindingId::BondSlavesOk, interface, messages.join(", "), min_slaves, active));
+                    }
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::BondNotABond, interface, "Interface is not a bond, --bond-min-slaves can't be checked".to_string()));
+                },
+            };
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn check_bond_mode(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // check bonding mode
+        if let Some(ref expected) = cfg.bond_mode {
+            match ifs.bond_mode {
+                Some(ref mode) => {
+                    if mode == expected {
+                        self.ok.push(Finding::with_values(FindingId::BondModeOk, interface, format!("Bonding mode is {}", mode), expected, mode));
+                    } else if cfg.report_critical {
+                        self.critical.push(Finding::with_values(FindingId::BondModeMismatch, interface, format!("Bonding mode is {} instead of {}", mode, expected), expected, mode));
+                    } else {
+                        self.warning.push(Finding::with_values(FindingId::BondModeMismatch, interface, format!("Bonding mode is {} instead of {}", mode, expected), expected, mode));
+                    }
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::BondNotABond, interface, "Interface is not a bond, --bond-mode can't be checked".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_team_ports(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) -> ControlFlow<()> {
+        // check number of active team ports, like the bond slaves
+        if let Some(min_ports) = cfg.team_min_ports {
+            match ifs.team_ports {
+                Some(ref ports) => {
+                    let active = ports.iter().filter(|p| p.up).count();
+                    let mut messages: Vec<String> = ports.iter().filter(|p| !p.up).map(|p| format!("port {} is down", p.name)).collect();
+                    messages.push(format!("{}/{} ports active", active, ports.len()));
+
+                    let mut perf = PerfData::new(&format!("{}_team_ports_active", interface), active as f64, "");
+                    perf.warn = Some(format!("{}:", min_ports));
+                    perf.crit = Some("1:".to_string());
+                    perf.min = Some(0.0);
+                    perf.max = Some(ports.len() as f64);
+                    self.perfdata.push(perf);
+
+                    if active == 0 {
+                        self.critical.push(Finding::with_values(FindingId::TeamPortsDown, interface, messages.join(", "), min_ports, active));
+                        // the team itself is down too, the ports are the more useful information
+                        return ControlFlow::Break(());
+                    } else if active < min_ports {
+                        self.warning.push(Finding::with_values(FindingId::TeamPortsDown, interface, messages.join(", "), min_ports, active));
+                    } else {
+                        self.ok.push(Finding::with_values(FindingId::TeamPortsOk, interface, messages.join(", "), min_ports, active));
+                    }
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::TeamNotATeam, interface, "Interface is not a team, --team-min-ports can't be checked".to_string()));
+                },
+            };
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn check_team_runner(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // check team runner
+        if let Some(ref expected) = cfg.team_runner {
+            match ifs.team_runner {
+                Some(ref runner) => {
+                    if runner == expected {
+                        self.ok.push(Finding::with_values(FindingId::TeamRunnerOk, interface, format!("Team runner is {}", runner), expected, runner));
+                    } else if cfg.report_critical {
+                        self.critical.push(Finding::with_values(FindingId::TeamRunnerMismatch, interface, format!("Team runner is {} instead of {}", runner, expected), expected, runner));
+                    } else {
+                        self.warning.push(Finding::with_values(FindingId::TeamRunnerMismatch, interface, format!("Team runner is {} instead of {}", runner, expected), expected, runner));
+                    }
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::TeamNotATeam, interface, "Interface is not a team, --team-runner can't be checked".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_bond_slave_status(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // one finding per slave, a slave which is down is a cable to check even if the bond
+        // has enough active slaves left
+        if cfg.bond_slave_status {
+            match ifs.bond_slave_status {
+                Some(ref slaves) => {
+                    for slave in slaves {
+                        let mut message = format!("slave {}: MII status {}, {} link failures", slave.name, slave.mii_status, slave.link_failures);
+                        match slave.new_link_failures {
+                            Some(n) if n > 0 => { message = format!("{} ({} since last check)", message, n); },
+                            _ => {},
+                        };
+                        match (slave.active_aggregator, slave.aggregator_id) {
+                            (Some(true), Some(id)) => { message = format!("{}, attached to active aggregator {}", message, id); },
+                            (Some(false), Some(id)) => { message = format!("{}, aggregator {} is not the active aggregator", message, id); },
+                            _ => {},
+                        };
+
+                        if slave.mii_status != "up" {
+                            self.warning.push(Finding::with_values(FindingId::BondSlaveDown, interface, message, "up", &slave.mii_status));
+                        } else if slave.active_aggregator == Some(false) {
+                            self.warning.push(Finding::new(FindingId::BondSlaveNotAggregated, interface, message));
+                        } else {
+                            self.ok.push(Finding::with_values(FindingId::BondSlaveOk, interface, message, "up", &slave.mii_status));
+                        }
+                    }
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::BondNotABond, interface, "Interface is not a bond, --bond-slave-status can't be checked".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_lacp(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // all slaves up doesn't mean the switch completed LACP, without a synchronized partner
+        // the traffic hashed to a slave is lost
+        if cfg.check_lacp {
+            match ifs.bond_slave_status {
+                Some(ref slaves) if slaves.iter().any(|s| s.partner_port_state.is_some()) => {
+                    for slave in slaves {
+                        let partner = slave.partner_system.clone().unwrap_or_else(|| "unknown".to_string());
+                        match (slave.partner_in_sync(), slave.partner_port_state) {
+                            (Some(true), Some(state)) => {
+                                self.ok.push(Finding::with_values(FindingId::LacpPartnerOk, interface, format!("slave {}: LACP partner {} is synchronized, collecting and distributing", slave.name, partner), "synchronization,collecting,distributing", format!("{:#04x}", state)));
+                            },
+                            (_, state) => {
+                                let flags = match state {
+                                    Some(s) if s != 0 => { format!("{:#04x} ({})", s, bonding::lacp_state_names(s).join(", ")) },
+                                    Some(s) => { format!("{:#04x}", s) },
+                                    None => { "unknown".to_string() },
+                                };
+                                self.critical.push(Finding::with_values(FindingId::LacpPartnerNotInSync, interface, format!("slave {}: LACP partner {} is not synchronized, port state {}", slave.name, partner, flags), "synchronization,collecting,distributing", state.map(|s| format!("{:#04x}", s)).unwrap_or_default()));
+                            },
+                        };
+                    }
+
+                    let mut aggregators: Vec<u32> = slaves.iter().filter_map(|s| s.aggregator_id).collect();
+                    aggregators.sort_unstable();
+                    aggregators.dedup();
+                    if aggregators.len() > 1 {
+                        let members: Vec<String> = slaves.iter().map(|s| format!("{} in {}", s.name, s.aggregator_id.map(|id| id.to_string()).unwrap_or_else(|| "none".to_string()))).collect();
+                        self.warning.push(Finding::new(FindingId::LacpSplitAggregation, interface, format!("Slaves are in {} different aggregators: {}", aggregators.len(), members.join(", "))));
+                    }
+                },
+                Some(_) => {
+                    self.unknown.push(Finding::new(FindingId::LacpNotInUse, interface, "Bond is not in 802.3ad mode, --check-lacp can't be checked".to_string()));
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::BondNotABond, interface, "Interface is not a bond, --check-lacp can't be checked".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_bridge_ports(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // check bridge ports, a bridge without ports is down too
+        if !cfg.bridge_ports.is_empty() {
+            match ifs.bridge_ports {
+                Some(ref ports) => {
+                    let missing: Vec<String> = cfg.bridge_ports.iter().filter(|p| !ports.contains(p)).cloned().collect();
+                    let extra: Vec<String> = ports.iter().filter(|p| !cfg.bridge_ports.contains(p)).cloned().collect();
+
+                    if !missing.is_empty() {
+                        self.critical.push(Finding::with_values(FindingId::BridgePortMissing, interface, format!("Bridge port(s) missing: {}", missing.join(", ")), cfg.bridge_ports.join(","), ports.join(",")));
+                    }
+                    if !extra.is_empty() && !cfg.bridge_ports_min_match {
+                        self.warning.push(Finding::with_values(FindingId::BridgePortUnexpected, interface, format!("Unexpected bridge port(s): {}", extra.join(", ")), cfg.bridge_ports.join(","), ports.join(",")));
+                    }
+                    if missing.is_empty() {
+                        self.ok.push(Finding::with_values(FindingId::BridgePortsOk, interface, format!("Bridge ports present: {}", cfg.bridge_ports.join(", ")), cfg.bridge_ports.join(","), ports.join(",")));
+                    }
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::BridgeNotABridge, interface, "Interface is not a bridge, --bridge-ports can't be checked".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_vlan(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // traffic of a VLAN with the wrong id or lower device lands in the wrong segment
+        if cfg.vlan_id.is_some() || cfg.vlan_parent.is_some() {
+            match ifs.vlan {
+                Some(ref v) => {
+                    let mut mismatch = false;
+                    if let Some(id) = cfg.vlan_id {
+                        if v.id != id {
+                            self.critical.push(Finding::with_values(FindingId::VlanIdMismatch, interface, format!("VLAN id is {} instead of {}", v.id, id), id, v.id));
+                            mismatch = true;
+                        }
+                    }
+                    if let Some(ref parent) = cfg.vlan_parent {
+                        if v.parent != *parent {
+                            self.critical.push(Finding::with_values(FindingId::VlanParentMismatch, interface, format!("VLAN lower device is {} instead of {}", v.parent, parent), parent, &v.parent));
+                            mismatch = true;
+                        }
+                    }
+
+                    let parent_operstate = ifs.vlan_parent_operstate.clone().unwrap_or_else(|| "unknown".to_string());
+                    if parent_operstate != "up" {
+                        self.critical.push(Finding::with_values(FindingId::VlanParentDown, interface, format!("VLAN lower device {} is {}", v.parent, parent_operstate), "up", &parent_operstate));
+                    } else if !mismatch {
+                        self.ok.push(Finding::new(FindingId::VlanOk, interface, format!("VLAN {} on {}", v.id, v.parent)));
+                    }
+                },
+                None => {
+                    self.critical.push(Finding::new(FindingId::VlanNotAVlan, interface, "Interface is not a VLAN interface".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_qdisc(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // check the queueing discipline, multi queue interfaces are checked per transmit queue
+        if let (Some(expected), Some(qdisc)) = (&cfg.expect_qdisc, &ifs.qdisc) {
+            if qdisc.root == "mq" && expected != "mq" {
+                let mismatches: Vec<String> = qdisc.queues.iter().enumerate()
+                    .filter(|(_, kind)| *kind != expected)
+                    .map(|(queue, kind)| format!("queue {} uses {}", queue, kind))
+                    .collect();
+                if mismatches.is_empty() {
+                    self.ok.push(Finding::with_values(FindingId::QdiscOk, interface, format!("All {} transmit queues use qdisc {} (root qdisc mq)", qdisc.queues.len(), expected), expected, expected));
+                } else {
+                    self.warning.push(Finding::with_values(FindingId::QdiscQueueMismatch, interface, format!("{} of {} transmit queues don't use qdisc {} (root qdisc mq): {}", mismatches.len(), qdisc.queues.len(), expected, mismatches.join(", ")), expected, mismatches.join(",")));
+                }
+            } else if qdisc.root != *expected {
+                self.warning.push(Finding::with_values(FindingId::QdiscMismatch, interface, format!("Root qdisc is {} instead of {}", qdisc.root, expected), expected, &qdisc.root));
+            } else {
+                self.ok.push(Finding::with_values(FindingId::QdiscOk, interface, format!("Root qdisc is {}", qdisc.root), expected, &qdisc.root));
+            }
+        }
+    }
+
+    fn check_runtime_pm(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        if let Some(expected) = cfg.expect_runtime_pm {
+            let expected_state = if expected { "on" } else { "off" };
+            match ifs.runtime_pm {
+                Some(ref pm) if pm.enabled() != expected => {
+                    let actual = if pm.enabled() { "on" } else { "off" };
+                    self.warning.push(Finding::with_values(FindingId::RuntimePmMismatch, interface, format!("Runtime power management is {} instead of {} (control {}, status {})", actual, expected_state, pm.control, pm.runtime_status), expected_state, actual));
+                },
+                Some(ref pm) => {
+                    self.ok.push(Finding::with_values(FindingId::RuntimePmOk, interface, format!("Runtime power management is {} (status {})", expected_state, pm.runtime_status), expected_state, expected_state));
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::RuntimePmUnavailable, interface, "Device has no runtime power management".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_driver(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // a NIC falling back to a generic driver after a kernel upgrade still works, only slower
+        if let Some(ref expected) = cfg.driver {
+            match ifs.driver {
+                Some(ref actual) if actual == expected => {
+                    self.ok.push(Finding::with_values(FindingId::DriverOk, interface, format!("Driver is {}", actual), expected, actual));
+                },
+                Some(ref actual) => {
+                    self.warning.push(Finding::with_values(FindingId::DriverMismatch, interface, format!("Driver is {} instead of {}", actual, expected), expected, actual));
+                },
+                None => {
+                    self.warning.push(Finding::with_values(FindingId::DriverMismatch, interface, format!("No driver (virtual interface) instead of {}", expected), expected, "none"));
+                },
+            };
+        }
+    }
+
+    fn check_link_state(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) -> ControlFlow<()> {
+        // administratively up but no physical link
+        if ifs.carrier == Some(false) {
+            self.critical.push(Finding::with_values(FindingId::CarrierNone, interface, "No carrier detected".to_string(), 1, 0));
+            // no need to check futher parameters
+            return ControlFlow::Break(());
+        }
+
+        if let Some(err) = ifs.read_errors.get("operstate") {
+            self.unknown.push(Finding::new(FindingId::AttributeUnreadable, interface, format!("Interface exists, but its operational state could not be read: {}", err)));
+            // no need to check futher parameters
+            return ControlFlow::Break(());
+        }
+
+        if ifs.operstate == "down" {
+            self.critical.push(Finding::with_values(FindingId::LinkDown, interface, "Interface is DOWN".to_string(), "up", &ifs.operstate));
+            // no need to check futher parameters
+            return ControlFlow::Break(());
+        }
+
+        // e.g. a VLAN whose parent lost its link
+        if ifs.operstate == "lowerlayerdown" {
+            self.critical.push(Finding::with_values(FindingId::LinkLowerLayerDown, interface, "Interface is LOWERLAYERDOWN, the device it is stacked on has no link".to_string(), "up", &ifs.operstate));
+            // no need to check futher parameters
+            return ControlFlow::Break(());
+        }
+
+        // waiting for an external event, e.g. 802.1X authentication of the port
+        if ifs.operstate == "dormant" {
+            self.warning.push(Finding::with_values(FindingId::LinkDormant, interface, "Interface is dormant, waiting for an external event like 802.1X authentication".to_string(), "up", &ifs.operstate));
+            // no need to check futher parameters
+            return ControlFlow::Break(());
+        }
+
+        // the loopback interface has no lower layer to report a state, "unknown" is normal
+        let loopback_up = ifs.loopback && ifs.operstate == "unknown";
+
+        // many virtual drivers never set an operational state
+        if ifs.operstate == "unknown" && !loopback_up && cfg.operstate_unknown != STATE_UNKNOWN {
+            let finding = Finding::with_values(FindingId::LinkStateUnknown, interface, "Interface state is unknown".to_string(), "up", &ifs.operstate);
+            match cfg.operstate_unknown {
+                STATE_CRITICAL => { self.critical.push(finding); },
+                STATE_WARNING => { self.warning.push(finding); },
+                _ => { self.ok.push(finding); },
+            };
+        }
+
+        if ifs.operstate == "up" || loopback_up || (ifs.operstate == "unknown" && cfg.operstate_unknown != STATE_UNKNOWN) {
+            if ifs.operstate == "up" || loopback_up {
+                self.ok.push(Finding::new(FindingId::LinkUp, interface, "Interface is up".to_string()));
+            }
+
+            if let Some(changes) = ifs.carrier_changes {
+                let mut counts = vec![format!("{} carrier changes", changes)];
+                for (name, value) in &[("carrier_changes", Some(changes)), ("carrier_up_count", ifs.carrier_up_count), ("carrier_down_count", ifs.carrier_down_count)] {
+                    if let Some(v) = value {
+                        let mut perf = PerfData::new(&format!("{}_{}", interface, name), *v as f64, "c");
+                        perf.min = Some(0.0);
+                        self.perfdata.push(perf);
+                    }
+                }
+                if let (Some(up), Some(down)) = (ifs.carrier_up_count, ifs.carrier_down_count) {
+                    counts.push(format!("{} up", up));
+                    counts.push(format!("{} down", down));
+                }
+                self.ok.push(Finding::new(FindingId::CarrierOk, interface, format!("Carrier detected ({})", counts.join(", "))));
+            }
+        } else {
+            // notpresent, testing and unknown without --operstate-unknown-is
+            self.unknown.push(Finding::with_values(FindingId::LinkStateUnknown, interface, format!("Interface is {}", ifs.operstate), "up", &ifs.operstate));
+            // no need to check futher parameters
+            return ControlFlow::Break(());
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn check_link_type(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // virtual devices have no speed and duplex to check, say so unless configured explicitly
+        if cfg.virtual_interface.is_none() && ifs.virtual_device && !ifs.loopback && ifs.wireless.is_none() {
+            self.ok.push(Finding::new(FindingId::InterfaceVirtual, interface, "Virtual interface, speed/duplex not checked".to_string()));
+        }
+
+        // the association replaces speed and duplex of wireless devices
+        if let Some(ref w) = ifs.wireless {
+            let mut message = match w.ssid {
+                Some(ref ssid) => { format!("Wireless interface associated with SSID {}", ssid) },
+                None => { "Wireless interface not associated".to_string() },
+            };
+            let mut parts: Vec<String> = Vec::new();
+            if let Some(signal) = w.signal {
+                parts.push(format!("signal {} dBm", signal));
+            }
+            if let Some(quality) = w.link_quality {
+                parts.push(format!("link quality {}", quality));
+            }
+            if let Some(bitrate) = w.bitrate {
+                parts.push(format!("bitrate {} MBit/s", bitrate));
+            }
+            if !parts.is_empty() {
+                message = format!("{} ({})", message, parts.join(", "));
+            }
+            self.ok.push(Finding::new(FindingId::WirelessOk, interface, format!("{}, speed/duplex not checked", message)));
+
+            if let Some(bitrate) = w.bitrate {
+                let mut perf = PerfData::new(&format!("{}_bitrate", interface), bitrate, "");
+                perf.min = Some(0.0);
+                self.perfdata.push(perf);
+            }
+        }
+    }
+
+    fn check_signal(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // check the signal level, dBm are negative and lower is worse
+        if cfg.signal_warn.is_some() || cfg.signal_crit.is_some() {
+            match ifs.wireless.as_ref().map(|w| w.signal) {
+                Some(Some(signal)) => {
+                    let mut perf = PerfData::new(&format!("{}_signal", interface), signal, "");
+                    perf.warn = cfg.signal_warn.map(|v| format!("{}:", v));
+                    perf.crit = cfg.signal_crit.map(|v| format!("{}:", v));
+                    self.perfdata.push(perf);
+
+                    let cmp = evaluate(cfg, ifs, &threshold::Threshold::below(cfg.signal_warn, cfg.signal_crit), "signal", signal);
+                    let message = match cmp.state {
+                        STATE_CRITICAL if cmp.held => { format!("Signal level {} dBm back above {} dBm but within hysteresis band", signal, format_threshold(cfg.signal_crit).unwrap_or_default()) },
+                        STATE_WARNING if cmp.held => { format!("Signal level {} dBm back above {} dBm but within hysteresis band", signal, format_threshold(cfg.signal_warn).unwrap_or_default()) },
+                        STATE_CRITICAL => { format!("Signal level {} dBm is below critical threshold ({} dBm)", signal, format_threshold(cfg.signal_crit).unwrap_or_default()) },
+                        STATE_WARNING => { format!("Signal level {} dBm is below warning threshold ({} dBm)", signal, format_threshold(cfg.signal_warn).unwrap_or_default()) },
+                        _ => { format!("Signal level is {} dBm", signal) },
+                    };
+                    let state = cmp.state;
+                    self.audit.push(cmp);
+                    match state {
+                        STATE_CRITICAL => { self.critical.push(Finding::with_values(FindingId::WirelessSignalLow, interface, message, format_threshold(cfg.signal_crit).unwrap_or_default(), signal)); },
+                        STATE_WARNING => { self.warning.push(Finding::with_values(FindingId::WirelessSignalLow, interface, message, format_threshold(cfg.signal_warn).unwrap_or_default(), signal)); },
+                        _ => { self.ok.push(Finding::new(FindingId::WirelessSignalOk, interface, message)); },
+                    };
+                },
+                Some(None) => {
+                    self.unknown.push(Finding::new(FindingId::WirelessSignalUnavailable, interface, "Can't read the signal level, the interface is not associated or the driver doesn't report it".to_string()));
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::WirelessNotWireless, interface, "Not a wireless interface, can't check the signal level".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_speed(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // check negotiated interface speed and duplex mode, netlink doesn't report them and
+        // IF-MIB only the speed
+        let speed_thresholds = cfg.speed_warn.is_some() || cfg.speed_crit.is_some();
+        if (cfg.speed.is_some() || speed_thresholds || !cfg.allowed_speeds.is_empty()) && ifs.source != SOURCE_NETLINK && !virtual_interface(cfg, ifs) {
+            if let Some(err) = ifs.read_errors.get("speed") {
+                self.unknown.push(Finding::new(FindingId::AttributeUnreadable, interface, unreadable(ifs, "speed", err)));
+            } else {
+                let (speed_threshold, expected) = speed_threshold(cfg);
+                let cmp = if speed_thresholds {
+                    evaluate(cfg, ifs, &speed_threshold, "speed", ifs.speed as f64)
+                } else {
+                    speed_threshold.evaluate(interface, "speed", ifs.speed as f64)
+                };
+                let requested = match cfg.speed {
+                    Some(threshold::Range{ start: Some(start), end: None, inside: false }) => {
+                        format!("minimal interface speed ({})", format_speed(start))
+                    },
+                    _ => { format!("requested interface speed ({} MBit/s)", expected) },
+                };
+                match (cfg.speed, cmp.state) {
+                    (_, STATE_OK) if !cfg.allowed_speeds.is_empty() => {
+                        self.ok.push(Finding::with_values(FindingId::SpeedOk, interface, format!("Negotiated interface speed matches allowed interface speed of {}", format_speed(ifs.speed as f64)), expected, ifs.speed));
+                    },
+                    (_, STATE_OK) => {
+                        self.ok.push(Finding::with_values(FindingId::SpeedOk, interface, format!("Negotiated interface speed is {}", format_speed(ifs.speed as f64)), expected, ifs.speed));
+                    },
+                    (_, state) if !cfg.allowed_speeds.is_empty() => {
+                        let msg = format!("Negotiated interface speed ({}) is none of the allowed interface speeds ({})", format_speed(ifs.speed as f64), cfg.allowed_speeds.iter().map(|v| format_speed(*v as f64)).collect::<Vec<String>>().join(", "));
+                        if state == STATE_CRITICAL {
+                            self.critical.push(Finding::with_values(FindingId::SpeedNotAllowed, interface, msg, expected, ifs.speed));
+                        } else {
+                            self.warning.push(Finding::with_values(FindingId::SpeedNotAllowed, interface, msg, expected, ifs.speed));
+                        }
+                    },
+                    (_, state) if cmp.held => {
+                        let (level, limit) = if state == STATE_CRITICAL { ("critical", cfg.speed_crit) } else { ("warning", cfg.speed_warn) };
+                        let msg = format!("Negotiated interface speed ({}) is back above {} threshold ({}) but within hysteresis band", format_speed(ifs.speed as f64), level, format_speed(limit.unwrap_or_default() as f64));
+                        if state == STATE_CRITICAL {
+                            self.critical.push(Finding::with_values(FindingId::SpeedBelowExpected, interface, msg, expected, ifs.speed));
+                        } else {
+                            self.warning.push(Finding::with_values(FindingId::SpeedBelowExpected, interface, msg, expected, ifs.speed));
+                        }
+                    },
+                    (_, STATE_CRITICAL) if speed_thresholds => {
+                        self.critical.push(Finding::with_values(FindingId::SpeedBelowExpected, interface, format!("Negotiated interface speed ({}) is below critical threshold ({})", format_speed(ifs.speed as f64), format_speed(cfg.speed_crit.unwrap_or_default() as f64)), expected, ifs.speed));
+                    },
+                    (_, _) if speed_thresholds => {
+                        self.warning.push(Finding::with_values(FindingId::SpeedBelowExpected, interface, format!("Negotiated interface speed ({}) is below warning threshold ({})", format_speed(ifs.speed as f64), format_speed(cfg.speed_warn.unwrap_or_default() as f64)), expected, ifs.speed));
+                    },
+                    (Some(speed), state) if speed.inside => {
+                        let msg = format!("Negotiated interface speed ({}) is inside of excluded interface speeds ({} MBit/s)", format_speed(ifs.speed as f64), speed.interval());
+                        if state == STATE_CRITICAL {
+                            self.critical.push(Finding::with_values(FindingId::SpeedExcluded, interface, msg, expected, ifs.speed));
+                        } else {
+                            self.warning.push(Finding::with_values(FindingId::SpeedExcluded, interface, msg, expected, ifs.speed));
+                        }
+                    },
+                    (Some(speed), STATE_WARNING) if speed.end.map(|e| ifs.speed as f64 > e).unwrap_or(false) => {
+                        self.warning.push(Finding::with_values(FindingId::SpeedAboveExpected, interface, format!("Negotiated interface speed ({}) is greater than requested interface speed ({} MBit/s)", format_speed(ifs.speed as f64), speed), expected, ifs.speed));
+                    },
+                    (_, STATE_CRITICAL) => {
+                        self.critical.push(Finding::with_values(FindingId::SpeedBelowExpected, interface, format!("Negotiated interface speed ({}) is below {}", format_speed(ifs.speed as f64), requested), expected, ifs.speed));
+                    },
+                    (_, _) => {
+                        self.warning.push(Finding::with_values(FindingId::SpeedBelowExpected, interface, format!("Negotiated interface speed ({}) is below {}", format_speed(ifs.speed as f64), requested), expected, ifs.speed));
+                    },
+                };
+                self.audit.push(cmp);
+            }
+        }
+    }
+
+    fn check_duplex(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // check negotiated duplex mode independent of the speed, netlink and IF-MIB don't
+        // report it
+        if let Some(ref duplex) = cfg.duplex {
+            if ifs.source != SOURCE_NETLINK && ifs.source != SOURCE_SNMP && !virtual_interface(cfg, ifs) {
+                if let Some(err) = ifs.read_errors.get("duplex") {
+                    self.unknown.push(Finding::new(FindingId::AttributeUnreadable, interface, unreadable(ifs, "duplex", err)));
+                } else if ifs.duplex != "half" && ifs.duplex != "full" {
+                    self.unknown.push(Finding::with_values(FindingId::DuplexUnknown, interface, format!("Unknown duplex mode {}", ifs.duplex), duplex, &ifs.duplex));
+                } else if &ifs.duplex != duplex {
+                    if cfg.report_critical {
+                        self.critical.push(Finding::with_values(FindingId::DuplexMismatch, interface, format!("Negotiated duplex mode is {} instead of {}", ifs.duplex, duplex), duplex, &ifs.duplex));
+                    } else {
+                        self.warning.push(Finding::with_values(FindingId::DuplexMismatch, interface, format!("Negotiated duplex mode is {} instead of {}", ifs.duplex, duplex), duplex, &ifs.duplex));
+                    }
+                } else {
+                    self.ok.push(Finding::with_values(FindingId::DuplexOk, interface, format!("Negotiated duplex mode is {}", ifs.duplex), duplex, &ifs.duplex));
+                }
+            }
+        }
+    }
+
+    fn check_autoneg(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // a link mode forced on one side only ends up in a duplex mismatch sooner or later
+        if let Some(expected) = cfg.autoneg {
+            let on_off = |v: bool| if v { "on" } else { "off" };
+            match ifs.autoneg {
+                Some(actual) if actual != expected => {
+                    let msg = format!("Auto-negotiation is {} (expected {})", on_off(actual), on_off(expected));
+                    if cfg.report_critical {
+                        self.critical.push(Finding::with_values(FindingId::AutonegMismatch, interface, msg, on_off(expected), on_off(actual)));
+                    } else {
+                        self.warning.push(Finding::with_values(FindingId::AutonegMismatch, interface, msg, on_off(expected), on_off(actual)));
+                    }
+                },
+                Some(actual) => {
+                    self.ok.push(Finding::with_values(FindingId::AutonegOk, interface, format!("Auto-negotiation is {}", on_off(actual)), on_off(expected), on_off(actual)));
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::AutonegUnknown, interface, "Can't read auto-negotiation state, the interface doesn't support the ethtool ioctl".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_fec(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // a FEC mode differing from the link partner shows up as sporadic CRC errors
+        if let Some(ref expected) = cfg.fec {
+            match ifs.fec {
+                Some(ref fec) => {
+                    // auto is a configuration, the active mode is whatever was negotiated
+                    let (actual, matches) = if expected == "auto" {
+                        (fec.configured.join(","), fec.configured.iter().any(|m| m == "auto"))
+                    } else {
+                        (fec.active.clone(), &fec.active == expected)
+                    };
+                    if !matches {
+                        let msg = format!("FEC mode is {} (expected {})", actual, expected);
+                        if cfg.report_critical {
+                            self.critical.push(Finding::with_values(FindingId::FecMismatch, interface, msg, expected, &actual));
+                        } else {
+                            self.warning.push(Finding::with_values(FindingId::FecMismatch, interface, msg, expected, &actual));
+                        }
+                    } else {
+                        self.ok.push(Finding::with_values(FindingId::FecOk, interface, format!("FEC mode is {}", actual), expected, &actual));
+                    }
+                },
+                None => {
+                    let err = ifs.read_errors.get("fec").map(|e| e.as_str()).unwrap_or("not supported");
+                    self.unknown.push(Finding::new(FindingId::FecUnknown, interface, format!("Can't read FEC mode: {}", err)));
+                },
+            };
+        } else if let Some(ref fec) = ifs.fec {
+            self.details.push(format!("{} active FEC mode is {}", interface, fec.active));
+        }
+    }
+
+    fn check_flags(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // promiscuous mode on production NICs is usually a forgotten tcpdump or a broken bridge
+        if let Some(expected) = cfg.promisc {
+            let on_off = |v: bool| if v { "on" } else { "off" };
+            match ifs.flags.map(|f| f & libc::IFF_PROMISC as u32 != 0) {
+                Some(actual) if actual != expected => {
+                    let msg = format!("Promiscuous mode is {} (expected {})", on_off(actual), on_off(expected));
+                    if cfg.report_critical {
+                        self.critical.push(Finding::with_values(FindingId::PromiscMismatch, interface, msg, on_off(expected), on_off(actual)));
+                    } else {
+                        self.warning.push(Finding::with_values(FindingId::PromiscMismatch, interface, msg, on_off(expected), on_off(actual)));
+                    }
+                },
+                Some(actual) => {
+                    self.ok.push(Finding::with_values(FindingId::PromiscOk, interface, format!("Promiscuous mode is {}", on_off(actual)), on_off(expected), on_off(actual)));
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::PromiscUnknown, interface, "Can't read interface flags".to_string()));
+                },
+            };
+        }
+
+        // arbitrary flags, every wrong flag is listed
+        if cfg.flags_set != 0 || cfg.flags_clear != 0 {
+            match ifs.flags {
+                Some(flags) => {
+                    let mut wrong: Vec<String> = Vec::new();
+                    wrong.extend(flag_names(cfg.flags_set & !flags).iter().map(|f| format!("{} not set", f)));
+                    wrong.extend(flag_names(cfg.flags_clear & flags).iter().map(|f| format!("{} set", f)));
+                    let actual = flag_names(flags).join(",");
+                    let mut expected: Vec<String> = flag_names(cfg.flags_set).iter().map(|f| f.to_string()).collect();
+                    expected.extend(flag_names(cfg.flags_clear).iter().map(|f| format!("-{}", f)));
+
+                    if wrong.is_empty() {
+                        self.ok.push(Finding::with_values(FindingId::FlagsOk, interface, format!("Interface flags are {}", actual), expected.join(","), &actual));
+                    } else if cfg.report_critical {
+                        self.critical.push(Finding::with_values(FindingId::FlagsMismatch, interface, format!("Wrong interface flags: {}", wrong.join(", ")), expected.join(","), &actual));
+                    } else {
+                        self.warning.push(Finding::with_values(FindingId::FlagsMismatch, interface, format!("Wrong interface flags: {}", wrong.join(", ")), expected.join(","), &actual));
+                    }
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::FlagsUnknown, interface, "Can't read interface flags".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_mtu(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // check MTU
+        if let Some(mtu) = cfg.mtu {
+            if let Some(err) = ifs.read_errors.get("mtu") {
+                self.unknown.push(Finding::new(FindingId::AttributeUnreadable, interface, unreadable(ifs, "MTU", err)));
+            } else {
+                // a route MTU towards a destination can clamp the interface MTU
+                let mtu_desc = match cfg.effective_mtu_to {
+                    Some(dst) if ifs.effective_mtu != ifs.mtu => {
+                        format!("Effective MTU size of {} towards {} (interface MTU size {})", ifs.effective_mtu, dst, ifs.mtu)
+                    },
+                    _ => { format!("MTU size of {}", ifs.effective_mtu) },
+                };
+
+                let cmp = threshold::Threshold::range(mtu, cfg.report_critical).evaluate(interface, "mtu", ifs.effective_mtu as f64);
+                let state = cmp.state;
+                self.audit.push(cmp);
+
+                let mismatch = if mtu.is_exact() || mtu.inside {
+                    format!("{} does not match requested MTU size of {}", mtu_desc, mtu)
+                } else {
+                    format!("{} is outside of the accepted MTU range {}", mtu_desc, mtu)
+                };
+                if state == STATE_CRITICAL {
+                    self.critical.push(Finding::with_values(FindingId::MtuMismatch, interface, mismatch, mtu, ifs.effective_mtu));
+                } else if state == STATE_WARNING {
+                    self.warning.push(Finding::with_values(FindingId::MtuMismatch, interface, mismatch, mtu, ifs.effective_mtu));
+                } else if ifs.effective_mtu != ifs.mtu {
+                    self.ok.push(Finding::with_values(FindingId::MtuOk, interface, format!("{} matches requested MTU size", mtu_desc), mtu, ifs.effective_mtu));
+                } else {
+                    self.ok.push(Finding::with_values(FindingId::MtuOk, interface, format!("MTU size is {}", ifs.mtu), mtu, ifs.mtu));
+                }
+            }
+        }
+    }
+
+    fn check_mtu_consistency(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // a VLAN with a larger MTU than its parent drops the large frames silently
+        if cfg.mtu_consistency && ifs.mtu > 0 && !ifs.related_mtus.is_empty() {
+            let mut consistent = true;
+            for r in &ifs.related_mtus {
+                if r.relation == "lower" && ifs.mtu > r.mtu {
+                    consistent = false;
+                    self.warning.push(Finding::with_values(FindingId::MtuAboveLower, interface, format!("MTU size of {} exceeds MTU size of {} of lower device {}", ifs.mtu, r.mtu, r.name), r.mtu, ifs.mtu));
+                } else if r.relation != "lower" && ifs.mtu != r.mtu {
+                    consistent = false;
+                    self.warning.push(Finding::with_values(FindingId::MtuDiffersFromMaster, interface, format!("MTU size of {} differs from MTU size of {} of {} master {}", ifs.mtu, r.mtu, r.relation, r.name), r.mtu, ifs.mtu));
+                }
+            }
+            if consistent {
+                let related: Vec<String> = ifs.related_mtus.iter().map(|r| format!("{} {}", r.name, r.mtu)).collect();
+                self.ok.push(Finding::new(FindingId::MtuConsistent, interface, format!("MTU size is consistent with related devices ({})", related.join(", "))));
+            }
+        }
+    }
+
+    fn check_txqueuelen(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // a queue length reset to 1 by some script drops packets without any other symptom
+        if let Some(expected) = cfg.txqueuelen {
+            match ifs.tx_queue_len {
+                Some(actual) => {
+                    let mut perf = PerfData::new(&format!("{}_txqueuelen", interface), actual as f64, "");
+                    perf.min = Some(0.0);
+                    if cfg.report_critical {
+                        perf.crit = Some(format!("{}:{}", expected, expected));
+                    } else {
+                        perf.warn = Some(format!("{}:{}", expected, expected));
+                    }
+                    self.perfdata.push(perf);
+
+                    if actual == expected {
+                        self.ok.push(Finding::with_values(FindingId::TxQueueLenOk, interface, format!("Transmit queue length is {}", actual), expected, actual));
+                    } else if cfg.report_critical {
+                        self.critical.push(Finding::with_values(FindingId::TxQueueLenMismatch, interface, format!("Transmit queue length is {} instead of {}", actual, expected), expected, actual));
+                    } else {
+                        self.warning.push(Finding::with_values(FindingId::TxQueueLenMismatch, interface, format!("Transmit queue length is {} instead of {}", actual, expected), expected, actual));
+                    }
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::TxQueueLenUnknown, interface, "Can't read transmit queue length from sysfs".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_rings(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // driver updates tend to reset the ring buffers to defaults too small for the traffic
+        if cfg.ring_rx.is_some() || cfg.ring_tx.is_some() {
+            match ifs.rings {
+                Some(ref rings) => {
+                    for (name, expected, actual, max) in [("RX", cfg.ring_rx, rings.rx, rings.rx_max), ("TX", cfg.ring_tx, rings.tx, rings.tx_max)] {
+                        let expected = match expected {
+                            Some(v) => { v },
+                            None => { continue; },
+                        };
+                        let mut perf = PerfData::new(&format!("{}_ring_{}", interface, name.to_lowercase()), actual as f64, "");
+                        perf.min = Some(0.0);
+                        perf.max = Some(max as f64);
+                        if cfg.report_critical {
+                            perf.crit = Some(format!("{}:{}", expected, expected));
+                        } else {
+                            perf.warn = Some(format!("{}:{}", expected, expected));
+                        }
+                        self.perfdata.push(perf);
+
+                        if actual == expected {
+                            self.ok.push(Finding::with_values(FindingId::RingSizeOk, interface, format!("{} ring buffer size is {}", name, actual), expected, actual));
+                        } else if cfg.report_critical {
+                            self.critical.push(Finding::with_values(FindingId::RingSizeMismatch, interface, format!("{} ring buffer size is {} instead of {}", name, actual, expected), expected, actual));
+                        } else {
+                            self.warning.push(Finding::with_values(FindingId::RingSizeMismatch, interface, format!("{} ring buffer size is {} instead of {}", name, actual, expected), expected, actual));
+                        }
+                    }
+                },
+                None => {
+                    let err = ifs.read_errors.get("rings").map(|e| e.as_str()).unwrap_or("not supported");
+                    self.unknown.push(Finding::new(FindingId::RingSizeUnknown, interface, format!("Can't read ring buffer sizes: {}", err)));
+                },
+            };
+        }
+        if let Some(ref rings) = ifs.rings {
+            self.details.push(format!("{} ring buffer sizes RX {} (max. {}), TX {} (max. {})", interface, rings.rx, rings.rx_max, rings.tx, rings.tx_max));
+        }
+    }
+
+    fn check_features(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // config management and driver defaults disagree on offloads like LRO
+        if !cfg.features.is_empty() {
+            let on_off = |v: bool| if v { "on" } else { "off" };
+            match ifs.features {
+                Some(ref features) => {
+                    let mut unknown_names = false;
+                    for (name, expected) in &cfg.features {
+                        let kernel_name = ethtool::feature_name(name);
+                        match features.iter().find(|f| f.name == kernel_name) {
+                            Some(f) if f.active != *expected => {
+                                let fixed = if f.fixed { ", fixed" } else { "" };
+                                let msg = format!("Feature {} is {} (expected {}{})", name, on_off(f.active), on_off(*expected), fixed);
+                                if cfg.report_critical {
+                                    self.critical.push(Finding::with_values(FindingId::FeatureMismatch, interface, msg, on_off(*expected), on_off(f.active)));
+                                } else {
+                                    self.warning.push(Finding::with_values(FindingId::FeatureMismatch, interface, msg, on_off(*expected), on_off(f.active)));
+                                }
+                            },
+                            Some(f) => {
+                                self.ok.push(Finding::with_values(FindingId::FeatureOk, interface, format!("Feature {} is {}", name, on_off(f.active)), on_off(*expected), on_off(f.active)));
+                            },
+                            None => {
+                                unknown_names = true;
+                                self.unknown.push(Finding::new(FindingId::FeatureUnknown, interface, format!("Feature {} is not known to the driver", name)));
+                            },
+                        };
+                    }
+                    if unknown_names {
+                        self.details.push(format!("{} features: {}", interface, features.iter().map(|f| f.name.as_str()).collect::<Vec<&str>>().join(", ")));
+                    }
+                },
+                None => {
+                    let err = ifs.read_errors.get("features").map(|e| e.as_str()).unwrap_or("not supported");
+                    self.unknown.push(Finding::new(FindingId::FeatureUnknown, interface, format!("Can't read offload features: {}", err)));
+                },
+            };
+        }
+    }
+
+    fn check_pause(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // flow control differing from the switch port collapses the throughput under load
+        if !cfg.pause.is_empty() {
+            let on_off = |v: bool| if v { "on" } else { "off" };
+            match ifs.pause {
+                Some(ref pause) => {
+                    for (setting, expected) in &cfg.pause {
+                        let actual = match setting.as_str() {
+                            "rx" => { pause.rx },
+                            "tx" => { pause.tx },
+                            _ => { pause.autoneg },
+                        };
+                        if actual != *expected {
+                            let msg = format!("Pause frame setting {} is {} (expected {})", setting, on_off(actual), on_off(*expected));
+                            if cfg.report_critical {
+                                self.critical.push(Finding::with_values(FindingId::PauseMismatch, interface, msg, on_off(*expected), on_off(actual)));
+                            } else {
+                                self.warning.push(Finding::with_values(FindingId::PauseMismatch, interface, msg, on_off(*expected), on_off(actual)));
+                            }
+                        } else {
+                            self.ok.push(Finding::with_values(FindingId::PauseOk, interface, format!("Pause frame setting {} is {}", setting, on_off(actual)), on_off(*expected), on_off(actual)));
+                        }
+                    }
+                },
+                None => {
+                    let err = ifs.read_errors.get("pause").map(|e| e.as_str()).unwrap_or("not supported");
+                    self.unknown.push(Finding::new(FindingId::PauseUnknown, interface, format!("Can't read pause frame settings: {}", err)));
+                },
+            };
+        }
+        if let Some(ref pause) = ifs.pause {
+            let on_off = |v: bool| if v { "on" } else { "off" };
+            self.details.push(format!("{} pause frames autoneg {}, rx {}, tx {}", interface, on_off(pause.autoneg), on_off(pause.rx), on_off(pause.tx)));
+        }
+    }
+
+    fn check_queues(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // a NIC that came up with a single queue on a big machine is slow without any error
+        let combined = cfg.queues_combined.map(|q| match q {
+            QueueCount::Exact(n) => { n },
+            QueueCount::OnlineCpus => {
+                let cpus = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) }.max(1) as u32;
+                match ifs.channels {
+                    Some(ref c) if c.max_combined > 0 => { cpus.min(c.max_combined) },
+                    _ => { cpus },
+                }
+            },
+        });
+        let queues = [
+            ("RX queues", "rx", cfg.queues_rx, ifs.rx_queues, "Can't read RX queues from sysfs".to_string()),
+            ("TX queues", "tx", cfg.queues_tx, ifs.tx_queues, "Can't read TX queues from sysfs".to_string()),
+            ("combined channels", "combined", combined, ifs.channels.as_ref().map(|c| c.combined),
+                format!("Can't read channel parameters: {}", ifs.read_errors.get("channels").map(|e| e.as_str()).unwrap_or("not supported"))),
+        ];
+        for (name, label, expected, actual, err) in queues {
+            let expected = match expected {
+                Some(v) => { v },
+                None => { continue; },
+            };
+            match actual {
+                Some(actual) => {
+                    let mut perf = PerfData::new(&format!("{}_queues_{}", interface, label), actual as f64, "");
+                    perf.min = Some(0.0);
+                    perf.warn = Some(format!("{}:{}", expected, expected));
+                    self.perfdata.push(perf);
+
+                    if actual == expected {
+                        self.ok.push(Finding::with_values(FindingId::QueuesOk, interface, format!("Number of {} is {}", name, actual), expected, actual));
+                    } else {
+                        self.warning.push(Finding::with_values(FindingId::QueuesMismatch, interface, format!("Number of {} is {} instead of {}", name, actual, expected), expected, actual));
+                    }
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::QueuesUnknown, interface, err));
+                },
+            };
+        }
+    }
+
+    fn check_numa(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // workloads are pinned to the NUMA node of the NIC, a reseated card breaks that silently
+        if let Some(expected) = cfg.numa_node {
+            let finding = match ifs.numa_node {
+                Some(-1) => {
+                    Some(Finding::with_values(FindingId::NumaNodeNoAffinity, interface, format!("Device has no NUMA affinity (expected node {})", expected), expected, -1))
+                },
+                Some(actual) if actual != expected as i32 => {
+                    Some(Finding::with_values(FindingId::NumaNodeMismatch, interface, format!("Device is attached to NUMA node {} instead of {}", actual, expected), expected, actual))
+                },
+                Some(actual) => {
+                    self.ok.push(Finding::with_values(FindingId::NumaNodeOk, interface, format!("Device is attached to NUMA node {}", actual), expected, actual));
+                    None
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::NumaNodeUnknown, interface, "Can't read NUMA node, the interface has no device directory".to_string()));
+                    None
+                },
+            };
+            if let Some(f) = finding {
+                if cfg.report_critical {
+                    self.critical.push(f);
+                } else {
+                    self.warning.push(f);
+                }
+            }
+        } else if let Some(node) = ifs.numa_node {
+            self.details.push(format!("{} NUMA node {}", interface, if node < 0 { "none".to_string() } else { node.to_string() }));
+        }
+    }
+
+    fn check_sriov(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // VMs fail to start long after sriov_numvfs was reset by a firmware update
+        if let Some(expected) = cfg.sriov_vfs {
+            match (ifs.sriov_numvfs, ifs.sriov_totalvfs) {
+                (Some(actual), total) => {
+                    let mut perf = PerfData::new(&format!("{}_sriov_vfs", interface), actual as f64, "");
+                    perf.min = Some(0.0);
+                    perf.max = total.map(|t| t as f64);
+                    perf.crit = Some(format!("{}:{}", expected, expected));
+                    self.perfdata.push(perf);
+                    if let Some(t) = total {
+                        let mut perf = PerfData::new(&format!("{}_sriov_totalvfs", interface), t as f64, "");
+                        perf.min = Some(0.0);
+                        self.perfdata.push(perf);
+                    }
+
+                    let total = total.map(|t| t.to_string()).unwrap_or_else(|| "unknown".to_string());
+                    if actual == expected {
+                        self.ok.push(Finding::with_values(FindingId::SriovVfsOk, interface, format!("{} of {} SR-IOV virtual functions enabled", actual, total), expected, actual));
+                    } else {
+                        self.critical.push(Finding::with_values(FindingId::SriovVfsMismatch, interface, format!("{} SR-IOV virtual functions enabled instead of {} (supported: {})", actual, expected, total), expected, actual));
+                    }
+                },
+                (None, _) => {
+                    self.unknown.push(Finding::new(FindingId::SriovUnsupported, interface, "Device doesn't support SR-IOV".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_pcie(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // a NIC negotiated down to a few lanes never reaches line rate, and nothing else tells
+        let pcie_expected = cfg.pcie_speed.is_some() || cfg.pcie_width.is_some();
+        if (pcie_expected || cfg.pcie_check_max) && !ifs.virtual_device {
+            let mut findings: Vec<Finding> = Vec::new();
+            match ifs.pcie {
+                Some(ref link) => {
+                    let actual = format!("{} GT/s x{}", link.speed, link.width);
+                    if let Some(speed) = cfg.pcie_speed {
+                        if link.speed < speed {
+                            findings.push(Finding::with_values(FindingId::PcieLinkBelowExpected, interface, format!("PCIe link speed is {} GT/s (expected at least {} GT/s)", link.speed, speed), speed, link.speed));
+                        }
+                    }
+                    if let Some(width) = cfg.pcie_width {
+                        if link.width < width {
+                            findings.push(Finding::with_values(FindingId::PcieLinkBelowExpected, interface, format!("PCIe link width is x{} (expected at least x{})", link.width, width), width, link.width));
+                        }
+                    }
+                    if cfg.pcie_check_max {
+                        let max_speed = link.max_speed.unwrap_or(link.speed);
+                        let max_width = link.max_width.unwrap_or(link.width);
+                        if link.speed < max_speed || link.width < max_width {
+                            let max = format!("{} GT/s x{}", max_speed, max_width);
+                            findings.push(Finding::with_values(FindingId::PcieLinkBelowMax, interface, format!("PCIe link runs at {}, below the maximum of {}", actual, max), &max, &actual));
+                        }
+                    }
+                    if findings.is_empty() {
+                        self.ok.push(Finding::new(FindingId::PcieLinkOk, interface, format!("PCIe link is {}", actual)));
+                    }
+                },
+                None if pcie_expected => {
+                    self.unknown.push(Finding::new(FindingId::PcieLinkUnknown, interface, "Can't read PCIe link, the device has no PCIe link in sysfs".to_string()));
+                },
+                None => {},
+            };
+            if cfg.report_critical {
+                self.critical.extend(findings);
+            } else {
+                self.warning.extend(findings);
+            }
+        }
+    }
+
+    fn check_firmware(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // hosts which missed the firmware rollout
+        if cfg.firmware.is_some() || cfg.firmware_min.is_some() {
+            match ifs.driver_info.as_ref().map(|d| d.fw_version.as_str()) {
+                Some(actual) if !actual.is_empty() => {
+                    if let Some(ref expected) = cfg.firmware {
+                        if actual == expected {
+                            self.ok.push(Finding::with_values(FindingId::FirmwareOk, interface, format!("Firmware version is {}", actual), expected, actual));
+                        } else {
+                            self.warning.push(Finding::with_values(FindingId::FirmwareMismatch, interface, format!("Firmware version is {} instead of {}", actual, expected), expected, actual));
+                        }
+                    }
+                    if let Some(ref minimum) = cfg.firmware_min {
+                        if ethtool::compare_versions(actual, minimum) == Ordering::Less {
+                            self.warning.push(Finding::with_values(FindingId::FirmwareTooOld, interface, format!("Firmware version {} is older than {}", actual, minimum), minimum, actual));
+                        } else {
+                            self.ok.push(Finding::with_values(FindingId::FirmwareOk, interface, format!("Firmware version is {}", actual), minimum, actual));
+                        }
+                    }
+                },
+                Some(_) => {
+                    self.unknown.push(Finding::new(FindingId::FirmwareUnknown, interface, "Driver doesn't report a firmware version".to_string()));
+                },
+                None => {
+                    let err = ifs.read_errors.get("driver_info").map(|e| e.as_str()).unwrap_or("not supported");
+                    self.unknown.push(Finding::new(FindingId::FirmwareUnknown, interface, format!("Can't read firmware version: {}", err)));
+                },
+            };
+        }
+        if let Some(ref info) = ifs.driver_info {
+            let fw_version = if info.fw_version.is_empty() { "unknown" } else { info.fw_version.as_str() };
+            self.details.push(format!("{} driver {} {}, firmware {}", interface, info.driver, info.version, fw_version));
+        }
+    }
+
+    fn check_master(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // a bond slave or bridge port without its master still looks healthy on its own
+        if let Some(ref expected) = cfg.master {
+            match ifs.master {
+                Some(ref actual) if actual == expected => {
+                    self.ok.push(Finding::with_values(FindingId::MasterOk, interface, format!("Interface is enslaved to {}", actual), expected, actual));
+                },
+                Some(ref actual) => {
+                    self.critical.push(Finding::with_values(FindingId::MasterMismatch, interface, format!("Interface is enslaved to {} instead of {}", actual, expected), expected, actual));
+                },
+                None => {
+                    self.critical.push(Finding::with_values(FindingId::MasterMissing, interface, format!("Interface is not enslaved to {}", expected), expected, "none"));
+                },
+            };
+        }
+
+        // an uplink added to a bridge by some hook loses its addresses and routes
+        if cfg.no_master {
+            match ifs.master {
+                Some(ref actual) => {
+                    self.critical.push(Finding::with_values(FindingId::MasterUnexpected, interface, format!("Interface is enslaved to {}", actual), "none", actual));
+                },
+                None => {
+                    self.ok.push(Finding::new(FindingId::MasterOk, interface, "Interface is not enslaved".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_altname(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // automation refers to the interface by an alternative name, the primary name may drift
+        if let Some(ref expected) = cfg.expect_altname {
+            match ifs.altnames {
+                Some(ref altnames) if altnames.contains(expected) => {
+                    self.ok.push(Finding::new(FindingId::AltnameOk, interface, format!("Alternative name {} is configured", expected)));
+                },
+                Some(ref altnames) => {
+                    let actual = if altnames.is_empty() { "none".to_string() } else { altnames.join(", ") };
+                    self.warning.push(Finding::with_values(FindingId::AltnameMissing, interface, format!("Alternative name {} is missing (configured: {})", expected, actual), expected, &actual));
+                },
+                None => {
+                    let err = ifs.read_errors.get("altnames").map(|e| e.as_str()).unwrap_or("not supported");
+                    self.unknown.push(Finding::new(FindingId::AltnameUnknown, interface, format!("Can't read alternative names: {}", err)));
+                },
+            };
+        }
+    }
+
+    fn check_loopback_addresses(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // the addresses of the loopback interface are fixed, services break without them
+        if ifs.loopback && !cfg.address_minimums.is_empty() {
+            let mut expected: Vec<&str> = Vec::new();
+            if cfg.address_minimums.iter().any(|m| m.family != AddressFamily::Ipv6) {
+                expected.push("127.0.0.1/8");
+            }
+            if cfg.address_minimums.iter().any(|m| m.family != AddressFamily::Ipv4) {
+                expected.push("::1/128");
+            }
+            let assigned: Vec<String> = ifs.ips.iter().map(|n| n.to_string()).collect();
+            let missing: Vec<&str> = expected.iter().filter(|e| !assigned.iter().any(|a| a == *e)).cloned().collect();
+            if missing.is_empty() {
+                self.ok.push(Finding::with_values(FindingId::AddrLoopbackOk, interface, format!("Loopback address(es) assigned: {}", expected.join(", ")), expected.join(","), assigned.join(",")));
+            } else {
+                self.critical.push(Finding::with_values(FindingId::AddrLoopbackMissing, interface, format!("Loopback address(es) missing: {}", missing.join(", ")), expected.join(","), assigned.join(",")));
+            }
+        }
+    }
+
+    fn check_address_state(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // addresses failing duplicate address detection can't be used
+        for a in &ifs.unusable_addresses {
+            match (a.state, a.age) {
+                ("dadfailed", _) => {
+                    self.critical.push(Finding::new(FindingId::AddrDadFailed, interface, format!("Duplicate address detected for {}", a.ip)));
+                },
+                (_, Some(age)) if age > cfg.tentative_grace.as_secs() => {
+                    self.warning.push(Finding::with_values(FindingId::AddrTentative, interface, format!("Address {} is tentative for {} seconds", a.ip, age), cfg.tentative_grace.as_secs(), age));
+                },
+                _ => {},
+            };
+        }
+
+        // privacy extensions only add temporary addresses, inbound connections need a stable one
+        if cfg.ipv6_require_stable && !ifs.loopback {
+            match ifs.temporary_addresses {
+                Some(ref temporary) => {
+                    let global: Vec<IpAddr> = ifs.ips.iter().filter_map(|n| match n {
+                        ipnetwork::IpNetwork::V6(addr) if !link_local_ipv6().contains(addr.ip()) => { Some(IpAddr::V6(addr.ip())) },
+                        _ => { None },
+                    }).filter(|ip| !ifs.unusable_addresses.iter().any(|a| a.ip == *ip)).collect();
+                    let temporary_count = global.iter().filter(|ip| temporary.contains(ip)).count();
+                    let stable_count = global.len() - temporary_count;
+                    if stable_count == 0 {
+                        self.critical.push(Finding::with_values(FindingId::AddrNoStable, interface, format!("No stable IPv6 address assigned (0 stable, {} temporary)", temporary_count), 1, stable_count));
+                    } else {
+                        self.ok.push(Finding::with_values(FindingId::AddrStableOk, interface, format!("{} stable and {} temporary IPv6 address(es) assigned", stable_count, temporary_count), 1, stable_count));
+                    }
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::AddrFlagsUnavailable, interface, "Can't tell stable from temporary IPv6 addresses, address flags can't be read from netlink".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_address_assignment(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // addresses which failed or are still in duplicate address detection don't count
+        let usable = |n: &&ipnetwork::IpNetwork| !ifs.unusable_addresses.iter().any(|a| a.ip == n.ip());
+        // neither do addresses managed by someone else, e.g. CNI, for the assignment checks
+        let ignored_by = |n: &ipnetwork::IpNetwork| cfg.ignore_prefixes.iter().find(|p| p.contains(n.ip()));
+        let counted = |n: &&ipnetwork::IpNetwork| usable(n) && ignored_by(n).is_none();
+        if !cfg.address_minimums.is_empty() || !cfg.address_counts.is_empty() {
+            for n in &ifs.ips {
+                if let Some(p) = ignored_by(n) {
+                    self.details.push(format!("{} address {} ignored, inside --ignore-prefix {}", interface, n, p));
+                }
+            }
+        }
+        let link_local_6 = ifs.ips.iter().filter(usable).filter(|n| match n {
+            ipnetwork::IpNetwork::V6(addr) => { link_local_ipv6().contains(addr.ip()) },
+            _ => { false },
+        }).count();
+
+        // routing protocols like OSPFv3 or BGP unnumbered need a link local address,
+        // there is none if IPv6 is disabled on the interface
+        if cfg.require_ipv6_link_local && !ifs.loopback {
+            if link_local_6 == 0 {
+                self.critical.push(Finding::with_values(FindingId::AddrNoLinkLocal, interface, "No IPv6 link local address assigned".to_string(), 1, link_local_6));
+            } else {
+                self.ok.push(Finding::with_values(FindingId::AddrLinkLocalOk, interface, format!("{} IPv6 link local address(es) assigned", link_local_6), 1, link_local_6));
+            }
+        }
+
+        // check assigned addresses, per address family
+        for m in cfg.address_minimums.iter().filter(|_| !ifs.loopback) {
+            let mut link_local = 0;
+            let mut non_link_local = 0;
+            // only counted separately with --ipv6-require-global
+            let mut ula_6 = 0;
+            for n in ifs.ips.iter().filter(counted) {
+                let is_link_local = match n {
+                    ipnetwork::IpNetwork::V4(addr) if m.family != AddressFamily::Ipv6 => { link_local_ipv4().contains(addr.ip()) },
+                    ipnetwork::IpNetwork::V6(addr) if m.family != AddressFamily::Ipv4 => {
+                        if cfg.ipv6_require_global && unique_local_ipv6().contains(addr.ip()) {
+                            ula_6 += 1;
+                            continue;
+                        }
+                        link_local_ipv6().contains(addr.ip())
+                    },
+                    _ => { continue; },
+                };
+                if is_link_local {
+                    link_local += 1;
+                } else {
+                    non_link_local += 1;
+                }
+            }
+
+            let family = m.family.as_str();
+            let bound = Some(threshold::Bound::Below(m.count as f64));
+            let addr_threshold = if m.explicit && !cfg.report_critical {
+                threshold::Threshold{ warn: bound, crit: None }
+            } else {
+                threshold::Threshold{ warn: None, crit: bound }
+            };
+            let cmp = addr_threshold.evaluate(interface, &format!("non link local {} addresses", family), non_link_local as f64);
+            let state = cmp.state;
+            self.audit.push(cmp);
+
+            // Nagios range "n:" alerts below n
+            let mut perf = PerfData::new(&format!("{}_{}_addresses", interface, family), non_link_local as f64, "");
+            if m.explicit && !cfg.report_critical {
+                perf.warn = Some(format!("{}:", m.count));
+            } else {
+                perf.crit = Some(format!("{}:", m.count));
+            }
+            perf.min = Some(0.0);
+            self.perfdata.push(perf);
+
+            if !m.explicit {
+                if non_link_local == 0 && link_local == 0 && ula_6 == 0 {
+                    // no address assigned
+                    self.critical.push(Finding::new(FindingId::AddrNone, interface, "No IP address assigned".to_string()));
+                } else if non_link_local == 0 && ula_6 > 0 {
+                    // no global address, e.g. SLAAC of the global prefix failed
+                    self.critical.push(Finding::new(FindingId::AddrUlaOnly, interface, "Only ULA address(es) assigned".to_string()));
+                } else if non_link_local == 0 && link_local > 0 {
+                    // only link local addresses assigned
+                    self.critical.push(Finding::new(FindingId::AddrLinkLocalOnly, interface, "Only link local address(es) are assigned".to_string()));
+                } else {
+                    // OK: non-link local address(es) and zero ore more link local addresses
+                    self.ok.push(Finding::new(FindingId::AddrOk, interface, "Non link local address(es) assigned".to_string()));
+                }
+                continue;
+            }
+
+            let message = if non_link_local == 0 && ula_6 > 0 {
+                format!("Only ULA address(es) assigned, at least {} non link local {} address(es) required", m.count, family)
+            } else if ula_6 > 0 {
+                format!("{} non link local {} address(es) assigned ({} ULA not counted), at least {} required", non_link_local, family, ula_6, m.count)
+            } else {
+                format!("{} non link local {} address(es) assigned, at least {} required", non_link_local, family, m.count)
+            };
+            match state {
+                STATE_CRITICAL if non_link_local == 0 && ula_6 > 0 => { self.critical.push(Finding::with_values(FindingId::AddrUlaOnly, interface, message, m.count, non_link_local)); },
+                STATE_WARNING if non_link_local == 0 && ula_6 > 0 => { self.warning.push(Finding::with_values(FindingId::AddrUlaOnly, interface, message, m.count, non_link_local)); },
+                STATE_CRITICAL => { self.critical.push(Finding::with_values(FindingId::AddrBelowMinimum, interface, message, m.count, non_link_local)); },
+                STATE_WARNING => { self.warning.push(Finding::with_values(FindingId::AddrBelowMinimum, interface, message, m.count, non_link_local)); },
+                _ => { self.ok.push(Finding::with_values(FindingId::AddrOk, interface, format!("{} non link local {} address(es) assigned", non_link_local, family), m.count, non_link_local)); },
+            };
+        }
+
+        // exact number of addresses, e.g. virtual IPs moved by keepalived
+        for c in cfg.address_counts.iter().filter(|_| !ifs.loopback) {
+            let family = c.family.as_str();
+            let assigned = ifs.ips.iter().filter(counted).filter(|n| match n {
+                ipnetwork::IpNetwork::V4(addr) if c.family != AddressFamily::Ipv6 => { !link_local_ipv4().contains(addr.ip()) },
+                ipnetwork::IpNetwork::V6(addr) if c.family != AddressFamily::Ipv4 => { !link_local_ipv6().contains(addr.ip()) },
+                _ => { false },
+            }).count();
+
+            let expected = c.count as f64;
+            let addr_threshold = if cfg.report_critical {
+                threshold::Threshold{ warn: None, crit: Some(threshold::Bound::Range(threshold::Range::exact(expected))) }
+            } else {
+                threshold::Threshold{ warn: Some(threshold::Bound::Above(expected)), crit: Some(threshold::Bound::Below(expected)) }
+            };
+            let cmp = addr_threshold.evaluate(interface, &format!("number of {} addresses", family), assigned as f64);
+            let state = cmp.state;
+            self.audit.push(cmp);
+
+            // replaces the perfdata of -a for the same family, the thresholds are stricter
+            let label = format!("{}_{}_addresses", interface, family);
+            self.perfdata.retain(|p| p.label != label);
+            let mut perf = PerfData::new(&label, assigned as f64, "");
+            if cfg.report_critical {
+                perf.crit = Some(format!("{}:{}", c.count, c.count));
+            } else {
+                perf.warn = Some(c.count.to_string());
+                perf.crit = Some(format!("{}:", c.count));
+            }
+            perf.min = Some(0.0);
+            self.perfdata.push(perf);
+
+            let message = format!("{} non link local {} address(es) assigned, expected exactly {}", assigned, family, c.count);
+            match state {
+                STATE_CRITICAL if assigned < c.count => { self.critical.push(Finding::with_values(FindingId::AddrCountBelow, interface, message, c.count, assigned)); },
+                STATE_CRITICAL => { self.critical.push(Finding::with_values(FindingId::AddrCountAbove, interface, message, c.count, assigned)); },
+                STATE_WARNING => { self.warning.push(Finding::with_values(FindingId::AddrCountAbove, interface, message, c.count, assigned)); },
+                _ => { self.ok.push(Finding::with_values(FindingId::AddrCountOk, interface, format!("Exactly {} non link local {} address(es) assigned", assigned, family), c.count, assigned)); },
+            };
+        }
+    }
+
+    fn check_no_address(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // bond slaves, bridge ports, ... must not carry any address
+        if cfg.expect_no_address {
+            let mut found: Vec<String> = Vec::new();
+            for n in &ifs.ips {
+                // the kernel adds IPv6 link local addresses automatically
+                if let ipnetwork::IpNetwork::V6(addr) = n {
+                    if !cfg.strict && link_local_ipv6().contains(addr.ip()) {
+                        continue;
+                    }
+                }
+                found.push(n.to_string());
+            }
+
+            if found.is_empty() {
+                self.ok.push(Finding::new(FindingId::AddrAbsent, interface, "No addresses assigned, as expected".to_string()));
+            } else {
+                self.critical.push(Finding::with_values(FindingId::AddrUnexpected, interface, format!("Unexpected address(es) assigned: {}", found.join(", ")), "none", found.join(",")));
+            }
+        }
+    }
+
+    fn check_expected_addresses(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // every listed address must be assigned, with the prefix length if one was given
+        if !cfg.expect_address.is_empty() {
+            let mut expected: Vec<String> = Vec::new();
+            let mut missing: Vec<String> = Vec::new();
+            let mut wrong_prefix: Vec<String> = Vec::new();
+            for (addr, prefix) in &cfg.expect_address {
+                let wanted = match prefix {
+                    Some(p) => { format!("{}/{}", addr, p) },
+                    None => { addr.to_string() },
+                };
+                match ifs.ips.iter().find(|n| n.ip() == *addr) {
+                    Some(n) => {
+                        if let Some(p) = prefix {
+                            if n.prefix() != *p {
+                                wrong_prefix.push(format!("{} instead of {}", n, wanted));
+                            }
+                        }
+                    },
+                    None => { missing.push(wanted.clone()); },
+                };
+                expected.push(wanted);
+            }
+
+            let assigned: Vec<String> = ifs.ips.iter().map(|n| n.to_string()).collect();
+            if !missing.is_empty() {
+                self.critical.push(Finding::with_values(FindingId::AddrExpectedMissing, interface, format!("Expected address(es) not assigned: {}", missing.join(", ")), expected.join(","), assigned.join(",")));
+            }
+            if !wrong_prefix.is_empty() {
+                self.warning.push(Finding::with_values(FindingId::AddrPrefixMismatch, interface, format!("Address(es) assigned with wrong prefix length: {}", wrong_prefix.join(", ")), expected.join(","), assigned.join(",")));
+            }
+            if missing.is_empty() && wrong_prefix.is_empty() {
+                self.ok.push(Finding::with_values(FindingId::AddrExpectedOk, interface, format!("Expected address(es) assigned: {}", expected.join(", ")), expected.join(","), assigned.join(",")));
+            }
+        }
+    }
+
+    fn check_neighbors(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // peers like the VRRP partner must be resolvable on this segment, with the known MAC address
+        if let Some(ref neighbors) = ifs.neighbors {
+            for (ip, mac) in &cfg.expect_neighbors {
+                match neighbors.iter().find(|n| n.ip == *ip) {
+                    None => {
+                        self.critical.push(Finding::with_values(FindingId::NeighborMissing, interface, format!("Neighbor {} not in neighbor table", ip), ip, ""));
+                    },
+                    // DELAY and PROBE are resolved entries being confirmed again
+                    Some(n) if n.mac.is_none() || n.state == "FAILED" || n.state == "INCOMPLETE" || n.state == "NONE" => {
+                        self.critical.push(Finding::with_values(FindingId::NeighborUnresolved, interface, format!("Neighbor {} not resolved ({})", ip, n.state), "REACHABLE", n.state));
+                    },
+                    Some(n) => {
+                        let actual = n.mac.clone().unwrap_or_default();
+                        match mac {
+                            Some(m) if *m != actual => {
+                                self.critical.push(Finding::with_values(FindingId::NeighborMacMismatch, interface, format!("Neighbor {} has MAC address {} instead of {}", ip, actual, m), m, &actual));
+                            },
+                            _ => {
+                                self.ok.push(Finding::with_values(FindingId::NeighborOk, interface, format!("Neighbor {} resolved to {} ({})", ip, actual, n.state), mac.clone().unwrap_or_default(), &actual));
+                            },
+                        };
+                    },
+                };
+            }
+        }
+    }
+
+    fn check_default_route(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // an interface with addresses is still useless if the default route left it
+        if cfg.expect_default_route {
+            match ifs.default_routes {
+                Some(ref table) => {
+                    for family in &cfg.default_route_families {
+                        let name = if *family == AddressFamily::Ipv6 { "IPv6" } else { "IPv4" };
+                        let routes: Vec<&route::DefaultRoute> = table.routes.iter().filter(|r| r.family == family.as_str()).collect();
+                        let here: Vec<Option<IpAddr>> = routes.iter().filter(|r| r.interface == interface).map(|r| r.gateway).collect();
+                        let gateways: Vec<String> = here.iter().map(|g| match g {
+                            Some(gw) => { gw.to_string() },
+                            None => { "none".to_string() },
+                        }).collect();
+
+                        if here.is_empty() {
+                            let mut elsewhere: Vec<String> = Vec::new();
+                            for r in routes {
+                                if !elsewhere.contains(&r.interface) {
+                                    elsewhere.push(r.interface.clone());
+                                }
+                            }
+                            let message = if elsewhere.is_empty() {
+                                format!("No default {} route", name)
+                            } else {
+                                format!("No default {} route via {}, default route via {}", name, interface, elsewhere.join(", "))
+                            };
+                            self.critical.push(Finding::with_values(FindingId::RouteDefaultMissing, interface, message, interface, elsewhere.join(",")));
+                        } else if let Some(gw) = cfg.default_gateway.filter(|gw| !here.contains(&Some(*gw))) {
+                            self.warning.push(Finding::with_values(FindingId::RouteGatewayMismatch, interface, format!("Default {} route uses gateway {} instead of {}", name, gateways.join(", "), gw), gw, gateways.join(",")));
+                        } else {
+                            self.ok.push(Finding::with_values(FindingId::RouteDefaultOk, interface, format!("Default {} route via gateway {}", name, gateways.join(", ")), cfg.default_gateway.map(|gw| gw.to_string()).unwrap_or_default(), gateways.join(",")));
+                        }
+                    }
+                },
+                None => {
+                    let err = ifs.read_errors.get("default_routes").map(|e| e.as_str()).unwrap_or("Can't read routing table");
+                    self.unknown.push(Finding::new(FindingId::RouteUnavailable, interface, err.to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_counter_rates(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // check error, drop and collision rates and the counters of --stat
+        let mut rate_checks = vec![
+            (vec!["rx_errors", "tx_errors"], cfg.errors_warn, cfg.errors_crit, FindingId::ErrorsAboveThreshold, FindingId::ErrorsOk),
+            (vec!["rx_dropped", "tx_dropped"], cfg.drops_warn, cfg.drops_crit, FindingId::DropsAboveThreshold, FindingId::DropsOk),
+            (vec!["collisions"], cfg.collisions_warn, cfg.collisions_crit, FindingId::CollisionsAboveThreshold, FindingId::CollisionsOk),
+        ];
+        for stat in &cfg.stats {
+            rate_checks.push((vec![stat.name.as_str()], stat.warn, stat.crit, FindingId::StatAboveThreshold, FindingId::StatOk));
+        }
+        for (names, warn, crit, above_id, ok_id) in &rate_checks {
+            if warn.is_none() && crit.is_none() {
+                continue;
+            }
+
+            for name in names {
+                let counter = match counters::find(&ifs.counters, name) {
+                    Some(v) => { v },
+                    None => {
+                        if let Some(err) = ifs.read_errors.get(&format!("stat.{}", name)) {
+                            self.unknown.push(Finding::new(FindingId::StatUnknown, interface, err.clone()));
+                        }
+                        continue;
+                    },
+                };
+
+                let mut perf = PerfData::new(&format!("{}_{}", interface, name), counter.value as f64, "c");
+                perf.min = Some(0.0);
+                self.perfdata.push(perf);
+
+                match counter.rate {
+                    Some(rate) => {
+                        let mut perf = PerfData::new(&format!("{}_{}_rate", interface, name), rate, "");
+                        perf.warn = format_threshold(*warn);
+                        perf.crit = format_threshold(*crit);
+                        perf.min = Some(0.0);
+                        self.perfdata.push(perf);
+
+                        let cmp = evaluate(cfg, ifs, &threshold::Threshold::above(*warn, *crit), &format!("{} rate", name), rate);
+                        let message = match cmp.state {
+                            STATE_CRITICAL if cmp.held => { format!("{} rate {:.2}/s back under {} threshold but within hysteresis band", name, rate, format_threshold(*crit).unwrap_or_default()) },
+                            STATE_WARNING if cmp.held => { format!("{} rate {:.2}/s back under {} threshold but within hysteresis band", name, rate, format_threshold(*warn).unwrap_or_default()) },
+                            _ => { format!("{} rate is {:.2}/s", name, rate) },
+                        };
+                        let state = cmp.state;
+                        self.audit.push(cmp);
+                        match state {
+                            STATE_CRITICAL => { self.critical.push(Finding::with_values(*above_id, interface, message, format_threshold(*crit).unwrap_or_default(), rate)); },
+                            STATE_WARNING => { self.warning.push(Finding::with_values(*above_id, interface, message, format_threshold(*warn).unwrap_or_default(), rate)); },
+                            _ => { self.ok.push(Finding::new(*ok_id, interface, message)); },
+                        };
+                    },
+                    None => {
+                        self.ok.push(Finding::new(FindingId::CounterBaseline, interface, format!("{} counter is {}, no rate for this interval (first run, counter reset or counter source changed)", name, counter.value)));
+                    },
+                };
+            }
+        }
+    }
+
+    fn check_collisions(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // collisions can't happen on a full duplex link, counting them anyway usually means
+        // the other side runs half duplex. With thresholds the rate check above already
+        // exports the counter.
+        if let (None, None, Some(n)) = (cfg.collisions_warn, cfg.collisions_crit, ifs.collisions) {
+            let mut perf = PerfData::new(&format!("{}_collisions", interface), n as f64, "c");
+            perf.min = Some(0.0);
+            self.perfdata.push(perf);
+            if n > 0 && ifs.duplex == "full" {
+                self.notes.push(format!("{}: {} collisions counted on a full duplex link", interface, n));
+            }
+        }
+    }
+
+    fn check_flaps(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // check link flaps per hour, a link bouncing every few minutes passes every point
+        // in time check
+        if cfg.flaps_warn.is_some() || cfg.flaps_crit.is_some() {
+            match ifs.flaps {
+                Some(ref counter) => {
+                    match counter.rate {
+                        Some(rate) => {
+                            let per_hour = rate * 3600.0;
+                            let mut perf = PerfData::new(&format!("{}_flaps_per_hour", interface), per_hour, "");
+                            perf.warn = format_threshold(cfg.flaps_warn);
+                            perf.crit = format_threshold(cfg.flaps_crit);
+                            perf.min = Some(0.0);
+                            self.perfdata.push(perf);
+
+                            let cmp = evaluate(cfg, ifs, &threshold::Threshold::above(cfg.flaps_warn, cfg.flaps_crit), "carrier changes per hour", per_hour);
+                            let message = match cmp.state {
+                                STATE_CRITICAL if cmp.held => { format!("Link flaps {:.1}/h back under {}/h threshold but within hysteresis band", per_hour, format_threshold(cfg.flaps_crit).unwrap_or_default()) },
+                                STATE_WARNING if cmp.held => { format!("Link flaps {:.1}/h back under {}/h threshold but within hysteresis band", per_hour, format_threshold(cfg.flaps_warn).unwrap_or_default()) },
+                                STATE_CRITICAL => { format!("Link flaps {:.1} times per hour, above critical threshold ({}/h)", per_hour, format_threshold(cfg.flaps_crit).unwrap_or_default()) },
+                                STATE_WARNING => { format!("Link flaps {:.1} times per hour, above warning threshold ({}/h)", per_hour, format_threshold(cfg.flaps_warn).unwrap_or_default()) },
+                                _ => { format!("Link flaps {:.1} times per hour", per_hour) },
+                            };
+                            let state = cmp.state;
+                            self.audit.push(cmp);
+                            match state {
+                                STATE_CRITICAL => { self.critical.push(Finding::with_values(FindingId::FlapsAboveThreshold, interface, message, format_threshold(cfg.flaps_crit).unwrap_or_default(), per_hour)); },
+                                STATE_WARNING => { self.warning.push(Finding::with_values(FindingId::FlapsAboveThreshold, interface, message, format_threshold(cfg.flaps_warn).unwrap_or_default(), per_hour)); },
+                                _ => { self.ok.push(Finding::new(FindingId::FlapsOk, interface, message)); },
+                            };
+                        },
+                        None => {
+                            self.ok.push(Finding::new(FindingId::CounterBaseline, interface, format!("carrier_changes counter is {}, no rate for this interval (first run or counter reset)", counter.value)));
+                        },
+                    };
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::FlapsUnavailable, interface, "Can't read carrier_changes, link flaps can't be checked".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_bandwidth(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // check throughput as percentage of the link speed
+        if cfg.bandwidth_warn.is_some() || cfg.bandwidth_crit.is_some() {
+            // virtual interfaces report no speed
+            let max_mbits = if ifs.speed > 0 { Some(ifs.speed as f64) } else { cfg.bandwidth_max };
+
+            for (counter_name, label) in &[("rx_bytes", "rx_mbits"), ("tx_bytes", "tx_mbits")] {
+                let counter = match counters::find(&ifs.counters, counter_name) {
+                    Some(v) => { v },
+                    None => { continue; },
+                };
+
+                let rate = match counter.rate {
+                    Some(v) => { v },
+                    None => {
+                        self.ok.push(Finding::new(FindingId::CounterBaseline, interface, format!("{} counter is {}, no rate for this interval (first run, counter reset or counter source changed)", counter_name, counter.value)));
+                        continue;
+                    },
+                };
+                let mbits = rate * 8.0 / 1_000_000.0;
+
+                let mut perf = PerfData::new(&format!("{}_{}", interface, label), mbits, "");
+                perf.min = Some(0.0);
+                perf.max = max_mbits;
+
+                match max_mbits {
+                    Some(max) => {
+                        perf.warn = format_threshold(cfg.bandwidth_warn.map(|p| max * p / 100.0));
+                        perf.crit = format_threshold(cfg.bandwidth_crit.map(|p| max * p / 100.0));
+
+                        let pct = mbits * 100.0 / max;
+                        let cmp = evaluate(cfg, ifs, &threshold::Threshold::above(cfg.bandwidth_warn, cfg.bandwidth_crit), &format!("{} percent of link speed", label), pct);
+                        let message = match cmp.state {
+                            STATE_CRITICAL if cmp.held => { format!("{} {:.1}% back under {}% threshold but within hysteresis band", label, pct, format_threshold(cfg.bandwidth_crit).unwrap_or_default()) },
+                            STATE_WARNING if cmp.held => { format!("{} {:.1}% back under {}% threshold but within hysteresis band", label, pct, format_threshold(cfg.bandwidth_warn).unwrap_or_default()) },
+                            _ => { format!("{} is {:.2} MBit/s ({:.1}% of {} MBit/s)", label, mbits, pct, max) },
+                        };
+                        let state = cmp.state;
+                        self.audit.push(cmp);
+                        match state {
+                            STATE_CRITICAL => { self.critical.push(Finding::with_values(FindingId::BandwidthAboveThreshold, interface, message, format_threshold(cfg.bandwidth_crit).unwrap_or_default(), pct)); },
+                            STATE_WARNING => { self.warning.push(Finding::with_values(FindingId::BandwidthAboveThreshold, interface, message, format_threshold(cfg.bandwidth_warn).unwrap_or_default(), pct)); },
+                            _ => { self.ok.push(Finding::new(FindingId::BandwidthOk, interface, message)); },
+                        };
+                    },
+                    None => {
+                        self.unknown.push(Finding::new(FindingId::BandwidthNoSpeed, interface, format!("Interface reports no link speed, can't check {} (use --bandwidth-max)", label)));
+                    },
+                };
+                self.perfdata.push(perf);
+            }
+        }
+    }
+
+    fn check_subnet_peers(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // check for other hosts in the assigned subnets
+        if cfg.probe_subnet_peers {
+            for p in &ifs.subnet_peers {
+                match (p.peer, &p.method) {
+                    (Some(peer), Some(method)) => {
+                        self.ok.push(Finding::new(FindingId::SubnetPeer, interface, format!("Subnet {} has peer {} ({})", p.network, peer, method)));
+                    },
+                    _ => {
+                        self.warning.push(Finding::new(FindingId::SubnetNoPeer, interface, format!("No peer found in subnet {}", p.network)));
+                    },
+                };
+            }
+        }
+    }
+
+    fn check_first_hop(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // check reachability of the default gateway(s)
+        if cfg.check_first_hop {
+            if ifs.first_hop.is_empty() {
+                self.unknown.push(Finding::new(FindingId::FirstHopNoGateway, interface, "No default gateway via this interface".to_string()));
+            }
+            for hop in &ifs.first_hop {
+                match hop.verdict {
+                    firsthop::VERDICT_ALIVE => {
+                        self.ok.push(Finding::with_values(FindingId::FirstHopAlive, interface, format!("Gateway {} alive ({})", hop.gateway, hop.detail), firsthop::VERDICT_ALIVE, hop.verdict));
+                    },
+                    firsthop::VERDICT_STALE => {
+                        self.warning.push(Finding::with_values(FindingId::FirstHopStale, interface, format!("Gateway {} not confirmed ({})", hop.gateway, hop.detail), firsthop::VERDICT_ALIVE, hop.verdict));
+                    },
+                    firsthop::VERDICT_DEAD => {
+                        self.critical.push(Finding::with_values(FindingId::FirstHopDead, interface, format!("Gateway {} not reachable ({})", hop.gateway, hop.detail), firsthop::VERDICT_ALIVE, hop.verdict));
+                    },
+                    _ => {
+                        self.unknown.push(Finding::with_values(FindingId::FirstHopUnknown, interface, format!("Can't check gateway {} ({})", hop.gateway, hop.detail), firsthop::VERDICT_ALIVE, hop.verdict));
+                    },
+                };
+
+                if let Some(rtt) = hop.rtt_ms {
+                    let mut perf = PerfData::new(&format!("{}_first_hop_{}_rtt", interface, hop.gateway), rtt, "ms");
+                    perf.min = Some(0.0);
+                    self.perfdata.push(perf);
+                }
+            }
+        }
+    }
+
+    fn check_gateway_probe(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // ask the gateway itself, a neighbor cache entry doesn't prove it answers now
+        if cfg.probe_gateway {
+            if ifs.gateway_probes.is_empty() {
+                self.unknown.push(Finding::new(FindingId::GatewayNone, interface, "No default gateway via this interface to probe".to_string()));
+            }
+            for hop in &ifs.gateway_probes {
+                match (hop.verdict, hop.rtt_ms) {
+                    (firsthop::VERDICT_ALIVE, Some(rtt)) if cfg.probe_warn_ms.map(|w| rtt > w).unwrap_or(false) => {
+                        self.warning.push(Finding::with_values(FindingId::GatewaySlow, interface, format!("Gateway {} answers slowly ({}, more than {}ms)", hop.gateway, hop.detail, cfg.probe_warn_ms.unwrap_or_default()), cfg.probe_warn_ms.unwrap_or_default(), rtt));
+                    },
+                    (firsthop::VERDICT_ALIVE, _) => {
+                        self.ok.push(Finding::with_values(FindingId::GatewayAlive, interface, format!("Gateway {} answers ({})", hop.gateway, hop.detail), firsthop::VERDICT_ALIVE, hop.verdict));
+                    },
+                    (firsthop::VERDICT_DEAD, _) => {
+                        self.critical.push(Finding::with_values(FindingId::GatewayDead, interface, format!("Gateway {} doesn't answer ({})", hop.gateway, hop.detail), firsthop::VERDICT_ALIVE, hop.verdict));
+                    },
+                    _ => {
+                        self.unknown.push(Finding::with_values(FindingId::GatewayUnknown, interface, format!("Can't probe gateway {} ({})", hop.gateway, hop.detail), firsthop::VERDICT_ALIVE, hop.verdict));
+                    },
+                };
+
+                if let Some(rtt) = hop.rtt_ms {
+                    let mut perf = PerfData::new(&format!("{}_gateway_{}_rtt", interface, hop.gateway), rtt, "ms");
+                    perf.warn = cfg.probe_warn_ms.map(|w| w.to_string());
+                    perf.min = Some(0.0);
+                    self.perfdata.push(perf);
+                }
+            }
+        }
+    }
+
+    fn check_uplink(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // every failed part of the uplink contract is a finding of its own
+        if let (Some(provider), Some(up)) = (cfg.uplink_check, &ifs.uplink) {
+            let assigned: Vec<String> = up.addresses.iter().map(|n| n.to_string()).collect();
+            match up.addresses.as_slice() {
+                [] => {
+                    self.critical.push(Finding::with_values(FindingId::UplinkNoAddress, interface, format!("No address from provider prefix {} assigned", provider), provider, ""));
+                },
+                [net] if !uplink::is_point_to_point(net) || uplink::peer_address(net).is_none() => {
+                    self.critical.push(Finding::with_values(FindingId::UplinkNotPointToPoint, interface, format!("Address {} from provider prefix {} is not a point-to-point address (/31 or host address of a /30)", net, provider), provider, net));
+                },
+                [_] => {},
+                _ => {
+                    self.critical.push(Finding::with_values(FindingId::UplinkAmbiguous, interface, format!("{} addresses from provider prefix {} assigned instead of one: {}", assigned.len(), provider, assigned.join(", ")), provider, assigned.join(",")));
+                },
+            };
+
+            if let Some(ref hop) = up.peer {
+                match hop.verdict {
+                    firsthop::VERDICT_ALIVE => {},
+                    firsthop::VERDICT_STALE => {
+                        self.warning.push(Finding::with_values(FindingId::UplinkPeerStale, interface, format!("Uplink peer {} not confirmed ({})", hop.gateway, hop.detail), firsthop::VERDICT_ALIVE, hop.verdict));
+                    },
+                    firsthop::VERDICT_DEAD => {
+                        self.critical.push(Finding::with_values(FindingId::UplinkPeerDead, interface, format!("Uplink peer {} not reachable ({})", hop.gateway, hop.detail), firsthop::VERDICT_ALIVE, hop.verdict));
+                    },
+                    _ => {
+                        self.unknown.push(Finding::with_values(FindingId::UplinkPeerUnknown, interface, format!("Can't check uplink peer {} ({})", hop.gateway, hop.detail), firsthop::VERDICT_ALIVE, hop.verdict));
+                    },
+                };
+                if !up.default_route {
+                    self.critical.push(Finding::with_values(FindingId::UplinkNoDefaultRoute, interface, format!("No default route via uplink peer {}", hop.gateway), hop.gateway, ""));
+                }
+                if hop.verdict == firsthop::VERDICT_ALIVE && up.default_route {
+                    self.ok.push(Finding::with_values(FindingId::UplinkOk, interface, format!("Uplink {} to peer {} alive ({}), default route via peer", assigned.join(","), hop.gateway, hop.detail), provider, assigned.join(",")));
+                }
+
+                if let Some(rtt) = hop.rtt_ms {
+                    let mut perf = PerfData::new(&format!("{}_uplink_peer_rtt", interface), rtt, "ms");
+                    perf.min = Some(0.0);
+                    self.perfdata.push(perf);
+                }
+            }
+        }
+    }
+
+    fn check_frame_sizes(&mut self, interface: &str, ifs: &InterfaceState) {
+        // sizes of the frames received during the sampling window
+        if let Some(ref histogram) = ifs.frame_sizes {
+            for ((bucket, _), frames) in framesize::BUCKETS.iter().zip(&histogram.frames) {
+                let mut perf = PerfData::new(&format!("{}_frames_{}", interface, bucket), *frames as f64, "");
+                perf.min = Some(0.0);
+                self.perfdata.push(perf);
+            }
+
+            // jumbo frames are expected if the MTU doesn't fit into a standard frame
+            if ifs.mtu > 1500 {
+                let total = histogram.total();
+                let jumbo = histogram.jumbo();
+                if total == 0 {
+                    self.ok.push(Finding::new(FindingId::FramesNoTraffic, interface, format!("No frames received within {:.0}s, can't check for jumbo frames", histogram.window)));
+                } else if jumbo == 0 {
+                    self.warning.push(Finding::with_values(FindingId::FramesNoJumbo, interface, format!("MTU size is {} but none of {} frames received within {:.0}s was larger than {} bytes", ifs.mtu, total, histogram.window, framesize::MAX_STANDARD_FRAME), format!("> {}", framesize::MAX_STANDARD_FRAME), jumbo));
+                } else {
+                    self.ok.push(Finding::with_values(FindingId::FramesJumbo, interface, format!("{} of {} frames received within {:.0}s were jumbo frames", jumbo, total, histogram.window), format!("> {}", framesize::MAX_STANDARD_FRAME), jumbo));
+                }
+            }
+        }
+    }
+
+    fn check_changes(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // report metrics which changed since the last run
+        if !cfg.alert_on_change.is_empty() {
+            let now = unix_now();
+            if ifs.changes.is_empty() {
+                self.ok.push(Finding::new(FindingId::MetricUnchanged, interface, format!("No change of {}", cfg.alert_on_change.join(", "))));
+            }
+            for c in &ifs.changes {
+                self.warning.push(Finding::with_values(FindingId::MetricChanged, interface, format!("{} changed from {} to {} (previous value last observed {} ago)", c.metric, c.previous, c.current, changes::format_age(now.saturating_sub(c.last_seen))), &c.previous, &c.current));
+            }
+        }
+    }
+
+    fn check_backends(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // compare sysfs (and getifaddrs for addresses) with rtnetlink, link speed isn't
+        // available via rtnetlink
+        if cfg.cross_check_backends && ifs.source == SOURCE_SYSFS {
+            match ifs.netlink {
+                Some(ref nl) => {
+                    let mut mismatches: Vec<String> = Vec::new();
+                    if nl.operstate != ifs.operstate {
+                        mismatches.push(format!("operstate sysfs={} netlink={}", ifs.operstate, nl.operstate));
+                    }
+                    if nl.mtu != ifs.mtu {
+                        mismatches.push(format!("MTU sysfs={} netlink={}", ifs.mtu, nl.mtu));
+                    }
+
+                    let mut sysfs_ips: Vec<String> = ifs.ips.iter().map(|n| n.to_string()).collect();
+                    let mut netlink_ips: Vec<String> = nl.ips.iter().map(|n| n.to_string()).collect();
+                    sysfs_ips.sort();
+                    netlink_ips.sort();
+                    if sysfs_ips != netlink_ips {
+                        mismatches.push(format!("addresses sysfs=[{}] netlink=[{}]", sysfs_ips.join(","), netlink_ips.join(",")));
+                    }
+
+                    if mismatches.is_empty() {
+                        self.ok.push(Finding::new(FindingId::BackendAgree, interface, "sysfs and netlink agree".to_string()));
+                    } else {
+                        self.warning.push(Finding::new(FindingId::BackendMismatch, interface, format!("sysfs and netlink disagree: {}", mismatches.join(", "))));
+                    }
+                },
+                None => {
+                    self.ok.push(Finding::new(FindingId::BackendUnavailable, interface, "netlink not available, backends not cross-checked".to_string()));
+                },
+            };
+        }
+    }
+
+    fn check_napi_balance(&mut self, cfg: &Configuration, interface: &str, ifs: &InterfaceState) {
+        // check CPU affinity of threaded NAPI polling
+        if cfg.check_napi_balance {
+            match ifs.local_cpus {
+                Some(ref local) => {
+                    if ifs.napi_threads.is_empty() {
+                        self.ok.push(Finding::new(FindingId::NapiNotThreaded, interface, "NAPI is not threaded, polling runs on the interrupted CPU".to_string()));
+                    } else {
+                        let mut remote = Vec::new();
+                        for t in &ifs.napi_threads {
+                            let foreign: Vec<String> = t.cpus.iter().filter(|c| !local.contains(c)).map(|c| c.to_string()).collect();
+                            if !foreign.is_empty() {
+                                remote.push(format!("{} on CPU {}", t.name, foreign.join(",")));
+                            }
+                        }
+                        if remote.is_empty() {
+                            self.ok.push(Finding::with_values(FindingId::NapiLocal, interface, format!("All {} NAPI thread(s) are bound to local CPUs", ifs.napi_threads.len()), format_cpu_list(local), format_cpu_list(local)));
+                        } else {
+                            self.warning.push(Finding::with_values(FindingId::NapiRemoteCpu, interface, format!("NAPI polling allowed on non-local CPUs (local CPUs are {}): {}", format_cpu_list(local), remote.join(", ")), format_cpu_list(local), remote.join(", ")));
+                        }
+                    }
+                },
+                None => {
+                    self.unknown.push(Finding::new(FindingId::NapiNoLocality, interface, "Can't determine local CPUs of the network device".to_string()));
+                },
+            };
+        }
+    }
+
+    // collect the findings of another interface, the worst state wins when printing
+    pub fn merge(&mut self, other: NagiosStatus) {
+        self.critical.extend(other.critical);
+        self.warning.extend(other.warning);
+        self.ok.extend(other.ok);
+        self.unknown.extend(other.unknown);
+        self.notes.extend(other.notes);
+        self.perfdata.extend(other.perfdata);
+        self.audit.extend(other.audit);
+        self.details.extend(other.details);
+    }
+
+    // demote acknowledged findings to OK, expired acknowledgments are ignored
+    pub fn apply_acks(&mut self, acks: &[ack::Ack], today: i64) {
+        let mut lapsed: Vec<Finding> = Vec::new();
+
+        for findings in [&mut self.critical, &mut self.warning, &mut self.unknown] {
+            let mut kept: Vec<Finding> = Vec::new();
+            for mut f in findings.drain(..) {
+                let matching: Vec<&ack::Ack> = acks.iter().filter(|a| a.matches(f.id.as_str(), &f.interface)).collect();
+                match matching.iter().find(|a| !a.is_expired(today)) {
+                    Some(a) => {
+                        f.message = format!("{} {}", f.message, a.annotation());
+                        self.ok.push(f);
+                    },
+                    None => {
+                        for a in matching {
+                            lapsed.push(Finding::new(FindingId::AckExpired, &f.interface, format!("Acknowledgment of {} expired on {}", a.id, a.until.clone().unwrap_or_default())));
+                        }
+                        kept.push(f);
+                    },
+                };
+            }
+            *findings = kept;
+        }
+
+        self.warning.extend(lapsed);
+    }
+
+    // interfaces with at least one non-OK finding
+    pub fn problem_interfaces(&self) -> Vec<String> {
+        let mut result: Vec<String> = Vec::new();
+        for f in self.critical.iter().chain(self.warning.iter()).chain(self.unknown.iter()) {
+            if !result.contains(&f.interface) {
+                result.push(f.interface.clone());
+            }
+        }
+        result
+    }
+
+    // state of a single interface when several interfaces are checked
+    pub fn interface_state(&self, interface: &str) -> i32 {
+        let has = |findings: &[Finding]| findings.iter().any(|f| f.interface == interface);
+
+        if has(&self.unknown) {
+            return STATE_UNKNOWN;
+        };
+        if has(&self.critical) {
+            return STATE_CRITICAL;
+        };
+        if has(&self.warning) {
+            return STATE_WARNING;
+        };
+        if has(&self.ok) {
+            return STATE_OK;
+        };
+        STATE_UNKNOWN
+    }
+
+    // exit code, the worst state wins
+    pub fn state(&self) -> i32 {
+        if !self.unknown.is_empty() {
+            return STATE_UNKNOWN;
+        };
+        if !self.critical.is_empty() {
+            return STATE_CRITICAL;
+        };
+        if !self.warning.is_empty() {
+            return STATE_WARNING;
+        };
+        if !self.ok.is_empty() {
+            return STATE_OK;
+        };
+        STATE_UNKNOWN
+    }
+}
+
+// state of a value compared against optional upper warning and critical thresholds
+// with --hysteresis the state reported by the previous run is kept until the value clears the threshold
+fn evaluate(cfg: &Configuration, ifs: &InterfaceState, threshold: &threshold::Threshold, metric: &str, value: f64) -> threshold::Comparison {
+    match (cfg.hysteresis, ifs.reported_states.get(&hysteresis::key(metric))) {
+        (Some(margin), Some(previous)) => { threshold.evaluate_with_hysteresis(&ifs.name, metric, value, *previous, margin) },
+        _ => { threshold.evaluate(&ifs.name, metric, value) },
+    }
+}
+
+// a speed above the requested speed is never critical, a speed inside an excluded
+// range (@start:end) always is with -C/--critical
+pub fn speed_threshold(cfg: &Configuration) -> (threshold::Threshold, String) {
+    let speed_thresholds = cfg.speed_warn.is_some() || cfg.speed_crit.is_some();
+    match cfg.speed {
+        _ if !cfg.allowed_speeds.is_empty() => {
+            let allowed = cfg.allowed_speeds.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(",");
+            (threshold::Threshold::one_of(cfg.allowed_speeds.iter().map(|v| *v as f64).collect(), cfg.report_critical), allowed)
+        },
+        Some(speed) if !speed_thresholds => {
+            let crit = if !cfg.report_critical {
+                None
+            } else if speed.inside {
+                Some(threshold::Bound::Range(speed))
+            } else {
+                speed.start.map(threshold::Bound::Below)
+            };
+            (threshold::Threshold{ warn: Some(threshold::Bound::Range(speed)), crit }, speed.to_string())
+        },
+        _ => {
+            let t = threshold::Threshold::below(cfg.speed_warn.map(|v| v as f64), cfg.speed_crit.map(|v| v as f64));
+            let expected = t.to_string();
+            (t, expected)
+        },
+    }
+}
+
+// "Interface is up, but speed could not be read: Permission denied (os error 13)"
+// virtual devices report no speed and duplex, unless a speed was requested explicitly
+fn virtual_interface(cfg: &Configuration, ifs: &InterfaceState) -> bool {
+    ifs.loopback || ifs.wireless.is_some() || cfg.virtual_interface.unwrap_or(ifs.virtual_device)
+}
+
+fn link_local_ipv4() -> ipnetwork::Ipv4Network {
+    "169.254.0.0/16".parse().unwrap()
+}
+
+fn link_local_ipv6() -> ipnetwork::Ipv6Network {
+    "fe80::/10".parse().unwrap()
+}
+
+fn unique_local_ipv6() -> ipnetwork::Ipv6Network {
+    "fc00::/7".parse().unwrap()
+}
+
+fn unreadable(ifs: &InterfaceState, attribute: &str, err: &str) -> String {
+    format!("Interface is {}, but {} could not be read: {}", ifs.operstate, attribute, err)
+}
+
+// 10000 as 10 GBit/s and 2500 as 2.5 GBit/s, speeds not a multiple of 100 MBit/s stay in MBit/s
+fn format_speed(mbit: f64) -> String {
+    if mbit >= 1000.0 && mbit % 100.0 == 0.0 {
+        format!("{} GBit/s", mbit / 1000.0)
+    } else {
+        format!("{} MBit/s", mbit)
+    }
+}
+
+fn format_threshold(value: Option<f64>) -> Option<String> {
+    value.map(|v| v.to_string())
+}
+
+// UNKNOWN naming the probes which didn't complete before the global deadline
+pub fn probe_timeout(interface: &str, timeout: Duration, incomplete: &[String]) -> Option<Finding> {
+    if incomplete.is_empty() {
+        return None;
+    }
+    Some(Finding::new(FindingId::ProbeTimeout, interface, format!("Timeout after {}s, probes not completed: {}", timeout.as_secs(), incomplete.join(", "))))
+}
+
+// unbound workqueues are shared by all interfaces and checked once. A cpumask narrowed from
+// the default should only contain CPUs local to the checked interfaces.
+pub fn wq_balance_status(workqueues: &Result<Workqueues, String>, states: &[InterfaceState]) -> NagiosStatus {
+    let mut status = NagiosStatus::default();
+    let checked = states.iter().map(|s| s.name.as_str()).collect::<Vec<&str>>().join(",");
+
+    let workqueues = match workqueues {
+        Ok(v) => { v },
+        Err(e) => {
+            status.unknown.push(Finding::new(FindingId::WqUnavailable, &checked, format!("Can't read unbound workqueues: {}", e)));
+            return status;
+        },
+    };
+
+    let mut local: Vec<u32> = states.iter().filter_map(|s| s.local_cpus.as_ref()).flatten().copied().collect();
+    local.sort_unstable();
+    local.dedup();
+    if local.is_empty() {
+        status.unknown.push(Finding::new(FindingId::WqNoLocality, &checked, "Can't determine local CPUs of the network device".to_string()));
+        return status;
+    }
+
+    let narrowed: Vec<&Workqueue> = workqueues.queues.iter().filter(|w| w.cpus != workqueues.default_cpus).collect();
+    if narrowed.is_empty() {
+        status.ok.push(Finding::new(FindingId::WqDefault, &checked, format!("All {} unbound workqueue(s) use the default cpumask", workqueues.queues.len())));
+        return status;
+    }
+
+    let mut remote = Vec::new();
+    for w in &narrowed {
+        let foreign: Vec<u32> = w.cpus.iter().filter(|c| !local.contains(c)).copied().collect();
+        if !foreign.is_empty() {
+            remote.push(format!("{} on CPU {}", w.name, format_cpu_list(&foreign)));
+        }
+    }
+    if remote.is_empty() {
+        status.ok.push(Finding::with_values(FindingId::WqLocal, &checked, format!("All {} narrowed workqueue(s) are bound to local CPUs", narrowed.len()), format_cpu_list(&local), format_cpu_list(&local)));
+    } else {
+        status.warning.push(Finding::with_values(FindingId::WqRemoteCpu, &checked, format!("Workqueues narrowed to non-local CPUs (local CPUs are {}): {}", format_cpu_list(&local), remote.join(", ")), format_cpu_list(&local), remote.join(", ")));
+    }
+    status
+}
+
+// findings for the VLANs of the --check-vlans-of parent as a whole, the VLAN
+// interfaces themselves are checked like any other interface
+pub fn vlan_status(cfg: &Configuration, parent: &str, vlans: &[vlan::Vlan], states: &[InterfaceState]) -> NagiosStatus {
+    let mut status = NagiosStatus::default();
+
+    if let Some(ref expected) = cfg.expect_vlan_set {
+        let found: Vec<u16> = vlans.iter().map(|v| v.id).collect();
+        let missing: Vec<String> = expected.iter().filter(|i| !found.contains(i)).map(|i| i.to_string()).collect();
+        let extra: Vec<String> = found.iter().filter(|i| !expected.contains(i)).map(|i| i.to_string()).collect();
+        let expected_list = expected.iter().map(|i| i.to_string()).collect::<Vec<String>>().join(",");
+        let found_list = found.iter().map(|i| i.to_string()).collect::<Vec<String>>().join(",");
+
+        if !missing.is_empty() {
+            status.critical.push(Finding::with_values(FindingId::VlanSetMismatch, parent, format!("VLAN(s) missing: {}", missing.join(", ")), &expected_list, &found_list));
+        }
+        if !extra.is_empty() {
+            status.warning.push(Finding::with_values(FindingId::VlanSetMismatch, parent, format!("Unexpected VLAN(s): {}", extra.join(", ")), &expected_list, &found_list));
+        }
+        if missing.is_empty() && extra.is_empty() {
+            status.ok.push(Finding::with_values(FindingId::VlanSetOk, parent, format!("VLANs present: {}", found_list), &expected_list, &found_list));
+        }
+    }
+
+    // a VLAN can't be up without its parent, but it can be down on an up parent
+    let parent_operstate = match fs::read_to_string(format!("{}/{}/operstate", cfg.sysfs_root, parent)) {
+        Ok(s) => { s.trim().to_string() },
+        Err(_) => {
+            status.unknown.push(Finding::new(FindingId::InterfaceMissing, parent, "VLAN parent interface not found".to_string()));
+            return status;
+        },
+    };
+
+    for v in vlans {
+        let ifs = match states.iter().find(|s| s.name == v.name && s.present) {
+            Some(s) => { s },
+            None => { continue; },
+        };
+        if ifs.operstate == parent_operstate {
+            status.ok.push(Finding::with_values(FindingId::VlanOperstateOk, &v.name, format!("VLAN {} has the state {} of {}", v.id, ifs.operstate, parent), &parent_operstate, &ifs.operstate));
+        } else if cfg.report_critical {
+            status.critical.push(Finding::with_values(FindingId::VlanOperstateMismatch, &v.name, format!("VLAN {} is {} but {} is {}", v.id, ifs.operstate, parent, parent_operstate), &parent_operstate, &ifs.operstate));
+        } else {
+            status.warning.push(Finding::with_values(FindingId::VlanOperstateMismatch, &v.name, format!("VLAN {} is {} but {} is {}", v.id, ifs.operstate, parent, parent_operstate), &parent_operstate, &ifs.operstate));
+        }
+    }
+
+    status
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::config;
+    use super::super::interface::SysfsFixture;
+
+    fn configuration(args: &[&str]) -> Configuration {
+        let mut argv = vec!["check_ethernet".to_string(), "-i".to_string(), "fix0".to_string()];
+        argv.extend(args.iter().map(|a| a.to_string()));
+        Configuration::new(&argv, &config::options()).unwrap()
+    }
+
+    fn physical(operstate: &str, speed: &str, duplex: &str) -> SysfsFixture {
+        SysfsFixture::new()
+            .file("/sys/class/net/fix0/operstate", operstate)
+            .file("/sys/class/net/fix0/mtu", "1500")
+            .file("/sys/class/net/fix0/speed", speed)
+            .file("/sys/class/net/fix0/duplex", duplex)
+            .file("/sys/class/net/fix0/carrier", "1")
+            .file("/sys/class/net/fix0/device/vendor", "0x8086")
+    }
+
+    // a physical interface with a 100 MBit/s full duplex link
+    fn link(operstate: &str) -> SysfsFixture {
+        physical(operstate, "100", "full")
+    }
+
+    fn status(args: &[&str], fixture: &SysfsFixture) -> NagiosStatus {
+        let cfg = configuration(args);
+        let ifs = InterfaceState::new(&cfg, "fix0", fixture).unwrap();
+        NagiosStatus::new(&cfg, "fix0", &ifs)
+    }
+
+    fn ids(findings: &[Finding]) -> Vec<FindingId> {
+        findings.iter().map(|f| f.id).collect()
+    }
+
+    #[test]
+    fn missing_interface_stops_the_check() {
+        let st = status(&["-m", "9000"], &SysfsFixture::new());
+        assert_eq!(ids(&st.critical), vec![FindingId::InterfaceMissing]);
+        assert!(st.warning.is_empty() && st.ok.is_empty() && st.unknown.is_empty());
+    }
+
+    #[test]
+    fn missing_interface_state_is_configurable() {
+        let st = status(&["--missing-state", "ok"], &SysfsFixture::new());
+        assert_eq!(ids(&st.ok), vec![FindingId::InterfaceMissing]);
+        assert!(st.critical.is_empty());
+    }
+
+    #[test]
+    fn down_interface_stops_the_check() {
+        let st = status(&["-m", "9000"], &link("down"));
+        assert_eq!(ids(&st.critical), vec![FindingId::LinkDown]);
+        assert!(st.warning.is_empty());
+    }
+
+    #[test]
+    fn link_local_addresses_only() {
+        let cfg = configuration(&["-a", "ip"]);
+        let mut ifs = InterfaceState::new(&cfg, "fix0", &link("up")).unwrap();
+        ifs.ips = vec!["fe80::1/64".parse().unwrap(), "169.254.1.1/16".parse().unwrap()];
+        let st = NagiosStatus::new(&cfg, "fix0", &ifs);
+        assert_eq!(ids(&st.critical), vec![FindingId::AddrLinkLocalOnly]);
+
+        ifs.ips.push("192.0.2.1/24".parse().unwrap());
+        let st = NagiosStatus::new(&cfg, "fix0", &ifs);
+        assert!(st.critical.is_empty());
+        assert!(ids(&st.ok).contains(&FindingId::AddrOk));
+    }
+
+    #[test]
+    fn speed_mismatch_is_a_warning() {
+        let st = status(&["-s", "1000"], &link("up"));
+        assert_eq!(ids(&st.warning), vec![FindingId::SpeedBelowExpected]);
+        assert!(st.critical.is_empty());
+        assert_eq!(st.warning[0].expected.as_deref(), Some("1000"));
+        assert_eq!(st.warning[0].actual.as_deref(), Some("100"));
+    }
+
+    #[test]
+    fn speed_mismatch_is_critical_with_report_critical() {
+        let st = status(&["-s", "1000", "-C"], &link("up"));
+        assert_eq!(ids(&st.critical), vec![FindingId::SpeedBelowExpected]);
+        assert!(st.warning.is_empty());
+    }
+
+    fn has(findings: &[Finding], id: FindingId) -> bool {
+        findings.iter().any(|f| f.id == id)
+    }
+
+    // the findings of the link state alone, speed and duplex are not checked
+    fn link_state(args: &[&str], fixture: &SysfsFixture) -> (Vec<FindingId>, Vec<FindingId>, Vec<FindingId>, Vec<FindingId>) {
+        let mut argv = vec!["-s", "0"];
+        argv.extend_from_slice(args);
+        let st = status(&argv, fixture);
+        (ids(&st.critical), ids(&st.warning), ids(&st.unknown), ids(&st.ok))
+    }
+
+    #[test]
+    fn operstate_up() {
+        assert_eq!(link_state(&[], &link("up")), (vec![], vec![], vec![], vec![FindingId::LinkUp]));
+    }
+
+    #[test]
+    fn operstate_down() {
+        assert_eq!(link_state(&[], &link("down")), (vec![FindingId::LinkDown], vec![], vec![], vec![]));
+    }
+
+    #[test]
+    fn operstate_lowerlayerdown() {
+        let st = status(&[], &link("lowerlayerdown"));
+        assert_eq!(ids(&st.critical), vec![FindingId::LinkLowerLayerDown]);
+        assert_eq!(st.critical[0].message, "Interface is LOWERLAYERDOWN, the device it is stacked on has no link");
+    }
+
+    #[test]
+    fn operstate_dormant() {
+        assert_eq!(link_state(&[], &link("dormant")), (vec![], vec![FindingId::LinkDormant], vec![], vec![]));
+    }
+
+    #[test]
+    fn operstate_testing_and_notpresent() {
+        for operstate in &["testing", "notpresent"] {
+            let st = status(&[], &link(operstate));
+            assert_eq!(ids(&st.unknown), vec![FindingId::LinkStateUnknown]);
+            assert_eq!(st.unknown[0].message, format!("Interface is {}", operstate));
+            // not changed by --operstate-unknown-is
+            let st = status(&["--operstate-unknown-is", "ok"], &link(operstate));
+            assert_eq!(ids(&st.unknown), vec![FindingId::LinkStateUnknown]);
+        }
+    }
+
+    #[test]
+    fn operstate_unknown() {
+        assert_eq!(link_state(&[], &link("unknown")), (vec![], vec![], vec![FindingId::LinkStateUnknown], vec![]));
+        assert_eq!(link_state(&["--operstate-unknown-is", "ok"], &link("unknown")), (vec![], vec![], vec![], vec![FindingId::LinkStateUnknown]));
+        assert_eq!(link_state(&["--operstate-unknown-is", "warning"], &link("unknown")), (vec![], vec![FindingId::LinkStateUnknown], vec![], vec![]));
+        assert_eq!(link_state(&["--operstate-unknown-is", "critical"], &link("unknown")), (vec![FindingId::LinkStateUnknown], vec![], vec![], vec![]));
+    }
+
+    #[test]
+    fn operstate_unknown_of_loopback_is_up() {
+        let lo = SysfsFixture::new()
+            .file("/sys/class/net/fix0/operstate", "unknown")
+            .file("/sys/class/net/fix0/mtu", "65536")
+            .file("/sys/class/net/fix0/flags", "0x9")
+            .file("/sys/class/net/fix0/carrier", "1");
+        assert_eq!(link_state(&[], &lo), (vec![], vec![], vec![], vec![FindingId::LinkUp]));
+    }
+
+    #[test]
+    fn duplex_only() {
+        let st = status(&["-s", ":full"], &physical("up", "100", "full"));
+        assert!(has(&st.ok, FindingId::DuplexOk));
+        assert!(!has(&st.ok, FindingId::SpeedOk) && st.warning.is_empty() && st.audit.is_empty());
+
+        let st = status(&["-s", ":full"], &physical("up", "100", "half"));
+        assert_eq!(ids(&st.warning), vec![FindingId::DuplexMismatch]);
+        assert_eq!(st.warning[0].message, "Negotiated duplex mode is half instead of full");
+
+        let st = status(&["-s", ":full", "-C"], &physical("up", "100", "half"));
+        assert_eq!(ids(&st.critical), vec![FindingId::DuplexMismatch]);
+    }
+
+    #[test]
+    fn minimal_speed() {
+        let st = status(&["-s", "1000:"], &physical("up", "100", "full"));
+        assert_eq!(ids(&st.warning), vec![FindingId::SpeedBelowExpected]);
+        assert_eq!(st.warning[0].message, "Negotiated interface speed (100 MBit/s) is below minimal interface speed (1 GBit/s)");
+        // the link mode defaults to full duplex
+        assert!(has(&st.ok, FindingId::DuplexOk));
+
+        let st = status(&["-s", "1000:"], &physical("up", "10000", "full"));
+        assert!(st.warning.is_empty() && st.critical.is_empty());
+        assert!(has(&st.ok, FindingId::SpeedOk));
+    }
+
+    #[test]
+    fn duplex_option_without_speed() {
+        let st = status(&["--duplex", "half"], &physical("up", "1000", "full"));
+        assert_eq!(ids(&st.warning), vec![FindingId::DuplexMismatch]);
+        assert_eq!(st.warning[0].message, "Negotiated duplex mode is full instead of half");
+
+        let st = status(&["--duplex", "any"], &physical("up", "1000", "half"));
+        assert!(!has(&st.ok, FindingId::DuplexOk) && st.warning.is_empty());
+    }
+
+    #[test]
+    fn duplex_option_with_speed() {
+        let st = status(&["--duplex", "full", "-s", "100"], &physical("up", "100", "full"));
+        assert!(has(&st.ok, FindingId::SpeedOk) && has(&st.ok, FindingId::DuplexOk));
+        assert!(st.warning.is_empty());
+
+        let st = status(&["--duplex", "full", "-s", "1000"], &physical("up", "100", "half"));
+        assert_eq!(ids(&st.warning), vec![FindingId::SpeedBelowExpected, FindingId::DuplexMismatch]);
+    }
+
+    #[test]
+    fn duplex_option_and_link_mode_in_state() {
+        let argv: Vec<String> = ["check_ethernet", "-i", "fix0", "--duplex", "full", "-s", "100:half"].iter().map(|a| a.to_string()).collect();
+        assert!(Configuration::new(&argv, &config::options()).is_err());
+    }
+
+    fn audit(st: &NagiosStatus) -> Vec<String> {
+        st.audit.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn audit_lines_of_speed_and_mtu() {
+        let st = status(&["-s", "1000", "-m", "1500:9000"], &link("up"));
+        assert_eq!(audit(&st), vec![
+            "fix0 speed: observed 100, threshold warning if != 1000, violates != 1000 -> WARNING".to_string(),
+            "fix0 mtu: observed 1500, threshold warning if outside 1500:9000, satisfies all bounds -> OK".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn audit_line_of_address_count() {
+        let cfg = configuration(&["-a", "ipv4:2", "-s", "100"]);
+        let mut ifs = InterfaceState::new(&cfg, "fix0", &link("up")).unwrap();
+        ifs.ips = vec!["192.0.2.1/24".parse().unwrap(), "fe80::1/64".parse().unwrap()];
+        let st = NagiosStatus::new(&cfg, "fix0", &ifs);
+        assert_eq!(audit(&st), vec![
+            "fix0 speed: observed 100, threshold warning if != 100, satisfies all bounds -> OK".to_string(),
+            "fix0 non link local ipv4 addresses: observed 1, threshold warning if < 2, violates < 2 -> WARNING".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn vlan_needs_exactly_one_ipv4_address() {
+        let argv: Vec<String> = ["check_ethernet", "--check-vlans-of", "eth0", "-s", "0", "--addr-count=ipv4=1"].iter().map(|a| a.to_string()).collect();
+        let cfg = Configuration::new(&argv, &config::options()).unwrap();
+        let mut ifs = InterfaceState::new(&cfg, "fix0", &link("up")).unwrap();
+        ifs.ips = vec!["192.0.2.1/24".parse().unwrap()];
+        assert!(has(&NagiosStatus::new(&cfg, "fix0", &ifs).ok, FindingId::AddrCountOk));
+
+        ifs.ips.push("192.0.2.2/24".parse().unwrap());
+        assert!(has(&NagiosStatus::new(&cfg, "fix0", &ifs).warning, FindingId::AddrCountAbove));
+        ifs.ips.clear();
+        assert!(has(&NagiosStatus::new(&cfg, "fix0", &ifs).critical, FindingId::AddrCountBelow));
+    }
+
+    fn workqueues(default: &[u32], queues: &[(&str, &[u32])]) -> Result<Workqueues, String> {
+        Ok(Workqueues{
+            default_cpus: default.to_vec(),
+            queues: queues.iter().map(|(name, cpus)| Workqueue{ name: name.to_string(), cpus: cpus.to_vec() }).collect(),
+        })
+    }
+
+    fn local_state(local_cpus: &[u32]) -> InterfaceState {
+        let cfg = configuration(&["-s", "100", "--check-wq-balance"]);
+        let fixture = link("up").file("/sys/class/net/fix0/device/local_cpulist", &format_cpu_list(local_cpus));
+        InterfaceState::new(&cfg, "fix0", &fixture).unwrap()
+    }
+
+    fn state_without_locality() -> InterfaceState {
+        InterfaceState::new(&configuration(&["-s", "100", "--check-wq-balance"]), "fix0", &link("up")).unwrap()
+    }
+
+    #[test]
+    fn workqueues_with_the_default_cpumask() {
+        let all: &[u32] = &[0, 1, 2, 3, 4, 5, 6, 7];
+        let st = wq_balance_status(&workqueues(all, &[("writeback", all), ("netns", all)]), &[local_state(&[0, 1, 2, 3])]);
+        assert_eq!(ids(&st.ok), vec![FindingId::WqDefault]);
+        assert!(st.warning.is_empty());
+    }
+
+    #[test]
+    fn workqueues_narrowed_to_remote_cpus() {
+        let all: &[u32] = &[0, 1, 2, 3, 4, 5, 6, 7];
+        let st = wq_balance_status(&workqueues(all, &[("writeback", all), ("netns", &[4, 5])]), &[local_state(&[0, 1, 2, 3])]);
+        assert_eq!(ids(&st.warning), vec![FindingId::WqRemoteCpu]);
+        assert_eq!(st.warning[0].message, "Workqueues narrowed to non-local CPUs (local CPUs are 0-3): netns on CPU 4-5");
+
+        let st = wq_balance_status(&workqueues(all, &[("netns", &[2, 3])]), &[local_state(&[0, 1, 2, 3])]);
+        assert_eq!(ids(&st.ok), vec![FindingId::WqLocal]);
+    }
+
+    #[test]
+    fn workqueues_without_locality() {
+        let all: &[u32] = &[0, 1];
+        let st = wq_balance_status(&workqueues(all, &[("netns", &[1])]), &[state_without_locality()]);
+        assert_eq!(ids(&st.unknown), vec![FindingId::WqNoLocality]);
+
+        let st = wq_balance_status(&Err("not mounted".to_string()), &[state_without_locality()]);
+        assert_eq!(ids(&st.unknown), vec![FindingId::WqUnavailable]);
+    }
+
+    #[test]
+    fn finding_ids_are_unique() {
+        let mut seen = std::collections::HashSet::new();
+        for id in FindingId::ALL {
+            let json = serde_json::to_string(id).unwrap();
+            assert_eq!(json, format!("\"{}\"", id.as_str()));
+            assert!(seen.insert(json), "duplicate finding id {}", id.as_str());
+        }
+    }
+
+    #[test]
+    fn finding_ids_are_dotted_lower_case() {
+        for id in FindingId::ALL {
+            let name = id.as_str();
+            assert!(name.contains('.') && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '_'), "{}", name);
+        }
+    }
+
+    #[test]
+    fn matching_speed_is_ok() {
+        let st = status(&["-s", "100"], &link("up"));
+        assert!(st.warning.is_empty() && st.critical.is_empty());
+        assert!(ids(&st.ok).contains(&FindingId::SpeedOk));
+    }
+}
@@ -0,0 +1,137 @@
+// State of team (libteam) interfaces. The ports are the interfaces whose master is
+// the team device, the runner is asked from teamd over its control socket.
+use super::error::CheckError;
+use super::interface::SysfsReader;
+use super::netlink;
+
+use std::io;
+use std::mem;
+
+// teamd listens on /var/run/teamd/<if>.sock, the same socket teamdctl uses
+const TEAMD_RUN_DIR: &str = "/var/run/teamd";
+const TEAMD_TIMEOUT: u64 = 5;
+// a state dump of a team with many ports easily exceeds a page
+const TEAMD_REPLY_SIZE: usize = 256 * 1024;
+
+pub const RUNNERS: &[&str] = &["activebackup", "broadcast", "lacp", "loadbalance", "random", "roundrobin"];
+
+#[derive(Serialize)]
+pub struct Port {
+    pub name: String,
+    // operstate is up and a carrier is present
+    pub up: bool,
+}
+
+// the team driver has no sysfs directory of its own on most kernels, the link kind
+// from netlink is the fallback
+pub fn is_team(sysfs: &dyn SysfsReader, sysfs_path: &str, ifindex: u32) -> bool {
+    if sysfs.read_dir(&format!("{}/team", sysfs_path)).is_ok() {
+        return true;
+    }
+    match netlink::link_get(ifindex) {
+        Ok(l) => { l.kind.as_deref() == Some("team") },
+        Err(_) => { false },
+    }
+}
+
+// sorted by name
+pub fn read_ports(sysfs: &dyn SysfsReader, sysfs_root: &str, interface: &str) -> Result<Vec<Port>, CheckError> {
+    let entries = match sysfs.read_dir(sysfs_root) {
+        Ok(v) => { v },
+        Err(e) => { return Err(CheckError::io(sysfs_root, e)); },
+    };
+
+    let mut ports = Vec::new();
+    for name in entries {
+        let master = match sysfs.read_link(&format!("{}/{}/master", sysfs_root, name)) {
+            Ok(p) => { p.file_name().map(|n| n.to_string_lossy().to_string()) },
+            Err(_) => { None },
+        };
+        if master.as_deref() != Some(interface) {
+            continue;
+        }
+
+        // a port without carrier or with unreadable state counts as down
+        let operstate = sysfs.read_to_string(&format!("{}/{}/operstate", sysfs_root, name)).unwrap_or_default();
+        let carrier = sysfs.read_to_string(&format!("{}/{}/carrier", sysfs_root, name)).unwrap_or_default();
+        ports.push(Port{ name, up: operstate.trim() == "up" && carrier.trim() == "1" });
+    }
+
+    ports.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(ports)
+}
+
+// runner name from the "setup" section of the teamd state dump
+pub fn read_runner(interface: &str) -> Result<String, CheckError> {
+    let path = format!("{}/{}.sock", TEAMD_RUN_DIR, interface);
+    let dump = match state_dump(&path) {
+        Ok(v) => { v },
+        Err(e) => { return Err(CheckError::io(&path, e)); },
+    };
+
+    let state: serde_json::Value = match serde_json::from_str(&dump) {
+        Ok(v) => { v },
+        Err(_) => { return Err(CheckError::parse(&format!("teamd state dump from {}", path), &dump)); },
+    };
+    match state.pointer("/setup/runner_name").and_then(|r| r.as_str()) {
+        Some(r) => { Ok(r.to_string()) },
+        None => { Err(CheckError::Missing(format!("Can't find the runner name in the teamd state dump from {}", path))) },
+    }
+}
+
+struct Socket {
+    fd: libc::c_int,
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+// "REQUEST\nStateDump\n" is answered by "REPLY_SUCCESS\n<json>" or "REPLY_ERROR\n<message>"
+fn state_dump(path: &str) -> io::Result<String> {
+    let fd = unsafe { libc::socket(libc::AF_UNIX, libc::SOCK_SEQPACKET | libc::SOCK_CLOEXEC, 0) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let sock = Socket{ fd };
+
+    let timeout = libc::timeval{ tv_sec: TEAMD_TIMEOUT as libc::time_t, tv_usec: 0 };
+    let rc = unsafe { libc::setsockopt(sock.fd, libc::SOL_SOCKET, libc::SO_RCVTIMEO, &timeout as *const libc::timeval as *const libc::c_void, mem::size_of::<libc::timeval>() as libc::socklen_t) };
+    if rc < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut addr: libc::sockaddr_un = unsafe { mem::zeroed() };
+    addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+    if path.len() >= addr.sun_path.len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "socket path too long"));
+    }
+    for (dst, src) in addr.sun_path.iter_mut().zip(path.bytes()) {
+        *dst = src as libc::c_char;
+    }
+    let rc = unsafe { libc::connect(sock.fd, &addr as *const libc::sockaddr_un as *const libc::sockaddr, mem::size_of::<libc::sockaddr_un>() as libc::socklen_t) };
+    if rc < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let request = b"REQUEST\nStateDump\n";
+    let rc = unsafe { libc::send(sock.fd, request.as_ptr() as *const libc::c_void, request.len(), 0) };
+    if rc < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut buf = vec![0u8; TEAMD_REPLY_SIZE];
+    let rc = unsafe { libc::recv(sock.fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+    if rc < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let reply = String::from_utf8_lossy(&buf[..rc as usize]).to_string();
+
+    match reply.split_once('\n') {
+        Some(("REPLY_SUCCESS", body)) => { Ok(body.to_string()) },
+        Some(("REPLY_ERROR", body)) => { Err(io::Error::other(format!("teamd: {}", body.trim().replace('\n', ": ")))) },
+        _ => { Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected reply from teamd")) },
+    }
+}
@@ -0,0 +1,409 @@
+// Thresholds and the comparisons made with them. Every evaluation is recorded so
+// --verbose can show why a value resulted in a state.
+use super::{STATE_CRITICAL, STATE_OK, STATE_WARNING};
+
+use std::fmt;
+
+// Nagios range as described by the plugin development guidelines:
+//
+//   10:20   alert outside of 10 to 20
+//   10:     alert below 10
+//   ~:10    alert above 10
+//   @10:20  alert inside of 10 to 20
+//
+// Unlike the guidelines a plain number is an exact value, not 0 to the number,
+// so -m 9000 keeps its meaning.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Range {
+    // None is negative infinity
+    pub start: Option<f64>,
+    // None is positive infinity
+    pub end: Option<f64>,
+    // alert inside instead of outside of the range
+    pub inside: bool,
+}
+
+impl Range {
+    pub fn exact(value: f64) -> Range {
+        Range{ start: Some(value), end: Some(value), inside: false }
+    }
+
+    pub fn parse(raw: &str) -> Result<Range, &'static str> {
+        let (inside, bounds) = match raw.strip_prefix('@') {
+            Some(r) => { (true, r) },
+            None => { (false, raw) },
+        };
+
+        let (start, end) = match bounds.find(':') {
+            Some(pos) => {
+                let start = match &bounds[..pos] {
+                    "~" => { None },
+                    "" => { Some(0.0) },
+                    s => {
+                        match s.parse() {
+                            Ok(v) => { Some(v) },
+                            Err(_) => { return Err("Invalid start of range"); },
+                        }
+                    },
+                };
+                let end = match &bounds[pos + 1..] {
+                    "" => { None },
+                    e => {
+                        match e.parse() {
+                            Ok(v) => { Some(v) },
+                            Err(_) => { return Err("Invalid end of range"); },
+                        }
+                    },
+                };
+                (start, end)
+            },
+            None => {
+                let value: f64 = match bounds.parse() {
+                    Ok(v) => { v },
+                    Err(_) => { return Err("Invalid range"); },
+                };
+                if !inside {
+                    return Ok(Range::exact(value));
+                }
+                // @10 is @0:10
+                (Some(0.0), Some(value))
+            },
+        };
+
+        if let (Some(s), Some(e)) = (start, end) {
+            if s > e {
+                return Err("Invalid range, start is greater than end");
+            }
+        }
+
+        Ok(Range{ start, end, inside })
+    }
+
+    pub fn contains(&self, value: f64) -> bool {
+        self.start.map(|s| value >= s).unwrap_or(true) && self.end.map(|e| value <= e).unwrap_or(true)
+    }
+
+    pub fn is_violated_by(&self, value: f64) -> bool {
+        self.contains(value) == self.inside
+    }
+
+    pub fn is_exact(&self) -> bool {
+        !self.inside && self.start.is_some() && self.start == self.end
+    }
+
+    // start:end without the @ prefix
+    pub fn interval(&self) -> String {
+        let start = match self.start {
+            Some(s) => { s.to_string() },
+            None => { "~".to_string() },
+        };
+        let end = match self.end {
+            Some(e) => { e.to_string() },
+            None => { String::new() },
+        };
+        format!("{}:{}", start, end)
+    }
+}
+
+// normalized, exact values are shown as the plain number
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_exact() {
+            if let Some(v) = self.start {
+                return write!(f, "{}", v);
+            }
+        }
+        if self.inside {
+            return write!(f, "@{}", self.interval());
+        }
+        write!(f, "{}", self.interval())
+    }
+}
+
+// the bound is violated if the value is ...
+#[derive(Clone)]
+pub enum Bound {
+    Above(f64),
+    Below(f64),
+    Range(Range),
+    // ... none of the values
+    OneOf(Vec<f64>),
+}
+
+impl Bound {
+    fn is_violated_by(&self, value: f64) -> bool {
+        match self {
+            Bound::Above(b) => { value > *b },
+            Bound::Below(b) => { value < *b },
+            Bound::Range(r) => { r.is_violated_by(value) },
+            Bound::OneOf(values) => { !values.contains(&value) },
+        }
+    }
+
+    // a violated bound stays violated until the value clears it by margin percent
+    // of the bound, only lower and upper limits have a direction to clear
+    fn is_held_by(&self, value: f64, margin: f64) -> bool {
+        match self {
+            Bound::Above(b) => { value > b - b.abs() * margin / 100.0 },
+            Bound::Below(b) => { value < b + b.abs() * margin / 100.0 },
+            _ => { false },
+        }
+    }
+}
+
+impl fmt::Display for Bound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Bound::Above(b) => { write!(f, "> {}", b) },
+            Bound::Below(b) => { write!(f, "< {}", b) },
+            Bound::Range(r) if r.is_exact() => { write!(f, "!= {}", r) },
+            Bound::Range(r) if r.inside => { write!(f, "inside {}", r.interval()) },
+            Bound::Range(r) => { write!(f, "outside {}", r.interval()) },
+            Bound::OneOf(values) => { write!(f, "not in {}", values.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(", ")) },
+        }
+    }
+}
+
+pub struct Threshold {
+    pub warn: Option<Bound>,
+    pub crit: Option<Bound>,
+}
+
+impl Threshold {
+    // rates and percentages, higher is worse
+    pub fn above(warn: Option<f64>, crit: Option<f64>) -> Threshold {
+        Threshold{ warn: warn.map(Bound::Above), crit: crit.map(Bound::Above) }
+    }
+
+    // speeds, lower is worse
+    pub fn below(warn: Option<f64>, crit: Option<f64>) -> Threshold {
+        Threshold{ warn: warn.map(Bound::Below), crit: crit.map(Bound::Below) }
+    }
+
+    // values like the MTU, a violation is only critical with -C/--critical
+    pub fn range(range: Range, critical: bool) -> Threshold {
+        if critical {
+            return Threshold{ warn: None, crit: Some(Bound::Range(range)) };
+        }
+        Threshold{ warn: Some(Bound::Range(range)), crit: None }
+    }
+
+    // a list of acceptable values, anything else is only critical with -C/--critical
+    pub fn one_of(values: Vec<f64>, critical: bool) -> Threshold {
+        if critical {
+            return Threshold{ warn: None, crit: Some(Bound::OneOf(values)) };
+        }
+        Threshold{ warn: Some(Bound::OneOf(values)), crit: None }
+    }
+
+    // the critical bound is checked first
+    pub fn evaluate(&self, interface: &str, metric: &str, value: f64) -> Comparison {
+        let (state, bound) = match (&self.crit, &self.warn) {
+            (Some(c), _) if c.is_violated_by(value) => { (STATE_CRITICAL, Some(c)) },
+            (_, Some(w)) if w.is_violated_by(value) => { (STATE_WARNING, Some(w)) },
+            _ => { (STATE_OK, None) },
+        };
+
+        Comparison{
+            interface: interface.to_string(),
+            metric: metric.to_string(),
+            observed: value,
+            threshold: self.to_string(),
+            violated: bound.map(|b| b.to_string()),
+            state,
+            held: false,
+        }
+    }
+
+    // like evaluate, but a state worse than the current one reported by the previous
+    // run is kept as long as the value is within margin percent of the violated bound
+    pub fn evaluate_with_hysteresis(&self, interface: &str, metric: &str, value: f64, previous: i32, margin: f64) -> Comparison {
+        let mut cmp = self.evaluate(interface, metric, value);
+
+        let held = match (&self.crit, &self.warn) {
+            (Some(c), _) if previous == STATE_CRITICAL && cmp.state < STATE_CRITICAL && c.is_held_by(value, margin) => { Some((STATE_CRITICAL, c)) },
+            (_, Some(w)) if previous >= STATE_WARNING && cmp.state < STATE_WARNING && w.is_held_by(value, margin) => { Some((STATE_WARNING, w)) },
+            _ => { None },
+        };
+        if let Some((state, bound)) = held {
+            cmp.state = state;
+            cmp.violated = Some(bound.to_string());
+            cmp.held = true;
+        }
+
+        cmp
+    }
+}
+
+// "warning if > 10, critical if > 100"
+impl fmt::Display for Threshold {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.warn, &self.crit) {
+            (Some(w), Some(c)) => { write!(f, "warning if {}, critical if {}", w, c) },
+            (Some(w), None) => { write!(f, "warning if {}", w) },
+            (None, Some(c)) => { write!(f, "critical if {}", c) },
+            (None, None) => { write!(f, "none") },
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Comparison {
+    pub interface: String,
+    pub metric: String,
+    pub observed: f64,
+    // as parsed, not as given on the command line
+    pub threshold: String,
+    // None if all bounds are satisfied
+    pub violated: Option<String>,
+    pub state: i32,
+    // the bound is satisfied, but the state of the previous run is kept by --hysteresis
+    pub held: bool,
+}
+
+pub fn state_name(state: i32) -> &'static str {
+    match state {
+        STATE_OK => { "OK" },
+        STATE_WARNING => { "WARNING" },
+        STATE_CRITICAL => { "CRITICAL" },
+        _ => { "UNKNOWN" },
+    }
+}
+
+// "eth0 rx_errors rate: observed 12.5, threshold warning if > 10, critical if > 100, violates > 10 -> WARNING"
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let outcome = match self.violated {
+            Some(ref b) if self.held => { format!("within hysteresis band of {}", b) },
+            Some(ref b) => { format!("violates {}", b) },
+            None => { "satisfies all bounds".to_string() },
+        };
+        write!(f, "{} {}: observed {}, threshold {}, {} -> {}", self.interface, self.metric, self.observed, self.threshold, outcome, state_name(self.state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(raw: &str) -> Range {
+        Range::parse(raw).unwrap()
+    }
+
+    fn violations(raw: &str, values: &[f64]) -> Vec<bool> {
+        let r = range(raw);
+        values.iter().map(|v| r.is_violated_by(*v)).collect()
+    }
+
+    #[test]
+    fn exact_value() {
+        let r = range("9000");
+        assert!(r.is_exact());
+        assert_eq!(r.to_string(), "9000");
+        assert_eq!(violations("9000", &[1500.0, 9000.0, 9001.0]), vec![true, false, true]);
+    }
+
+    #[test]
+    fn open_end() {
+        let r = range("1000:");
+        assert!(r.start == Some(1000.0) && r.end.is_none() && !r.inside);
+        assert_eq!(r.to_string(), "1000:");
+        assert_eq!(violations("1000:", &[999.0, 1000.0, 100000.0]), vec![true, false, false]);
+    }
+
+    #[test]
+    fn open_start() {
+        let r = range("~:1500");
+        assert!(r.start.is_none() && r.end == Some(1500.0));
+        assert_eq!(r.to_string(), "~:1500");
+        assert_eq!(violations("~:1500", &[-1.0, 1500.0, 1501.0]), vec![false, false, true]);
+    }
+
+    #[test]
+    fn empty_start_is_zero() {
+        let r = range(":10");
+        assert!(r.start == Some(0.0) && r.end == Some(10.0));
+        assert_eq!(violations(":10", &[-1.0, 0.0, 10.0, 11.0]), vec![true, false, false, true]);
+    }
+
+    #[test]
+    fn outside_of_range() {
+        assert_eq!(range("1500:9000").to_string(), "1500:9000");
+        assert_eq!(violations("1500:9000", &[1499.0, 1500.0, 9000.0, 9001.0]), vec![true, false, false, true]);
+    }
+
+    #[test]
+    fn inside_of_range() {
+        let r = range("@100:999");
+        assert!(r.inside);
+        assert_eq!(r.to_string(), "@100:999");
+        assert_eq!(violations("@100:999", &[99.0, 100.0, 999.0, 1000.0]), vec![false, true, true, false]);
+    }
+
+    #[test]
+    fn inside_up_to_value() {
+        // @10 is @0:10
+        assert_eq!(range("@10").to_string(), "@0:10");
+        assert_eq!(violations("@10", &[-1.0, 0.0, 10.0, 11.0]), vec![false, true, true, false]);
+    }
+
+    #[test]
+    fn single_value_range_is_exact() {
+        assert!(range("1500:1500").is_exact());
+        assert!(!range("@1500:1500").is_exact());
+    }
+
+    #[test]
+    fn invalid_ranges() {
+        assert!(Range::parse("").is_err());
+        assert!(Range::parse("@").is_err());
+        assert!(Range::parse("~").is_err());
+        assert!(Range::parse("fast").is_err());
+        assert_eq!(Range::parse("x:10").err(), Some("Invalid start of range"));
+        assert_eq!(Range::parse("10:x").err(), Some("Invalid end of range"));
+        assert_eq!(Range::parse("1:2:3").err(), Some("Invalid end of range"));
+        assert_eq!(Range::parse("9000:1500").err(), Some("Invalid range, start is greater than end"));
+        assert_eq!(Range::parse("@20:10").err(), Some("Invalid range, start is greater than end"));
+    }
+
+    #[test]
+    fn audit_line_of_a_rate() {
+        let t = Threshold::above(Some(10.0), Some(100.0));
+        assert_eq!(t.evaluate("eth0", "rx_errors rate", 12.5).to_string(), "eth0 rx_errors rate: observed 12.5, threshold warning if > 10, critical if > 100, violates > 10 -> WARNING");
+        assert_eq!(t.evaluate("eth0", "rx_errors rate", 100.5).to_string(), "eth0 rx_errors rate: observed 100.5, threshold warning if > 10, critical if > 100, violates > 100 -> CRITICAL");
+        assert_eq!(t.evaluate("eth0", "rx_errors rate", 0.0).to_string(), "eth0 rx_errors rate: observed 0, threshold warning if > 10, critical if > 100, satisfies all bounds -> OK");
+    }
+
+    #[test]
+    fn audit_line_of_a_speed() {
+        let t = Threshold::below(Some(1000.0), None);
+        assert_eq!(t.evaluate("eth0", "speed", 940.0).to_string(), "eth0 speed: observed 940, threshold warning if < 1000, violates < 1000 -> WARNING");
+    }
+
+    #[test]
+    fn audit_line_of_a_range() {
+        let mtu = Threshold::range(range("9000"), false);
+        assert_eq!(mtu.evaluate("eth0", "mtu", 1500.0).to_string(), "eth0 mtu: observed 1500, threshold warning if != 9000, violates != 9000 -> WARNING");
+        let mtu = Threshold::range(range("1500:9000"), true);
+        assert_eq!(mtu.evaluate("eth0", "mtu", 9216.0).to_string(), "eth0 mtu: observed 9216, threshold critical if outside 1500:9000, violates outside 1500:9000 -> CRITICAL");
+        let speed = Threshold::range(range("@100:999"), false);
+        assert_eq!(speed.evaluate("eth0", "speed", 100.0).to_string(), "eth0 speed: observed 100, threshold warning if inside 100:999, violates inside 100:999 -> WARNING");
+    }
+
+    #[test]
+    fn audit_line_of_allowed_values() {
+        let t = Threshold::one_of(vec![1000.0, 10000.0], false);
+        assert_eq!(t.evaluate("eth0", "speed", 100.0).to_string(), "eth0 speed: observed 100, threshold warning if not in 1000, 10000, violates not in 1000, 10000 -> WARNING");
+    }
+
+    #[test]
+    fn audit_line_within_hysteresis_band() {
+        let t = Threshold::above(Some(10.0), None);
+        let cmp = t.evaluate_with_hysteresis("eth0", "rx_errors rate", 9.5, STATE_WARNING, 10.0);
+        assert!(cmp.held);
+        assert_eq!(cmp.to_string(), "eth0 rx_errors rate: observed 9.5, threshold warning if > 10, within hysteresis band of > 10 -> WARNING");
+        // cleared by more than the margin
+        let cmp = t.evaluate_with_hysteresis("eth0", "rx_errors rate", 8.5, STATE_WARNING, 10.0);
+        assert_eq!(cmp.to_string(), "eth0 rx_errors rate: observed 8.5, threshold warning if > 10, satisfies all bounds -> OK");
+    }
+}
@@ -0,0 +1,61 @@
+// Composite check of a routed point-to-point uplink: the interface carries a /31 or
+// /30 from the provider block, the other end of that subnet answers and the default
+// route points to it.
+use super::error::CheckError;
+use super::firsthop;
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
+
+#[derive(Serialize)]
+pub struct Uplink {
+    // IPv4 addresses of the interface inside the provider prefix
+    pub addresses: Vec<ipnetwork::Ipv4Network>,
+    // None unless exactly one point-to-point address is assigned
+    pub peer: Option<firsthop::FirstHop>,
+    // a default route via the peer exists
+    pub default_route: bool,
+}
+
+pub fn is_point_to_point(net: &ipnetwork::Ipv4Network) -> bool {
+    net.prefix() == 31 || net.prefix() == 30
+}
+
+// the other address of a /31, the other host address of a /30
+pub fn peer_address(net: &ipnetwork::Ipv4Network) -> Option<Ipv4Addr> {
+    let own = u32::from(net.ip());
+    match net.prefix() {
+        31 => { Some(Ipv4Addr::from(own ^ 1)) },
+        30 => {
+            let network = u32::from(net.network());
+            match own - network {
+                1 => { Some(Ipv4Addr::from(network + 2)) },
+                2 => { Some(Ipv4Addr::from(network + 1)) },
+                // the network or broadcast address can't be used on a /30
+                _ => { None },
+            }
+        },
+        _ => { None },
+    }
+}
+
+pub fn check(interface: &str, ifindex: u32, ips: &[ipnetwork::IpNetwork], provider: ipnetwork::Ipv4Network, methods: &[firsthop::Method], timeout: Duration) -> Result<Uplink, CheckError> {
+    let addresses: Vec<ipnetwork::Ipv4Network> = ips.iter().filter_map(|n| match n {
+        ipnetwork::IpNetwork::V4(net) if provider.contains(net.ip()) => { Some(*net) },
+        _ => { None },
+    }).collect();
+
+    let peer = match addresses.as_slice() {
+        [net] if is_point_to_point(net) => { peer_address(net) },
+        _ => { None },
+    };
+    let peer = match peer {
+        Some(p) => { p },
+        None => { return Ok(Uplink{ addresses, peer: None, default_route: false }); },
+    };
+
+    let default_route = firsthop::gateways(ifindex)?.contains(&IpAddr::V4(peer));
+    let hop = firsthop::check(interface, ifindex, ips, IpAddr::V4(peer), methods, timeout);
+
+    Ok(Uplink{ addresses, peer: Some(hop), default_route })
+}
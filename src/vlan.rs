@@ -0,0 +1,112 @@
+// VLAN sub-interfaces of a parent device, from /proc/net/vlan/config or netlink if
+// the 8021q proc interface is not available.
+use super::error::CheckError;
+use super::interface::SysfsReader;
+use super::netlink;
+
+use std::fs;
+
+#[derive(Serialize)]
+pub struct Vlan {
+    pub name: String,
+    pub id: u16,
+    // lower device
+    pub parent: String,
+}
+
+// "eth0.10        | 10  | eth0", after two header lines
+fn parse_proc_config(content: &str, parent: &str) -> Vec<Vlan> {
+    let mut result = Vec::new();
+    for line in content.lines().skip(2) {
+        let fields: Vec<&str> = line.split('|').map(|f| f.trim()).collect();
+        if fields.len() != 3 || fields[2] != parent {
+            continue;
+        }
+        if let Ok(id) = fields[1].parse() {
+            result.push(Vlan{ name: fields[0].to_string(), id, parent: parent.to_string() });
+        }
+    }
+    result
+}
+
+// sorted by VLAN id
+pub fn discover(sysfs: &dyn SysfsReader, sysfs_root: &str, parent: &str) -> Result<Vec<Vlan>, CheckError> {
+    let mut result = match fs::read_to_string("/proc/net/vlan/config") {
+        Ok(s) => { parse_proc_config(&s, parent) },
+        Err(_) => {
+            let index_file = format!("{}/{}/ifindex", sysfs_root, parent);
+            let ifindex: u32 = match sysfs.read_to_string(&index_file) {
+                Ok(s) => {
+                    match s.trim().parse() {
+                        Ok(v) => { v },
+                        Err(_) => { return Err(CheckError::parse(&format!("interface index in {}", index_file), &s)); },
+                    }
+                },
+                Err(e) => { return Err(CheckError::io(&index_file, e)); },
+            };
+            match netlink::vlans() {
+                Ok(v) => { v.into_iter().filter(|l| l.parent == ifindex).map(|l| Vlan{ name: l.name, id: l.id, parent: parent.to_string() }).collect() },
+                Err(e) => { return Err(CheckError::netlink("VLAN interfaces", e)); },
+            }
+        },
+    };
+
+    result.sort_by_key(|v| v.id);
+    Ok(result)
+}
+
+// "eth0.10  VID: 10\t REORDER_HDR: 1  dev->priv_flags: 1" and "Device: eth0"
+fn parse_proc_device(content: &str, interface: &str) -> Option<Vlan> {
+    let mut id: Option<u16> = None;
+    let mut parent: Option<String> = None;
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        while let Some(f) = fields.next() {
+            match f {
+                "VID:" if id.is_none() => { id = fields.next().and_then(|v| v.parse().ok()); },
+                "Device:" if parent.is_none() => { parent = fields.next().map(|v| v.to_string()); },
+                _ => {},
+            };
+        }
+    }
+    match (id, parent) {
+        (Some(id), Some(parent)) => { Some(Vlan{ name: interface.to_string(), id, parent }) },
+        _ => { None },
+    }
+}
+
+fn interface_name(sysfs: &dyn SysfsReader, sysfs_root: &str, ifindex: u32) -> Option<String> {
+    for name in sysfs.read_dir(sysfs_root).ok()? {
+        let index = sysfs.read_to_string(&format!("{}/{}/ifindex", sysfs_root, name)).unwrap_or_default();
+        if index.trim().parse() == Ok(ifindex) {
+            return Some(name);
+        }
+    }
+    None
+}
+
+// None if the interface is not a VLAN device
+pub fn read(sysfs: &dyn SysfsReader, sysfs_root: &str, interface: &str) -> Result<Option<Vlan>, CheckError> {
+    let proc_file = format!("/proc/net/vlan/{}", interface);
+    if let Ok(s) = fs::read_to_string(&proc_file) {
+        return match parse_proc_device(&s, interface) {
+            Some(v) => { Ok(Some(v)) },
+            None => { Err(CheckError::parse(&format!("VLAN configuration in {}", proc_file), s.lines().next().unwrap_or_default())) },
+        };
+    }
+
+    // the 8021q proc interface only lists VLAN devices, netlink knows all links
+    let links = match netlink::vlans() {
+        Ok(v) => { v },
+        Err(e) => { return Err(CheckError::netlink("VLAN interfaces", e)); },
+    };
+    match links.into_iter().find(|l| l.name == interface) {
+        Some(l) => {
+            match interface_name(sysfs, sysfs_root, l.parent) {
+                Some(parent) => { Ok(Some(Vlan{ name: l.name, id: l.id, parent })) },
+                None => { Err(CheckError::Missing(format!("Can't find lower device with interface index {} of VLAN interface {}", l.parent, interface))) },
+            }
+        },
+        None => { Ok(None) },
+    }
+}
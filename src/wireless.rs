@@ -0,0 +1,133 @@
+// Link quality, signal level, bitrate and SSID of wireless interfaces from
+// /proc/net/wireless and nl80211. Speed and duplex mean nothing for them.
+use super::interface::SysfsReader;
+use super::netlink;
+
+use std::fs;
+use std::io;
+
+const NL80211_CMD_GET_INTERFACE: u8 = 5;
+const NL80211_CMD_GET_STATION: u8 = 17;
+
+const NL80211_ATTR_IFINDEX: u16 = 3;
+const NL80211_ATTR_STA_INFO: u16 = 21;
+const NL80211_ATTR_SSID: u16 = 52;
+
+const NL80211_STA_INFO_SIGNAL: u16 = 7;
+const NL80211_STA_INFO_TX_BITRATE: u16 = 8;
+
+// units of 100 kbit/s, the 16 bit value can't describe rates above 6.5 GBit/s
+const NL80211_RATE_INFO_BITRATE: u16 = 1;
+const NL80211_RATE_INFO_BITRATE32: u16 = 5;
+
+#[derive(Serialize)]
+pub struct Wireless {
+    // None if the interface is not associated
+    pub ssid: Option<String>,
+    pub link_quality: Option<f64>,
+    // dBm
+    pub signal: Option<f64>,
+    // transmit bitrate to the access point in MBit/s
+    pub bitrate: Option<f64>,
+}
+
+// cfg80211 devices have phy80211, old wireless extension drivers only wireless
+pub fn is_wireless(sysfs: &dyn SysfsReader, sysfs_path: &str) -> bool {
+    sysfs.read_dir(&format!("{}/phy80211", sysfs_path)).is_ok() || sysfs.read_dir(&format!("{}/wireless", sysfs_path)).is_ok()
+}
+
+// " wlan0: 0000   70.  -40.  -256        0      0      0      0      0        0"
+fn parse_proc_wireless(content: &str, interface: &str) -> Option<(f64, f64)> {
+    for line in content.lines().skip(2) {
+        let (name, values) = match line.split_once(':') {
+            Some(v) => { v },
+            None => { continue; },
+        };
+        if name.trim() != interface {
+            continue;
+        }
+
+        let fields: Vec<&str> = values.split_whitespace().collect();
+        if fields.len() < 3 {
+            return None;
+        }
+        let quality = fields[1].trim_end_matches('.').parse().ok()?;
+        let signal = fields[2].trim_end_matches('.').parse().ok()?;
+        return Some((quality, signal));
+    }
+    None
+}
+
+fn ifindex_attribute(ifindex: u32) -> Vec<u8> {
+    let mut attributes: Vec<u8> = Vec::new();
+    netlink::push_attribute(&mut attributes, NL80211_ATTR_IFINDEX, &ifindex.to_ne_bytes());
+    attributes
+}
+
+fn ssid(family: u16, ifindex: u32) -> io::Result<Option<String>> {
+    for answer in netlink::genl_request(family, NL80211_CMD_GET_INTERFACE, false, &ifindex_attribute(ifindex))? {
+        for attr in netlink::parse_attributes(&answer) {
+            if attr.kind == NL80211_ATTR_SSID {
+                return Ok(Some(String::from_utf8_lossy(&attr.data).to_string()));
+            }
+        }
+    }
+    Ok(None)
+}
+
+// signal and transmit bitrate of the first station, the access point for a client
+fn station(family: u16, ifindex: u32) -> io::Result<(Option<f64>, Option<f64>)> {
+    let mut signal: Option<f64> = None;
+    let mut bitrate: Option<f64> = None;
+
+    for answer in netlink::genl_request(family, NL80211_CMD_GET_STATION, true, &ifindex_attribute(ifindex))? {
+        for attr in netlink::parse_attributes(&answer).iter().filter(|a| a.kind == NL80211_ATTR_STA_INFO) {
+            for info in netlink::parse_attributes(&attr.data) {
+                match info.kind {
+                    NL80211_STA_INFO_SIGNAL if !info.data.is_empty() => { signal = Some(info.data[0] as i8 as f64); },
+                    NL80211_STA_INFO_TX_BITRATE => {
+                        for rate in netlink::parse_attributes(&info.data) {
+                            match rate.kind {
+                                NL80211_RATE_INFO_BITRATE32 => { bitrate = netlink::attribute_u32(&rate.data).map(|v| v as f64 / 10.0); },
+                                NL80211_RATE_INFO_BITRATE if bitrate.is_none() && rate.data.len() >= 2 => {
+                                    bitrate = Some(u16::from_ne_bytes([rate.data[0], rate.data[1]]) as f64 / 10.0);
+                                },
+                                _ => {},
+                            };
+                        }
+                    },
+                    _ => {},
+                };
+            }
+        }
+        if signal.is_some() || bitrate.is_some() {
+            break;
+        }
+    }
+
+    Ok((signal, bitrate))
+}
+
+// every source is optional, drivers differ in what they report
+pub fn read(interface: &str, ifindex: u32) -> Wireless {
+    let mut result = Wireless{ ssid: None, link_quality: None, signal: None, bitrate: None };
+
+    if let Ok(content) = fs::read_to_string("/proc/net/wireless") {
+        if let Some((quality, signal)) = parse_proc_wireless(&content, interface) {
+            result.link_quality = Some(quality);
+            result.signal = Some(signal);
+        }
+    }
+
+    if let Ok(family) = netlink::genl_family("nl80211") {
+        result.ssid = ssid(family, ifindex).unwrap_or(None);
+        if let Ok((signal, bitrate)) = station(family, ifindex) {
+            if result.signal.is_none() {
+                result.signal = signal;
+            }
+            result.bitrate = bitrate;
+        }
+    }
+
+    result
+}
@@ -0,0 +1,131 @@
+// Files written by earlier versions must still be understood: JSON output of schema
+// version 1 as consumers stored it and state files written before a key was added.
+extern crate check_ethernet;
+extern crate regex;
+extern crate serde_json;
+
+use check_ethernet::{counters, hysteresis, state};
+
+use serde_json::Value;
+
+use std::fs;
+
+fn golden(name: &str) -> String {
+    format!("{}/tests/golden/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn schema() -> Value {
+    let path = format!("{}/schema/output-v1.json", env!("CARGO_MANIFEST_DIR"));
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    match schema.get("$ref").and_then(|r| r.as_str()) {
+        Some(r) => { root.pointer(r.trim_start_matches('#')).unwrap() },
+        None => { schema },
+    }
+}
+
+fn type_matches(name: &str, value: &Value) -> bool {
+    match name {
+        "object" => { value.is_object() },
+        "array" => { value.is_array() },
+        "string" => { value.is_string() },
+        "integer" => { value.is_i64() || value.is_u64() },
+        "number" => { value.is_number() },
+        "boolean" => { value.is_boolean() },
+        "null" => { value.is_null() },
+        _ => { false },
+    }
+}
+
+// the subset of draft-07 used by schema/output-v1.json
+fn validate(root: &Value, schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let schema = resolve(root, schema);
+    match schema.get("type") {
+        Some(Value::String(t)) if !type_matches(t, value) => { errors.push(format!("{}: not of type {}", path, t)); return; },
+        Some(Value::Array(ts)) if !ts.iter().any(|t| type_matches(t.as_str().unwrap(), value)) => { errors.push(format!("{}: none of the types {:?}", path, ts)); return; },
+        _ => {},
+    };
+    if let Some(c) = schema.get("const") {
+        if c != value {
+            errors.push(format!("{}: {} instead of {}", path, value, c));
+        }
+    }
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            errors.push(format!("{}: {} not in {:?}", path, value, allowed));
+        }
+    }
+    if let (Some(p), Some(s)) = (schema.get("pattern").and_then(|p| p.as_str()), value.as_str()) {
+        if !regex::Regex::new(p).unwrap().is_match(s) {
+            errors.push(format!("{}: {} doesn't match {}", path, s, p));
+        }
+    }
+    if let Some(object) = value.as_object() {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for r in required.iter().filter_map(|r| r.as_str()) {
+                if !object.contains_key(r) {
+                    errors.push(format!("{}: {} missing", path, r));
+                }
+            }
+        }
+        for (k, v) in object {
+            match schema.get("properties").and_then(|p| p.get(k)) {
+                Some(s) => { validate(root, s, v, &format!("{}.{}", path, k), errors); },
+                None => {
+                    if let Some(s) = schema.get("additionalProperties").filter(|s| s.is_object()) {
+                        validate(root, s, v, &format!("{}.{}", path, k), errors);
+                    }
+                },
+            };
+        }
+    }
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (i, v) in array.iter().enumerate() {
+            validate(root, items, v, &format!("{}[{}]", path, i), errors);
+        }
+    }
+}
+
+fn assert_valid(name: &str) {
+    let schema = schema();
+    let output: Value = serde_json::from_str(&fs::read_to_string(golden(name)).unwrap()).unwrap();
+    let mut errors = Vec::new();
+    validate(&schema, &schema, &output, "$", &mut errors);
+    assert!(errors.is_empty(), "{} violates schema/output-v1.json:\n{}", name, errors.join("\n"));
+}
+
+#[test]
+fn output_of_schema_version_1_validates() {
+    // written by the release introducing schema version 1
+    assert_valid("output-v1-missing.json");
+}
+
+#[test]
+fn current_output_validates_against_version_1() {
+    assert_valid("json.txt");
+}
+
+#[test]
+fn schema_rejects_missing_fields() {
+    let schema = schema();
+    let output: Value = serde_json::from_str(r#"{"schema_version":2,"critical":[{"id":"Link Down"}]}"#).unwrap();
+    let mut errors = Vec::new();
+    validate(&schema, &schema, &output, "$", &mut errors);
+    assert!(errors.iter().any(|e| e == "$: exit_code missing"), "{:?}", errors);
+    assert!(errors.iter().any(|e| e.starts_with("$.schema_version: 2 instead of 1")), "{:?}", errors);
+    assert!(errors.iter().any(|e| e.starts_with("$.critical[0].id: Link Down doesn't match")), "{:?}", errors);
+}
+
+#[test]
+fn state_file_of_0_2_1_loads() {
+    let mut state = state::State::load(&golden("v0.2.1.state")).unwrap();
+    assert_eq!(state.get("lldp.neighbor"), Some("switch01 port Gi1/0/12"));
+    assert_eq!(hysteresis::load(&state).get("rx_errors_rate"), Some(&1));
+
+    // counters without the source of the value start a new baseline instead of
+    // comparing values of different sources
+    assert_eq!(counters::update(&mut state, "rx_bytes", 123_556_789, counters::WIDTH_64, "netlink", None, 1_760_580_060_000), (None, false));
+    assert_eq!(counters::update(&mut state, "rx_bytes", 123_656_789, counters::WIDTH_64, "netlink", None, 1_760_580_160_000), (Some(1000.0), false));
+}
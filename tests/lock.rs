@@ -0,0 +1,70 @@
+// Two instances checking the same interface with a shared state file: the first one
+// holds the lock while it is blocked reading operstate from a FIFO, the second one
+// gives up at once with --lock-policy=fail.
+use std::env;
+use std::ffi::CString;
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn copy_tree(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap().flatten() {
+        let target = to.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_tree(&entry.path(), &target);
+        } else {
+            fs::copy(entry.path(), &target).unwrap();
+        }
+    }
+}
+
+#[test]
+fn second_instance_fails_while_the_first_holds_the_lock() {
+    let dir = env::temp_dir().join(format!("check_ethernet_test_lock_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let root = dir.join("sysfs");
+    copy_tree(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sysfs/fixeth0"), &root.join("fixeth0"));
+
+    let fifo = root.join("fixeth0/operstate");
+    fs::remove_file(&fifo).unwrap();
+    let path = CString::new(fifo.to_string_lossy().as_bytes()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(path.as_ptr(), 0o600) }, 0);
+
+    let args = vec![
+        "-i".to_string(), "fixeth0".to_string(), "-s".to_string(), "10000".to_string(), "-m".to_string(), "9000".to_string(),
+        "--errors-warn=1".to_string(), "--lock-policy=fail".to_string(),
+        format!("--sysfs-root={}", root.display()),
+        format!("--state-file={}", dir.join("%i.state").display()),
+    ];
+
+    let first = Command::new(env!("CARGO_BIN_EXE_check_ethernet")).args(&args).stdout(Stdio::piped()).spawn().unwrap();
+
+    // opening a FIFO for writing without blocking fails until there is a reader, i.e. the
+    // first instance got the lock and reads the interface
+    let started = Instant::now();
+    let mut writer = loop {
+        match fs::OpenOptions::new().write(true).custom_flags(libc::O_NONBLOCK).open(&fifo) {
+            Ok(f) => { break f; },
+            Err(_) if started.elapsed() < Duration::from_secs(5) => { thread::sleep(Duration::from_millis(10)); },
+            Err(e) => { panic!("first instance didn't read {}: {}", fifo.display(), e); },
+        };
+    };
+
+    let second = Command::new(env!("CARGO_BIN_EXE_check_ethernet")).args(&args).output().unwrap();
+    let out = String::from_utf8_lossy(&second.stdout).to_string();
+    assert_eq!(second.status.code(), Some(3), "{}", out);
+    assert!(out.starts_with("Another check instance is running"), "{}", out);
+
+    writer.write_all(b"up\n").unwrap();
+    drop(writer);
+    let first = first.wait_with_output().unwrap();
+    let out = String::from_utf8_lossy(&first.stdout).to_string();
+    assert_eq!(first.status.code(), Some(0), "{}", out);
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,84 @@
+// Every renderer against the same result, compared byte for byte with the files in
+// tests/golden. Run with UPDATE_GOLDEN=1 to rewrite them after an intended change.
+extern crate check_ethernet;
+
+use check_ethernet::config::OutputFormat;
+use check_ethernet::interface::{InterfaceState, SOURCE_SYSFS};
+use check_ethernet::output::{self, CheckResult, OutputRenderer, PerfData, PrometheusRenderer};
+use check_ethernet::status::{Finding, FindingId, NagiosStatus};
+use check_ethernet::STATE_CRITICAL;
+
+use std::env;
+use std::fs;
+
+// eth0 is up with the wrong MTU, eth1 is down
+fn states() -> Vec<InterfaceState> {
+    let mut eth0 = InterfaceState::not_present("eth0", SOURCE_SYSFS);
+    eth0.present = true;
+    eth0.index = 2;
+    eth0.operstate = "up".to_string();
+    eth0.speed = 1000;
+    eth0.duplex = "full".to_string();
+    eth0.mtu = 9000;
+    eth0.ips = vec!["192.0.2.10/24".parse().unwrap(), "fe80::1/64".parse().unwrap()];
+
+    let mut eth1 = InterfaceState::not_present("eth1", SOURCE_SYSFS);
+    eth1.present = true;
+    eth1.index = 3;
+    eth1.operstate = "down".to_string();
+    eth1.mtu = 1500;
+
+    vec![eth0, eth1]
+}
+
+fn status() -> NagiosStatus {
+    let mut status = NagiosStatus::default();
+    status.critical.push(Finding::with_values(FindingId::LinkDown, "eth1", "Interface is DOWN".to_string(), "up", "down"));
+    status.warning.push(Finding::with_values(FindingId::MtuMismatch, "eth0", "MTU size of 9000 does not match requested MTU size of 1500".to_string(), 1500, 9000));
+    status.ok.push(Finding::new(FindingId::LinkUp, "eth0", "Interface is up".to_string()));
+    status.notes.push("eth0: 3 collisions counted on a full duplex link".to_string());
+
+    let mut perf = PerfData::new("eth0_rx_errors", 0.5, "");
+    perf.warn = Some("1".to_string());
+    perf.crit = Some("10".to_string());
+    perf.min = Some(0.0);
+    status.perfdata.push(perf);
+    status.perfdata.push(PerfData::new("eth0 collisions", 3.0, "c"));
+    status
+}
+
+fn render(renderer: &dyn OutputRenderer) -> String {
+    let states = states();
+    let status = status();
+    let result = CheckResult{ schema_version: output::SCHEMA_VERSION, status: &status, interfaces: &states, exit_code: STATE_CRITICAL, prefix_interface: true, count_interfaces: false, status_prefix: true, max_length: 4096, audit: None };
+    renderer.render(&result).unwrap()
+}
+
+fn compare(name: &str, rendered: &str) {
+    let path = format!("{}/tests/golden/{}", env!("CARGO_MANIFEST_DIR"), name);
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, rendered).unwrap();
+    }
+    let expected = fs::read_to_string(&path).unwrap();
+    assert!(rendered == expected, "output differs from {}:\n{}", path, rendered);
+}
+
+#[test]
+fn plain() {
+    compare("plain.txt", &render(output::renderer(OutputFormat::Plain, "").as_ref()));
+}
+
+#[test]
+fn json() {
+    compare("json.txt", &render(output::renderer(OutputFormat::Json, "").as_ref()));
+}
+
+#[test]
+fn checkmk() {
+    compare("checkmk.txt", &render(output::renderer(OutputFormat::Checkmk, "Ethernet").as_ref()));
+}
+
+#[test]
+fn prometheus() {
+    compare("prometheus.txt", &render(&PrometheusRenderer{}));
+}
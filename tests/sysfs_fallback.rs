@@ -0,0 +1,24 @@
+// A --sysfs-root which doesn't exist is refused at parse time, only the default
+// /sys/class/net falls back to netlink when it can't be read.
+use std::process::Command;
+
+const BOGUS_ROOT: &str = "/nonexistent/check_ethernet/sysfs";
+
+fn check(args: &[&str]) -> (i32, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_check_ethernet")).args(args).output().unwrap();
+    let out = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    (output.status.code().unwrap(), out)
+}
+
+#[test]
+fn bogus_sysfs_root_is_unknown() {
+    let (code, out) = check(&["-i", "lo", "--sysfs-root", BOGUS_ROOT]);
+    assert_eq!(code, 3, "{}", out);
+    assert!(out.contains(&format!("Sysfs root {} is not a directory", BOGUS_ROOT)), "{}", out);
+}
+
+#[test]
+fn sysfs_root_must_be_a_directory() {
+    let (code, out) = check(&["-i", "lo", "--sysfs-root", "/proc/self/status"]);
+    assert_eq!(code, 3, "{}", out);
+}
@@ -0,0 +1,68 @@
+// The check run against the sysfs tree in tests/fixtures/sysfs: fixeth0 is up with
+// a 10 GBit/s full duplex link and jumbo frames, fixeth1 has no carrier.
+use std::env;
+use std::fs;
+use std::process::Command;
+
+fn check(name: &str, args: &[&str]) -> (i32, String) {
+    let root = format!("{}/tests/fixtures/sysfs", env!("CARGO_MANIFEST_DIR"));
+    let state_file = env::temp_dir().join(format!("check_ethernet_test_{}_{}_%i.state", name, std::process::id())).to_string_lossy().to_string();
+    let output = Command::new(env!("CARGO_BIN_EXE_check_ethernet"))
+        .args(args)
+        .arg(format!("--sysfs-root={}", root))
+        .arg(format!("--state-file={}", state_file))
+        .output()
+        .unwrap();
+    for interface in &["fixeth0", "fixeth1"] {
+        let _ = fs::remove_file(state_file.replace("%i", interface));
+    }
+    (output.status.code().unwrap(), String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[test]
+fn interface_as_expected() {
+    let (code, out) = check("ok", &["-i", "fixeth0", "-s", "10000", "-m", "9000"]);
+    assert_eq!(code, 0, "{}", out);
+    assert_eq!(out, "OK - Interface is up, Carrier detected (3 carrier changes), Negotiated interface speed is 10 GBit/s, Negotiated duplex mode is full, MTU size is 9000 | fixeth0_carrier_changes=3c;;;0 fixeth0_collisions=0c;;;0\n");
+}
+
+#[test]
+fn speed_and_mtu_mismatch() {
+    let (code, out) = check("mismatch", &["-i", "fixeth0", "-s", "1000:half", "-m", "1500"]);
+    assert_eq!(code, 1, "{}", out);
+    assert!(out.starts_with("WARNING - 3 problems on fixeth0: "), "{}", out);
+    assert!(out.contains("\nWARNING: Negotiated duplex mode is full instead of half\n"), "{}", out);
+    assert!(out.contains("\nWARNING: MTU size of 9000 does not match requested MTU size of 1500"), "{}", out);
+
+    let (code, out) = check("mismatch_critical", &["-i", "fixeth0", "-s", "10000", "-m", "1500", "-C"]);
+    assert_eq!(code, 2, "{}", out);
+    assert!(out.starts_with("CRITICAL - MTU size of 9000 does not match requested MTU size of 1500"), "{}", out);
+}
+
+#[test]
+fn no_carrier() {
+    let (code, out) = check("carrier", &["-i", "fixeth0,fixeth1", "-s", "10000", "-m", "9000"]);
+    assert_eq!(code, 2, "{}", out);
+    assert!(out.starts_with("CRITICAL - fixeth1: No carrier detected |"), "{}", out);
+}
+
+#[test]
+fn all_interfaces_of_the_tree() {
+    let (code, out) = check("all", &["--all", "-s", "0"]);
+    assert_eq!(code, 2, "{}", out);
+    assert!(out.starts_with("CRITICAL - 2 interfaces checked, problems on fixeth1 |"), "{}", out);
+}
+
+#[test]
+fn missing_interface() {
+    let (code, out) = check("missing", &["-i", "fixeth9"]);
+    assert_eq!(code, 2, "{}", out);
+    assert_eq!(out, "CRITICAL - Interface is not present\n");
+}
+
+#[test]
+fn dry_run_leaves_vlans_and_address_holders_unresolved() {
+    let (code, out) = check("dry_run", &["-i", "fixeth0", "--check-vlans-of", "fixeth0", "--by-address", "192.0.2.1", "--dry-run"]);
+    assert_eq!(code, 0, "{}", out);
+    assert!(out.starts_with("Interfaces:\n    fixeth0: listed by -i/--interface\n    VLANs of fixeth0: resolved at run time\n    holders of 192.0.2.1: resolved at run time\n"), "{}", out);
+}